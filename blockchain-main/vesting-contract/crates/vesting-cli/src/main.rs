@@ -0,0 +1,690 @@
+// Command-line administration of token_vesting schedules.
+//
+//     vesting-cli --url devnet init --mint <MINT> --amount 1000000 --decimals 6 --start 1767225600 --name "Team"
+//     vesting-cli add-beneficiaries --mint <MINT> --csv team.csv
+//     vesting-cli release --mint <MINT> --percent 25
+//     vesting-cli claim --mint <MINT>
+//     vesting-cli cancel --mint <MINT> [--execute]
+//     vesting-cli status --mint <MINT> [--beneficiary <WALLET>]
+//
+// Schedules are addressed by their token mint; every PDA and bump is derived from it with the seeds
+// in `vesting_common::seeds`. Amounts are whole tokens, as in the program's instructions.
+
+use std::fs;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use anchor_client::anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anchor_client::solana_sdk::instruction::{AccountMeta, Instruction};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::{read_keypair_file, Keypair, Signature};
+use anchor_client::{Client, Cluster, Program};
+use anchor_spl::associated_token::{self, get_associated_token_address};
+use anchor_spl::token;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::DateTime;
+use clap::{Parser, Subcommand, ValueEnum};
+
+use token_vesting::{
+    accounts, instruction, BeneficiaryAccount, Cohort, Config, DataAccount, NewBeneficiary, ReleaseMode,
+    MAX_BENEFICIARIES_PER_CHUNK,
+};
+use vesting_common::math;
+use vesting_common::seeds::{
+    BENEFICIARY_INDEX_SEED, BENEFICIARY_SEED, BLACKLIST_SEED, CLAIM_RECORD_SEED, COHORT_SEED, CONFIG_SEED,
+    CREATOR_REGISTRY_SEED, DATA_ACCOUNT_SEED, ESCROW_WALLET_SEED, PROGRAM_STATE_SEED,
+};
+
+// Seed of the PDA Anchor's `#[event_cpi]` instructions sign their self-CPI with.
+const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+#[derive(Parser)]
+#[command(name = "vesting-cli", about = "Administer token_vesting schedules")]
+struct Cli {
+    /// Cluster name (mainnet, devnet, localnet, ...) or RPC URL
+    #[arg(long, short = 'u', global = true, default_value = "localnet")]
+    url: String,
+
+    /// Keypair paying for and signing every transaction (defaults to the Solana CLI keypair)
+    #[arg(long, short = 'k', global = true)]
+    keypair: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a schedule for a mint and fund its escrow from the signer's token account
+    Init {
+        #[arg(long)]
+        mint: Pubkey,
+        /// Whole tokens to lock
+        #[arg(long)]
+        amount: u64,
+        #[arg(long)]
+        decimals: u8,
+        /// Unix timestamp vesting starts at
+        #[arg(long)]
+        start: i64,
+        /// Token account the tokens are taken from (defaults to the signer's ATA)
+        #[arg(long)]
+        from: Option<Pubkey>,
+        #[arg(long)]
+        cancellable: bool,
+        #[arg(long)]
+        calendar_months: bool,
+        #[arg(long)]
+        retroactive: bool,
+        #[arg(long, default_value_t = 0)]
+        cancellation_penalty_bps: u16,
+        #[arg(long)]
+        guardian: Option<Pubkey>,
+        #[arg(long, default_value_t = 0)]
+        tge_unlock_bps: u16,
+        #[arg(long, default_value_t = 0)]
+        lockup_seconds: u32,
+        #[arg(long, value_enum, default_value_t = ReleaseModeArg::Both)]
+        release_mode: ReleaseModeArg,
+        #[arg(long, default_value = "")]
+        name: String,
+        #[arg(long, default_value = "")]
+        uri: String,
+    },
+    /// Add the beneficiaries of a CSV file (`wallet,allocated_tokens[,recovery_authority]`) and close the batch
+    AddBeneficiaries {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        csv: String,
+        /// Leave the batch open so more files can be added before finalizing
+        #[arg(long)]
+        no_finalize: bool,
+    },
+    /// Release another percentage of the schedule
+    Release {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        percent: u8,
+    },
+    /// Claim vested tokens of the signer (or of `--beneficiary` when the signer is its claim delegate)
+    Claim {
+        #[arg(long)]
+        mint: Pubkey,
+        /// Whole tokens to claim (0 = everything claimable)
+        #[arg(long, default_value_t = 0)]
+        amount: u64,
+        #[arg(long)]
+        beneficiary: Option<Pubkey>,
+    },
+    /// Schedule a cancellation, or execute one whose grace period has passed
+    Cancel {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        execute: bool,
+        /// Token account receiving the unvested tokens on `--execute` (defaults to the signer's ATA)
+        #[arg(long)]
+        recipient: Option<Pubkey>,
+    },
+    /// Print the schedule's state, and a beneficiary's position if given
+    Status {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        beneficiary: Option<Pubkey>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ReleaseModeArg {
+    TimeOnly,
+    ManualOnly,
+    Both,
+}
+
+impl From<ReleaseModeArg> for ReleaseMode {
+    fn from(mode: ReleaseModeArg) -> Self {
+        match mode {
+            ReleaseModeArg::TimeOnly => ReleaseMode::TimeOnly,
+            ReleaseModeArg::ManualOnly => ReleaseMode::ManualOnly,
+            ReleaseModeArg::Both => ReleaseMode::Both,
+        }
+    }
+}
+
+// PDAs of the schedule of one mint. `escrow_wallet` is where `initialize` creates the escrow; after
+// `migrate_escrow` the data account's `escrow_wallet` is the one to use.
+struct ScheduleAddresses {
+    data_account: Pubkey,
+    escrow_wallet: Pubkey,
+}
+
+impl ScheduleAddresses {
+    fn new(mint: &Pubkey) -> Self {
+        Self {
+            data_account: pda(&[DATA_ACCOUNT_SEED, mint.as_ref()]),
+            escrow_wallet: pda(&[ESCROW_WALLET_SEED, mint.as_ref()]),
+        }
+    }
+
+    fn beneficiary(&self, wallet: &Pubkey) -> Pubkey {
+        pda(&[BENEFICIARY_SEED, self.data_account.as_ref(), wallet.as_ref()])
+    }
+
+    fn beneficiary_index(&self, index: u32) -> Pubkey {
+        pda(&[BENEFICIARY_INDEX_SEED, self.data_account.as_ref(), &index.to_le_bytes()])
+    }
+
+    fn cohort(&self, cohort_id: u32) -> Pubkey {
+        pda(&[COHORT_SEED, self.data_account.as_ref(), &cohort_id.to_le_bytes()])
+    }
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &token_vesting::ID).0
+}
+
+// The program state PDA every state-changing instruction takes, whether or not it exists yet.
+fn program_status() -> accounts::ProgramStatus {
+    accounts::ProgramStatus { program_state: pda(&[PROGRAM_STATE_SEED]) }
+}
+
+fn event_authority() -> Pubkey {
+    pda(&[EVENT_AUTHORITY_SEED])
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let keypair_path = match cli.keypair {
+        Some(path) => path,
+        None => {
+            let home = std::env::var("HOME").context("HOME is not set; pass --keypair")?;
+            format!("{home}/.config/solana/id.json")
+        }
+    };
+    let payer = read_keypair_file(&keypair_path).map_err(|e| anyhow!("reading {keypair_path}: {e}"))?;
+    let cluster = Cluster::from_str(&cli.url).map_err(|e| anyhow!("invalid --url: {e}"))?;
+    let client = Client::new_with_options(cluster, Rc::new(payer), CommitmentConfig::confirmed());
+    let program = client.program(token_vesting::ID)?;
+
+    match cli.command {
+        Command::Init {
+            mint,
+            amount,
+            decimals,
+            start,
+            from,
+            cancellable,
+            calendar_months,
+            retroactive,
+            cancellation_penalty_bps,
+            guardian,
+            tge_unlock_bps,
+            lockup_seconds,
+            release_mode,
+            name,
+            uri,
+        } => {
+            let addresses = ScheduleAddresses::new(&mint);
+            let sender = program.payer();
+            let args = instruction::Initialize {
+                amount,
+                decimals,
+                start_timestamp: start,
+                is_cancellable: cancellable,
+                calendar_months,
+                retroactive,
+                cancellation_penalty_bps,
+                guardian,
+                tge_unlock_bps,
+                lockup_seconds,
+                release_mode: release_mode.into(),
+                name: fixed_bytes(&name, "name")?,
+                uri: fixed_bytes(&uri, "uri")?,
+            };
+            let accounts = accounts::Initialize {
+                data_account: addresses.data_account,
+                escrow_wallet: addresses.escrow_wallet,
+                wallet_to_withdraw_from: from.unwrap_or_else(|| get_associated_token_address(&sender, &mint)),
+                creator_registry: pda(&[CREATOR_REGISTRY_SEED, sender.as_ref()]),
+                token_mint: mint,
+                sender,
+                system_program: system_program::ID,
+                token_program: token::ID,
+                program_status: program_status(),
+            };
+            let signature = send(&program, accounts, args, vec![])?;
+            println!("Schedule {} created: {signature}", addresses.data_account);
+        }
+        Command::AddBeneficiaries { mint, csv, no_finalize } => {
+            add_beneficiaries(&program, &mint, &csv, !no_finalize)?;
+        }
+        Command::Release { mint, percent } => {
+            let addresses = ScheduleAddresses::new(&mint);
+            let accounts = accounts::Release {
+                data_account: addresses.data_account,
+                token_mint: mint,
+                sender: program.payer(),
+                system_program: system_program::ID,
+                program_status: program_status(),
+                event_authority: event_authority(),
+                program: token_vesting::ID,
+            };
+            let signature = send(&program, accounts, instruction::Release { percent }, vec![])?;
+            let data_account: DataAccount = program.account(addresses.data_account)?;
+            println!("Released {percent}% ({}% available): {signature}", data_account.percent_available);
+        }
+        Command::Claim { mint, amount, beneficiary } => {
+            claim(&program, &mint, amount, beneficiary)?;
+        }
+        Command::Cancel { mint, execute, recipient } => {
+            let addresses = ScheduleAddresses::new(&mint);
+            let sender = program.payer();
+            if execute {
+                let data_account: DataAccount = program.account(addresses.data_account)?;
+                let accounts = accounts::ExecuteCancel {
+                    data_account: addresses.data_account,
+                    escrow_wallet: data_account.escrow_wallet,
+                    recipient: recipient.unwrap_or_else(|| get_associated_token_address(&sender, &mint)),
+                    sender,
+                    token_mint: mint,
+                    token_program: token::ID,
+                    program_status: program_status(),
+                    event_authority: event_authority(),
+                    program: token_vesting::ID,
+                };
+                let signature = send(&program, accounts, instruction::ExecuteCancel {}, vec![])?;
+                println!("Cancellation executed: {signature}");
+            } else {
+                let accounts = accounts::ScheduleCancel {
+                    data_account: addresses.data_account,
+                    token_mint: mint,
+                    sender,
+                    program_status: program_status(),
+                    event_authority: event_authority(),
+                    program: token_vesting::ID,
+                };
+                let signature = send(&program, accounts, instruction::ScheduleCancel {}, vec![])?;
+                let data_account: DataAccount = program.account(addresses.data_account)?;
+                println!(
+                    "Cancellation scheduled, executable from {}: {signature}",
+                    format_timestamp(data_account.cancel_effective_at)
+                );
+            }
+        }
+        Command::Status { mint, beneficiary } => {
+            status(&program, &mint, beneficiary)?;
+        }
+    }
+    Ok(())
+}
+
+// Builds and sends one instruction; `remaining` is appended after the context's accounts.
+fn send(
+    program: &Program<Rc<Keypair>>,
+    accounts: impl ToAccountMetas,
+    args: impl InstructionData,
+    remaining: Vec<AccountMeta>,
+) -> Result<Signature> {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(remaining);
+    let ix = Instruction {
+        program_id: token_vesting::ID,
+        accounts: metas,
+        data: args.data(),
+    };
+    Ok(program.request().instruction(ix).send()?)
+}
+
+// Reads `wallet,allocated_tokens[,recovery_authority]` rows. Blank lines, `#` comments and a header row are skipped.
+fn read_beneficiaries(path: &str) -> Result<Vec<NewBeneficiary>> {
+    let contents = fs::read_to_string(path).with_context(|| format!("reading {path}"))?;
+    let mut rows = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let key = match Pubkey::from_str(fields[0]) {
+            Ok(key) => key,
+            Err(_) if rows.is_empty() => continue,
+            Err(_) => bail!("{path}:{}: invalid wallet {:?}", index + 1, fields[0]),
+        };
+        let allocated_tokens = fields
+            .get(1)
+            .ok_or_else(|| anyhow!("{path}:{}: missing allocated_tokens", index + 1))?
+            .parse::<u64>()
+            .with_context(|| format!("{path}:{}: invalid allocated_tokens", index + 1))?;
+        let recovery_authority = match fields.get(2).filter(|field| !field.is_empty()) {
+            Some(field) => Some(
+                Pubkey::from_str(field).map_err(|_| anyhow!("{path}:{}: invalid recovery_authority", index + 1))?,
+            ),
+            None => None,
+        };
+        rows.push(NewBeneficiary { key, allocated_tokens, recovery_authority });
+    }
+    Ok(rows)
+}
+
+// Sends the CSV in chunks of `MAX_BENEFICIARIES_PER_CHUNK`. Rows before the schedule's `batch_cursor` are
+// taken as already added, so rerunning the same file resumes an interrupted upload.
+fn add_beneficiaries(program: &Program<Rc<Keypair>>, mint: &Pubkey, csv: &str, finalize: bool) -> Result<()> {
+    let addresses = ScheduleAddresses::new(mint);
+    let rows = read_beneficiaries(csv)?;
+    let data_account: DataAccount = program.account(addresses.data_account)?;
+    let cursor = data_account.batch_cursor as usize;
+    if cursor > rows.len() {
+        bail!("the open batch already has {cursor} entries but {csv} only has {}", rows.len());
+    }
+    if cursor > 0 {
+        println!("Resuming the open batch at row {cursor}");
+    }
+
+    let mut offset = cursor;
+    let mut next_index = data_account.beneficiary_index_len;
+    for chunk in rows[cursor..].chunks(MAX_BENEFICIARIES_PER_CHUNK) {
+        let accounts = accounts::AddBeneficiaries {
+            data_account: addresses.data_account,
+            sender: program.payer(),
+            token_mint: *mint,
+            system_program: system_program::ID,
+            program_status: program_status(),
+        };
+        let remaining = chunk
+            .iter()
+            .zip(next_index..)
+            .flat_map(|(row, index)| {
+                [
+                    AccountMeta::new(addresses.beneficiary(&row.key), false),
+                    AccountMeta::new(addresses.beneficiary_index(index), false),
+                ]
+            })
+            .collect();
+        let args = instruction::AddBeneficiaries {
+            new_beneficiaries: chunk.to_vec(),
+            batch_offset: offset as u32,
+        };
+        let signature = send(program, accounts, args, remaining)?;
+        offset += chunk.len();
+        next_index += chunk.len() as u32;
+        println!("Added rows {}..{offset}: {signature}", offset - chunk.len());
+    }
+
+    if finalize {
+        let accounts = accounts::FinalizeBeneficiaryBatch {
+            data_account: addresses.data_account,
+            token_mint: *mint,
+            sender: program.payer(),
+            program_status: program_status(),
+        };
+        let args = instruction::FinalizeBeneficiaryBatch { expected_count: rows.len() as u32 };
+        let signature = send(program, accounts, args, vec![])?;
+        println!("Batch of {} beneficiaries finalized: {signature}", rows.len());
+    }
+    Ok(())
+}
+
+fn claim(program: &Program<Rc<Keypair>>, mint: &Pubkey, amount: u64, beneficiary: Option<Pubkey>) -> Result<()> {
+    let addresses = ScheduleAddresses::new(mint);
+    let sender = program.payer();
+    let wallet = beneficiary.unwrap_or(sender);
+    let beneficiary_account = addresses.beneficiary(&wallet);
+
+    let data_account: DataAccount = program.account(addresses.data_account)?;
+    let position: BeneficiaryAccount = program
+        .account(beneficiary_account)
+        .with_context(|| format!("{wallet} is not a beneficiary of this schedule"))?;
+    // Claims are free, and take no fee accounts, until the protocol config exists
+    let config_address = pda(&[CONFIG_SEED]);
+    let config: Option<Config> = program.account(config_address).ok();
+    let fee_recipient = config.as_ref().map(|config| config.fee_recipient);
+
+    if data_account.claim_approver.is_some() {
+        bail!("this schedule's claims must be co-signed by its claim approver; the CLI can't collect that signature");
+    }
+
+    // Optional accounts the schedule's and position's settings make mandatory
+    let claim_record = data_account.record_claims.then(|| {
+        pda(&[CLAIM_RECORD_SEED, beneficiary_account.as_ref(), &position.claim_count.to_le_bytes()])
+    });
+    let blacklist = data_account
+        .blacklist_enabled
+        .then(|| pda(&[BLACKLIST_SEED, addresses.data_account.as_ref()]));
+    let gate_config = data_account.gate_program.map(|_| data_account.gate_config);
+    // Payout mint claims go to the NFT holder's or the beneficiary's ATA
+    let recipient = if position.position_mint.is_some() { sender } else { wallet };
+
+    let accounts = accounts::Claim {
+        data_account: addresses.data_account,
+        beneficiary_account,
+        // Mint-vesting schedules created without an escrow have none to pass
+        escrow_wallet: (data_account.escrow_wallet != Pubkey::default()).then_some(data_account.escrow_wallet),
+        beneficiary_wallet: wallet,
+        sender,
+        token_mint: *mint,
+        wallet_to_deposit_to: get_associated_token_address(&wallet, mint),
+        payout_token_account: position.payout_token_account,
+        config: config.as_ref().map(|_| config_address),
+        fee_recipient,
+        fee_token_account: fee_recipient.map(|fee_recipient| get_associated_token_address(&fee_recipient, mint)),
+        claim_approver: None,
+        memo_program: None,
+        position_token_account: position.position_mint.map(|position_mint| {
+            get_associated_token_address(&sender, &position_mint)
+        }),
+        claim_record,
+        secondary_token_account: position
+            .secondary_recipient
+            .map(|recipient| get_associated_token_address(&recipient, mint)),
+        blacklist,
+        gate_program: data_account.gate_program,
+        gate_config,
+        claim_hook_program: data_account.on_claim_hook,
+        payout_mint: data_account.payout_mint,
+        payout_escrow: data_account.payout_mint.map(|_| data_account.payout_escrow),
+        payout_destination: data_account
+            .payout_mint
+            .map(|payout_mint| get_associated_token_address(&recipient, &payout_mint)),
+        payout_fee_token_account: data_account
+            .payout_mint
+            .zip(fee_recipient)
+            .map(|(payout_mint, fee_recipient)| get_associated_token_address(&fee_recipient, &payout_mint)),
+        withholding_token_account: data_account.withholding_account,
+        bonus_escrow: (data_account.bonus_rate_bps_per_year > 0).then_some(data_account.bonus_escrow),
+        cohort: position.cohort_id.map(|cohort_id| addresses.cohort(cohort_id)),
+        associated_token_program: associated_token::ID,
+        token_program: token::ID,
+        system_program: system_program::ID,
+        program_status: program_status(),
+        event_authority: event_authority(),
+        program: token_vesting::ID,
+    };
+    let signature = send(program, accounts, instruction::Claim { amount, dry_run: false, claim_nonce: None }, vec![])?;
+
+    let position: BeneficiaryAccount = program.account(beneficiary_account)?;
+    println!(
+        "Claimed; {} of {} tokens claimed so far: {signature}",
+        position.claimed_tokens,
+        position.entitlement(&data_account)
+    );
+    Ok(())
+}
+
+fn status(program: &Program<Rc<Keypair>>, mint: &Pubkey, beneficiary: Option<Pubkey>) -> Result<()> {
+    let addresses = ScheduleAddresses::new(mint);
+    let data_account: DataAccount = program
+        .account(addresses.data_account)
+        .with_context(|| format!("no schedule for mint {mint}"))?;
+    let rpc = program.rpc();
+    let now = data_account.monotonic_now(rpc.get_block_time(rpc.get_slot()?)?);
+    let vested_bps = data_account.vested_bps(now);
+
+    println!("Schedule          {}", addresses.data_account);
+    println!("Name              {}", fixed_str(&data_account.name));
+    if !fixed_str(&data_account.uri).is_empty() {
+        println!("URI               {}", fixed_str(&data_account.uri));
+    }
+    println!("Mint              {mint} ({} decimals)", data_account.decimals);
+    println!("Escrow            {}", data_account.escrow_wallet);
+    println!("Initializer       {}", data_account.initializer);
+    println!("Layout version    {}", data_account.version);
+    if let Some(source) = data_account.cloned_from {
+        println!("Cloned from       {source}");
+    }
+    if data_account.mint_compromised {
+        println!("Mint integrity    compromised: authorities changed, withdrawals blocked");
+    }
+    println!();
+    println!("Locked            {} tokens", data_account.token_amount);
+    println!("Beneficiaries     {}", data_account.beneficiary_count);
+    println!("Allocated         {} tokens", data_account.total_allocated);
+    println!("Claimed           {} tokens", data_account.claimed_total);
+    println!("Withdrawn         {} tokens", data_account.unclaimed_withdrawn);
+    if data_account.staked_amount > 0 {
+        println!("Staked            {} tokens", data_account.staked_amount);
+    }
+    if data_account.parked_total > 0 {
+        println!("Parked            {} tokens", data_account.parked_total);
+    }
+    if data_account.batch_cursor > 0 {
+        println!("Open batch        {} entries", data_account.batch_cursor);
+    }
+    println!();
+    println!("Start             {}", format_timestamp(data_account.start_timestamp));
+    if data_account.lockup_seconds > 0 {
+        println!("Lockup            {} s", data_account.lockup_seconds);
+    }
+    println!(
+        "Duration          {} months ({} month cliff{})",
+        data_account.vesting_months,
+        data_account.cliff_months,
+        if data_account.calendar_months { ", calendar months" } else { "" }
+    );
+    if data_account.tge_unlock_bps > 0 {
+        println!("TGE unlock        {}", format_bps(data_account.tge_unlock_bps));
+    }
+    println!("End               {}", format_timestamp(data_account.vesting_end_timestamp()));
+    println!("Release mode      {}", release_mode_name(data_account.release_mode));
+    println!("Released          {}%", data_account.percent_available);
+    for announcement in data_account.announced_releases.iter().filter(|a| a.effective_at != 0) {
+        println!(
+            "Release announced {}% from {} (id {})",
+            announcement.percent,
+            format_timestamp(announcement.effective_at),
+            announcement.id
+        );
+    }
+    println!("Vested now        {}", format_bps(vested_bps));
+    let next_unlock = data_account.next_unlock_timestamp(now);
+    if next_unlock != 0 {
+        println!("Next unlock       {}", format_timestamp(next_unlock));
+    }
+    if data_account.bonus_rate_bps_per_year > 0 {
+        println!("Unclaimed bonus   {} a year", format_bps(data_account.bonus_rate_bps_per_year));
+    }
+    println!();
+    println!("Cancellable       {}", yes_no(data_account.is_cancellable));
+    if data_account.cancelled {
+        println!("Cancelled         yes (final vesting {})", format_bps(data_account.final_vested_bps));
+    } else if data_account.cancel_effective_at != 0 {
+        println!("Cancel scheduled  {}", format_timestamp(data_account.cancel_effective_at));
+    }
+    println!("Frozen            {}", yes_no(data_account.frozen));
+    println!("Config frozen     {}", yes_no(data_account.is_frozen));
+    if data_account.require_acceptance {
+        println!("Acceptance        required before claiming");
+    }
+    if !data_account.approvers.is_empty() {
+        println!(
+            "Multisig          {} of {} approvers",
+            data_account.approval_threshold,
+            data_account.approvers.len()
+        );
+    }
+    if data_account.claim_deadline != 0 {
+        println!("Claim deadline    {}", format_timestamp(data_account.claim_deadline));
+    }
+
+    if let Some(wallet) = beneficiary {
+        let position: BeneficiaryAccount = program
+            .account(addresses.beneficiary(&wallet))
+            .with_context(|| format!("{wallet} is not a beneficiary of this schedule"))?;
+        let entitlement = position.entitlement(&data_account);
+        let cohort: Option<Cohort> = position
+            .cohort_id
+            .map(|cohort_id| program.account(addresses.cohort(cohort_id)))
+            .transpose()?;
+        let position_bps = position.vested_bps(&data_account, cohort.as_ref(), now)?;
+        let vested = position.vested_amount(&data_account, position_bps);
+        let claimable = if data_account.claim_window_expired(now) {
+            0
+        } else {
+            math::claimable_amount(entitlement, position.claimed_tokens, position_bps)
+        };
+        println!();
+        println!("Beneficiary       {wallet}");
+        println!("Entitlement       {entitlement} tokens");
+        println!("Vested            {vested} tokens");
+        println!("Claimed           {} tokens", position.claimed_tokens);
+        println!("Claimable now     {claimable} tokens");
+        if position.voluntary_extension_seconds != 0 {
+            println!("Extended by       {}s (ends {})", position.voluntary_extension_seconds,
+                format_timestamp(position.vesting_end_timestamp(&data_account)));
+        }
+        if position.last_claim_timestamp != 0 {
+            println!("Last claim        {}", format_timestamp(position.last_claim_timestamp));
+        }
+        if position.terminated {
+            println!("Terminated        yes");
+        }
+    }
+    Ok(())
+}
+
+// Right-pads `text` with zeros to the program's fixed-size metadata fields.
+fn fixed_bytes<const N: usize>(text: &str, field: &str) -> Result<[u8; N]> {
+    let bytes = text.as_bytes();
+    if bytes.len() > N {
+        bail!("--{field} is longer than {N} bytes");
+    }
+    let mut out = [0u8; N];
+    out[..bytes.len()].copy_from_slice(bytes);
+    Ok(out)
+}
+
+fn fixed_str(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    match DateTime::from_timestamp(timestamp, 0) {
+        Some(time) => format!("{} ({timestamp})", time.format("%Y-%m-%d %H:%M:%S UTC")),
+        None => timestamp.to_string(),
+    }
+}
+
+fn format_bps(bps: u16) -> String {
+    format!("{}.{:02}%", bps / 100, bps % 100)
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+fn release_mode_name(mode: ReleaseMode) -> &'static str {
+    match mode {
+        ReleaseMode::TimeOnly => "time only",
+        ReleaseMode::ManualOnly => "manual only",
+        ReleaseMode::Both => "time and manual release",
+    }
+}
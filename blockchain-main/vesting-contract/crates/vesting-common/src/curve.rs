@@ -0,0 +1,94 @@
+// Vesting curves: how the part of an allocation above the TGE unlock vests over `vesting_months`.
+// `math::Schedule::time_vested_bps` evaluates the schedule's curve once the lockup and cliff have passed,
+// so claims, previews and off-chain code all follow the same shape.
+
+// Most points a custom curve can have.
+pub const MAX_CURVE_POINTS: usize = 8;
+
+// A point of a custom curve: `cumulative_bps` of the remainder has vested after `month` whole months.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CurvePoint {
+    pub month: u8,
+    pub cumulative_bps: u16,
+}
+
+// Shape of a month-based schedule. Every curve is fully vested once `vesting_months` have elapsed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Curve {
+    // Evenly in whole percent steps, every month
+    #[default]
+    Linear,
+    // In yearly tranches: a year's share unlocks at once on each anniversary, the rest at the end
+    Stepped,
+    // Every month of year N weighs N times a month of the first year, e.g. 10/20/30/40% over four years
+    BackWeighted,
+    // Interpolated linearly, month by month, between (0, 0) and the first `len` of `points`
+    Custom { points: [CurvePoint; MAX_CURVE_POINTS], len: u8 },
+}
+
+impl Curve {
+    // Custom curve through `points`, or `None` unless there are 1 to `MAX_CURVE_POINTS` points at strictly
+    // increasing months from month 1, with non-decreasing `cumulative_bps` ending at exactly 10_000.
+    pub fn custom(points: &[CurvePoint]) -> Option<Curve> {
+        let last = points.last()?;
+        if points.len() > MAX_CURVE_POINTS || points[0].month == 0 || last.cumulative_bps != 10_000 {
+            return None;
+        }
+        if !points
+            .windows(2)
+            .all(|pair| pair[0].month < pair[1].month && pair[0].cumulative_bps <= pair[1].cumulative_bps)
+        {
+            return None;
+        }
+        let mut stored = [CurvePoint::default(); MAX_CURVE_POINTS];
+        stored[..points.len()].copy_from_slice(points);
+        Some(Curve::Custom { points: stored, len: points.len() as u8 })
+    }
+
+    // Points of a custom curve; empty for the built-in ones.
+    pub fn points(&self) -> &[CurvePoint] {
+        match self {
+            Curve::Custom { points, len } => &points[..core::cmp::min(*len as usize, MAX_CURVE_POINTS)],
+            _ => &[],
+        }
+    }
+
+    // Share (0–10_000 basis points) of the remainder vested after `elapsed_months` of `vesting_months`.
+    // Never decreases as `elapsed_months` grows, and is 10_000 from `vesting_months` on.
+    pub fn vested_bps(&self, elapsed_months: u64, vesting_months: u8) -> u16 {
+        let total = vesting_months as u64;
+        if elapsed_months >= total {
+            return 10_000;
+        }
+        let bps = match self {
+            // (elapsed_months * 100) / total vesting months, in whole percent
+            Curve::Linear => elapsed_months * 100 / total * 100,
+            Curve::Stepped => elapsed_months / 12 * 12 * 10_000 / total,
+            Curve::BackWeighted => back_weight(elapsed_months) * 10_000 / back_weight(total),
+            Curve::Custom { .. } => custom_bps(self.points(), elapsed_months),
+        };
+        core::cmp::min(bps, 10_000) as u16
+    }
+}
+
+// Sum of the monthly weights of the first `months` months, each month of year N weighing N:
+// 12 * (1 + ... + years) for the full years, plus (years + 1) for each month of the current one.
+fn back_weight(months: u64) -> u64 {
+    let (years, rest) = (months / 12, months % 12);
+    6 * years * (years + 1) + rest * (years + 1)
+}
+
+// Value of the piecewise-linear curve through (0, 0) and `points` after `elapsed_months`, rounded down.
+fn custom_bps(points: &[CurvePoint], elapsed_months: u64) -> u64 {
+    let mut previous = CurvePoint::default();
+    for point in points {
+        let (from, to) = (previous.month as u64, point.month as u64);
+        if elapsed_months < to {
+            let rise = point.cumulative_bps.saturating_sub(previous.cumulative_bps) as u64;
+            return previous.cumulative_bps as u64 + rise * (elapsed_months - from) / (to - from);
+        }
+        previous = *point;
+    }
+    // A curve whose points end before `vesting_months` is fully vested at its last point
+    10_000
+}
@@ -0,0 +1,443 @@
+// Error codes of the program. The list lives in `with_vesting_errors!` so the program can generate its
+// Anchor `#[error_code]` enum from it, while this crate turns the same list into a plain enum clients can
+// match on-chain error codes against. New errors go at the end: codes are assigned in order.
+
+// Anchor numbers custom errors from 6000 in declaration order.
+pub const ERROR_CODE_OFFSET: u32 = 6000;
+
+// Invokes `$callback!` with every error as `#[msg("...")] Name,`, in code order.
+#[macro_export]
+macro_rules! with_vesting_errors {
+    ($callback:ident) => {
+        $callback! {
+            #[msg("Sender is not owner of Data Account")]
+            InvalidSender,
+            #[msg("Not allowed to claim new tokens currently")]
+            ClaimNotAllowed,
+            #[msg("Beneficiary does not exist in account")]
+            BeneficiaryNotFound,
+            #[msg("Vesting period has not started yet")]
+            VestingNotStarted,
+            #[msg("Invalid percentage provided (must be between 0 and 100)")]
+            InvalidPercentage,
+            #[msg("Total vesting amount must be greater than 0")]
+            ZeroVestingAmount,
+            #[msg("Unclaimed tokens are not yet withdrawable")]
+            VestingStillActive,
+            #[msg("No unclaimed tokens available for withdrawal")]
+            NoUnclaimedTokens,
+            #[msg("Missing account in remaining_accounts")]
+            MissingRemainingAccount,
+            #[msg("Provided account does not match expected beneficiary PDA")]
+            InvalidBeneficiaryPDA,
+            #[msg("Provided account does not match expected beneficiary PDA")]
+            BeneficiaryAlreadyExists,
+            #[msg("Vesting already completed, cannot cancel")]
+            VestingAlreadyCompleted,
+            #[msg("Vesting still has tokens that are neither claimed nor withdrawn")]
+            VestingNotSettled,
+            #[msg("Beneficiary still has vested tokens to claim")]
+            BeneficiaryHasUnclaimedTokens,
+            #[msg("Escrow balance does not cover the outstanding vesting amount")]
+            EscrowBalanceTooLow,
+            #[msg("Total beneficiary allocations exceed the tokens held in escrow")]
+            AllocationExceedsEscrow,
+            #[msg("This operation requires approval from the approver set")]
+            MultisigRequired,
+            #[msg("Approver set has already been configured")]
+            ApproversAlreadySet,
+            #[msg("Approver set is empty, too large or contains duplicates")]
+            InvalidApprovers,
+            #[msg("Approval threshold must be between 1 and the number of approvers")]
+            InvalidThreshold,
+            #[msg("Signer is not in the approver set")]
+            NotAnApprover,
+            #[msg("Signer has already approved this proposal")]
+            AlreadyApproved,
+            #[msg("Proposal does not have enough approvals yet")]
+            NotEnoughApprovals,
+            #[msg("A cancellation is already scheduled")]
+            CancelAlreadyScheduled,
+            #[msg("No cancellation has been scheduled")]
+            CancelNotScheduled,
+            #[msg("Cancellation grace period has not passed yet")]
+            CancelGracePeriodActive,
+            #[msg("Vesting schedule is irrevocable")]
+            VestingNotCancellable,
+            #[msg("Provided account is not the data account PDA for its mint")]
+            InvalidDataAccount,
+            #[msg("Escrow wallet does not belong to this vesting schedule")]
+            InvalidEscrowWallet,
+            #[msg("Token account has the wrong mint or owner")]
+            InvalidTokenAccount,
+            #[msg("Recipient token account must be owned by the initializer")]
+            InvalidRecipient,
+            #[msg("Start timestamp is too far in the past or future")]
+            StartTimestampOutOfRange,
+            #[msg("Start timestamp is in the past; initialize as retroactive to back-date a grant")]
+            StartTimestampInPast,
+            #[msg("Signer is neither the beneficiary nor its claim delegate")]
+            UnauthorizedClaimer,
+            #[msg("Claim must be paid to the beneficiary's payout account")]
+            PayoutAccountMismatch,
+            #[msg("Amendment would shorten the schedule, move a passed cliff or cut vested tokens")]
+            InvalidAmendment,
+            #[msg("Reducing an allocation requires the beneficiary's signature")]
+            BeneficiaryConsentRequired,
+            #[msg("Cancellation penalty must be at most 10000 basis points")]
+            InvalidCancellationPenalty,
+            #[msg("Vesting schedule has been cancelled")]
+            VestingCancelled,
+            #[msg("Fee must be at most 1000 basis points")]
+            InvalidFee,
+            #[msg("Fee account does not belong to the fee recipient")]
+            InvalidFeeAccount,
+            #[msg("Claim must be co-signed by the claim approver")]
+            ClaimApprovalRequired,
+            #[msg("Vesting has already been accelerated")]
+            AlreadyAccelerated,
+            #[msg("Schedule records claims; pass a claim record to claim")]
+            ClaimRecordRequired,
+            #[msg("Beneficiary registry is full")]
+            RegistryFull,
+            #[msg("Beneficiary registry belongs to another schedule")]
+            InvalidRegistry,
+            #[msg("Vesting schedule is frozen by its guardian")]
+            VestingFrozen,
+            #[msg("Only the guardian can freeze the schedule")]
+            NotGuardian,
+            #[msg("TGE unlock must be at most 10000 basis points")]
+            InvalidTgeUnlock,
+            #[msg("Manual release is disabled for time-only schedules")]
+            ManualReleaseDisabled,
+            #[msg("Vesting token is not wrapped SOL")]
+            NotNativeMint,
+            #[msg("Part of the escrow is staked; unstake it first")]
+            EscrowStaked,
+            #[msg("Staking program moved an unexpected amount")]
+            StakingAmountMismatch,
+            #[msg("Staking program is not whitelisted")]
+            StakingProgramNotWhitelisted,
+            #[msg("Unlock table does not fit in return data")]
+            PreviewTooLong,
+            #[msg("Claim cooldown has not elapsed yet")]
+            ClaimTooSoon,
+            #[msg("A per-period claim cap needs a claim interval")]
+            InvalidClaimLimits,
+            #[msg("Rate-limited schedules can't be claimed through the registry")]
+            ClaimLimitsUnsupported,
+            #[msg("Beneficiary has been terminated")]
+            BeneficiaryTerminated,
+            #[msg("Invalid forfeiture pool or share")]
+            InvalidForfeiture,
+            #[msg("Too many vesting legs")]
+            TooManyVestingLegs,
+            #[msg("Vesting legs can only be added before the first claim")]
+            VestingLegsLocked,
+            #[msg("Invalid vesting leg account")]
+            InvalidVestingLeg,
+            #[msg("Not supported on schedules with vesting legs")]
+            VestingLegsUnsupported,
+            #[msg("Invitation key did not sign")]
+            InvalidInvitationCode,
+            #[msg("Requested amount exceeds the claimable amount")]
+            ClaimAmountTooHigh,
+            #[msg("Some beneficiaries have not claimed their full allocation")]
+            BeneficiariesNotFullyClaimed,
+            #[msg("No residual escrow balance to sweep")]
+            NoDustToSweep,
+            #[msg("Memo is too long")]
+            MemoTooLong,
+            #[msg("The memo program account is required")]
+            MemoProgramRequired,
+            #[msg("The position NFT must be held by the claimer")]
+            PositionNftRequired,
+            #[msg("Not supported for NFT-backed positions")]
+            PositionNftUnsupported,
+            #[msg("Decimals don't match the token mint")]
+            DecimalMismatch,
+            #[msg("Template needs at least one vesting month and a cliff no longer than the vesting period")]
+            InvalidTemplate,
+            #[msg("Too many beneficiaries in one chunk")]
+            BatchChunkTooLarge,
+            #[msg("Chunk offset doesn't match the batch cursor")]
+            BatchCursorMismatch,
+            #[msg("Beneficiary batch doesn't have the expected number of entries")]
+            BatchIncomplete,
+            #[msg("Schedule has a blacklist; pass it to claim")]
+            BlacklistRequired,
+            #[msg("Wallet is blacklisted")]
+            WalletBlacklisted,
+            #[msg("Blacklist is full")]
+            BlacklistFull,
+            #[msg("The claim window has expired")]
+            ClaimWindowExpired,
+            #[msg("Claim deadline must not be before the end of vesting")]
+            InvalidClaimDeadline,
+            #[msg("Allocation can't be lower than the tokens already claimed")]
+            AllocationBelowClaimed,
+            #[msg("Not supported for mint-vesting schedules")]
+            MintVestingUnsupported,
+            #[msg("Claim would mint beyond the schedule's cap")]
+            MintCapExceeded,
+            #[msg("Sender is not the mint authority")]
+            InvalidMintAuthority,
+            #[msg("Secondary recipient share must be between 1 and 10000 bps")]
+            InvalidSecondaryRecipient,
+            #[msg("Secondary recipient token account doesn't match")]
+            SecondaryAccountMismatch,
+            #[msg("Not supported for beneficiaries with a secondary recipient")]
+            SecondaryRecipientUnsupported,
+            #[msg("The schedules' initializers or terms differ, or one isn't fully funded")]
+            IncompatibleSchedules,
+            #[msg("The source position has a vote lock, pending recovery, pending acceptance or different extension")]
+            PositionNotConsolidatable,
+            #[msg("Clock is behind the last processed timestamp")]
+            ClockWentBackwards,
+            #[msg("Clock tolerance is too large")]
+            InvalidClockTolerance,
+            #[msg("Identity gate did not verify the claimer")]
+            GateCheckFailed,
+            #[msg("Identity-gated schedules can only be claimed with claim")]
+            ClaimGateUnsupported,
+            #[msg("Too many positions for one claim_all_for_signer call")]
+            TooManyPositions,
+            #[msg("Splitting a position of this schedule needs the initializer's approval")]
+            SplitApprovalRequired,
+            #[msg("Split amount must be unvested and less than the allocation")]
+            InvalidSplitAmount,
+            #[msg("Not available for schedules in shares mode")]
+            SharesModeUnsupported,
+            #[msg("Shares can no longer be changed")]
+            SharesLocked,
+            #[msg("Signer is not the position's recovery authority")]
+            InvalidRecoveryAuthority,
+            #[msg("Invalid recovery wallet")]
+            InvalidRecoveryWallet,
+            #[msg("No recovery is pending")]
+            NoPendingRecovery,
+            #[msg("The recovery delay has not elapsed yet")]
+            RecoveryDelayNotElapsed,
+            #[msg("Recovery authorities are not supported for registry entries")]
+            RecoveryUnsupported,
+            #[msg("Token amount overflows a u64 when scaled by the mint's decimals or a ratio")]
+            MathOverflow,
+            #[msg("The owner role can only be handed over to a real key, never revoked")]
+            InvalidRole,
+            #[msg("The treasurer can only withdraw once the claim window has expired")]
+            ClaimWindowNotExpired,
+            #[msg("Not enough lamports in the fee pool")]
+            FeePoolInsufficient,
+            #[msg("An interval schedule needs both an interval length and a number of intervals")]
+            InvalidIntervalSchedule,
+            #[msg("The interval schedule can only be set before the start and before anything is allocated")]
+            IntervalScheduleLocked,
+            #[msg("Interval schedules can't be amended by month")]
+            IntervalScheduleUnsupported,
+            #[msg("The schedule's claim hook program is missing or doesn't match")]
+            ClaimHookRequired,
+            #[msg("Batch or registry claims are not supported on schedules with a claim hook")]
+            ClaimHookUnsupported,
+            #[msg("The rent reserve doesn't hold enough lamports")]
+            RentReserveInsufficient,
+            #[msg("Release steps must be in time order, with non-decreasing whole-percent amounts of at most 100%")]
+            InvalidReleaseSchedule,
+            #[msg("The release calendar has nothing new to release")]
+            NothingToRelease,
+            #[msg("Not supported once the schedule pays out in a payout mint")]
+            PayoutMintUnsupported,
+            #[msg("Payout mint accounts are missing or don't match the schedule")]
+            PayoutMintAccountMismatch,
+            #[msg("The payout ratio must be positive and the payout mint must differ from the vesting mint")]
+            InvalidPayoutRatio,
+            #[msg("No schedule hash has been committed")]
+            ScheduleNotCommitted,
+            #[msg("Extension must be positive and end by the claim deadline")]
+            InvalidVestingExtension,
+            #[msg("Manual-only schedules can't be extended")]
+            VestingExtensionUnsupported,
+            #[msg("Token account has a delegate")]
+            TokenAccountHasDelegate,
+            #[msg("Token account has a close authority")]
+            TokenAccountHasCloseAuthority,
+            #[msg("A dry-run claim can't take a claim record")]
+            DryRunClaimRecord,
+            #[msg("Withholding must be at most 10000 basis points, with an account exactly when non-zero")]
+            InvalidWithholding,
+            #[msg("Withholding token account is missing or doesn't match the schedule")]
+            WithholdingAccountMismatch,
+            #[msg("Not supported on schedules that withhold tax from claims")]
+            WithholdingUnsupported,
+            #[msg("Vote locks must last up to four years and can't end earlier than the current lock")]
+            InvalidVoteLockDuration,
+            #[msg("No vested tokens to lock")]
+            NothingToLock,
+            #[msg("The schedule's configuration is frozen")]
+            ConfigFrozen,
+            #[msg("Releases on this schedule must be announced with announce_release")]
+            ReleaseNoticeRequired,
+            #[msg("Announced releases need a percent from 1 to 100 and at least a day of notice")]
+            InvalidReleaseAnnouncement,
+            #[msg("Too many pending release announcements")]
+            ReleaseQueueFull,
+            #[msg("No pending release announcement with this id")]
+            ReleaseAnnouncementNotFound,
+            #[msg("The announced release is not effective yet")]
+            ReleaseNotYetEffective,
+            #[msg("The release veto authority is already set")]
+            VetoAuthorityAlreadySet,
+            #[msg("This claim nonce was already used")]
+            DuplicateClaim,
+            #[msg("Bonus rate must be at most 10000 basis points a year")]
+            InvalidBonusRate,
+            #[msg("Bonus escrow is missing or doesn't match the schedule")]
+            BonusEscrowMismatch,
+            #[msg("Not supported on schedules that pay a bonus on unclaimed tokens")]
+            BonusUnsupported,
+            #[msg("Accounting invariant violated: claimed, allocated or escrowed amounts have drifted")]
+            InvariantViolated,
+            #[msg("The schedule wasn't cloned from the given source schedule")]
+            NotClonedFrom,
+            #[msg("The beneficiary hasn't accepted the grant terms yet")]
+            GrantNotAccepted,
+            #[msg("The grant was already accepted")]
+            GrantAlreadyAccepted,
+            #[msg("The escrow token account is frozen by the mint's freeze authority")]
+            EscrowFrozen,
+            #[msg("The recipient token account is frozen by the mint's freeze authority")]
+            RecipientFrozen,
+            #[msg("Neither the escrow nor the recipient token account is frozen")]
+            AccountsNotFrozen,
+            #[msg("Parked tokens have to be paid out with claim_parked first")]
+            ParkedClaimPending,
+            #[msg("The mint's authorities changed since the schedule was created; withdrawals are blocked")]
+            MintCompromised,
+            #[msg("Beneficiary index entry doesn't match the expected PDA")]
+            InvalidBeneficiaryIndex,
+            #[msg("Swap program is not whitelisted")]
+            SwapProgramNotWhitelisted,
+            #[msg("The swap didn't trade exactly the claimed tokens")]
+            SwapAmountMismatch,
+            #[msg("The swap returned less than the minimum output amount")]
+            SlippageExceeded,
+            #[msg("The program is halted by its upgrade authority")]
+            ProgramHalted,
+            #[msg("The instruction doesn't support the schedule's seed version")]
+            SeedVersionUnsupported,
+            #[msg("The schedule already has its maximum number of beneficiaries")]
+            BeneficiaryLimitReached,
+            #[msg("Too many entries for a single call")]
+            BatchTooLarge,
+            #[msg("The report period ends before it starts")]
+            InvalidReportPeriod,
+            #[msg("The schedule's vesting and finalization grace period haven't ended yet")]
+            ScheduleNotExpired,
+            #[msg("The schedule is already finalized")]
+            ScheduleAlreadyCompleted,
+            #[msg("Installment must be positive and fit in the committed amount")]
+            InvalidInstallment,
+            #[msg("Schedule is not funded in installments")]
+            InstallmentFundingDisabled,
+            #[msg("Beneficiary removal already scheduled")]
+            RemovalAlreadyScheduled,
+            #[msg("Beneficiary removal not scheduled")]
+            RemovalNotScheduled,
+            #[msg("Beneficiary removal is still in its dispute window")]
+            DisputeWindowActive,
+            #[msg("Dispute window out of range")]
+            InvalidDisputeWindow,
+            #[msg("Removal arbiter already set")]
+            ArbiterAlreadySet,
+            #[msg("Rounding policy can't change once claims have started")]
+            RoundingLocked,
+            #[msg("Vesting curve can't change once the schedule has started")]
+            CurveLocked,
+            #[msg("Invalid custom vesting curve")]
+            InvalidCurve,
+            #[msg("Vesting curves only apply to month-based schedules")]
+            CurveUnsupported,
+            #[msg("Withdrawal split shares must be non-zero and add up to 10_000 bps")]
+            InvalidWithdrawSplit,
+            #[msg("Withdrawal split can't change once the schedule has started")]
+            WithdrawSplitLocked,
+            #[msg("Sending cancelled tokens to the treasury requires a treasurer")]
+            TreasurerNotSet,
+            #[msg("This feature is not enabled on this cluster")]
+            FeatureDisabled,
+            #[msg("Unknown feature bit")]
+            UnknownFeature,
+            #[msg("The cohort account is missing or isn't the beneficiary's cohort")]
+            CohortMismatch,
+            #[msg("Cohort overrides need a cliff within the vesting, ending by the claim deadline")]
+            InvalidCohort,
+            #[msg("The funding account holds fewer tokens than the deposit")]
+            InsufficientFunding,
+            #[msg("The funding account is not for the vested mint")]
+            FundingMintMismatch,
+            #[msg("The program sunset has not taken effect")]
+            SunsetNotActive,
+            #[msg("The program sunset has taken effect and can't be changed")]
+            SunsetActive,
+            #[msg("The schedule still has beneficiaries")]
+            BeneficiariesRemaining,
+            #[msg("Some vested tokens are still locked for voting or not funded yet")]
+            VestedTokensWithheld,
+            #[msg("Cancellation grace period out of range")]
+            InvalidCancelGracePeriod,
+            #[msg("Cliff can't be longer than the vesting period")]
+            InvalidCliffMonths,
+            #[msg("Payout mints need an irrevocable schedule without a claim deadline")]
+            PayoutMintRequiresIrrevocable,
+        }
+    };
+}
+
+macro_rules! plain_vesting_error {
+    ($(#[msg($msg:literal)] $name:ident,)*) => {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[repr(u32)]
+        pub enum VestingError {
+            $($name,)*
+        }
+
+        impl VestingError {
+            // Every error, in code order.
+            pub const ALL: &'static [VestingError] = &[$(VestingError::$name,)*];
+
+            // Custom error code reported by the program, e.g. in `InstructionError::Custom`.
+            pub fn code(self) -> u32 {
+                ERROR_CODE_OFFSET + self as u32
+            }
+
+            // The error for a custom error code, if it is one of the program's.
+            pub fn from_code(code: u32) -> Option<Self> {
+                Self::ALL.get(code.checked_sub(ERROR_CODE_OFFSET)? as usize).copied()
+            }
+
+            // Variant name, as shown by Anchor clients (e.g. "VestingNotStarted").
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(VestingError::$name => stringify!($name),)*
+                }
+            }
+
+            // Human-readable message, the same as the program logs.
+            pub fn msg(self) -> &'static str {
+                match self {
+                    $(VestingError::$name => $msg,)*
+                }
+            }
+        }
+    };
+}
+
+with_vesting_errors!(plain_vesting_error);
+
+impl core::fmt::Display for VestingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.msg())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VestingError {}
@@ -0,0 +1,28 @@
+// Program version and feature flags, so integrators can tell what a deployment supports before calling it.
+// `get_program_version` returns the version, the oldest client interface it still serves and the features the
+// upgrade authority has enabled on the cluster (`set_program_features`); instructions of a disabled feature fail
+// with `FeatureDisabled`.
+
+// Semver of the program's instruction interface, kept in step with the program crate's version. Minor versions
+// only add instructions, accounts and fields; a major version removes or changes some, and raises
+// `MIN_SUPPORTED_VERSION` to itself.
+pub const PROGRAM_VERSION: [u16; 3] = [0, 1, 0];
+
+// Oldest interface version clients can be built against and still work with this program.
+pub const MIN_SUPPORTED_VERSION: [u16; 3] = [0, 1, 0];
+
+// Bits of `ProgramState::features`. New features start disabled on every cluster until the upgrade authority
+// turns them on, so a rollout can reach devnet before mainnet.
+
+// `set_vesting_curve`
+pub const FEATURE_VESTING_CURVES: u64 = 1 << 0;
+// `set_withdraw_split`
+pub const FEATURE_WITHDRAW_SPLIT: u64 = 1 << 1;
+// `set_cancel_disposition`
+pub const FEATURE_CANCEL_DISPOSITION: u64 = 1 << 2;
+// `create_cohort`, `update_cohort` and `assign_to_cohort`
+pub const FEATURE_COHORTS: u64 = 1 << 3;
+
+// Every feature this build knows about; `set_program_features` rejects other bits.
+pub const KNOWN_FEATURES: u64 =
+    FEATURE_VESTING_CURVES | FEATURE_WITHDRAW_SPLIT | FEATURE_CANCEL_DISPOSITION | FEATURE_COHORTS;
@@ -0,0 +1,26 @@
+// Account sizes and layout constants clients need to allocate or parse program accounts.
+
+use crate::curve::MAX_CURVE_POINTS;
+
+// Maximum number of keys in a schedule's approver set.
+// Approvals are tracked as a `u8` bitmask, so this must stay at or below 8.
+pub const MAX_APPROVERS: usize = 5;
+
+// Number of pending `announce_release` announcements a schedule can hold at once.
+pub const MAX_ANNOUNCED_RELEASES: usize = 4;
+
+// Most shares `withdraw_unclaimed` can split the residual into (see `set_withdraw_split`).
+pub const MAX_WITHDRAW_SPLITS: usize = 4;
+
+// Serialized size of a `DataAccount` including the discriminator; the field layout is documented
+// on the program's `Initialize` accounts.
+pub const DATA_ACCOUNT_LEN: usize = 8 + 1 + 8 + 32 + 32 + 32 + 1 + 8 + 1 + 8 + 8 + 8 + 4 + 32 * MAX_APPROVERS + 1 + 8
+    + 1 + 1 + 1 + 1 + 1 + 4 + 2 + 1 + 2 + 33 + 8 + 1 + 33 + 1 + 2 + 4 + 1 + 8 + 4 + 8 + 33 + 2 + 1 + 1 + 4 + 1 + 8 + 1 + 8 + 4 + 33 + 32 + 32 + 128 + 1 + 1 + 8
+    + 33 + 33 + 8 + 8 + 4 + 2 + 33 + 8 + 33 + 32 + 8 + 8 + 32 + 32 + 8 + 4 + 2 + 33 + 8 + 1
+    + 33 + 4 + 13 * MAX_ANNOUNCED_RELEASES + 2 + 32 + 8 + 4 + 33 + 1 + 8 + 33 + 33 + 1 + 4 + 1 + 8 + 4 + 1
+    + 1 + 8 + 4 + 33 + 1 + 1 + 4 + 3 * MAX_CURVE_POINTS + 4 + 35 * MAX_WITHDRAW_SPLITS
+    + 1 + 1 + 4 + 8;
+
+// Layout version written by `initialize` and `migrate_data_account`.
+// Accounts created before `version` existed (v1) read it as 0.
+pub const DATA_ACCOUNT_VERSION: u8 = 41;
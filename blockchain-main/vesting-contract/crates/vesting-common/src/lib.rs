@@ -0,0 +1,19 @@
+// Definitions shared by the token_vesting program and off-chain code (CLIs, indexers, bots),
+// so clients don't have to keep their own copies of seeds, account sizes and offsets, vesting math and error codes.
+// The crate has no dependencies and is `no_std`, so it builds both for the program and natively;
+// the default `std` feature only adds conveniences for off-chain use.
+//
+//     vesting-common = { path = "..." }                             # off-chain
+//     vesting-common = { path = "...", default-features = false }   # on-chain
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub mod curve;
+pub mod error;
+pub mod features;
+pub mod layout;
+pub mod math;
+pub mod schema;
+pub mod seeds;
+
+pub use error::VestingError;
@@ -0,0 +1,356 @@
+// Vesting math shared by the program and by programs composing with it.
+// Everything here is plain integer arithmetic on `core` types (no Anchor, no allocation),
+// so other programs and off-chain code can compute vested and claimable amounts without a CPI.
+
+use crate::curve::Curve;
+
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+// Length of a month when a schedule doesn't use calendar months.
+pub const SECONDS_PER_MONTH: i64 = 30 * SECONDS_PER_DAY;
+
+// Inputs of the vesting formula; mirrors the matching `DataAccount` fields.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Schedule {
+    pub start_timestamp: i64,
+    pub vesting_months: u8,
+    pub cliff_months: u8,
+    // Manually released percentage that caps the time-vested one
+    pub percent_available: u8,
+    pub calendar_months: bool,
+    // 0 if the schedule was never accelerated
+    pub accelerated_at: i64,
+    pub cancelled: bool,
+    // Share every allocation is frozen at once `cancelled` is set
+    pub final_vested_bps: u16,
+    // Share unlocked at `start_timestamp` (the TGE), before any lockup or cliff
+    pub tge_unlock_bps: u16,
+    // Time after `start_timestamp` before linear vesting of the remainder begins
+    pub lockup_seconds: u32,
+    // Which gates apply (see `ReleaseMode` in the program): time vesting, `percent_available`, or both
+    pub time_vesting: bool,
+    pub manual_release: bool,
+    // Interval schedules: when `interval_seconds` is non-zero, the remainder after the TGE unlocks in
+    // `total_intervals` equal tranches, one every `interval_seconds`, and the month fields are ignored
+    pub interval_seconds: u32,
+    pub total_intervals: u16,
+    // How the remainder vests over `vesting_months` (month-based schedules only)
+    pub curve: Curve,
+}
+
+impl Schedule {
+    // Share (0–10_000 basis points) of each allocation that has vested by time alone at `now`.
+    // Shape: `tge_unlock_bps` at the start, nothing more during the lockup and cliff,
+    // then the remainder vests along `curve` over `vesting_months` (linearly, in whole percent steps, by default),
+    // or for interval schedules in `total_intervals` equal tranches.
+    pub fn time_vested_bps(&self, now: i64) -> u16 {
+        if now < self.start_timestamp {
+            return 0;
+        }
+        if self.is_interval() {
+            return self.interval_vested_bps(now);
+        }
+        // A schedule with no vesting months, or one that has been accelerated, is fully time-vested
+        if self.vesting_months == 0 || self.is_accelerated(now) {
+            return 10_000;
+        }
+        let tge_unlock_bps = core::cmp::min(self.tge_unlock_bps, 10_000) as u64;
+        let elapsed_months = self.elapsed_months(now);
+        // Only the TGE unlock is available until the lockup and cliff have passed
+        if now < self.vesting_start_timestamp() || elapsed_months < self.cliff_months as i64 {
+            return tge_unlock_bps as u16;
+        }
+        let curve_bps = self.curve.vested_bps(elapsed_months as u64, self.vesting_months) as u64;
+        (tge_unlock_bps + (10_000 - tge_unlock_bps) * curve_bps / 10_000) as u16
+    }
+
+    // Whether the schedule unlocks in fixed intervals instead of months.
+    pub fn is_interval(&self) -> bool {
+        self.interval_seconds > 0
+    }
+
+    // `time_vested_bps` of an interval schedule, at or after `start_timestamp`.
+    fn interval_vested_bps(&self, now: i64) -> u16 {
+        if self.total_intervals == 0 || self.is_accelerated(now) {
+            return 10_000;
+        }
+        let tge_unlock_bps = core::cmp::min(self.tge_unlock_bps, 10_000) as u64;
+        let elapsed = core::cmp::min(self.elapsed_intervals(now), self.total_intervals as i64) as u64;
+        (tge_unlock_bps + (10_000 - tge_unlock_bps) * elapsed / self.total_intervals as u64) as u16
+    }
+
+    // Number of whole intervals elapsed at `now` (0 before the lockup ends, or for month-based schedules).
+    pub fn elapsed_intervals(&self, now: i64) -> i64 {
+        let vesting_start = self.vesting_start_timestamp();
+        if !self.is_interval() || now < vesting_start {
+            return 0;
+        }
+        now.saturating_sub(vesting_start) / self.interval_seconds as i64
+    }
+
+    // Timestamp at which `intervals` whole intervals have elapsed.
+    pub fn interval_timestamp(&self, intervals: i64) -> i64 {
+        self.vesting_start_timestamp()
+            .saturating_add(intervals.saturating_mul(self.interval_seconds as i64))
+    }
+
+    // Whether the schedule has been accelerated at `now`.
+    pub fn is_accelerated(&self, now: i64) -> bool {
+        self.accelerated_at != 0 && now >= self.accelerated_at
+    }
+
+    // Share (0–10_000 basis points) of each allocation that is claimable at `now`:
+    // the lesser of the time-vested share and the manually released `percent_available`,
+    // skipping whichever gate is disabled. Once the schedule is cancelled this is the frozen `final_vested_bps`.
+    pub fn vested_bps(&self, now: i64) -> u16 {
+        if self.cancelled {
+            return self.final_vested_bps;
+        }
+        if now < self.start_timestamp {
+            return 0;
+        }
+        let time_vested_bps = if self.time_vesting { self.time_vested_bps(now) } else { 10_000 };
+        let released_bps = if self.manual_release { self.percent_available as u16 * 100 } else { 10_000 };
+        core::cmp::min(time_vested_bps, released_bps)
+    }
+
+    // Share vested at `timestamp` under the current parameters, for reporting on past periods: like `vested_bps`,
+    // except that a cancelled schedule follows its curve up to the share it was frozen at, instead of that share
+    // at every time. Manual releases count at their current level.
+    pub fn reported_vested_bps(&self, timestamp: i64) -> u16 {
+        if !self.cancelled {
+            return self.vested_bps(timestamp);
+        }
+        let uncancelled = Schedule { cancelled: false, ..*self };
+        core::cmp::min(uncancelled.vested_bps(timestamp), self.final_vested_bps)
+    }
+
+    // Tokens of `allocated_tokens` that vested from `period_start` to `period_end`, by `reported_vested_bps`.
+    pub fn accrued_amount(&self, allocated_tokens: u64, period_start: i64, period_end: i64) -> u64 {
+        vested_amount(allocated_tokens, self.reported_vested_bps(period_end))
+            .saturating_sub(vested_amount(allocated_tokens, self.reported_vested_bps(period_start)))
+    }
+
+    // Timestamp at which the lockup ends and linear vesting (and the cliff) starts counting.
+    pub fn vesting_start_timestamp(&self) -> i64 {
+        self.start_timestamp.saturating_add(self.lockup_seconds as i64)
+    }
+
+    // Timestamp of the next time-based unlock after `now`, or 0 once the schedule is fully time-vested.
+    // Does not account for manual releases, which can still hold tokens back.
+    pub fn next_unlock_timestamp(&self, now: i64) -> i64 {
+        let first_unlock = core::cmp::max(1, self.cliff_months as i64);
+        if now < self.start_timestamp && self.tge_unlock_bps > 0 {
+            return self.start_timestamp;
+        }
+        if self.is_interval() {
+            let elapsed = self.elapsed_intervals(now);
+            if elapsed >= self.total_intervals as i64 || self.is_accelerated(now) {
+                return 0;
+            }
+            return self.interval_timestamp(if now < self.vesting_start_timestamp() { 1 } else { elapsed + 1 });
+        }
+        if now < self.vesting_start_timestamp() {
+            return self.next_curve_unlock(now, first_unlock);
+        }
+        let elapsed_months = self.elapsed_months(now);
+        if elapsed_months >= self.vesting_months as i64 || self.is_accelerated(now) {
+            return 0;
+        }
+        self.next_curve_unlock(now, core::cmp::max(elapsed_months + 1, first_unlock))
+    }
+
+    // Boundary of the first month from `month` on at which the time-vested share grows past its value at `now`.
+    // Linear schedules grow every month; other curves can stay flat for months (e.g. between yearly tranches).
+    fn next_curve_unlock(&self, now: i64, month: i64) -> i64 {
+        if self.curve == Curve::Linear {
+            return self.month_timestamp(month);
+        }
+        let vested_bps = self.time_vested_bps(now);
+        (month..=self.vesting_months as i64)
+            .map(|month| self.month_timestamp(month))
+            .find(|&timestamp| self.time_vested_bps(timestamp) > vested_bps)
+            .unwrap_or(0)
+    }
+
+    // Time-based unlock table for an allocation of `allocated_tokens`: (timestamp, cumulative vested tokens)
+    // at the TGE (if anything unlocks there) and at every month boundary from the cliff to the end. A schedule with
+    // no vesting months has a single entry, the full allocation at the TGE.
+    // Manual releases are not taken into account.
+    pub fn unlock_table(&self, allocated_tokens: u64) -> impl Iterator<Item = (i64, u64)> + '_ {
+        let point = move |timestamp: i64| (timestamp, vested_amount(allocated_tokens, self.time_vested_bps(timestamp)));
+        let unlocks_at_tge = self.tge_unlock_bps > 0 || (!self.is_interval() && self.vesting_months == 0);
+        let tge = if unlocks_at_tge { Some(point(self.start_timestamp)) } else { None };
+        // Interval schedules unlock at every interval boundary, month-based ones at month boundaries from the cliff
+        let (first_unlock, last_unlock) = if self.is_interval() {
+            (1, self.total_intervals as i64)
+        } else {
+            (core::cmp::max(1, self.cliff_months as i64), self.vesting_months as i64)
+        };
+        tge.into_iter().chain((first_unlock..=last_unlock).map(move |index| {
+            point(if self.is_interval() { self.interval_timestamp(index) } else { self.month_timestamp(index) })
+        }))
+    }
+
+    // Timestamp at which the schedule has run for its lockup plus its full `vesting_months`
+    // (or all its intervals).
+    pub fn vesting_end_timestamp(&self) -> i64 {
+        if self.is_interval() {
+            return self.interval_timestamp(self.total_intervals as i64);
+        }
+        self.month_timestamp(self.vesting_months as i64)
+    }
+
+    // Timestamp at which `months` whole months of linear vesting have elapsed.
+    pub fn month_timestamp(&self, months: i64) -> i64 {
+        let vesting_start = self.vesting_start_timestamp();
+        if self.calendar_months {
+            add_calendar_months(vesting_start, months)
+        } else {
+            vesting_start.saturating_add(months.saturating_mul(SECONDS_PER_MONTH))
+        }
+    }
+
+    // Number of whole months of linear vesting elapsed at `now` (0 before the lockup ends).
+    pub fn elapsed_months(&self, now: i64) -> i64 {
+        let vesting_start = self.vesting_start_timestamp();
+        if now < vesting_start {
+            return 0;
+        }
+        if !self.calendar_months {
+            return now.saturating_sub(vesting_start) / SECONDS_PER_MONTH;
+        }
+        // Estimate from the calendar dates, then step back if the day/time of month hasn't been reached yet
+        let (start_year, start_month, _) = civil_from_days(vesting_start.div_euclid(SECONDS_PER_DAY));
+        let (now_year, now_month, _) = civil_from_days(now.div_euclid(SECONDS_PER_DAY));
+        let months = (now_year - start_year) * 12 + (now_month as i64 - start_month as i64);
+        if self.month_timestamp(months) > now {
+            months - 1
+        } else {
+            months
+        }
+    }
+}
+
+// Tokens of an allocation that have vested at the given share, in basis points.
+// Formula: (allocated_tokens * bps) / 10_000, rounded down, except that a fully vested
+// allocation is returned as is so the final claim never leaves a remainder behind.
+pub fn vested_amount(allocated_tokens: u64, bps: u16) -> u64 {
+    vested_amount_rounded(allocated_tokens, bps, Rounding::Down)
+}
+
+// How a share of an allocation is rounded to whole tokens.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Rounding {
+    #[default]
+    Down,
+    // Halves round up
+    Nearest,
+    Up,
+}
+
+// `vested_amount` with the given rounding. Whatever the rounding, the result never exceeds the allocation
+// and never decreases as `bps` grows.
+pub fn vested_amount_rounded(allocated_tokens: u64, bps: u16, rounding: Rounding) -> u64 {
+    if bps >= 10_000 {
+        return allocated_tokens;
+    }
+    let scaled = allocated_tokens as u128 * bps as u128;
+    let vested = match rounding {
+        Rounding::Down => scaled / 10_000,
+        Rounding::Nearest => (scaled + 5_000) / 10_000,
+        Rounding::Up => scaled.div_ceil(10_000),
+    };
+    // Below 10_000 bps the result is at most the allocation, so it always fits
+    vested as u64
+}
+
+// Tokens a beneficiary can claim right now: what has vested minus what was already claimed.
+pub fn claimable_amount(allocated_tokens: u64, claimed_tokens: u64, bps: u16) -> u64 {
+    vested_amount(allocated_tokens, bps).saturating_sub(claimed_tokens)
+}
+
+// Raw token units (smallest denomination) of `tokens` whole tokens of a mint with `decimals` decimals,
+// or `None` if the result doesn't fit in a u64 (e.g. more than ~18.4B tokens at 9 decimals).
+// Scaled in u128, so only the final conversion can fail.
+pub fn to_raw_amount(tokens: u64, decimals: u8) -> Option<u64> {
+    let unit = 10u128.checked_pow(decimals as u32)?;
+    (tokens as u128).checked_mul(unit)?.try_into().ok()
+}
+
+// `amount * numerator / denominator`, rounded down, computed in u128.
+// `None` for a zero denominator or a result that doesn't fit in a u64.
+pub fn mul_div(amount: u64, numerator: u64, denominator: u64) -> Option<u64> {
+    if denominator == 0 {
+        return None;
+    }
+    (amount as u128 * numerator as u128 / denominator as u128).try_into().ok()
+}
+
+// `bps` basis points of `amount`, rounded down. `None` only for shares above 100% that overflow a u64.
+pub fn bps_of(amount: u64, bps: u16) -> Option<u64> {
+    mul_div(amount, bps as u64, 10_000)
+}
+
+// Longest stretch a bonus compounds for, so the loop in `compound_bonus` stays bounded.
+pub const MAX_BONUS_MONTHS: i64 = 120;
+
+// Bonus earned by `amount` left unclaimed for `elapsed_seconds` at `rate_bps_per_year`, compounded every
+// whole month (`SECONDS_PER_MONTH`) at a twelfth of the yearly rate, for at most `MAX_BONUS_MONTHS`.
+// Partial months earn nothing; each month's growth is rounded down. `None` if the bonus doesn't fit in a u64.
+pub fn compound_bonus(amount: u64, rate_bps_per_year: u16, elapsed_seconds: i64) -> Option<u64> {
+    let months = (elapsed_seconds / SECONDS_PER_MONTH).clamp(0, MAX_BONUS_MONTHS);
+    let mut balance = amount as u128;
+    for _ in 0..months {
+        balance = balance * (120_000 + rate_bps_per_year as u128) / 120_000;
+    }
+    (balance - amount as u128).try_into().ok()
+}
+
+// Adds calendar months to a UNIX timestamp, keeping the time of day.
+// The day is clamped to the end of shorter months (e.g. Jan 31 + 1 month = Feb 28/29).
+pub fn add_calendar_months(timestamp: i64, months: i64) -> i64 {
+    let days = timestamp.div_euclid(SECONDS_PER_DAY);
+    let seconds_of_day = timestamp.rem_euclid(SECONDS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+    let total = year * 12 + (month as i64 - 1) + months;
+    let new_year = total.div_euclid(12);
+    let new_month = (total.rem_euclid(12) + 1) as u32;
+    let new_day = core::cmp::min(day, days_in_month(new_year, new_month));
+    days_from_civil(new_year, new_month, new_day) * SECONDS_PER_DAY + seconds_of_day
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
+
+// Converts days since 1970-01-01 to a (year, month, day) date in the proleptic Gregorian calendar.
+// Deterministic integer-only algorithm from Howard Hinnant's `civil_from_days`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// Inverse of `civil_from_days`: days since 1970-01-01 for a Gregorian date.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = month as i64;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
@@ -0,0 +1,37 @@
+// Byte offsets and discriminators of the program's accounts, for indexers: `memcmp` filters of
+// `getProgramAccounts`, Geyser plugins and raw account parsing without the IDL.
+// Accounts are Borsh-serialized after Anchor's 8-byte discriminator, so a field's offset is the
+// discriminator plus the sizes of the fields before it. New fields are only ever appended, so these offsets
+// stay valid across layout versions; the program asserts at compile time that the discriminators match.
+//
+//     // Every schedule created by `initializer`
+//     memcmp { offset: DATA_ACCOUNT_INITIALIZER_OFFSET, bytes: initializer }
+
+pub const DISCRIMINATOR_LEN: usize = 8;
+const PUBKEY_LEN: usize = 32;
+
+// First 8 bytes of SHA-256("account:DataAccount")
+pub const DATA_ACCOUNT_DISCRIMINATOR: [u8; 8] = [85, 240, 182, 158, 76, 7, 18, 233];
+// First 8 bytes of SHA-256("account:BeneficiaryAccount")
+pub const BENEFICIARY_ACCOUNT_DISCRIMINATOR: [u8; 8] = [63, 115, 143, 239, 199, 217, 180, 40];
+
+// `DataAccount` starts with percent_available (u8), token_amount (u64), initializer, escrow_wallet,
+// token_mint and decimals (u8).
+pub const DATA_ACCOUNT_INITIALIZER_OFFSET: usize = DISCRIMINATOR_LEN + 1 + 8;
+pub const DATA_ACCOUNT_ESCROW_WALLET_OFFSET: usize = DATA_ACCOUNT_INITIALIZER_OFFSET + PUBKEY_LEN;
+pub const DATA_ACCOUNT_TOKEN_MINT_OFFSET: usize = DATA_ACCOUNT_ESCROW_WALLET_OFFSET + PUBKEY_LEN;
+pub const DATA_ACCOUNT_DECIMALS_OFFSET: usize = DATA_ACCOUNT_TOKEN_MINT_OFFSET + PUBKEY_LEN;
+
+// `BeneficiaryAccount` starts with the beneficiary wallet, allocated_tokens (u64) and claimed_tokens (u64).
+pub const BENEFICIARY_ACCOUNT_KEY_OFFSET: usize = DISCRIMINATOR_LEN;
+pub const BENEFICIARY_ACCOUNT_ALLOCATED_TOKENS_OFFSET: usize = BENEFICIARY_ACCOUNT_KEY_OFFSET + PUBKEY_LEN;
+pub const BENEFICIARY_ACCOUNT_CLAIMED_TOKENS_OFFSET: usize = BENEFICIARY_ACCOUNT_ALLOCATED_TOKENS_OFFSET + 8;
+
+// The published offsets; indexers hardcode them, so they must never move.
+const _: () = assert!(DATA_ACCOUNT_INITIALIZER_OFFSET == 17);
+const _: () = assert!(DATA_ACCOUNT_ESCROW_WALLET_OFFSET == 49);
+const _: () = assert!(DATA_ACCOUNT_TOKEN_MINT_OFFSET == 81);
+const _: () = assert!(DATA_ACCOUNT_DECIMALS_OFFSET == 113);
+const _: () = assert!(BENEFICIARY_ACCOUNT_KEY_OFFSET == 8);
+const _: () = assert!(BENEFICIARY_ACCOUNT_ALLOCATED_TOKENS_OFFSET == 40);
+const _: () = assert!(BENEFICIARY_ACCOUNT_CLAIMED_TOKENS_OFFSET == 48);
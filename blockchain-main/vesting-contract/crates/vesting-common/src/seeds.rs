@@ -0,0 +1,62 @@
+// PDA seed prefixes. Every program account is derived from one of these followed by the keys
+// documented next to it (see also "PDA Seeds" in the docs).
+
+// ["data_account", token_mint], plus schedule_id as u64 little-endian for seed version 2
+pub const DATA_ACCOUNT_SEED: &[u8] = b"data_account";
+// ["escrow_wallet", token_mint], plus schedule_id as u64 little-endian for seed version 2
+pub const ESCROW_WALLET_SEED: &[u8] = b"escrow_wallet";
+// ["beneficiary", data_account, beneficiary_wallet]
+pub const BENEFICIARY_SEED: &[u8] = b"beneficiary";
+// ["claim_record", beneficiary_account, claim_count as u32 little-endian]
+pub const CLAIM_RECORD_SEED: &[u8] = b"claim_record";
+// ["config"]
+pub const CONFIG_SEED: &[u8] = b"config";
+// ["release_proposal", data_account]
+pub const RELEASE_PROPOSAL_SEED: &[u8] = b"release_proposal";
+// ["invitation", data_account, invite_key]
+pub const INVITATION_SEED: &[u8] = b"invitation";
+// ["vesting_leg", data_account, leg_index as u8]
+pub const VESTING_LEG_SEED: &[u8] = b"vesting_leg";
+// ["leg_escrow", data_account, leg_mint]
+pub const LEG_ESCROW_SEED: &[u8] = b"leg_escrow";
+// ["voter-weight-record", realm, token_mint, beneficiary_wallet], as SPL Governance plugins expect
+pub const VOTER_WEIGHT_RECORD_SEED: &[u8] = b"voter-weight-record";
+// ["position_mint", beneficiary_account]
+pub const POSITION_MINT_SEED: &[u8] = b"position_mint";
+// ["unwrap", beneficiary_account]
+pub const UNWRAP_SEED: &[u8] = b"unwrap";
+// ["schedule_template", authority, template_id as u64 little-endian]
+pub const SCHEDULE_TEMPLATE_SEED: &[u8] = b"schedule_template";
+// ["creator_registry", creator]
+pub const CREATOR_REGISTRY_SEED: &[u8] = b"creator_registry";
+// ["blacklist", data_account]
+pub const BLACKLIST_SEED: &[u8] = b"blacklist";
+// ["release_calendar", data_account]
+pub const RELEASE_CALENDAR_SEED: &[u8] = b"release_calendar";
+// ["payout_escrow", data_account]
+pub const PAYOUT_ESCROW_SEED: &[u8] = b"payout_escrow";
+// ["vote_lock", beneficiary_account]
+pub const VOTE_LOCK_SEED: &[u8] = b"vote_lock";
+// ["statement", beneficiary_account]
+pub const STATEMENT_SEED: &[u8] = b"statement";
+// ["bonus_escrow", data_account]
+pub const BONUS_ESCROW_SEED: &[u8] = b"bonus_escrow";
+// ["mock_clock"]; only used by builds with the `mock-clock` feature
+pub const MOCK_CLOCK_SEED: &[u8] = b"mock_clock";
+// ["beneficiary_index", data_account, index as u32 little-endian]
+pub const BENEFICIARY_INDEX_SEED: &[u8] = b"beneficiary_index";
+// ["swap", beneficiary_account]; temporary, closed within `claim_and_swap`
+pub const SWAP_SEED: &[u8] = b"swap";
+// ["swap_authority", beneficiary_account]
+pub const SWAP_AUTHORITY_SEED: &[u8] = b"swap_authority";
+// ["swap_config"]
+pub const SWAP_CONFIG_SEED: &[u8] = b"swap_config";
+// ["program_state"]
+pub const PROGRAM_STATE_SEED: &[u8] = b"program_state";
+// ["cohort", data_account, cohort_id as u32 little-endian]
+pub const COHORT_SEED: &[u8] = b"cohort";
+
+// Seed layouts of a schedule's data account and escrow, recorded as the data account's `seed_version`.
+// v1 schedules are keyed by their mint alone, so a mint has at most one; v2 schedules add a schedule id.
+pub const SEED_VERSION_V1: u8 = 1;
+pub const SEED_VERSION_V2: u8 = 2;
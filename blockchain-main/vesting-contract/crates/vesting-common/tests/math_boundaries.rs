@@ -0,0 +1,137 @@
+// Boundary checks of the scaling and percentage math at the edges of a u64: results that fit
+// must be exact, anything larger must be rejected rather than wrapped or panicking.
+//
+//     cargo test -p vesting-common
+
+use vesting_common::curve::{Curve, CurvePoint};
+use vesting_common::math::{
+    bps_of, compound_bonus, mul_div, to_raw_amount, vested_amount, vested_amount_rounded, Rounding, MAX_BONUS_MONTHS,
+    SECONDS_PER_MONTH,
+};
+
+// Largest whole-token amount that still fits in a u64 at 9 decimals (~18.4B tokens).
+const MAX_TOKENS_AT_9_DECIMALS: u64 = u64::MAX / 1_000_000_000;
+
+#[test]
+fn raw_amounts_at_nine_decimals() {
+    assert_eq!(to_raw_amount(1, 9), Some(1_000_000_000));
+    assert_eq!(
+        to_raw_amount(MAX_TOKENS_AT_9_DECIMALS, 9),
+        Some(MAX_TOKENS_AT_9_DECIMALS * 1_000_000_000)
+    );
+    assert_eq!(to_raw_amount(MAX_TOKENS_AT_9_DECIMALS + 1, 9), None);
+    assert_eq!(to_raw_amount(u64::MAX, 9), None);
+}
+
+#[test]
+fn raw_amounts_at_extreme_decimals() {
+    assert_eq!(to_raw_amount(u64::MAX, 0), Some(u64::MAX));
+    assert_eq!(to_raw_amount(1, 19), Some(10_000_000_000_000_000_000));
+    assert_eq!(to_raw_amount(2, 19), None);
+    // 10^20 doesn't fit a u64 but fits the u128 intermediate; zero tokens are still zero
+    assert_eq!(to_raw_amount(0, 20), Some(0));
+    assert_eq!(to_raw_amount(1, 20), None);
+    // 10^39 overflows even the u128 intermediate
+    assert_eq!(to_raw_amount(0, 39), None);
+    assert_eq!(to_raw_amount(1, u8::MAX), None);
+}
+
+#[test]
+fn mul_div_keeps_full_precision() {
+    // The product overflows a u64 but the quotient fits
+    assert_eq!(mul_div(u64::MAX, u64::MAX, u64::MAX), Some(u64::MAX));
+    assert_eq!(mul_div(u64::MAX, 3, 4), Some(u64::MAX / 4 * 3 + 2));
+    assert_eq!(mul_div(7, 1, 2), Some(3));
+    assert_eq!(mul_div(u64::MAX, 2, 1), None);
+    assert_eq!(mul_div(1, 1, 0), None);
+}
+
+#[test]
+fn bps_of_the_largest_amounts() {
+    assert_eq!(bps_of(u64::MAX, 10_000), Some(u64::MAX));
+    assert_eq!(bps_of(u64::MAX, 5_000), Some(u64::MAX / 2));
+    assert_eq!(bps_of(u64::MAX, 0), Some(0));
+    assert_eq!(bps_of(9_999, 1), Some(0));
+    assert_eq!(bps_of(u64::MAX, 10_001), None);
+    assert_eq!(vested_amount(u64::MAX, 9_999), (u64::MAX as u128 * 9_999 / 10_000) as u64);
+    assert_eq!(vested_amount(u64::MAX, u16::MAX), u64::MAX);
+}
+
+#[test]
+fn rounding_policies_of_partial_shares() {
+    // 33% of 5 is 1.65
+    assert_eq!(vested_amount_rounded(5, 3_300, Rounding::Down), 1);
+    assert_eq!(vested_amount_rounded(5, 3_300, Rounding::Nearest), 2);
+    assert_eq!(vested_amount_rounded(5, 3_300, Rounding::Up), 2);
+    // Halves round up; 12% of 5 is 0.6
+    assert_eq!(vested_amount_rounded(7, 5_000, Rounding::Nearest), 4);
+    assert_eq!(vested_amount_rounded(5, 1_200, Rounding::Down), 0);
+    assert_eq!(vested_amount_rounded(5, 1_200, Rounding::Nearest), 1);
+    // Exact shares and the extremes don't depend on the rounding
+    for rounding in [Rounding::Down, Rounding::Nearest, Rounding::Up] {
+        assert_eq!(vested_amount_rounded(10, 5_000, rounding), 5);
+        assert_eq!(vested_amount_rounded(u64::MAX, 0, rounding), 0);
+        assert_eq!(vested_amount_rounded(u64::MAX, 10_000, rounding), u64::MAX);
+        assert!(vested_amount_rounded(u64::MAX, 9_999, rounding) < u64::MAX);
+    }
+    assert_eq!(vested_amount_rounded(u64::MAX, 9_999, Rounding::Down), vested_amount(u64::MAX, 9_999));
+}
+
+#[test]
+fn back_weighted_curve_vests_ten_twenty_thirty_forty() {
+    let curve = Curve::BackWeighted;
+    let yearly: Vec<u16> = (1..=4).map(|year| curve.vested_bps(year * 12, 48)).collect();
+    assert_eq!(yearly, [1_000, 3_000, 6_000, 10_000]);
+    // Within a year each month vests the same amount: a twelfth of the first year's 10% in month 1
+    assert_eq!(curve.vested_bps(1, 48), 83);
+    assert_eq!(curve.vested_bps(0, 48), 0);
+    // Over three years the weights are 1/6, 2/6 and 3/6
+    assert_eq!(curve.vested_bps(12, 36), 1_666);
+    assert_eq!(curve.vested_bps(24, 36), 5_000);
+    assert_eq!(curve.vested_bps(255, 36), 10_000);
+}
+
+#[test]
+fn stepped_and_custom_curves() {
+    // Yearly tranches of a 30-month schedule: 40% after each of the first two years, the rest at the end
+    assert_eq!(Curve::Stepped.vested_bps(11, 30), 0);
+    assert_eq!(Curve::Stepped.vested_bps(12, 30), 4_000);
+    assert_eq!(Curve::Stepped.vested_bps(29, 30), 8_000);
+    assert_eq!(Curve::Stepped.vested_bps(30, 30), 10_000);
+    // Schedules without vesting months are fully vested whatever the curve
+    assert_eq!(Curve::Stepped.vested_bps(0, 0), 10_000);
+
+    let point = |month, cumulative_bps| CurvePoint { month, cumulative_bps };
+    let curve = Curve::custom(&[point(6, 3_000), point(12, 3_000), point(24, 10_000)]).unwrap();
+    assert_eq!(curve.vested_bps(3, 24), 1_500);
+    assert_eq!(curve.vested_bps(9, 24), 3_000);
+    assert_eq!(curve.vested_bps(18, 24), 6_500);
+    assert_eq!(curve.vested_bps(24, 24), 10_000);
+    // A curve ending early is fully vested from its last point
+    assert_eq!(curve.vested_bps(30, 36), 10_000);
+
+    assert_eq!(Curve::custom(&[]), None);
+    assert_eq!(Curve::custom(&[point(0, 10_000)]), None);
+    assert_eq!(Curve::custom(&[point(6, 5_000)]), None);
+    assert_eq!(Curve::custom(&[point(6, 5_000), point(6, 10_000)]), None);
+    assert_eq!(Curve::custom(&[point(6, 5_000), point(12, 4_000), point(18, 10_000)]), None);
+    assert_eq!(Curve::custom(&[point(1, 10_000); 9]), None);
+}
+
+#[test]
+fn bonus_compounds_monthly() {
+    let month = SECONDS_PER_MONTH;
+    // 12% a year is 1% a month: 1% of 1_000_000, then 1% of 1_010_000
+    assert_eq!(compound_bonus(1_000_000, 1_200, month), Some(10_000));
+    assert_eq!(compound_bonus(1_000_000, 1_200, 2 * month), Some(20_100));
+    // Partial months, zero rates and clocks behind the last claim earn nothing
+    assert_eq!(compound_bonus(1_000_000, 1_200, month - 1), Some(0));
+    assert_eq!(compound_bonus(1_000_000, 0, 24 * month), Some(0));
+    assert_eq!(compound_bonus(1_000_000, 1_200, -month), Some(0));
+    // Compounding stops after MAX_BONUS_MONTHS
+    assert_eq!(
+        compound_bonus(1_000_000, 10_000, MAX_BONUS_MONTHS * month),
+        compound_bonus(1_000_000, 10_000, i64::MAX)
+    );
+    assert_eq!(compound_bonus(u64::MAX, 10_000, 12 * month), None);
+}
@@ -0,0 +1,321 @@
+// Property-based checks of the vesting math: random schedules, allocations and claim times,
+// asserting the invariants the program relies on.
+//
+//     cargo test -p vesting-common
+//
+// Set PROPTEST_CASES to run more cases than the default 256 per property.
+
+use proptest::prelude::*;
+use vesting_common::curve::{Curve, CurvePoint, MAX_CURVE_POINTS};
+use vesting_common::math::{
+    add_calendar_months, claimable_amount, mul_div, to_raw_amount, vested_amount, vested_amount_rounded, Rounding,
+    Schedule, SECONDS_PER_DAY,
+};
+
+// Start timestamps between 1970 and ~2096, and claim times up to 15 years around them.
+const MAX_START: i64 = 4_000_000_000;
+const MAX_OFFSET: i64 = 15 * 365 * SECONDS_PER_DAY;
+
+fn schedule() -> impl Strategy<Value = Schedule> {
+    (
+        (0..MAX_START, 0u8..=120, 0u8..=120, 0u8..=100),
+        (any::<bool>(), 0u16..=10_000, 0u32..=2 * 365 * 86_400),
+        (any::<bool>(), any::<bool>(), prop::option::of(0..MAX_OFFSET)),
+        // Half of the schedules are interval-based
+        prop_oneof![Just((0u32, 0u16)), (1u32..=365 * 86_400, 0u16..=500)],
+        curve(),
+    )
+        .prop_map(
+            |(
+                (start_timestamp, vesting_months, cliff_months, percent_available),
+                (calendar_months, tge_unlock_bps, lockup_seconds),
+                (time_vesting, manual_release, accelerate_after),
+                (interval_seconds, total_intervals),
+                curve,
+            )| Schedule {
+                start_timestamp,
+                vesting_months,
+                // The program keeps the cliff within the vesting period
+                cliff_months: cliff_months.min(vesting_months),
+                percent_available,
+                calendar_months,
+                accelerated_at: accelerate_after.map_or(0, |offset| start_timestamp + offset),
+                cancelled: false,
+                final_vested_bps: 0,
+                tge_unlock_bps,
+                lockup_seconds,
+                time_vesting,
+                manual_release,
+                interval_seconds,
+                total_intervals,
+                curve,
+            },
+        )
+}
+
+// Built-in curves, and custom ones of up to `MAX_CURVE_POINTS` points (which may end before or after the
+// schedule's last month).
+fn curve() -> impl Strategy<Value = Curve> {
+    let custom = prop::collection::vec((1u8..=24, 0u16..=3_000), 0..MAX_CURVE_POINTS).prop_map(|steps| {
+        let mut points = Vec::new();
+        let (mut month, mut cumulative_bps) = (0u8, 0u16);
+        for (months, bps) in steps {
+            month += months;
+            cumulative_bps = cumulative_bps.saturating_add(bps).min(10_000);
+            points.push(CurvePoint { month, cumulative_bps });
+        }
+        points.push(CurvePoint { month: month + 1, cumulative_bps: 10_000 });
+        Curve::custom(&points).unwrap()
+    });
+    prop_oneof![Just(Curve::Linear), Just(Curve::Stepped), Just(Curve::BackWeighted), custom]
+}
+
+fn rounding() -> impl Strategy<Value = Rounding> {
+    prop_oneof![Just(Rounding::Down), Just(Rounding::Nearest), Just(Rounding::Up)]
+}
+
+// Claim times relative to the start, in increasing order (some before the start).
+fn claim_times() -> impl Strategy<Value = Vec<i64>> {
+    prop::collection::vec(-MAX_OFFSET / 10..MAX_OFFSET, 1..24).prop_map(|mut offsets| {
+        offsets.sort_unstable();
+        offsets
+    })
+}
+
+// Freezes the schedule the way `execute_cancel` does at `now`.
+fn cancel(schedule: &mut Schedule, now: i64, penalty_bps: u16) {
+    let vested_bps = schedule.vested_bps(now) as u64;
+    schedule.final_vested_bps = (vested_bps + (10_000 - vested_bps) * penalty_bps as u64 / 10_000) as u16;
+    schedule.cancelled = true;
+}
+
+// Schedules as above, some of them cancelled (with a random penalty) at a random time.
+fn maybe_cancelled_schedule() -> impl Strategy<Value = Schedule> {
+    (schedule(), prop::option::of((-MAX_OFFSET / 10..MAX_OFFSET, 0u16..=10_000))).prop_map(|(mut schedule, cancel_at)| {
+        if let Some((offset, penalty_bps)) = cancel_at {
+            let at = schedule.start_timestamp + offset;
+            cancel(&mut schedule, at, penalty_bps);
+        }
+        schedule
+    })
+}
+
+proptest! {
+    #[test]
+    fn vested_amount_never_exceeds_the_allocation(allocated in any::<u64>(), bps in 0u16..=u16::MAX) {
+        let vested = vested_amount(allocated, bps);
+        prop_assert!(vested <= allocated);
+        if bps >= 10_000 {
+            prop_assert_eq!(vested, allocated);
+        }
+    }
+
+    #[test]
+    fn vested_amount_is_monotone_in_bps(allocated in any::<u64>(), a in 0u16..=10_000, b in 0u16..=10_000) {
+        let (low, high) = (a.min(b), a.max(b));
+        prop_assert!(vested_amount(allocated, low) <= vested_amount(allocated, high));
+    }
+
+    #[test]
+    fn vested_bps_stays_within_bounds(schedule in schedule(), offset in -MAX_OFFSET..MAX_OFFSET) {
+        prop_assert!(schedule.vested_bps(schedule.start_timestamp + offset) <= 10_000);
+    }
+
+    #[test]
+    fn vesting_is_monotone_in_time(schedule in schedule(), times in claim_times(), allocated in any::<u64>()) {
+        let mut previous_bps = 0;
+        for offset in times {
+            let bps = schedule.vested_bps(schedule.start_timestamp + offset);
+            prop_assert!(bps >= previous_bps, "vested share went from {} to {} bps", previous_bps, bps);
+            prop_assert!(vested_amount(allocated, bps) >= vested_amount(allocated, previous_bps));
+            previous_bps = bps;
+        }
+    }
+
+    #[test]
+    fn next_unlock_is_in_the_future(schedule in schedule(), offset in -MAX_OFFSET / 10..MAX_OFFSET) {
+        let now = schedule.start_timestamp + offset;
+        let next = schedule.next_unlock_timestamp(now);
+        if next != 0 {
+            prop_assert!(next > now);
+            // Curves with flat stretches skip straight to the month where vesting grows again
+            if schedule.curve != Curve::Linear && !schedule.is_interval() {
+                prop_assert!(schedule.time_vested_bps(next) > schedule.time_vested_bps(now));
+            }
+        }
+    }
+
+    #[test]
+    fn accruals_of_consecutive_periods_add_up(
+        schedule in maybe_cancelled_schedule(),
+        times in claim_times(),
+        allocated in any::<u64>(),
+    ) {
+        let first = schedule.start_timestamp + times[0];
+        let mut total = 0u64;
+        for pair in times.windows(2) {
+            total += schedule.accrued_amount(
+                allocated,
+                schedule.start_timestamp + pair[0],
+                schedule.start_timestamp + pair[1],
+            );
+        }
+        let last = schedule.start_timestamp + times[times.len() - 1];
+        prop_assert_eq!(total, schedule.accrued_amount(allocated, first, last));
+        prop_assert!(vested_amount(allocated, schedule.reported_vested_bps(last)) <= allocated);
+    }
+
+    #[test]
+    fn time_vesting_completes_at_the_end(schedule in schedule(), allocated in any::<u64>(), after in 0..MAX_OFFSET) {
+        let schedule = Schedule { time_vesting: true, manual_release: false, ..schedule };
+        let now = schedule.vesting_end_timestamp().saturating_add(after);
+        prop_assert_eq!(schedule.vested_bps(now), 10_000);
+        prop_assert_eq!(claimable_amount(allocated, 0, schedule.vested_bps(now)), allocated);
+    }
+
+    #[test]
+    fn cumulative_claims_never_exceed_the_allocation(
+        schedule in schedule(),
+        times in claim_times(),
+        allocated in any::<u64>(),
+        cancel_at in prop::option::of((any::<prop::sample::Index>(), 0u16..=10_000)),
+    ) {
+        let mut schedule = schedule;
+        let cancel_index = cancel_at.map(|(index, penalty_bps)| (index.index(times.len()), penalty_bps));
+        let mut claimed = 0u64;
+        let mut previous_vested = 0u64;
+        for (index, offset) in times.iter().enumerate() {
+            let now = schedule.start_timestamp + offset;
+            if let Some((cancel_index, penalty_bps)) = cancel_index {
+                if index == cancel_index {
+                    cancel(&mut schedule, now, penalty_bps);
+                }
+            }
+            let bps = schedule.vested_bps(now);
+            let vested = vested_amount(allocated, bps);
+            // A cancellation freezes at least what had vested, so claims stay monotone too
+            prop_assert!(vested >= previous_vested);
+            previous_vested = vested;
+
+            let claim = claimable_amount(allocated, claimed, bps);
+            claimed = claimed.checked_add(claim).expect("cumulative claims overflowed");
+            prop_assert!(claimed <= allocated);
+            prop_assert_eq!(claimed, vested);
+            // Claiming again at the same time yields nothing
+            prop_assert_eq!(claimable_amount(allocated, claimed, bps), 0);
+        }
+    }
+
+    #[test]
+    fn total_distributed_never_exceeds_the_escrow(
+        schedule in schedule(),
+        deposit in 1u64..=u64::MAX / 2,
+        weights in prop::collection::vec(0u64..=1_000, 1..10),
+        claims in prop::collection::vec((any::<prop::sample::Index>(), -MAX_OFFSET / 10..MAX_OFFSET), 1..40),
+        decimals in 0u8..=12,
+    ) {
+        // Split at most the deposit among the beneficiaries, as `add_beneficiaries` enforces
+        let total_weight: u64 = weights.iter().sum::<u64>().max(1);
+        let allocations: Vec<u64> = weights
+            .iter()
+            .map(|weight| (deposit as u128 * *weight as u128 / total_weight as u128) as u64)
+            .collect();
+        prop_assert!(allocations.iter().map(|a| *a as u128).sum::<u128>() <= deposit as u128);
+
+        let mut claims = claims;
+        claims.sort_unstable_by_key(|(_, offset)| *offset);
+        let mut claimed = vec![0u64; allocations.len()];
+        let mut distributed = 0u64;
+        for (beneficiary, offset) in claims {
+            let index = beneficiary.index(allocations.len());
+            let bps = schedule.vested_bps(schedule.start_timestamp + offset);
+            let claim = claimable_amount(allocations[index], claimed[index], bps);
+            claimed[index] += claim;
+            distributed += claim;
+            prop_assert!(distributed <= deposit);
+        }
+        // Scaled to raw units, whatever fits the deposit also fits what was paid out of it
+        if let Some(deposit_raw) = to_raw_amount(deposit, decimals) {
+            let distributed_raw = to_raw_amount(distributed, decimals);
+            prop_assert!(distributed_raw.is_some_and(|raw| raw <= deposit_raw));
+        }
+    }
+
+    #[test]
+    fn rounded_vesting_is_bounded_and_monotone(
+        allocated in any::<u64>(),
+        a in 0u16..=10_000,
+        b in 0u16..=10_000,
+        rounding in rounding(),
+    ) {
+        let (low, high) = (a.min(b), a.max(b));
+        let vested = vested_amount_rounded(allocated, high, rounding);
+        prop_assert!(vested <= allocated);
+        prop_assert!(vested_amount_rounded(allocated, low, rounding) <= vested);
+        // Any rounding is within a token of rounding down
+        prop_assert!(vested - vested_amount(allocated, high) <= 1);
+    }
+
+    #[test]
+    fn rounded_distributions_never_exceed_the_escrow(
+        schedule in maybe_cancelled_schedule(),
+        deposit in 1u64..=u64::MAX / 2,
+        weights in prop::collection::vec(0u64..=1_000, 1..10),
+        times in claim_times(),
+        // Who claims at each of the `times`
+        claimants in prop::collection::vec(any::<prop::sample::Index>(), 24),
+        rounding in rounding(),
+    ) {
+        let total_weight: u64 = weights.iter().sum::<u64>().max(1);
+        let allocations: Vec<u64> = weights
+            .iter()
+            .map(|weight| (deposit as u128 * *weight as u128 / total_weight as u128) as u64)
+            .collect();
+        let total_allocated: u64 = allocations.iter().sum();
+
+        if schedule.cancelled {
+            // What `execute_cancel` keeps in escrow covers every rounded frozen share
+            let frozen: u64 = allocations
+                .iter()
+                .map(|allocated| vested_amount_rounded(*allocated, schedule.final_vested_bps, rounding))
+                .sum();
+            let pool = mul_div(total_allocated, schedule.final_vested_bps as u64, 10_000).unwrap();
+            let retained = match rounding {
+                Rounding::Down => pool,
+                _ => (pool + allocations.len() as u64).min(total_allocated),
+            };
+            prop_assert!(frozen <= retained);
+        }
+
+        let mut claimed = vec![0u64; allocations.len()];
+        let mut distributed = 0u64;
+        for (offset, claimant) in times.into_iter().zip(&claimants) {
+            let index = claimant.index(allocations.len());
+            let bps = schedule.vested_bps(schedule.start_timestamp + offset);
+            let claim = vested_amount_rounded(allocations[index], bps, rounding).saturating_sub(claimed[index]);
+            claimed[index] += claim;
+            distributed += claim;
+            prop_assert!(claimed[index] <= allocations[index]);
+            prop_assert!(distributed <= deposit);
+        }
+    }
+
+    #[test]
+    fn raw_amounts_are_exact_or_rejected(tokens in any::<u64>(), decimals in 0u8..=19) {
+        // 10^19 is the largest power of ten that fits in a u64
+        let exact = 10u128.pow(decimals as u32) * tokens as u128;
+        let expected = u64::try_from(exact).ok();
+        prop_assert_eq!(to_raw_amount(tokens, decimals), expected);
+    }
+
+    #[test]
+    fn calendar_months_move_forward(start in 0..MAX_START, a in 0i64..600, b in 0i64..600) {
+        let (low, high) = (a.min(b), a.max(b));
+        let low_timestamp = add_calendar_months(start, low);
+        let high_timestamp = add_calendar_months(start, high);
+        prop_assert!(low_timestamp <= high_timestamp);
+        // A month is 28 to 31 days
+        prop_assert!(high_timestamp - low_timestamp >= (high - low) * 28 * SECONDS_PER_DAY);
+        prop_assert!(high_timestamp - low_timestamp <= (high - low) * 31 * SECONDS_PER_DAY);
+    }
+}
@@ -0,0 +1,62 @@
+// Helpers for programs that compose with token_vesting via CPI (DAO payroll, staking, ...).
+// Available with the `cpi` feature:
+//
+//     token_vesting = { path = "...", features = ["cpi"] }
+//
+// Each helper wraps the Anchor-generated `crate::cpi` call. `program` is the token_vesting program account,
+// and `signer_seeds` are the seeds of the caller's PDA when that PDA signs as `sender` (pass `&[]` otherwise).
+// For pure calculations without a CPI, use `crate::math` instead.
+
+use anchor_lang::prelude::*;
+
+use crate::cpi::{self, accounts};
+use crate::{ClaimableAmounts, NewBeneficiary, TransferResult};
+
+// Claims `amount` whole tokens (0 = everything claimable) of `accounts.beneficiary_wallet`.
+// `accounts.sender` must be the beneficiary wallet or its claim delegate. Returns what was paid out.
+pub fn claim<'info>(
+    program: AccountInfo<'info>,
+    accounts: accounts::Claim<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<TransferResult> {
+    Ok(cpi::claim(CpiContext::new_with_signer(program, accounts, signer_seeds), amount, false, None)?.get())
+}
+
+// Releases `percent` more of the schedule. `accounts.sender` must be the schedule's initializer or operator.
+pub fn release<'info>(
+    program: AccountInfo<'info>,
+    accounts: accounts::Release<'info>,
+    percent: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    cpi::release(CpiContext::new_with_signer(program, accounts, signer_seeds), percent)
+}
+
+// Adds a chunk of beneficiaries to the schedule's open batch, starting at `batch_offset` (the current `batch_cursor`).
+// `beneficiary_accounts` holds a writable (`BeneficiaryAccount` PDA, `BeneficiaryIndex` entry) pair per entry of
+// `new_beneficiaries`, in the same order, the index entries numbered on from the schedule's `beneficiary_index_len`.
+// `accounts.sender` must be the schedule's initializer or operator.
+pub fn add_beneficiaries<'info>(
+    program: AccountInfo<'info>,
+    accounts: accounts::AddBeneficiaries<'info>,
+    beneficiary_accounts: Vec<(AccountInfo<'info>, AccountInfo<'info>)>,
+    new_beneficiaries: Vec<NewBeneficiary>,
+    batch_offset: u32,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let remaining_accounts = beneficiary_accounts
+        .into_iter()
+        .flat_map(|(beneficiary, index_entry)| [beneficiary, index_entry])
+        .collect();
+    let ctx = CpiContext::new_with_signer(program, accounts, signer_seeds).with_remaining_accounts(remaining_accounts);
+    cpi::add_beneficiaries(ctx, new_beneficiaries, batch_offset)
+}
+
+// Reads the vested and claimable amounts of a beneficiary through the program's return data.
+pub fn get_claimable<'info>(
+    program: AccountInfo<'info>,
+    accounts: accounts::GetClaimable<'info>,
+) -> Result<ClaimableAmounts> {
+    Ok(cpi::get_claimable(CpiContext::new(program, accounts))?.get())
+}
@@ -3,18 +3,91 @@ use anchor_lang::prelude::*;
 // Import SPL Token program interfaces and helper functions:
 // - `Mint` represents a token mint (e.g., USDC).
 // - `TokenAccount` represents a user's or program's token holding account.
-// - `Transfer` is the instruction context for token transfers.
+// - `TransferChecked` is the instruction context for token transfers that verify the mint's decimals.
 // - `token` provides utility functions like `token::transfer`.
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, Burn, CloseAccount, Mint, MintTo, SetAuthority, Token, TokenAccount, TransferChecked};
+use anchor_spl::token::spl_token::instruction::AuthorityType;
+use anchor_lang::solana_program::program_option::COption;
 
 // Import the Associated Token Account interface.
 // Used to create or interact with associated token accounts (one per token per wallet).
 use anchor_spl::associated_token::AssociatedToken;
+// SPL Memo program interface, used by `claim_with_memo`.
+use anchor_spl::memo::{self, Memo};
 // Import `invoke_signed`, which allows programs to make Cross-Program Invocations (CPI) while using PDA signers.
-use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::program::{get_return_data, invoke_signed};
 // Import Solana's native system instructions (e.g., `create_account`, `transfer` for SOL).
 // Useful for operations involving SOL rather than SPL tokens.
 use anchor_lang::solana_program::system_instruction;
+// SHA-256, used to commit to a schedule's parameters (`commit_schedule_hash`).
+use anchor_lang::solana_program::hash::{hash, hashv};
+
+// Vesting math that doesn't depend on Anchor, usable by other programs without a CPI.
+pub use vesting_common::math;
+// Vesting curves evaluated by the schedule math.
+pub use vesting_common::curve;
+// Program version and feature flags.
+pub use vesting_common::features;
+use features::{
+    FEATURE_CANCEL_DISPOSITION, FEATURE_COHORTS, FEATURE_VESTING_CURVES, FEATURE_WITHDRAW_SPLIT, KNOWN_FEATURES,
+    MIN_SUPPORTED_VERSION, PROGRAM_VERSION,
+};
+// Account offsets and discriminators for indexers.
+pub use vesting_common::schema;
+use math::SECONDS_PER_DAY;
+use vesting_common::layout::{DATA_ACCOUNT_LEN, DATA_ACCOUNT_VERSION};
+pub use vesting_common::layout::{MAX_ANNOUNCED_RELEASES, MAX_APPROVERS, MAX_WITHDRAW_SPLITS};
+use vesting_common::seeds::{
+    BENEFICIARY_INDEX_SEED, BENEFICIARY_SEED, BLACKLIST_SEED, BONUS_ESCROW_SEED, CLAIM_RECORD_SEED, COHORT_SEED,
+    CONFIG_SEED, CREATOR_REGISTRY_SEED, DATA_ACCOUNT_SEED, ESCROW_WALLET_SEED, INVITATION_SEED, LEG_ESCROW_SEED,
+    PAYOUT_ESCROW_SEED, POSITION_MINT_SEED, PROGRAM_STATE_SEED, RELEASE_CALENDAR_SEED, RELEASE_PROPOSAL_SEED,
+    SCHEDULE_TEMPLATE_SEED, SEED_VERSION_V1, SEED_VERSION_V2, STATEMENT_SEED, SWAP_AUTHORITY_SEED, SWAP_CONFIG_SEED,
+    SWAP_SEED, UNWRAP_SEED, VESTING_LEG_SEED, VOTER_WEIGHT_RECORD_SEED, VOTE_LOCK_SEED,
+};
+#[cfg(feature = "mock-clock")]
+use vesting_common::seeds::MOCK_CLOCK_SEED;
+
+// Wrappers for calling this program via CPI.
+#[cfg(feature = "cpi")]
+pub mod cpi_builders;
+// Where instructions read the current time from: the clock sysvar, or a mock clock on devnet.
+pub mod time_source;
+#[cfg(feature = "mock-clock")]
+use time_source::MockClock;
+
+// Fails with `FeatureDisabled` unless the upgrade authority has enabled `feature` (a `features::FEATURE_*` bit)
+// in the program state, so partially rolled-out instructions can be turned on per cluster.
+macro_rules! require_feature {
+    ($program_status:expr, $feature:expr) => {
+        require!($program_status.load()?.has_feature($feature), VestingError::FeatureDisabled)
+    };
+}
+
+// The `NewScheduleAccounts` of a context creating a schedule, which all name these accounts alike. The escrow and
+// funding account default to the context's `escrow_wallet` and `wallet_to_withdraw_from`.
+macro_rules! new_schedule_accounts {
+    ($ctx:ident) => {
+        new_schedule_accounts!(
+            $ctx,
+            Some((&$ctx.accounts.escrow_wallet, $ctx.bumps.escrow_wallet)),
+            Some(&$ctx.accounts.wallet_to_withdraw_from)
+        )
+    };
+    ($ctx:ident, $escrow:expr, $wallet_to_withdraw_from:expr) => {
+        NewScheduleAccounts {
+            sender: &$ctx.accounts.sender,
+            token_mint: &$ctx.accounts.token_mint,
+            escrow: $escrow,
+            wallet_to_withdraw_from: $wallet_to_withdraw_from,
+            token_program: &$ctx.accounts.token_program,
+            system_program: &$ctx.accounts.system_program,
+            creator_registry: &mut $ctx.accounts.creator_registry,
+            program_state: $ctx.accounts.program_status.load()?,
+            bump: $ctx.bumps.data_account,
+            creator_registry_bump: $ctx.bumps.creator_registry,
+        }
+    };
+}
 
 
 // Declare the unique program ID for your smart contract on Solana.
@@ -30,574 +103,11452 @@ pub mod token_vesting {
     //
     // Arguments:
     // - `ctx`: The context includes all required accounts passed in from the client.
-    // - `_data_bump`: The bump used to derive the PDA for the data account (usually for signer verification).
     // - `amount`: The total amount of tokens to be vested.
-    // - `decimals`: Token precision (usually 6 or 9 for SPL tokens).
+    // - `decimals`: Token precision (usually 6 or 9 for SPL tokens); must match `token_mint.decimals`.
     // - `start_timestamp`: The UNIX timestamp at which vesting should begin.    
+    // - `is_cancellable`: Whether the initializer may cancel the schedule or withdraw unclaimed tokens.
+    // - `calendar_months`: Count real calendar months instead of 30-day months.
+    // - `retroactive`: Allow a `start_timestamp` in the past (e.g. back-dated employee grants);
+    //   time elapsed before initialization then counts as vested on the first claim.
 
     
     
 
 pub fn initialize(
     ctx: Context<Initialize>,
-    _data_bump: u8,
     amount: u64,
     decimals: u8,
     start_timestamp: i64, // NEW ARG
+    is_cancellable: bool,
+    calendar_months: bool,
+    retroactive: bool,
+    cancellation_penalty_bps: u16,
+    guardian: Option<Pubkey>,
+    tge_unlock_bps: u16,
+    lockup_seconds: u32,
+    release_mode: ReleaseMode,
+    name: [u8; 32],
+    uri: [u8; 128],
 ) -> Result<()> {
-    
-    // Function logic goes here...
-    // Get a mutable reference to the data account (PDA) where vesting configuration will be stored.
-       let data_account = &mut ctx.accounts.data_account;
-    // Ensure the vesting amount is greater than zero.
-// If not, throw a custom error `VestingError::ZeroVestingAmount`.
+        // Validation, the data account fields, the escrow deposit and the creator registry entry are shared
+        // with the other `initialize_*` variants in `init_schedule`.
+        let now = time_source::now(ctx.remaining_accounts)?;
+        init_schedule(
+            &mut ctx.accounts.data_account,
+            ScheduleParams {
+                amount,
+                decimals,
+                start_timestamp,
+                retroactive,
+                vesting_months: 36,
+                cliff_months: 0,
+                calendar_months,
+                is_cancellable,
+                cancellation_penalty_bps,
+                guardian,
+                tge_unlock_bps,
+                lockup_seconds,
+                release_mode,
+                name,
+                uri,
+                schedule_id: None,
+                funding: ScheduleFunding::Full,
+            },
+            new_schedule_accounts!(ctx),
+            now,
+        )
+    }
 
-        require!(amount > 0, VestingError::ZeroVestingAmount);
-    // Initialize vesting state variables in the data account:
-    // No tokens are available to claim initially; vesting will unlock over time.
-
-        data_account.percent_available = 0;
-    // Store the total token amount to be vested.
-        data_account.token_amount = amount;
-     // Store token precision (e.g., 6 or 9 for SPL tokens).
-        data_account.decimals = decimals;
-     // Save the initializer's public key (i.e., the user who called `initialize`).
-        data_account.initializer = ctx.accounts.sender.key();
-     // Save the public key of the escrow wallet where tokens are held.
-        data_account.escrow_wallet = ctx.accounts.escrow_wallet.key();
-    // Store the token mint address (i.e., the type of SPL token being vested).
-        data_account.token_mint = ctx.accounts.token_mint.key();
-     // Set the vesting period to 36 months (3 years).
-        data_account.vesting_months = 36;
-     // Record the UNIX timestamp when vesting should start.
-        data_account.start_timestamp = start_timestamp;
-
-    // Create a new SPL token `Transfer` instruction context.
-// This struct tells the Anchor SPL Token CPI which accounts to use for the transfer:
-//
-// - `from`: The token account from which tokens will be withdrawn.
-// - `to`: The escrow wallet token account where tokens will be deposited.
-// - `authority`: The signer/owner of the `from` token account (must approve the transfer).
-        let transfer_instruction = Transfer {
-            from: ctx.accounts.wallet_to_withdraw_from.to_account_info(), // Source token account
-            to: ctx.accounts.escrow_wallet.to_account_info(),  // Destination escrow token account
-            authority: ctx.accounts.sender.to_account_info(), // Owner/signer of the source account
-        
-        };
-    // Create a new Cross-Program Invocation (CPI) context for the SPL Token `transfer` instruction.
-//
-// This context tells Anchor how to perform the token transfer by specifying:
-// - The SPL Token program to invoke (`token_program`).
-// - The previously defined `transfer_instruction` which includes `from`, `to`, and `authority`.
-//
-// This context is later passed to `token::transfer(...)` to execute the actual transfer.
+    // Same as `initialize`, but derives the data account and escrow from the v2 seeds, which add `schedule_id`
+    // after the mint, so a mint can have any number of schedules (one per id). Every other instruction works
+    // with both seed versions.
+    pub fn initialize_v2(
+        ctx: Context<InitializeV2>,
+        schedule_id: u64,
+        amount: u64,
+        decimals: u8,
+        start_timestamp: i64,
+        is_cancellable: bool,
+        calendar_months: bool,
+        retroactive: bool,
+        cancellation_penalty_bps: u16,
+        guardian: Option<Pubkey>,
+        tge_unlock_bps: u16,
+        lockup_seconds: u32,
+        release_mode: ReleaseMode,
+        name: [u8; 32],
+        uri: [u8; 128],
+    ) -> Result<()> {
+        let now = time_source::now(ctx.remaining_accounts)?;
+        init_schedule(
+            &mut ctx.accounts.data_account,
+            ScheduleParams {
+                amount,
+                decimals,
+                start_timestamp,
+                retroactive,
+                vesting_months: 36,
+                cliff_months: 0,
+                calendar_months,
+                is_cancellable,
+                cancellation_penalty_bps,
+                guardian,
+                tge_unlock_bps,
+                lockup_seconds,
+                release_mode,
+                name,
+                uri,
+                schedule_id: Some(schedule_id),
+                funding: ScheduleFunding::Full,
+            },
+            new_schedule_accounts!(ctx),
+            now,
+        )
+    }
 
-        let cpi_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),// SPL Token Program account
-            transfer_instruction,  // Accounts required for the transfer
+    // `initialize` and `add_beneficiaries` in one instruction, so the escrow is never funded without its
+    // beneficiaries. `remaining_accounts` holds a (beneficiary PDA, beneficiary index entry) pair per entry, as in
+    // `add_beneficiaries`, with at most `MAX_BENEFICIARIES_PER_CHUNK` entries; the allocations must fit in `amount`.
+    // No batch is left open, so further beneficiaries are added with `add_beneficiaries` from offset 0.
+    pub fn initialize_with_beneficiaries<'info>(
+        ctx: Context<'_, '_, '_, 'info, Initialize<'info>>,
+        amount: u64,
+        decimals: u8,
+        start_timestamp: i64,
+        is_cancellable: bool,
+        calendar_months: bool,
+        retroactive: bool,
+        cancellation_penalty_bps: u16,
+        guardian: Option<Pubkey>,
+        tge_unlock_bps: u16,
+        lockup_seconds: u32,
+        release_mode: ReleaseMode,
+        name: [u8; 32],
+        uri: [u8; 128],
+        new_beneficiaries: Vec<NewBeneficiary>,
+    ) -> Result<()> {
+        require!(
+            new_beneficiaries.len() <= MAX_BENEFICIARIES_PER_CHUNK,
+            VestingError::BatchChunkTooLarge
         );
+        let now = time_source::now(ctx.remaining_accounts)?;
+        init_schedule(
+            &mut ctx.accounts.data_account,
+            ScheduleParams {
+                amount,
+                decimals,
+                start_timestamp,
+                retroactive,
+                vesting_months: 36,
+                cliff_months: 0,
+                calendar_months,
+                is_cancellable,
+                cancellation_penalty_bps,
+                guardian,
+                tge_unlock_bps,
+                lockup_seconds,
+                release_mode,
+                name,
+                uri,
+                schedule_id: None,
+                funding: ScheduleFunding::Full,
+            },
+            new_schedule_accounts!(ctx),
+            now,
+        )?;
 
-      // Execute the SPL token transfer using the previously created CPI context.
-//
-// The transfer amount is calculated as:
-// `data_account.token_amount * 10^decimals`
-// This converts the human-readable token amount into base units (smallest denomination of the token),
-// e.g., for 6 decimals, 1 token = 1_000_000 base units.
-//
-// This call will transfer the full vesting amount from the sender's token account to the escrow wallet.
+        // Each allocation is checked against `amount` as it is booked
+        let data_account = &mut ctx.accounts.data_account;
+        let mut remaining = time_source::instruction_accounts(ctx.remaining_accounts).iter();
+        for new in new_beneficiaries {
+            let beneficiary_account_info = remaining.next().ok_or(VestingError::MissingRemainingAccount)?;
+            let index_account_info = remaining.next().ok_or(VestingError::MissingRemainingAccount)?;
+            create_beneficiary_account(
+                ctx.program_id,
+                data_account,
+                &ctx.accounts.sender,
+                &ctx.accounts.system_program,
+                beneficiary_account_info,
+                index_account_info,
+                &new,
+            )?;
+        }
+        emit_schedule_stats(data_account);
+        Ok(())
+    }
+
+    // `initialize` for issuers that fund the escrow over time: `amount` is the committed total that allocations and
+    // vesting are based on, but only `first_installment` is transferred now. The rest follows with
+    // `fund_installment`; until then claims are capped at each beneficiary's share of what has been funded.
+    pub fn initialize_with_installments(
+        ctx: Context<Initialize>,
+        amount: u64,
+        decimals: u8,
+        start_timestamp: i64,
+        is_cancellable: bool,
+        calendar_months: bool,
+        retroactive: bool,
+        cancellation_penalty_bps: u16,
+        guardian: Option<Pubkey>,
+        tge_unlock_bps: u16,
+        lockup_seconds: u32,
+        release_mode: ReleaseMode,
+        name: [u8; 32],
+        uri: [u8; 128],
+        first_installment: u64,
+    ) -> Result<()> {
+        let now = time_source::now(ctx.remaining_accounts)?;
+        init_schedule(
+            &mut ctx.accounts.data_account,
+            ScheduleParams {
+                amount,
+                decimals,
+                start_timestamp,
+                retroactive,
+                vesting_months: 36,
+                cliff_months: 0,
+                calendar_months,
+                is_cancellable,
+                cancellation_penalty_bps,
+                guardian,
+                tge_unlock_bps,
+                lockup_seconds,
+                release_mode,
+                name,
+                uri,
+                schedule_id: None,
+                funding: ScheduleFunding::Installments { first_installment },
+            },
+            new_schedule_accounts!(ctx),
+            now,
+        )
+    }
+
+    // Stores a reusable set of schedule parameters under the sender's key, so identical pools
+    // (e.g. one per employee cohort) can be created with `initialize_from_template`.
+    // `calendar_months` is the vesting granularity: calendar months instead of 30-day months.
+    pub fn create_template(
+        ctx: Context<CreateTemplate>,
+        template_id: u64,
+        vesting_months: u8,
+        cliff_months: u8,
+        calendar_months: bool,
+        is_cancellable: bool,
+        tge_unlock_bps: u16,
+        lockup_seconds: u32,
+        cancellation_penalty_bps: u16,
+        release_mode: ReleaseMode,
+    ) -> Result<()> {
+        require!(vesting_months > 0 && cliff_months <= vesting_months, VestingError::InvalidTemplate);
+        require!(tge_unlock_bps <= 10_000, VestingError::InvalidTgeUnlock);
+        require!(cancellation_penalty_bps <= 10_000, VestingError::InvalidCancellationPenalty);
+
+        let template = &mut ctx.accounts.template;
+        template.authority = ctx.accounts.sender.key();
+        template.template_id = template_id;
+        template.vesting_months = vesting_months;
+        template.cliff_months = cliff_months;
+        template.calendar_months = calendar_months;
+        template.is_cancellable = is_cancellable;
+        template.tge_unlock_bps = tge_unlock_bps;
+        template.lockup_seconds = lockup_seconds;
+        template.cancellation_penalty_bps = cancellation_penalty_bps;
+        template.release_mode = release_mode;
+        template.bump = ctx.bumps.template;
+        Ok(())
+    }
 
-        token::transfer(cpi_ctx, data_account.token_amount * 10u64.pow(decimals as u32))?;
+    // Creates a vesting pool for `token_mint` from a template created by the sender: only the amount is given,
+    // decimals are read from the mint and vesting starts now. Otherwise behaves like `initialize`.
+    pub fn initialize_from_template(
+        ctx: Context<InitializeFromTemplate>,
+        amount: u64,
+        name: [u8; 32],
+        uri: [u8; 128],
+    ) -> Result<()> {
+        let template = &ctx.accounts.template;
+        let now = time_source::now(ctx.remaining_accounts)?;
+        // Schedule shape and policy come from the template
+        let params = ScheduleParams {
+            amount,
+            decimals: ctx.accounts.token_mint.decimals,
+            start_timestamp: now,
+            retroactive: false,
+            vesting_months: template.vesting_months,
+            cliff_months: template.cliff_months,
+            calendar_months: template.calendar_months,
+            is_cancellable: template.is_cancellable,
+            cancellation_penalty_bps: template.cancellation_penalty_bps,
+            guardian: None,
+            tge_unlock_bps: template.tge_unlock_bps,
+            lockup_seconds: template.lockup_seconds,
+            release_mode: template.release_mode,
+            name,
+            uri,
+            schedule_id: None,
+            funding: ScheduleFunding::Full,
+        };
+        init_schedule(
+            &mut ctx.accounts.data_account,
+            params,
+            new_schedule_accounts!(ctx),
+            now,
+        )
+    }
+    // Renews a schedule: creates a vesting pool for `token_mint` with the same shape and policy as
+    // `source_data_account`, but a new amount and start. Pools are keyed by mint, so the clone is for another mint
+    // than its source. Beneficiaries are copied afterwards, in batches, by `clone_beneficiaries`.
+    pub fn clone_schedule(
+        ctx: Context<CloneSchedule>,
+        amount: u64,
+        start_timestamp: i64,
+        name: [u8; 32],
+        uri: [u8; 128],
+    ) -> Result<()> {
+        let source = &ctx.accounts.source_data_account;
+        // Nothing is escrowed for mint-vesting schedules, so there is no deposit to renew
+        require!(!source.mint_vesting, VestingError::MintVestingUnsupported);
+        let now = time_source::now(ctx.remaining_accounts)?;
+        // Schedule shape and policy come from the source schedule
+        let params = ScheduleParams {
+            amount,
+            decimals: ctx.accounts.token_mint.decimals,
+            start_timestamp,
+            retroactive: false,
+            vesting_months: source.vesting_months,
+            cliff_months: source.cliff_months,
+            calendar_months: source.calendar_months,
+            is_cancellable: source.is_cancellable,
+            cancellation_penalty_bps: source.cancellation_penalty_bps,
+            guardian: source.guardian,
+            tge_unlock_bps: source.tge_unlock_bps,
+            lockup_seconds: source.lockup_seconds,
+            release_mode: source.release_mode,
+            name,
+            uri,
+            schedule_id: None,
+            funding: ScheduleFunding::Full,
+        };
+        init_schedule(
+            &mut ctx.accounts.data_account,
+            params,
+            new_schedule_accounts!(ctx),
+            now,
+        )?;
 
+        let source = &ctx.accounts.source_data_account;
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.interval_seconds = source.interval_seconds;
+        data_account.total_intervals = source.total_intervals;
+        data_account.min_claim_interval_seconds = source.min_claim_interval_seconds;
+        data_account.max_claim_per_period = source.max_claim_per_period;
+        data_account.clock_tolerance_seconds = source.clock_tolerance_seconds;
+        data_account.non_transferable = source.non_transferable;
+        data_account.shares_mode = source.shares_mode;
+        data_account.curve = source.curve.clone();
+        data_account.withdraw_split = source.withdraw_split.clone();
+        data_account.cloned_from = Some(source.key());
+        emit!(ScheduleCloned {
+            data_account: data_account.key(),
+            source_data_account: source.key(),
+            token_amount: amount,
+            start_timestamp,
+        });
         Ok(())
     }
+
+    // Alternative to `initialize` for projects that mint on claim instead of pre-funding an escrow.
+    // The sender, the mint's current mint authority, hands it to the `data_account` PDA; `claim` then mints
+    // each claim with `mint_to`, up to the hard cap `amount` (stored as `token_amount`).
+    // Decimals are read from the mint. Mint-vesting schedules are irrevocable and have no escrow, since nothing
+    // is escrowed.
+    pub fn initialize_mint_vesting(
+        ctx: Context<InitializeMintVesting>,
+        amount: u64,
+        start_timestamp: i64,
+        vesting_months: u8,
+        cliff_months: u8,
+        calendar_months: bool,
+        tge_unlock_bps: u16,
+        lockup_seconds: u32,
+        release_mode: ReleaseMode,
+        name: [u8; 32],
+        uri: [u8; 128],
+    ) -> Result<()> {
+        let decimals = ctx.accounts.token_mint.decimals;
+        // The cap has to be mintable in raw units
+        math::to_raw_amount(amount, decimals).ok_or(VestingError::MintCapExceeded)?;
+        let now = time_source::now(ctx.remaining_accounts)?;
+        let params = ScheduleParams {
+            amount,
+            decimals,
+            start_timestamp,
+            retroactive: false,
+            vesting_months,
+            cliff_months,
+            calendar_months,
+            is_cancellable: false,
+            cancellation_penalty_bps: 0,
+            guardian: None,
+            tge_unlock_bps,
+            lockup_seconds,
+            release_mode,
+            name,
+            uri,
+            schedule_id: None,
+            funding: ScheduleFunding::Minted,
+        };
+        init_schedule(
+            &mut ctx.accounts.data_account,
+            params,
+            new_schedule_accounts!(ctx, None, None),
+            now,
+        )
+    }
      // Public instruction to release a certain percentage of the vested tokens.
 // This function increases the `percent_available` in the `data_account`,
 // making that portion of tokens claimable by the beneficiary.
 
-    pub fn release(ctx: Context<Release>, _data_bump: u8, percent: u8) -> Result<()> {
+    pub fn release(ctx: Context<Release>, percent: u8) -> Result<()> {
           // Get mutable access to the on-chain data account storing vesting state.
         let data_account = &mut ctx.accounts.data_account;
+          // Once an approver set is configured, releases must go through the multisig flow.
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+          // With a veto authority, releases have to be announced ahead with `announce_release`.
+        require!(data_account.release_veto_authority.is_none(), VestingError::ReleaseNoticeRequired);
           // Ensure that the requested percentage is not more than 100%.
         require!(percent <= 100, VestingError::InvalidPercentage);
          // Increase the `percent_available` by the given `percent`,
     // but cap the result at a maximum of 100% to prevent over-release.
-    //
-    // `saturating_add` prevents overflow.
-    // `std::cmp::min` ensures the cap at 100.
+        data_account.add_release(percent)?;
+        emit_cpi!(TokensReleased {
+            data_account: data_account.key(),
+            percent,
+            percent_available: data_account.percent_available,
+        });
+        // Successfully complete the instruction.
+
+        Ok(())
+    }
 
-        data_account.percent_available = std::cmp::min(
-            data_account.percent_available.saturating_add(percent),
-            100,
+    // `release` for many schedules in one call, e.g. a treasury's monthly releases. `remaining_accounts` holds one
+    // (data_account, token_mint) pair per schedule, data accounts writable, and `percents[i]` is released on the
+    // i-th schedule. The signer must be allowed to `release` on every one of them; any failure reverts the batch.
+    pub fn release_many<'info>(ctx: Context<'_, '_, 'info, 'info, ReleaseMany<'info>>, percents: Vec<u8>) -> Result<()> {
+        let sender = ctx.accounts.sender.key();
+        require!(
+            !percents.is_empty() && ctx.remaining_accounts.len() == percents.len() * 2,
+            VestingError::MissingRemainingAccount
         );
-        // Successfully complete the instruction.
 
+        for (accounts, percent) in ctx.remaining_accounts.chunks(2).zip(percents) {
+            let (data_info, mint_info) = (&accounts[0], &accounts[1]);
+            // Deserializing checks owner and discriminator
+            let mut data_account: Account<'info, DataAccount> = Account::try_from(data_info)?;
+            // The data account must be the PDA for this mint, derived with its stored canonical bump
+            let expected_data = Pubkey::create_program_address(
+                &[DATA_ACCOUNT_SEED, mint_info.key.as_ref(), &data_account.schedule_seed(), &[data_account.bump]],
+                ctx.program_id,
+            )
+            .map_err(|_| VestingError::InvalidDataAccount)?;
+            require_keys_eq!(data_info.key(), expected_data, VestingError::InvalidDataAccount);
+            require!(data_account.can_operate(&sender), VestingError::InvalidSender);
+            require!(!data_account.is_frozen, VestingError::ConfigFrozen);
+            require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+            require!(data_account.release_veto_authority.is_none(), VestingError::ReleaseNoticeRequired);
+            require!(percent <= 100, VestingError::InvalidPercentage);
+
+            data_account.add_release(percent)?;
+            emit_cpi!(TokensReleased {
+                data_account: data_info.key(),
+                percent,
+                percent_available: data_account.percent_available,
+            });
+            data_account.exit(ctx.program_id)?;
+        }
+        Ok(())
+    }
+
+    // Pre-commits a release calendar instead of calling `release` by hand: once a step's `timestamp` has passed,
+    // anyone can call `crank_release` to raise `percent_available` to that step's `cumulative_bps`.
+    // Replaces any previous calendar. Steps must be in increasing time order, with non-decreasing
+    // whole-percent `cumulative_bps` of at most 10_000.
+    pub fn set_release_schedule(ctx: Context<SetReleaseSchedule>, steps: Vec<ReleaseStep>) -> Result<()> {
+        let data_account = &ctx.accounts.data_account;
+        // Once an approver set is configured, releases must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(data_account.release_mode != ReleaseMode::TimeOnly, VestingError::ManualReleaseDisabled);
+        require!(steps.len() <= MAX_RELEASE_STEPS, VestingError::InvalidReleaseSchedule);
+        // `percent_available` has whole-percent granularity
+        require!(
+            steps.iter().all(|step| step.cumulative_bps <= 10_000 && step.cumulative_bps.is_multiple_of(100)),
+            VestingError::InvalidReleaseSchedule
+        );
+        require!(
+            steps.windows(2).all(|pair| pair[0].timestamp < pair[1].timestamp
+                && pair[0].cumulative_bps <= pair[1].cumulative_bps),
+            VestingError::InvalidReleaseSchedule
+        );
+
+        let release_calendar = &mut ctx.accounts.release_calendar;
+        release_calendar.data_account = data_account.key();
+        release_calendar.steps = steps;
+        release_calendar.bump = ctx.bumps.release_calendar;
+        Ok(())
+    }
+
+    // Permissionless: raises `percent_available` to the calendar value of the latest step whose timestamp has
+    // passed. A manual `release` beyond the calendar is kept; the calendar never lowers `percent_available`.
+    pub fn crank_release(ctx: Context<CrankRelease>) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let now = data_account.advance_clock(time_source::now(ctx.remaining_accounts)?)?;
+        let percent = ctx
+            .accounts
+            .release_calendar
+            .released_percent(now)
+            .saturating_sub(data_account.percent_available);
+        require!(percent > 0, VestingError::NothingToRelease);
+
+        data_account.add_release(percent)?;
+        emit_cpi!(TokensReleased {
+            data_account: data_account.key(),
+            percent,
+            percent_available: data_account.percent_available,
+        });
+        Ok(())
+    }
+
+    // Permissionless crank for schedules whose vesting has ended: once `FINALIZE_GRACE_PERIOD` has passed after the
+    // latest (extended) end, marks the schedule `completed` and releases whatever manual releases still held back,
+    // so every allocation can be claimed in full and the schedule settled and closed. The caller gets up to
+    // `FINALIZE_BOUNTY_LAMPORTS` from the rent reserve, if there is one.
+    pub fn finalize_expired(ctx: Context<FinalizeExpired>) -> Result<()> {
+        let cranker = ctx.accounts.cranker.to_account_info();
+        let data_account = &mut ctx.accounts.data_account;
+        let now = data_account.advance_clock(time_source::now(ctx.remaining_accounts)?)?;
+        require!(!data_account.completed, VestingError::ScheduleAlreadyCompleted);
+        require!(
+            now >= data_account.latest_vesting_end_timestamp().saturating_add(FINALIZE_GRACE_PERIOD),
+            VestingError::ScheduleNotExpired
+        );
+
+        data_account.completed = true;
+        data_account.percent_available = 100;
+        let bounty = std::cmp::min(FINALIZE_BOUNTY_LAMPORTS, data_account.rent_reserve_lamports);
+        if bounty > 0 {
+            draw_rent_reserve(data_account, &cranker, bounty)?;
+        }
+        emit_cpi!(VestingCompleted {
+            data_account: data_account.key(),
+            cranker: cranker.key(),
+            bounty,
+        });
+        Ok(())
+    }
+
+    // Names the wallet that can veto announced releases, e.g. a beneficiaries' representative. From then on
+    // manual releases have to be announced with `announce_release` instead of applied with `release`.
+    // It can only be set once, so the initializer can't remove the veto to release without notice.
+    pub fn set_release_veto_authority(ctx: Context<SetReleaseVetoAuthority>, veto_authority: Pubkey) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(data_account.release_veto_authority.is_none(), VestingError::VetoAuthorityAlreadySet);
+        require!(veto_authority != Pubkey::default(), VestingError::InvalidSender);
+        data_account.release_veto_authority = Some(veto_authority);
+        Ok(())
+    }
+
+    // Sets how long a beneficiary removal stays open to dispute between `schedule_removal` and `execute_removal`:
+    // `REMOVAL_DISPUTE_WINDOW_MIN` to `REMOVAL_DISPUTE_WINDOW_MAX` seconds, or 0 for the default. Removals already
+    // scheduled keep the time they were given.
+    pub fn set_removal_dispute_window(ctx: Context<SetRemovalDispute>, dispute_window_seconds: u32) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(
+            dispute_window_seconds == 0
+                || (REMOVAL_DISPUTE_WINDOW_MIN..=REMOVAL_DISPUTE_WINDOW_MAX).contains(&(dispute_window_seconds as i64)),
+            VestingError::InvalidDisputeWindow
+        );
+        data_account.removal_dispute_seconds = dispute_window_seconds;
+        Ok(())
+    }
+
+    // Sets how long beneficiaries can keep claiming between `schedule_cancel` and `execute_cancel`:
+    // `CANCEL_GRACE_PERIOD_MIN` to `CANCEL_GRACE_PERIOD_MAX` seconds, or 0 for the default. A cancellation already
+    // scheduled keeps the time it was given.
+    pub fn set_cancel_grace_period(ctx: Context<SetRemovalDispute>, grace_period_seconds: u32) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(
+            grace_period_seconds == 0
+                || (CANCEL_GRACE_PERIOD_MIN..=CANCEL_GRACE_PERIOD_MAX).contains(&(grace_period_seconds as i64)),
+            VestingError::InvalidCancelGracePeriod
+        );
+        data_account.cancel_grace_seconds = grace_period_seconds;
+        Ok(())
+    }
+
+    // Names the wallet that can veto scheduled beneficiary removals, e.g. an HR or legal arbiter. Like the release
+    // veto authority it can only be set once, so the initializer can't drop the arbiter before removing someone.
+    pub fn set_removal_arbiter(ctx: Context<SetRemovalDispute>, arbiter: Pubkey) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(data_account.removal_arbiter.is_none(), VestingError::ArbiterAlreadySet);
+        require!(arbiter != Pubkey::default(), VestingError::InvalidSender);
+        data_account.removal_arbiter = Some(arbiter);
+        Ok(())
+    }
+
+    // Announces a manual release of `percent` that can be executed from `effective_at`, at least
+    // `MIN_RELEASE_NOTICE` ahead, so beneficiaries see it on-chain before it happens. Pending announcements
+    // live in a ring buffer of `MAX_ANNOUNCED_RELEASES` slots on the data account, indexed by id.
+    pub fn announce_release(ctx: Context<AnnounceRelease>, percent: u8, effective_at: i64) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, releases must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(data_account.release_mode != ReleaseMode::TimeOnly, VestingError::ManualReleaseDisabled);
+        let now = data_account.advance_clock(time_source::now(ctx.remaining_accounts)?)?;
+        require!(
+            percent > 0 && percent <= 100 && effective_at >= now.saturating_add(MIN_RELEASE_NOTICE),
+            VestingError::InvalidReleaseAnnouncement
+        );
+
+        let id = data_account.next_release_id;
+        let slot = &mut data_account.announced_releases[id as usize % MAX_ANNOUNCED_RELEASES];
+        // A pending announcement is never overwritten; it has to be executed or vetoed first
+        require!(slot.effective_at == 0, VestingError::ReleaseQueueFull);
+        *slot = AnnouncedRelease { id, percent, effective_at };
+        data_account.next_release_id = id.wrapping_add(1);
+
+        emit!(ReleaseAnnounced {
+            data_account: data_account.key(),
+            id,
+            percent,
+            effective_at,
+        });
+        Ok(())
+    }
+
+    // Applies announcement `id` once its `effective_at` has passed. Anyone can crank it.
+    pub fn execute_announced_release(ctx: Context<ExecuteAnnouncedRelease>, id: u32) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let now = data_account.advance_clock(time_source::now(ctx.remaining_accounts)?)?;
+        let announcement = data_account.take_announced_release(id)?;
+        require!(now >= announcement.effective_at, VestingError::ReleaseNotYetEffective);
+
+        data_account.add_release(announcement.percent)?;
+        emit_cpi!(TokensReleased {
+            data_account: data_account.key(),
+            percent: announcement.percent,
+            percent_available: data_account.percent_available,
+        });
+        Ok(())
+    }
+
+    // Drops announcement `id` before it is executed. Only the veto authority can call it.
+    pub fn veto_release(ctx: Context<VetoRelease>, id: u32) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let announcement = data_account.take_announced_release(id)?;
+        emit!(ReleaseVetoed {
+            data_account: data_account.key(),
+            id,
+            percent: announcement.percent,
+            vetoed_by: ctx.accounts.sender.key(),
+        });
         Ok(())
     }
      // Public instruction to allow a beneficiary to claim their vested tokens.
 //
 // This function will transfer the currently claimable portion of tokens
 // from the escrow wallet to the beneficiary's associated token account (ATA).
+// `amount` (whole tokens) claims only part of it, e.g. for tax planning; 0 claims everything claimable.
+// What was paid out is returned through `set_return_data` (see `TransferResult`).
+// With `dry_run` the claim is only validated and computed: the would-be result is returned, nothing is paid
+// and no state changes, so wallets can simulate it for an exact preview.
+// `claim_nonce` makes retries safe: each claim can carry a client-chosen nonce above the last one used for the
+// beneficiary, and resubmitting it fails with `DuplicateClaim` instead of claiming again.
 
-    pub fn claim(ctx: Context<Claim>, data_bump: u8, _beneficiary_bump: u8) -> Result<()> {
-         // Get a reference to the signer account (beneficiary trying to claim tokens).
-        let sender = &ctx.accounts.sender;
-         // Get a reference to the escrow wallet holding the vested tokens.
-        let escrow_wallet = &ctx.accounts.escrow_wallet;
-        // Get a mutable reference to the main vesting state account (PDA).
+    pub fn claim<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Claim<'info>>,
+        amount: u64,
+        dry_run: bool,
+        claim_nonce: Option<u64>,
+    ) -> Result<TransferResult> {
+        process_claim(ctx, amount, None, dry_run, claim_nonce)
+    }
+
+    // `claim` that also writes `memo` (at most `MAX_MEMO_LEN` bytes) through the SPL Memo program, e.g. a payroll
+    // entry ID, so finance teams can reconcile the claim. Its SHA-256 is included in `TokensClaimed`.
+    pub fn claim_with_memo<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Claim<'info>>,
+        amount: u64,
+        memo: String,
+    ) -> Result<TransferResult> {
+        require!(memo.len() <= MAX_MEMO_LEN, VestingError::MemoTooLong);
+        let memo_program = ctx
+            .accounts
+            .memo_program
+            .as_ref()
+            .ok_or(VestingError::MemoProgramRequired)?;
+        memo::build_memo(
+            CpiContext::new(memo_program.to_account_info(), memo::BuildMemo {}),
+            memo.as_bytes(),
+        )?;
+        let memo_hash = anchor_lang::solana_program::hash::hash(memo.as_bytes()).to_bytes();
+        process_claim(ctx, amount, Some(memo_hash), false, None)
+    }
+
+    // `claim` for wrapped-SOL schedules: the claimed tokens go through a temporary wSOL account
+    // that is closed right away, so the beneficiary wallet receives spendable SOL.
+    // The temporary account's rent is paid by `sender` and ends up with the beneficiary as well.
+    pub fn claim_and_unwrap(ctx: Context<ClaimAndUnwrap>) -> Result<()> {
+        let sender = ctx.accounts.sender.key();
+        let beneficiary_wallet = ctx.accounts.beneficiary_wallet.key();
         let data_account = &mut ctx.accounts.data_account;
-         // Get a reference to the SPL Token program account, needed for CPI.
-        let token_program = &ctx.accounts.token_program;
-         // Get the public key of the token mint used for vesting (e.g., USDC, custom SPL token).
-        let token_mint_key = ctx.accounts.token_mint.key();
-         // Get the associated token account (ATA) of the beneficiary — this is where tokens will be sent.
-        let beneficiary_ata = &ctx.accounts.wallet_to_deposit_to;
-        // Get the decimals (precision) used by the token (e.g., 6 decimals means 1 token = 1_000_000 units).
-        let decimals = data_account.decimals;
- // Get a mutable reference to the beneficiary's vesting tracking account.
         let beneficiary = &mut ctx.accounts.beneficiary_account;
-        
-        // Ensure the sender is the actual beneficiary by comparing public keys.
-      // If they don't match, return a custom error: `BeneficiaryNotFound`
-        require_keys_eq!(beneficiary.key, sender.key(), VestingError::BeneficiaryNotFound);
-         // Get the current on-chain UNIX timestamp from the Solana clock sysvar.
-        let now = Clock::get()?.unix_timestamp;
-         // Check that the vesting has started.
-// If current time is before the `start_timestamp`, throw `VestingNotStarted` error.
-        require!(now >= data_account.start_timestamp, VestingError::VestingNotStarted);
-// Calculate how many seconds have passed since vesting started.
-        let elapsed_seconds = now - data_account.start_timestamp;
-         // Convert elapsed seconds into months.
-// Assumes 1 month = 30 days = 30 * 24 * 60 * 60 seconds.
-
-        let elapsed_months = elapsed_seconds / (30 * 24 * 60 * 60);
-        
-        // Compute the percentage of the vesting period that has passed.
-// Formula: (elapsed_months * 100) / total vesting months
-// Clamp the result at 100% to prevent overflow.
+        let config = ctx.accounts.config.as_ref();
 
-// Calculate the percentage of tokens that should be unlocked based on elapsed time.
-//
-// Formula:
-// (elapsed_months * 100) / total_vesting_months
-//
-// This gives a linear vesting percentage (e.g., 50% after 18 months of a 36-month vesting).
-// `std::cmp::min(..., 100)` ensures the value never exceeds 100%, even if extra time has passed.
-// The result is cast to `u8` since percentages are stored as 0–100.
-
-        let time_vested_percent = std::cmp::min(
-            (elapsed_months as u64 * 100) / data_account.vesting_months as u64,
-            100,
-        ) as u8;
-        // Determine the effective claimable percentage for the beneficiary.
-//
-// Take the lesser of:
-// - `time_vested_percent`: how much has vested over time
-// - `data_account.percent_available`: how much has been manually released (e.g., via `release()`)
-// This ensures both time-based and manual vesting constraints are respected.
+        require_keys_eq!(beneficiary.key, beneficiary_wallet, VestingError::BeneficiaryNotFound);
+        beneficiary.require_plain_position(data_account)?;
+        require!(
+            sender == beneficiary_wallet || beneficiary.claim_delegate == Some(sender),
+            VestingError::UnauthorizedClaimer
+        );
+        data_account.check_claim_approval(ctx.accounts.claim_approver.as_ref())?;
+        require!(!data_account.frozen, VestingError::VestingFrozen);
+        data_account.require_plain_claim()?;
 
-        let effective_claim_percent = std::cmp::min(time_vested_percent, data_account.percent_available);
-          // Calculate the total number of tokens the beneficiary is eligible to claim at this point.
-// Formula:
-// (allocated_tokens * effective_percent) / 100
-
-        let total_eligible = (beneficiary.allocated_tokens * effective_claim_percent as u64) / 100;
-        // Calculate the remaining claimable amount by subtracting already claimed tokens.
-// `saturating_sub` ensures the result is not negative (prevents underflow).
-        let claimable_amount = total_eligible.saturating_sub(beneficiary.claimed_tokens);
-         // Prepare the signer seeds for invoking CPI as the data_account PDA.
-       // Seeds used to generate the PDA:
-// - "data_account": a static string prefix
-// - token_mint_key: identifies the specific vesting mint
-// - data_bump: bump used in PDA derivation
+        let now = data_account.advance_clock(time_source::now(ctx.remaining_accounts)?)?;
+        require!(now >= data_account.start_timestamp, VestingError::VestingNotStarted);
+        require!(!data_account.claim_window_expired(now), VestingError::ClaimWindowExpired);
+        require!(
+            now >= data_account.next_claim_allowed_at(beneficiary.last_claim_timestamp),
+            VestingError::ClaimTooSoon
+        );
+        let claimable_amount = beneficiary.claimable_amount(data_account, ctx.accounts.cohort.as_deref(), now)?;
+        require!(claimable_amount > 0, VestingError::ClaimNotAllowed);
 
-        let seeds = &[b"data_account", token_mint_key.as_ref(), &[data_bump]];
-          // Wrap the seeds in the required nested format for CPI signer support.
+        let token_mint_key = ctx.accounts.token_mint.key();
+        let schedule_seed = data_account.schedule_seed();
+        let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[data_account.bump]];
         let signer_seeds = &[&seeds[..]];
 
-         // Set up the SPL Token `Transfer` instruction to move claimable tokens from the escrow to the beneficiary.
-//
-// This instruction defines the required accounts:
-// - `from`: The program's escrow wallet holding the vested tokens.
-// - `to`: The beneficiary's associated token account (where tokens will be received).
-// - `authority`: The signer of the transfer — in this case, the `data_account` PDA,
-//                which must sign the transaction using `signer_seeds` and `with_signer`.
-
+        let amount_raw = math::to_raw_amount(claimable_amount, data_account.decimals).ok_or(VestingError::MathOverflow)?;
+        let (fee_raw, fee_account) = protocol_fee(config, ctx.accounts.fee_token_account.as_ref(), amount_raw)?;
+        // Move the claim into the temporary wSOL account owned by the data account PDA
+        pay_claim(
+            data_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.token_mint,
+            Some(&ctx.accounts.escrow_wallet),
+            [(ctx.accounts.unwrap_account.to_account_info(), amount_raw - fee_raw)]
+                .into_iter()
+                .chain(fee_account.map(|fee_account| (fee_account, fee_raw))),
+            signer_seeds,
+        )?;
 
-        let transfer_instruction = Transfer {
-            from: escrow_wallet.to_account_info(), // Source: escrow holding vested tokens
-            to: beneficiary_ata.to_account_info(), // Destination: beneficiary's token account
-            authority: data_account.to_account_info(), // PDA that authorizes the transfer
+        // Closing a native token account releases all of its lamports, unwrapping the SOL to the beneficiary
+        let close_instruction = CloseAccount {
+            account: ctx.accounts.unwrap_account.to_account_info(),
+            destination: ctx.accounts.beneficiary_wallet.to_account_info(),
+            authority: data_account.to_account_info(),
         };
+        let close_cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            close_instruction,
+            signer_seeds,
+        );
+        token::close_account(close_cpi_ctx)?;
 
-        // Create a CPI (Cross-Program Invocation) context for the token transfer,
-// allowing the program to sign on behalf of a PDA (`data_account`) using `signer_seeds`.
-//
-// This is required because the escrow wallet is controlled by a PDA, not a regular user,
-// and thus needs to be signed using its derived seeds.
-//
-// Parameters:
-// - `token_program`: The SPL Token program to invoke.
-// - `transfer_instruction`: Contains `from`, `to`, and `authority` accounts for the transfer.
-// - `signer_seeds`: Seeds used to regenerate the PDA that acts as the signer (i.e., `data_account`).
+        data_account.book_claim(beneficiary, claimable_amount, now);
+        check_invariants(data_account, Some(beneficiary), Some(&ctx.accounts.escrow_wallet.to_account_info()))?;
+        emit_schedule_stats(data_account);
+        emit_cpi!(TokensClaimed {
+            data_account: data_account.key(),
+            beneficiary: beneficiary_wallet,
+            amount: amount_raw - fee_raw,
+            fee: fee_raw,
+            fee_recipient: fee_recipient(config),
+            secondary_amount: 0,
+            memo_hash: None,
+        });
+        Ok(())
+    }
+
+    // `claim` paid out in another token: the claimed tokens go through a temporary account that the whitelisted swap
+    // program trades in full into `output_token_account`, e.g. the beneficiary's USDC account. `instruction_data` and
+    // `remaining_accounts` form the swap program's instruction (the route); the temporary account's own PDA authority
+    // signs it, so the route can't touch any other account of the schedule. Fails unless the beneficiary receives at
+    // least `min_amount_out` raw output tokens. The temporary account's rent goes back to `sender`.
+    pub fn claim_and_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimAndSwap<'info>>,
+        min_amount_out: u64,
+        instruction_data: Vec<u8>,
+    ) -> Result<()> {
+        let sender = ctx.accounts.sender.key();
+        let beneficiary_wallet = ctx.accounts.beneficiary_wallet.key();
+        let data_account = &mut ctx.accounts.data_account;
+        let beneficiary = &mut ctx.accounts.beneficiary_account;
+        let config = ctx.accounts.config.as_ref();
+
+        require_keys_eq!(beneficiary.key, beneficiary_wallet, VestingError::BeneficiaryNotFound);
+        require!(!beneficiary.terminated, VestingError::BeneficiaryTerminated);
+        // Grants of schedules with an acceptance step only pay out once accepted
+        require!(!data_account.require_acceptance || beneficiary.accepted, VestingError::GrantNotAccepted);
+        // NFT-backed positions are only claimable by the NFT holder through `claim`
+        require!(beneficiary.position_mint.is_none(), VestingError::PositionNftUnsupported);
+        // Payouts are only split with a secondary recipient by `claim`
+        require!(beneficiary.secondary_recipient.is_none(), VestingError::SecondaryRecipientUnsupported);
+        require!(
+            sender == beneficiary_wallet || beneficiary.claim_delegate == Some(sender),
+            VestingError::UnauthorizedClaimer
+        );
+        data_account.check_claim_approval(ctx.accounts.claim_approver.as_ref())?;
+        require!(!data_account.frozen, VestingError::VestingFrozen);
+        data_account.require_plain_claim()?;
+
+        let now = data_account.advance_clock(time_source::now(ctx.remaining_accounts)?)?;
+        require!(now >= data_account.start_timestamp, VestingError::VestingNotStarted);
+        require!(!data_account.claim_window_expired(now), VestingError::ClaimWindowExpired);
+        require!(
+            now >= data_account.next_claim_allowed_at(beneficiary.last_claim_timestamp),
+            VestingError::ClaimTooSoon
+        );
+        let vested_bps = beneficiary.vested_bps(data_account, ctx.accounts.cohort.as_deref(), now)?;
+        let claimable_amount = data_account.cap_claim(
+            data_account
+                .cap_funded(
+                    beneficiary,
+                    beneficiary.vested_amount(data_account, vested_bps),
+                )
+                .saturating_sub(beneficiary.claimed_tokens)
+                .saturating_sub(beneficiary.vote_locked(now)),
+        );
+        require!(claimable_amount > 0, VestingError::ClaimNotAllowed);
 
+        let token_mint_key = ctx.accounts.token_mint.key();
+        let schedule_seed = data_account.schedule_seed();
+        let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[data_account.bump]];
+        let signer_seeds = &[&seeds[..]];
 
+        let amount_raw = math::to_raw_amount(claimable_amount, data_account.decimals).ok_or(VestingError::MathOverflow)?;
+        let (fee_raw, fee_account) = protocol_fee(config, ctx.accounts.fee_token_account.as_ref(), amount_raw)?;
+        // Move the claim into the temporary account the swap trades from
+        let transfer_instruction = TransferChecked {
+            from: ctx.accounts.escrow_wallet.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
+            to: ctx.accounts.swap_account.to_account_info(),
+            authority: data_account.to_account_info(),
+        };
         let cpi_ctx = CpiContext::new_with_signer(
-            token_program.to_account_info(), // The SPL Token program account
-            transfer_instruction,  // The transfer instruction with source, destination, and PDA authority
-            signer_seeds,  // Seeds needed for PDA signing
+            ctx.accounts.token_program.to_account_info(),
+            transfer_instruction,
+            signer_seeds,
         );
- // Convert the human-readable token amount to raw amount by applying the token's decimal places
-        let amount_to_transfer_raw = claimable_amount * 10u64.pow(decimals as u32);
-         // Ensure that the effective claim percentage is greater than 0 before proceeding
+        token::transfer_checked(cpi_ctx, amount_raw - fee_raw, data_account.decimals)?;
+        if let Some(fee_account) = fee_account {
+            let fee_instruction = TransferChecked {
+                from: ctx.accounts.escrow_wallet.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                to: fee_account,
+                authority: data_account.to_account_info(),
+            };
+            let fee_cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                fee_instruction,
+                signer_seeds,
+            );
+            token::transfer_checked(fee_cpi_ctx, fee_raw, data_account.decimals)?;
+        }
 
-        require!(effective_claim_percent > 0, VestingError::ClaimNotAllowed);
-         // Perform the actual token transfer from escrow to the beneficiary
-        token::transfer(cpi_ctx, amount_to_transfer_raw)?;
-         // Update the beneficiary's claimed amount (in base units)
+        let beneficiary_account_key = beneficiary.key();
+        let swap_seeds = &[
+            SWAP_AUTHORITY_SEED,
+            beneficiary_account_key.as_ref(),
+            &[ctx.bumps.swap_authority],
+        ];
+        let output_before = ctx.accounts.output_token_account.amount;
+        invoke_swap_program(
+            &ctx.accounts.swap_program,
+            &ctx.accounts.swap_authority,
+            &swap_seeds[..],
+            time_source::instruction_accounts(ctx.remaining_accounts),
+            instruction_data,
+        )?;
+        ctx.accounts.swap_account.reload()?;
+        ctx.accounts.output_token_account.reload()?;
+        // The route has to trade the whole claim, and the temporary account must be closable
+        require!(ctx.accounts.swap_account.amount == 0, VestingError::SwapAmountMismatch);
+        let amount_out = ctx
+            .accounts
+            .output_token_account
+            .amount
+            .checked_sub(output_before)
+            .ok_or(VestingError::SwapAmountMismatch)?;
+        require!(amount_out >= min_amount_out, VestingError::SlippageExceeded);
+
+        let close_instruction = CloseAccount {
+            account: ctx.accounts.swap_account.to_account_info(),
+            destination: ctx.accounts.sender.to_account_info(),
+            authority: ctx.accounts.swap_authority.to_account_info(),
+        };
+        let swap_signer_seeds = &[&swap_seeds[..]];
+        let close_cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            close_instruction,
+            swap_signer_seeds,
+        );
+        token::close_account(close_cpi_ctx)?;
 
         beneficiary.claimed_tokens = beneficiary.claimed_tokens.saturating_add(claimable_amount);
-        // Update the total claimed amount in the data account (in base units)
+        beneficiary.last_claim_timestamp = now;
         data_account.claimed_total = data_account.claimed_total.saturating_add(claimable_amount);
-        
+        check_invariants(data_account, Some(beneficiary), Some(&ctx.accounts.escrow_wallet.to_account_info()))?;
+        emit_schedule_stats(data_account);
+        emit!(ClaimSwapped {
+            data_account: data_account.key(),
+            beneficiary: beneficiary_wallet,
+            amount_in: amount_raw - fee_raw,
+            output_mint: ctx.accounts.output_token_account.mint,
+            amount_out,
+        });
+        emit_cpi!(TokensClaimed {
+            data_account: data_account.key(),
+            beneficiary: beneficiary_wallet,
+            amount: amount_raw - fee_raw,
+            fee: fee_raw,
+            fee_recipient: fee_recipient(config),
+            secondary_amount: 0,
+            memo_hash: None,
+        });
+        Ok(())
+    }
+
+    // Parks a beneficiary's claimable tokens while the escrow or their token account is frozen by the mint's freeze
+    // authority. The tokens count as claimed, so the claim deadline and `withdraw_unclaimed` can no longer take them,
+    // but stay in the escrow until `claim_parked` pays them out once both accounts are thawed.
+    pub fn redirect_frozen_claim(ctx: Context<RedirectFrozenClaim>) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let beneficiary = &mut ctx.accounts.beneficiary_account;
+        require!(
+            ctx.accounts.escrow_wallet.is_frozen() || ctx.accounts.recipient_token_account.is_frozen(),
+            VestingError::AccountsNotFrozen
+        );
+        require!(!beneficiary.terminated, VestingError::BeneficiaryTerminated);
+        require!(!data_account.require_acceptance || beneficiary.accepted, VestingError::GrantNotAccepted);
+        require!(beneficiary.position_mint.is_none(), VestingError::PositionNftUnsupported);
+        require!(beneficiary.secondary_recipient.is_none(), VestingError::SecondaryRecipientUnsupported);
+        require!(!data_account.frozen, VestingError::VestingFrozen);
+        // Parked tokens are paid out by `claim_parked`, which only covers plain escrow claims
+        data_account.require_plain_claim()?;
 
+        let now = data_account.advance_clock(time_source::now(ctx.remaining_accounts)?)?;
+        require!(now >= data_account.start_timestamp, VestingError::VestingNotStarted);
+        require!(!data_account.claim_window_expired(now), VestingError::ClaimWindowExpired);
+        let vested_bps = beneficiary.vested_bps(data_account, ctx.accounts.cohort.as_deref(), now)?;
+        let claimable_amount = data_account.cap_claim(
+            data_account
+                .cap_funded(
+                    beneficiary,
+                    beneficiary.vested_amount(data_account, vested_bps),
+                )
+                .saturating_sub(beneficiary.claimed_tokens)
+                .saturating_sub(beneficiary.vote_locked(now)),
+        );
+        require!(claimable_amount > 0, VestingError::ClaimNotAllowed);
 
+        beneficiary.claimed_tokens = beneficiary.claimed_tokens.saturating_add(claimable_amount);
+        beneficiary.parked_tokens = beneficiary.parked_tokens.saturating_add(claimable_amount);
+        beneficiary.last_claim_timestamp = now;
+        data_account.claimed_total = data_account.claimed_total.saturating_add(claimable_amount);
+        data_account.parked_total = data_account.parked_total.saturating_add(claimable_amount);
+        check_invariants(data_account, Some(beneficiary), Some(&ctx.accounts.escrow_wallet.to_account_info()))?;
+        emit_schedule_stats(data_account);
+        emit!(ClaimParked {
+            data_account: data_account.key(),
+            beneficiary: beneficiary.key,
+            amount: claimable_amount,
+            parked_tokens: beneficiary.parked_tokens,
+        });
         Ok(())
     }
 
-    pub fn withdraw_unclaimed(ctx: Context<WithdrawUnclaimed>, data_bump: u8, _escrow_bump: u8) -> Result<()> {
-         // Get mutable reference to the main vesting data account
+    // Pays out the tokens parked by `redirect_frozen_claim`, minus the protocol fee, once the escrow and the
+    // beneficiary's token account are thawed. Signed by the beneficiary or their claim delegate.
+    pub fn claim_parked(ctx: Context<ClaimParkedTokens>) -> Result<()> {
+        let sender = ctx.accounts.sender.key();
         let data_account = &mut ctx.accounts.data_account;
-         // Get the current on-chain timestamp
-        let now = Clock::get()?.unix_timestamp;
-        // Calculate the number of seconds since vesting started
-        let elapsed_seconds = now - data_account.start_timestamp;
-        // Calculate total vesting duration in seconds (assuming 30-day months)
-        let vesting_duration = (data_account.vesting_months as i64) * 30 * 24 * 60 * 60;
-        // Ensure vesting period has fully elapsed before allowing withdrawal
-        require!(elapsed_seconds >= vesting_duration, VestingError::VestingStillActive);
-         // Read total claimed and total vested amounts
-
-        let total_claimed = data_account.claimed_total;
-        let total_vested_amount = data_account.token_amount;
-        // Calculate how much unclaimed amount remains after deducting claimed and previously withdrawn unclaimed tokens
-        let unclaimed = total_vested_amount.saturating_sub(total_claimed + data_account.unclaimed_withdrawn);
-        // Ensure there is something to withdraw
-        require!(unclaimed > 0, VestingError::NoUnclaimedTokens);
+        let beneficiary = &mut ctx.accounts.beneficiary_account;
+        let config = ctx.accounts.config.as_ref();
+        require!(
+            sender == beneficiary.key || beneficiary.claim_delegate == Some(sender),
+            VestingError::UnauthorizedClaimer
+        );
+        data_account.check_claim_approval(ctx.accounts.claim_approver.as_ref())?;
+        require!(!data_account.frozen, VestingError::VestingFrozen);
+        require!(beneficiary.parked_tokens > 0, VestingError::ClaimNotAllowed);
+        require_thawed(&ctx.accounts.escrow_wallet, &ctx.accounts.wallet_to_deposit_to)?;
 
-         // Prepare signer seeds for PDA authority
+        let parked_tokens = beneficiary.parked_tokens;
         let token_mint_key = ctx.accounts.token_mint.key();
-        let seeds = &[b"data_account", token_mint_key.as_ref(), &[data_bump]];
+        let schedule_seed = data_account.schedule_seed();
+        let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[data_account.bump]];
         let signer_seeds = &[&seeds[..]];
 
-        // Prepare transfer instruction from the escrow wallet to the recipient
-        let transfer_instruction = Transfer {
+        let amount_raw = raw_amount(parked_tokens, data_account.decimals)?;
+        let (fee_raw, fee_account) = protocol_fee(config, ctx.accounts.fee_token_account.as_ref(), amount_raw)?;
+        let transfer_instruction = TransferChecked {
             from: ctx.accounts.escrow_wallet.to_account_info(),
-            to: ctx.accounts.recipient.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
+            to: ctx.accounts.wallet_to_deposit_to.to_account_info(),
             authority: data_account.to_account_info(),
         };
-
-        // Build CPI context with signer seeds
         let cpi_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
             transfer_instruction,
             signer_seeds,
         );
+        token::transfer_checked(cpi_ctx, amount_raw - fee_raw, data_account.decimals)?;
+        if let Some(fee_account) = fee_account {
+            let fee_instruction = TransferChecked {
+                from: ctx.accounts.escrow_wallet.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                to: fee_account,
+                authority: data_account.to_account_info(),
+            };
+            let fee_cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                fee_instruction,
+                signer_seeds,
+            );
+            token::transfer_checked(fee_cpi_ctx, fee_raw, data_account.decimals)?;
+        }
 
-        // Calculate amount to withdraw in raw units (based on token decimals)
-        let amount_to_withdraw = unclaimed * 10u64.pow(data_account.decimals as u32);
-        // Perform token transfer from escrow to recipient
-        token::transfer(cpi_ctx, amount_to_withdraw)?;
-        // Update the amount of unclaimed tokens that have been withdrawn
-        data_account.unclaimed_withdrawn += unclaimed;
+        // The tokens were already counted as claimed when they were parked
+        beneficiary.parked_tokens = 0;
+        data_account.parked_total = data_account.parked_total.saturating_sub(parked_tokens);
+        check_invariants(data_account, Some(beneficiary), Some(&ctx.accounts.escrow_wallet.to_account_info()))?;
+        emit_cpi!(TokensClaimed {
+            data_account: data_account.key(),
+            beneficiary: beneficiary.key,
+            amount: amount_raw - fee_raw,
+            fee: fee_raw,
+            fee_recipient: fee_recipient(config),
+            secondary_amount: 0,
+            memo_hash: None,
+        });
         Ok(())
     }
 
-    pub fn cancel_vesting(
-    ctx: Context<CancelVesting>,
-    data_bump: u8,
-    escrow_bump: u8,
-) -> Result<()> {
-        // Get a mutable reference to the main vesting data account
-    let data_account = &mut ctx.accounts.data_account;
-         // Get the current on-chain timestamp
-    let now = Clock::get()?.unix_timestamp;
-// Ensure vesting is still active (i.e., has not yet fully completed)
-    require!(now < data_account.start_timestamp + (data_account.vesting_months as i64) * 30 * 24 * 60 * 60, VestingError::VestingAlreadyCompleted);
-        
-// Total tokens allocated for vesting
-    let total_allocated = data_account.token_amount;
-        // Total tokens claimed by all beneficiaries so far
-    let total_claimed = data_account.claimed_total;
-        // Calculate unclaimed tokens still in escrow (excluding previously withdrawn unclaimed tokens)
-    let unclaimed = total_allocated
-        .saturating_sub(total_claimed + data_account.unclaimed_withdrawn);
-// Ensure there are still unclaimed tokens available for transfer
-    require!(unclaimed > 0, VestingError::NoUnclaimedTokens);
+    // Claims from several schedules in one instruction.
+    // `remaining_accounts` holds one (data_account, escrow_wallet, beneficiary_account, wallet_to_deposit_to,
+    // fee_token_account, token_mint) tuple per schedule, all writable except the mint. Schedules with nothing claimable right now are skipped.
+    pub fn claim_many<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimMany<'info>>) -> Result<()> {
+        let program_id = ctx.program_id;
+        let sender = &ctx.accounts.sender;
+        let config = ctx.accounts.config.as_ref();
+        let now = time_source::now(ctx.remaining_accounts)?;
+        let positions = time_source::instruction_accounts(ctx.remaining_accounts);
+        require!(
+            !positions.is_empty() && positions.len().is_multiple_of(6),
+            VestingError::MissingRemainingAccount
+        );
 
-    // Derive the signer PDA seeds for signing the token transfer
-    let token_mint_key = ctx.accounts.token_mint.key();
-    let seeds = &[b"data_account", token_mint_key.as_ref(), &[data_bump]];
-    let signer_seeds = &[&seeds[..]];
+        let mut claimed_any = false;
+        for accounts in positions.chunks(6) {
+            let Some((amount_raw, fee_raw)) = claim_schedule_position(
+                program_id,
+                sender,
+                config,
+                ctx.accounts.claim_approver.as_ref(),
+                &ctx.accounts.token_program,
+                accounts,
+                now,
+            )?
+            else {
+                continue;
+            };
+            emit_cpi!(TokensClaimed {
+                data_account: accounts[0].key(),
+                beneficiary: sender.key(),
+                amount: amount_raw - fee_raw,
+                fee: fee_raw,
+                fee_recipient: fee_recipient(config),
+                secondary_amount: 0,
+                memo_hash: None,
+            });
+            claimed_any = true;
+        }
 
-     // Create a transfer instruction to move tokens from the program's escrow wallet to the recipient's account   
-    let transfer_instruction = Transfer {
-        from: ctx.accounts.escrow_wallet.to_account_info(), // Source escrow token account
-        to: ctx.accounts.recipient.to_account_info(),      // Destination recipient token account
-        authority: data_account.to_account_info(),     // PDA authority that signs the transfer
-    };
-        
- // Create a CPI (Cross-Program Invocation) context with signer seeds
-// This context is used to authorize the token transfer using the program-derived address (PDA) as the signer
-    let cpi_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(), // SPL Token program being invoked
-        transfer_instruction,      // Transfer instruction created earlier
-        signer_seeds,      // PDA seeds used to sign the CPI on behalf of the program
-    );
-// Calculate the actual token amount to transfer by scaling `unclaimed` with the token's decimal precision
-    let amount = unclaimed * 10u64.pow(data_account.decimals as u32);
-// Perform the token transfer from the escrow wallet to the recipient using the CPI context
-    token::transfer(cpi_ctx, amount)?;
+        require!(claimed_any, VestingError::ClaimNotAllowed);
+        Ok(())
+    }
 
-    data_account.unclaimed_withdrawn += unclaimed;
-    data_account.percent_available = 100; // Optional: to prevent further release
-    data_account.vesting_months = 0;      // Effectively ends vesting
+    // Claims every position of the signer across schedules, e.g. a founder's advisor, founder and investor grants.
+    // Takes the same `remaining_accounts` tuples as `claim_many`, but a position that fails its checks doesn't
+    // abort the others: the outcome of each tuple is returned instead, and the instruction succeeds even
+    // if nothing was claimed.
+    pub fn claim_all_for_signer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimAllForSigner<'info>>,
+    ) -> Result<Vec<PositionClaimResult>> {
+        let program_id = ctx.program_id;
+        let sender = &ctx.accounts.sender;
+        let config = ctx.accounts.config.as_ref();
+        let now = time_source::now(ctx.remaining_accounts)?;
+        let positions = time_source::instruction_accounts(ctx.remaining_accounts);
+        require!(
+            !positions.is_empty() && positions.len().is_multiple_of(6),
+            VestingError::MissingRemainingAccount
+        );
+        // The results have to fit in the return data
+        require!(
+            positions.len() / 6 <= MAX_CLAIM_ALL_POSITIONS,
+            VestingError::TooManyPositions
+        );
 
-    Ok(())
-}
+        let mut results = Vec::with_capacity(positions.len() / 6);
+        for accounts in positions.chunks(6) {
+            let outcome = claim_schedule_position(
+                program_id,
+                sender,
+                config,
+                ctx.accounts.claim_approver.as_ref(),
+                &ctx.accounts.token_program,
+                accounts,
+                now,
+            );
+            let (amount, error_code) = match outcome {
+                Ok(Some((amount_raw, fee_raw))) => {
+                    emit_cpi!(TokensClaimed {
+                        data_account: accounts[0].key(),
+                        beneficiary: sender.key(),
+                        amount: amount_raw - fee_raw,
+                        fee: fee_raw,
+                        fee_recipient: fee_recipient(config),
+                        secondary_amount: 0,
+                        memo_hash: None,
+                    });
+                    (amount_raw - fee_raw, 0)
+                }
+                Ok(None) => (0, 0),
+                // Accounting drift fails the whole instruction; the position's tokens may already have moved
+                Err(error) if error_code_number(&error) == u64::from(u32::from(VestingError::InvariantViolated)) => {
+                    return Err(error)
+                }
+                Err(error) => (0, error_code_number(&error)),
+            };
+            results.push(PositionClaimResult {
+                data_account: accounts[0].key(),
+                amount,
+                error_code,
+            });
+        }
+        Ok(results)
+    }
 
-pub fn add_beneficiaries<'info>(
-    ctx: Context<'_, '_, '_, 'info, AddBeneficiaries<'info>>,
-    new_beneficiaries: Vec<NewBeneficiary>,
-) -> Result<()> {
-     // Get the current program ID, data account, and payer (usually the authority/owner)
-    let program_id = ctx.program_id;
-    let data_account = &ctx.accounts.data_account;
-    let payer = &ctx.accounts.sender;
-    // Iterator over remaining accounts (used to receive dynamically generated PDAs for beneficiaries)
-    let mut remaining = ctx.remaining_accounts.iter();
+    // Backs the sender's position with an NFT: a 0-decimal mint with a fixed supply of 1 is minted to the sender.
+    // From then on `claim` pays whoever holds the NFT, so the position shows up in wallets
+    // and can be transferred by transferring the NFT. This can't be undone.
+    pub fn mint_position_nft(ctx: Context<MintPositionNft>) -> Result<()> {
+        let data_account = &ctx.accounts.data_account;
+        let beneficiary = &mut ctx.accounts.beneficiary_account;
+        require!(!beneficiary.terminated, VestingError::BeneficiaryTerminated);
+        beneficiary.position_mint = Some(ctx.accounts.position_mint.key());
 
-     // Loop through each new beneficiary to add
-    for new in new_beneficiaries {
-        let beneficiary_pubkey = new.key;
-        let allocated_tokens = new.allocated_tokens;
+        let token_mint_key = ctx.accounts.token_mint.key();
+        let schedule_seed = data_account.schedule_seed();
+        let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[data_account.bump]];
+        let signer_seeds = &[&seeds[..]];
+        let mint_instruction = MintTo {
+            mint: ctx.accounts.position_mint.to_account_info(),
+            to: ctx.accounts.position_token_account.to_account_info(),
+            authority: data_account.to_account_info(),
+        };
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                mint_instruction,
+                signer_seeds,
+            ),
+            1,
+        )?;
+        // Drop the mint authority so the supply stays at exactly one
+        let authority_instruction = SetAuthority {
+            account_or_mint: ctx.accounts.position_mint.to_account_info(),
+            current_authority: data_account.to_account_info(),
+        };
+        token::set_authority(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                authority_instruction,
+                signer_seeds,
+            ),
+            AuthorityType::MintTokens,
+            None,
+        )?;
+        Ok(())
+    }
 
-        let beneficiary_account_info = remaining
-            .next()
-            .ok_or(VestingError::MissingRemainingAccount)?;
-// Seeds used to generate the PDA for the beneficiary
-        let data_account_key = data_account.key();
-        let beneficiary_seeds = &[
-            b"beneficiary",
-            data_account_key.as_ref(),
-            beneficiary_pubkey.as_ref(),
+    // Attaches a secondary recipient (e.g. a recruiter or agency) that receives `secondary_bps` of every
+    // claim of this beneficiary, vesting legs included. The beneficiary always keeps a share. Both the initializer
+    // and the beneficiary sign; pass `None` to detach it.
+    pub fn set_secondary_recipient(
+        ctx: Context<SetSecondaryRecipient>,
+        secondary_recipient: Option<Pubkey>,
+        secondary_bps: u16,
+    ) -> Result<()> {
+        require!(
+            match secondary_recipient {
+                Some(_) => secondary_bps > 0 && secondary_bps < 10_000,
+                None => secondary_bps == 0,
+            },
+            VestingError::InvalidSecondaryRecipient
+        );
+        let beneficiary = &mut ctx.accounts.beneficiary_account;
+        require!(!beneficiary.terminated, VestingError::BeneficiaryTerminated);
+        beneficiary.secondary_recipient = secondary_recipient;
+        beneficiary.secondary_bps = secondary_bps;
+        Ok(())
+    }
+
+    // Lets a beneficiary authorize another wallet (e.g. a hot wallet) to call `claim` on their behalf.
+    // Tokens are still always deposited to the beneficiary's own ATA. Pass `None` to revoke.
+    pub fn set_claim_delegate(ctx: Context<SetClaimDelegate>, delegate: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.beneficiary_account.claim_delegate = delegate;
+        Ok(())
+    }
+
+    // Records the beneficiary's acceptance of the grant terms, optionally with the hash of the signed agreement
+    // (all zeroes if none). Schedules with `require_acceptance` only pay out accepted grants. Acceptance is final.
+    pub fn accept_grant(ctx: Context<AcceptGrant>, agreement_hash: Option<[u8; 32]>) -> Result<()> {
+        let beneficiary = &mut ctx.accounts.beneficiary_account;
+        require!(!beneficiary.terminated, VestingError::BeneficiaryTerminated);
+        require!(!beneficiary.accepted, VestingError::GrantAlreadyAccepted);
+        beneficiary.accepted = true;
+        beneficiary.agreement_hash = agreement_hash.unwrap_or_default();
+
+        emit!(GrantAccepted {
+            data_account: ctx.accounts.data_account.key(),
+            beneficiary: beneficiary.key,
+            agreement_hash: beneficiary.agreement_hash,
+        });
+        Ok(())
+    }
+
+    // Lets a beneficiary voluntarily re-lock their position for longer, e.g. for a DAO's lockup incentives: their
+    // time vesting runs `extension_seconds` later than the schedule's, pushing out every unlock and their end date.
+    // Extensions add up and can't be undone. Manual releases still apply as released.
+    pub fn extend_my_vesting(ctx: Context<ExtendMyVesting>, extension_seconds: u32) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let beneficiary = &mut ctx.accounts.beneficiary_account;
+        require!(extension_seconds > 0, VestingError::InvalidVestingExtension);
+        require!(!data_account.cancelled, VestingError::VestingCancelled);
+        require!(!beneficiary.terminated, VestingError::BeneficiaryTerminated);
+        // The NFT holder, not the original wallet, owns an NFT-backed position
+        require!(beneficiary.position_mint.is_none(), VestingError::PositionNftUnsupported);
+        // Nothing vests over time on a manual-only schedule, so there is nothing to extend
+        require!(data_account.release_mode != ReleaseMode::ManualOnly, VestingError::VestingExtensionUnsupported);
+
+        beneficiary.voluntary_extension_seconds = beneficiary
+            .voluntary_extension_seconds
+            .checked_add(extension_seconds)
+            .filter(|extension| *extension <= MAX_VOLUNTARY_EXTENSION)
+            .ok_or(VestingError::InvalidVestingExtension)?;
+        let end_timestamp = beneficiary.vesting_end_timestamp(data_account);
+        // Tokens vesting after the claim deadline could never be claimed
+        require!(
+            data_account.claim_deadline == 0 || end_timestamp <= data_account.claim_deadline,
+            VestingError::InvalidVestingExtension
+        );
+        data_account.max_voluntary_extension_seconds =
+            std::cmp::max(data_account.max_voluntary_extension_seconds, beneficiary.voluntary_extension_seconds);
+
+        emit!(VestingExtended {
+            data_account: data_account.key(),
+            beneficiary: beneficiary.key,
+            extension_seconds,
+            voluntary_extension_seconds: beneficiary.voluntary_extension_seconds,
+            end_timestamp,
+        });
+        Ok(())
+    }
+
+    // Lets a beneficiary name (or clear) the wallet that can recover the position if its key is lost.
+    pub fn set_recovery_authority(ctx: Context<SetRecoveryAuthority>, recovery_authority: Option<Pubkey>) -> Result<()> {
+        let beneficiary = &mut ctx.accounts.beneficiary_account;
+        beneficiary.recovery_authority = recovery_authority;
+        // A new authority doesn't inherit a recovery started by the previous one
+        beneficiary.pending_recovery_wallet = None;
+        beneficiary.recovery_initiated_at = 0;
+        Ok(())
+    }
+
+    // Starts moving a position whose key was lost to `new_wallet`. Signed by the recovery authority;
+    // `execute_recovery` can only run after `RECOVERY_DELAY`, during which the original key can `cancel_recovery`.
+    pub fn initiate_recovery(ctx: Context<InitiateRecovery>, new_wallet: Pubkey) -> Result<()> {
+        let beneficiary = &mut ctx.accounts.beneficiary_account;
+        require!(
+            beneficiary.recovery_authority == Some(ctx.accounts.sender.key()),
+            VestingError::InvalidRecoveryAuthority
+        );
+        require!(!beneficiary.terminated, VestingError::BeneficiaryTerminated);
+        // NFT-backed positions belong to whoever holds the NFT
+        require!(beneficiary.position_mint.is_none(), VestingError::PositionNftUnsupported);
+        require!(new_wallet != beneficiary.key, VestingError::InvalidRecoveryWallet);
+
+        let now = time_source::now(ctx.remaining_accounts)?;
+        beneficiary.pending_recovery_wallet = Some(new_wallet);
+        beneficiary.recovery_initiated_at = now;
+        emit!(RecoveryInitiated {
+            data_account: ctx.accounts.data_account.key(),
+            beneficiary: beneficiary.key,
+            new_wallet,
+            executable_at: now + RECOVERY_DELAY,
+        });
+        Ok(())
+    }
+
+    // Lets the original key stop a pending recovery during the delay.
+    pub fn cancel_recovery(ctx: Context<SetRecoveryAuthority>) -> Result<()> {
+        let beneficiary = &mut ctx.accounts.beneficiary_account;
+        require!(beneficiary.pending_recovery_wallet.is_some(), VestingError::NoPendingRecovery);
+        beneficiary.pending_recovery_wallet = None;
+        beneficiary.recovery_initiated_at = 0;
+        emit!(RecoveryCancelled {
+            data_account: ctx.accounts.data_account.key(),
+            beneficiary: beneficiary.key,
+        });
+        Ok(())
+    }
+
+    // Completes a recovery once `RECOVERY_DELAY` has passed: the position is moved to a new beneficiary account
+    // for the pending wallet and the old one is closed. Claim delegate and payout account were chosen by
+    // the lost key, so they are reset; the claim history of the old account stays under its address.
+    pub fn execute_recovery(ctx: Context<ExecuteRecovery>) -> Result<()> {
+        let old = &ctx.accounts.beneficiary_account;
+        require!(
+            old.recovery_authority == Some(ctx.accounts.sender.key()),
+            VestingError::InvalidRecoveryAuthority
+        );
+        let new_wallet = old.pending_recovery_wallet.ok_or(VestingError::NoPendingRecovery)?;
+        require_keys_eq!(ctx.accounts.new_wallet.key(), new_wallet, VestingError::InvalidRecoveryWallet);
+        require!(
+            time_source::now(ctx.remaining_accounts)? >= old.recovery_initiated_at + RECOVERY_DELAY,
+            VestingError::RecoveryDelayNotElapsed
+        );
+        // The position may have changed during the delay: it could have been terminated or backed by an NFT,
+        // or either wallet blacklisted
+        require!(!old.terminated, VestingError::BeneficiaryTerminated);
+        require!(old.position_mint.is_none(), VestingError::PositionNftUnsupported);
+        ctx.accounts
+            .data_account
+            .check_blacklist(ctx.accounts.blacklist.as_deref(), &[old.key, new_wallet])?;
+
+        let position = &mut ctx.accounts.new_beneficiary_account;
+        position.key = new_wallet;
+        position.allocated_tokens = old.allocated_tokens;
+        position.claimed_tokens = old.claimed_tokens;
+        position.bump = ctx.bumps.new_beneficiary_account;
+        position.last_claim_timestamp = old.last_claim_timestamp;
+        position.secondary_recipient = old.secondary_recipient;
+        position.secondary_bps = old.secondary_bps;
+        position.shares = old.shares;
+        position.recovery_authority = old.recovery_authority;
+        position.cohort_id = old.cohort_id;
+        // The lost key's extensions still bind the position
+        position.voluntary_extension_seconds = old.voluntary_extension_seconds;
+        // So do its vote locks, whose voting weight stays with the old position's `VoteLockAccount`
+        position.vote_locked_amount = old.vote_locked_amount;
+        position.vote_lock_expires_at = old.vote_lock_expires_at;
+        // Parked claims are still owed to the position, and the lost key can't `claim_parked` them
+        position.parked_tokens = old.parked_tokens;
+        // A pending removal stays scheduled, so recovering can't escape the dispute window's outcome
+        position.removal_executable_at = old.removal_executable_at;
+        // The position keeps its place in the beneficiary index
+        position.index = old.index;
+        ctx.accounts.beneficiary_index.beneficiary = new_wallet;
+
+        emit!(RecoveryExecuted {
+            data_account: ctx.accounts.data_account.key(),
+            previous_wallet: old.key,
+            new_wallet,
+        });
+        Ok(())
+    }
+
+    // Lets a beneficiary route future claims to another token account of the same mint
+    // (e.g. a treasury, multisig vault or exchange deposit address). Omit the account to reset to the ATA.
+    pub fn set_payout_account(ctx: Context<SetPayoutAccount>) -> Result<()> {
+        ctx.accounts.beneficiary_account.payout_token_account =
+            ctx.accounts.payout_token_account.as_ref().map(|account| account.key());
+        Ok(())
+    }
+
+    // Returns what was withdrawn through `set_return_data` (see `TransferResult`).
+    // With a withdrawal split configured (see `set_withdraw_split`), the residual is divided among its shares
+    // instead of going to `recipient`: `remaining_accounts` holds the token account of every share that isn't
+    // burned, in the order of the split.
+    pub fn withdraw_unclaimed<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawUnclaimed<'info>>,
+    ) -> Result<TransferResult> {
+         // Get mutable reference to the main vesting data account
+        let data_account = &mut ctx.accounts.data_account;
+         // Get the current on-chain timestamp, never earlier than the last one this schedule processed
+        let now = data_account.advance_clock(time_source::now(ctx.remaining_accounts)?)?;
+        // Once the claim deadline has passed, everything left in the escrow is forfeited and can be swept,
+        // whatever the schedule's revocability, vesting progress or released percentage
+        let claim_window_expired = data_account.claim_window_expired(now);
+        // Irrevocable schedules never hand tokens back to the initializer
+        require!(claim_window_expired || data_account.is_cancellable, VestingError::VestingNotCancellable);
+        require!(!data_account.mint_compromised, VestingError::MintCompromised);
+        // The treasurer only sweeps what was forfeited at the claim deadline
+        require!(
+            claim_window_expired || ctx.accounts.sender.key() == data_account.initializer,
+            VestingError::ClaimWindowNotExpired
+        );
+        require!(!data_account.frozen, VestingError::VestingFrozen);
+        // Unclaimed tokens of a mint-vesting schedule were never minted
+        require!(!data_account.mint_vesting, VestingError::MintVestingUnsupported);
+        require!(data_account.payout_mint.is_none(), VestingError::PayoutMintUnsupported);
+        // Staked tokens have to be back in the escrow before they can be paid out
+        require!(data_account.staked_amount == 0, VestingError::EscrowStaked);
+        // Ensure vesting period has fully elapsed before allowing withdrawal
+        require!(
+            claim_window_expired || now >= data_account.latest_vesting_end_timestamp(),
+            VestingError::VestingStillActive
+        );
+         // Read total claimed and total vested amounts
+
+        let total_claimed = data_account.claimed_total;
+        let total_vested_amount = data_account.token_amount;
+        // Calculate how much unclaimed amount remains after deducting claimed and previously withdrawn unclaimed tokens
+        let unclaimed = total_vested_amount.saturating_sub(total_claimed + data_account.unclaimed_withdrawn);
+        // Ensure there is something to withdraw
+        require!(unclaimed > 0, VestingError::NoUnclaimedTokens);
+
+         // Prepare signer seeds for PDA authority
+        let token_mint_key = ctx.accounts.token_mint.key();
+        let schedule_seed = data_account.schedule_seed();
+        let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[data_account.bump]];
+        let signer_seeds = &[&seeds[..]];
+        let decimals = data_account.decimals;
+        let authority = data_account.to_account_info();
+        let escrow_wallet = ctx.accounts.escrow_wallet.to_account_info();
+        let token_mint = ctx.accounts.token_mint.to_account_info();
+        let token_program = ctx.accounts.token_program.to_account_info();
+
+        // Transfers `amount` raw units from the escrow wallet to `to`, signed by the data account PDA
+        let transfer = |to: AccountInfo<'info>, amount: u64| {
+            let transfer_instruction = TransferChecked {
+                from: escrow_wallet.clone(),
+                mint: token_mint.clone(),
+                to,
+                authority: authority.clone(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(token_program.clone(), transfer_instruction, signer_seeds);
+            token::transfer_checked(cpi_ctx, amount, decimals)
+        };
+
+        // Calculate amount to withdraw in raw units (based on token decimals)
+        let amount_to_withdraw = raw_amount(unclaimed, decimals)?;
+        if data_account.withdraw_split.is_empty() {
+            // Perform token transfer from escrow to recipient
+            transfer(ctx.accounts.recipient.to_account_info(), amount_to_withdraw)?;
+        } else {
+            let mut destinations = time_source::instruction_accounts(ctx.remaining_accounts).iter();
+            let mut left = amount_to_withdraw;
+            let shares = data_account.withdraw_split.len();
+            for (index, share) in data_account.withdraw_split.iter().enumerate() {
+                // The last share takes the rounding remainder, so the whole residual is paid out
+                let amount = if index + 1 == shares {
+                    left
+                } else {
+                    math::bps_of(amount_to_withdraw, share.bps).ok_or(VestingError::MathOverflow)?
+                };
+                left -= amount;
+                match share.recipient {
+                    Some(recipient) => {
+                        let destination = destinations.next().ok_or(VestingError::MissingRemainingAccount)?;
+                        require_keys_eq!(destination.key(), recipient, VestingError::InvalidRecipient);
+                        transfer(destination.clone(), amount)?;
+                    }
+                    None => {
+                        let burn_instruction = Burn {
+                            mint: token_mint.clone(),
+                            from: escrow_wallet.clone(),
+                            authority: authority.clone(),
+                        };
+                        let burn_ctx =
+                            CpiContext::new_with_signer(token_program.clone(), burn_instruction, signer_seeds);
+                        token::burn(burn_ctx, amount)?;
+                    }
+                }
+                emit!(UnclaimedShareWithdrawn {
+                    data_account: authority.key(),
+                    recipient: share.recipient,
+                    bps: share.bps,
+                    amount,
+                });
+            }
+        }
+        // Update the amount of unclaimed tokens that have been withdrawn
+        data_account.unclaimed_withdrawn += unclaimed;
+        check_invariants(data_account, None, Some(&ctx.accounts.escrow_wallet.to_account_info()))?;
+        Ok(data_account.transfer_result(amount_to_withdraw))
+    }
+
+    // Chooses what `execute_cancel` does with the tokens beneficiaries lose: return them to the initializer (the
+    // default), burn them, or send them to the treasurer. Sending to the treasury requires a treasurer.
+    pub fn set_cancel_disposition(ctx: Context<SetCancelDisposition>, disposition: Disposition) -> Result<()> {
+        require_feature!(ctx.accounts.program_status, FEATURE_CANCEL_DISPOSITION);
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(!data_account.cancelled, VestingError::VestingCancelled);
+        require!(
+            disposition != Disposition::SendToTreasury || data_account.treasurer.is_some(),
+            VestingError::TreasurerNotSet
+        );
+        data_account.cancel_disposition = disposition;
+        Ok(())
+    }
+
+    // Creates cohort `cohort_id` of the schedule with its overrides: a cliff and vesting length (in months) for its
+    // members, and whether they are left out when the schedule is accelerated. See `Cohort` for how they apply.
+    pub fn create_cohort(
+        ctx: Context<CreateCohort>,
+        cohort_id: u32,
+        cliff_months: Option<u8>,
+        vesting_months: Option<u8>,
+        acceleration_exempt: bool,
+    ) -> Result<()> {
+        require_feature!(ctx.accounts.program_status, FEATURE_COHORTS);
+        let cohort = &mut ctx.accounts.cohort;
+        cohort.data_account = ctx.accounts.data_account.key();
+        cohort.cohort_id = cohort_id;
+        cohort.bump = ctx.bumps.cohort;
+        set_cohort_overrides(&mut ctx.accounts.data_account, cohort, cliff_months, vesting_months, acceleration_exempt)
+    }
+
+    // Replaces a cohort's overrides, e.g. to extend the cliff of a whole hiring batch. Members pick the change up at
+    // their next claim; their accounts aren't touched.
+    pub fn update_cohort(
+        ctx: Context<UpdateCohort>,
+        cliff_months: Option<u8>,
+        vesting_months: Option<u8>,
+        acceleration_exempt: bool,
+    ) -> Result<()> {
+        require_feature!(ctx.accounts.program_status, FEATURE_COHORTS);
+        set_cohort_overrides(
+            &mut ctx.accounts.data_account,
+            &mut ctx.accounts.cohort,
+            cliff_months,
+            vesting_months,
+            acceleration_exempt,
+        )
+    }
+
+    // Puts a beneficiary in `cohort`, or takes it out of its cohort when `cohort` is omitted.
+    pub fn assign_to_cohort(ctx: Context<AssignToCohort>) -> Result<()> {
+        require_feature!(ctx.accounts.program_status, FEATURE_COHORTS);
+        let data_account = &ctx.accounts.data_account;
+        let beneficiary = &mut ctx.accounts.beneficiary_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(!data_account.cancelled, VestingError::VestingCancelled);
+        require!(!beneficiary.terminated, VestingError::BeneficiaryTerminated);
+        beneficiary.cohort_id = ctx.accounts.cohort.as_ref().map(|cohort| cohort.cohort_id);
+        emit!(CohortAssigned {
+            data_account: data_account.key(),
+            beneficiary: beneficiary.key,
+            cohort_id: beneficiary.cohort_id,
+        });
+        Ok(())
+    }
+
+    // Splits what `withdraw_unclaimed` pays out among up to `MAX_WITHDRAW_SPLITS` shares, e.g. 50% to a treasury,
+    // 30% burned and 20% to a community fund. Each share goes to a token account of the vesting mint, or is burned
+    // when it has no `recipient`; the shares must be non-zero and add up to 10_000 bps. An empty list pays
+    // everything to the withdrawing wallet again. Part of setting the schedule up: only before the start.
+    pub fn set_withdraw_split(ctx: Context<SetWithdrawSplit>, shares: Vec<WithdrawShare>) -> Result<()> {
+        require_feature!(ctx.accounts.program_status, FEATURE_WITHDRAW_SPLIT);
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        let now = time_source::now(ctx.remaining_accounts)?;
+        require!(now < data_account.start_timestamp, VestingError::WithdrawSplitLocked);
+        require!(shares.len() <= MAX_WITHDRAW_SPLITS, VestingError::InvalidWithdrawSplit);
+        require!(
+            shares.is_empty()
+                || (shares.iter().all(|share| share.bps > 0)
+                    && shares.iter().map(|share| share.bps as u32).sum::<u32>() == 10_000),
+            VestingError::InvalidWithdrawSplit
+        );
+        data_account.withdraw_split = shares;
+        Ok(())
+    }
+
+    // Sets where `terminate_beneficiary` sends forfeited tokens: `forfeiture_bps` of each terminated
+    // beneficiary's unvested tokens go to the `forfeiture_pool` token account, the rest back to the initializer.
+    pub fn set_forfeiture_policy(
+        ctx: Context<SetForfeiturePolicy>,
+        forfeiture_pool: Option<Pubkey>,
+        forfeiture_bps: u16,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(forfeiture_bps <= 10_000, VestingError::InvalidForfeiture);
+        require!(forfeiture_bps == 0 || forfeiture_pool.is_some(), VestingError::InvalidForfeiture);
+        data_account.forfeiture_pool = forfeiture_pool;
+        data_account.forfeiture_bps = forfeiture_bps;
+        Ok(())
+    }
+
+    // Ends a single grant early, e.g. when an employee leaves. Everything vested but unclaimed is pushed
+    // to the beneficiary's ATA; the unvested rest is split between the forfeiture pool and the initializer
+    // per `forfeiture_bps`. The beneficiary account is marked `terminated`, so later claims fail.
+    // On schedules with vesting legs, `remaining_accounts` holds the leg tuples of `claim`, and the beneficiary gets
+    // the legs' share of the vested tokens too; the unvested share stays for `withdraw_leg_remainder`.
+    pub fn terminate_beneficiary<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TerminateBeneficiary<'info>>,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let beneficiary = &mut ctx.accounts.beneficiary_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        // Irrevocable schedules never hand tokens back to the initializer
+        require!(data_account.is_cancellable, VestingError::VestingNotCancellable);
+        require!(!data_account.cancelled, VestingError::VestingCancelled);
+        require!(!data_account.frozen, VestingError::VestingFrozen);
+        require!(!data_account.mint_compromised, VestingError::MintCompromised);
+        require!(data_account.staked_amount == 0, VestingError::EscrowStaked);
+        require!(data_account.payout_mint.is_none(), VestingError::PayoutMintUnsupported);
+        require!(!beneficiary.terminated, VestingError::BeneficiaryTerminated);
+        // The vested tokens would go to the original wallet rather than the NFT holder
+        require!(beneficiary.position_mint.is_none(), VestingError::PositionNftUnsupported);
+        // Parked tokens have to be paid out by `claim_parked` first
+        require!(beneficiary.parked_tokens == 0, VestingError::ParkedClaimPending);
+        // Shares-mode entitlements are derived from the pool, not stored per beneficiary
+        require!(!data_account.shares_mode, VestingError::SharesModeUnsupported);
+
+        let now = data_account.advance_clock(time_source::now(ctx.remaining_accounts)?)?;
+        let cohort = ctx.accounts.cohort.as_deref();
+        let vested = beneficiary.vested_amount(data_account, beneficiary.vested_bps(data_account, cohort, now)?);
+        let vested_unclaimed = vested.saturating_sub(beneficiary.claimed_tokens);
+        // Nothing is claimable after the termination, so it waits until `claim` could pay out every vested token:
+        // none locked for voting and, on installment-funded schedules, all of them funded.
+        let claimable = data_account
+            .cap_funded(beneficiary, vested)
+            .saturating_sub(beneficiary.claimed_tokens)
+            .saturating_sub(beneficiary.vote_locked(now));
+        require!(claimable == vested_unclaimed, VestingError::VestedTokensWithheld);
+        let unvested = beneficiary.allocated_tokens.saturating_sub(vested);
+        let forfeited = math::bps_of(unvested, data_account.forfeiture_bps).ok_or(VestingError::MathOverflow)?;
+        let returned = unvested - forfeited;
+
+        let token_mint_key = ctx.accounts.token_mint.key();
+        let schedule_seed = data_account.schedule_seed();
+        let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[data_account.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let payouts = [
+            (vested_unclaimed, Some(ctx.accounts.wallet_to_deposit_to.to_account_info())),
+            (forfeited, ctx.accounts.forfeiture_pool.as_ref().map(|pool| pool.to_account_info())),
+            (returned, Some(ctx.accounts.recipient.to_account_info())),
         ];
-// Derive the PDA and its bump for the beneficiary
-        let (beneficiary_pda, bump) =
-            Pubkey::find_program_address(beneficiary_seeds, program_id);
-         // Ensure the beneficiary account is still owned by the System Program (i.e., not yet initialized)
+        for (amount, destination) in payouts {
+            if amount == 0 {
+                continue;
+            }
+            let destination = destination.ok_or(VestingError::InvalidForfeiture)?;
+            let transfer_instruction = TransferChecked {
+                from: ctx.accounts.escrow_wallet.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                to: destination,
+                authority: data_account.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                transfer_instruction,
+                signer_seeds,
+            );
+            token::transfer_checked(cpi_ctx, raw_amount(amount, data_account.decimals)?, data_account.decimals)?;
+        }
+        pay_vesting_legs(
+            data_account,
+            time_source::instruction_accounts(ctx.remaining_accounts),
+            beneficiary.key,
+            None,
+            vested_unclaimed,
+            &ctx.accounts.token_program,
+            signer_seeds,
+        )?;
+
+        // The whole allocation has now left the escrow. Drop it from the pool totals, so they only count the active
+        // beneficiaries (and what removed ones claimed).
+        data_account.total_allocated = data_account
+            .total_allocated
+            .saturating_sub(beneficiary.allocated_tokens);
+        data_account.claimed_total = data_account
+            .claimed_total
+            .saturating_sub(beneficiary.claimed_tokens);
+        data_account.token_amount = data_account
+            .token_amount
+            .saturating_sub(beneficiary.allocated_tokens);
+        data_account.beneficiary_count = data_account.beneficiary_count.saturating_sub(1);
+
+        // Keep what the beneficiary ended up with; nothing is claimable from here on
+        beneficiary.allocated_tokens = vested;
+        beneficiary.claimed_tokens = vested;
+        beneficiary.terminated = true;
+        check_invariants(data_account, Some(beneficiary), Some(&ctx.accounts.escrow_wallet.to_account_info()))?;
+
+        emit!(BeneficiaryTerminated {
+            data_account: data_account.key(),
+            beneficiary: beneficiary.key,
+            vested_paid: raw_amount(vested_unclaimed, data_account.decimals)?,
+            forfeited: raw_amount(forfeited, data_account.decimals)?,
+            returned: raw_amount(returned, data_account.decimals)?,
+        });
+        emit_schedule_stats(data_account);
+        Ok(())
+    }
+
+    // Sends the escrow balance that no beneficiary or `withdraw_unclaimed` can ever claim
+    // (rounding remainders of cancelled schedules, tokens sent to the escrow directly, ...) to the initializer.
+    // Everything beneficiaries can still claim, parked claims and unallocated tokens stays in the escrow.
+    pub fn sweep_dust(ctx: Context<SweepDust>) -> Result<()> {
+        let data_account = &ctx.accounts.data_account;
+        require!(!data_account.frozen, VestingError::VestingFrozen);
+        require!(!data_account.mint_compromised, VestingError::MintCompromised);
+        require!(data_account.staked_amount == 0, VestingError::EscrowStaked);
+        require!(data_account.payout_mint.is_none(), VestingError::PayoutMintUnsupported);
+
+        // Allocations not yet claimed and unallocated tokens are still tracked, the latter stay available to
+        // `withdraw_unclaimed`, and parked claims are still owed
+        let tracked = data_account
+            .funded_tokens()
+            .saturating_sub(data_account.claimed_total + data_account.unclaimed_withdrawn)
+            + data_account.parked_total;
+        let dust = ctx
+            .accounts
+            .escrow_wallet
+            .amount
+            .saturating_sub(raw_amount(tracked, data_account.decimals)?);
+        require!(dust > 0, VestingError::NoDustToSweep);
+
+        let token_mint_key = ctx.accounts.token_mint.key();
+        let schedule_seed = data_account.schedule_seed();
+        let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[data_account.bump]];
+        let signer_seeds = &[&seeds[..]];
+        let transfer_instruction = TransferChecked {
+            from: ctx.accounts.escrow_wallet.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
+            to: ctx.accounts.recipient.to_account_info(),
+            authority: data_account.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            transfer_instruction,
+            signer_seeds,
+        );
+        token::transfer_checked(cpi_ctx, dust, data_account.decimals)?;
+        Ok(())
+    }
+
+    // Reconciles `token_amount` with the escrow balance, e.g. after tokens were transferred to the escrow directly.
+    // Whole tokens above what the schedule still tracks are added to `token_amount`, where they can be allocated
+    // or withdrawn like any other unallocated tokens; a shortfall is only reported through the event.
+    pub fn sync_escrow(ctx: Context<SyncEscrow>) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        require!(!data_account.mint_vesting, VestingError::MintVestingUnsupported);
+        require!(data_account.payout_mint.is_none(), VestingError::PayoutMintUnsupported);
+
+        // Staked tokens are delegated, not held, by the escrow; parked claims are still held
+        let tracked = data_account
+            .token_amount
+            .saturating_sub(data_account.claimed_total + data_account.unclaimed_withdrawn + data_account.staked_amount)
+            + data_account.parked_total;
+        let unit = raw_amount(1, data_account.decimals)?;
+        let expected_balance = raw_amount(tracked, data_account.decimals)?;
+        let escrow_balance = ctx.accounts.escrow_wallet.amount;
+
+        // Legs pay out against the `token_amount` they were added at, so schedules with legs leave the excess
+        // to `sweep_dust`
+        let absorbed_tokens = if data_account.leg_count == 0 {
+            escrow_balance.saturating_sub(expected_balance) / unit
+        } else {
+            0
+        };
+        let deficit = expected_balance.saturating_sub(escrow_balance);
+        data_account.token_amount += absorbed_tokens;
+        data_account.sync_pool_allocation();
+
+        emit!(EscrowSynced {
+            data_account: data_account.key(),
+            escrow_balance,
+            expected_balance,
+            absorbed_tokens,
+            deficit,
+        });
+        Ok(())
+    }
+
+    // Moves the escrow to a new token account, e.g. one at an institutional custodian's address: the custodian's
+    // program can sign for its PDA as `new_escrow_wallet` by invoking this instruction. The new account is still
+    // owned by the data account, so claims keep working; the whole balance is moved over, the old escrow is closed
+    // (its rent goes to the sender) and `escrow_wallet` points to the new account from then on.
+    pub fn migrate_escrow(ctx: Context<MigrateEscrow>) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(!data_account.frozen, VestingError::VestingFrozen);
+        require!(!data_account.mint_compromised, VestingError::MintCompromised);
+        // The staking program holds a delegation on the old escrow
+        require!(data_account.staked_amount == 0, VestingError::EscrowStaked);
+        require!(data_account.payout_mint.is_none(), VestingError::PayoutMintUnsupported);
+
+        let token_mint_key = ctx.accounts.token_mint.key();
+        let schedule_seed = data_account.schedule_seed();
+        let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[data_account.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        // Everything moves over, including dust and direct transfers `sync_escrow` hasn't absorbed yet
+        let amount = ctx.accounts.escrow_wallet.amount;
+        if amount > 0 {
+            let transfer_instruction = TransferChecked {
+                from: ctx.accounts.escrow_wallet.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                to: ctx.accounts.new_escrow_wallet.to_account_info(),
+                authority: data_account.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                transfer_instruction,
+                signer_seeds,
+            );
+            token::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+        }
+
+        // Closing the old escrow makes sure nothing can be deposited to or paid out of it any more
+        let close_instruction = CloseAccount {
+            account: ctx.accounts.escrow_wallet.to_account_info(),
+            destination: ctx.accounts.sender.to_account_info(),
+            authority: data_account.to_account_info(),
+        };
+        let close_cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            close_instruction,
+            signer_seeds,
+        );
+        token::close_account(close_cpi_ctx)?;
+
+        let old_escrow_wallet = data_account.escrow_wallet;
+        data_account.escrow_wallet = ctx.accounts.new_escrow_wallet.key();
+        emit!(EscrowMigrated {
+            data_account: data_account.key(),
+            old_escrow_wallet,
+            new_escrow_wallet: data_account.escrow_wallet,
+            amount,
+        });
+        Ok(())
+    }
+
+    // Switches payouts from a placeholder vesting mint to the real token, e.g. after TGE. The initializer deposits
+    // the escrow balance converted at `ratio_numerator / ratio_denominator` (raw payout units per raw escrow unit)
+    // into a new payout escrow, and the placeholder tokens in the escrow are burned. Allocations keep counting in the
+    // vesting mint; every later claim is converted at the same ratio and paid from the payout escrow.
+    pub fn set_payout_mint(ctx: Context<SetPayoutMint>, ratio_numerator: u64, ratio_denominator: u64) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(data_account.payout_mint.is_none(), VestingError::PayoutMintUnsupported);
+        require!(!data_account.frozen, VestingError::VestingFrozen);
+        require!(!data_account.mint_vesting, VestingError::MintVestingUnsupported);
+        require!(data_account.staked_amount == 0, VestingError::EscrowStaked);
+        // Cancellation, unclaimed withdrawals and closing only move the vesting mint, so nothing may need them
+        // once the escrow is swapped: no tokens may go back to the initializer, nor be forfeited at a deadline
         require!(
-            beneficiary_account_info.owner == &System::id(),
-            VestingError::BeneficiaryAlreadyExists
+            !data_account.is_cancellable && data_account.claim_deadline == 0,
+            VestingError::PayoutMintRequiresIrrevocable
         );
+        // Withholding and bonuses pay out in the vesting mint
+        require!(data_account.withholding_account.is_none(), VestingError::WithholdingUnsupported);
+        require!(data_account.bonus_rate_bps_per_year == 0, VestingError::BonusUnsupported);
+        require!(ratio_numerator > 0 && ratio_denominator > 0, VestingError::InvalidPayoutRatio);
+        require_keys_neq!(ctx.accounts.payout_mint.key(), data_account.token_mint, VestingError::InvalidPayoutRatio);
 
-        // Skip creation if already initialized
-        if beneficiary_account_info.owner == &System::id() {
-            let rent = Rent::get()?;
-            let space = std::mem::size_of::<BeneficiaryAccount>() + 8; // add discriminator
-            let lamports = rent.minimum_balance(space);
+        data_account.payout_mint = Some(ctx.accounts.payout_mint.key());
+        data_account.payout_escrow = ctx.accounts.payout_escrow.key();
+        data_account.payout_ratio_numerator = ratio_numerator;
+        data_account.payout_ratio_denominator = ratio_denominator;
 
-            invoke_signed(
-                &system_instruction::create_account(
-                    payer.key,
-                    &beneficiary_pda,
-                    lamports,
-                    space as u64,
-                    program_id,
-                ),
-                &[
-                    payer.to_account_info(),
-                    beneficiary_account_info.clone(),
-                    ctx.accounts.system_program.to_account_info(),
-                ],
-                &[&[
-                    b"beneficiary",
-                    data_account_key.as_ref(),
-                    beneficiary_pubkey.as_ref(),
-                    &[bump],
-                ]],
-            )?;
+        let escrow_balance = ctx.accounts.escrow_wallet.amount;
+        let payout_amount = data_account.to_payout_amount(escrow_balance)?;
+        let deposit_instruction = TransferChecked {
+            from: ctx.accounts.wallet_to_withdraw_from.to_account_info(),
+            mint: ctx.accounts.payout_mint.to_account_info(),
+            to: ctx.accounts.payout_escrow.to_account_info(),
+            authority: ctx.accounts.sender.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), deposit_instruction);
+        token::transfer_checked(cpi_ctx, payout_amount, ctx.accounts.payout_mint.decimals)?;
 
-            let mut account_data = BeneficiaryAccount {
-                key: beneficiary_pubkey,
-                allocated_tokens,
-                claimed_tokens: 0,
+        // The placeholder tokens are replaced, so they can never be paid out as well
+        let token_mint_key = ctx.accounts.token_mint.key();
+        let schedule_seed = data_account.schedule_seed();
+        let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[data_account.bump]];
+        let signer_seeds = &[&seeds[..]];
+        if escrow_balance > 0 {
+            let burn_instruction = Burn {
+                mint: ctx.accounts.token_mint.to_account_info(),
+                from: ctx.accounts.escrow_wallet.to_account_info(),
+                authority: data_account.to_account_info(),
             };
-            account_data
-                .try_serialize(&mut &mut beneficiary_account_info.data.borrow_mut()[..])?;
+            let burn_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                burn_instruction,
+                signer_seeds,
+            );
+            token::burn(burn_ctx, escrow_balance)?;
         }
+
+        emit!(PayoutMintSet {
+            data_account: data_account.key(),
+            payout_mint: ctx.accounts.payout_mint.key(),
+            ratio_numerator,
+            ratio_denominator,
+            burned: escrow_balance,
+            deposited: payout_amount,
+        });
+        Ok(())
     }
 
-    Ok(())
-}
+    // Turns the schedule into a trustless lockup, permanently: the initializer is cleared, so no admin
+    // instruction can be called anymore, the schedule switches to `TimeOnly` (disabling `release`),
+    // and it becomes irrevocable, disabling cancellation and `withdraw_unclaimed`.
+    // The guardian, claim approver and claim hook are cleared as well, since they could otherwise block claims
+    // forever.
+    pub fn renounce_admin(ctx: Context<RenounceAdmin>) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(!data_account.frozen, VestingError::VestingFrozen);
+        // Nobody could unstake the escrow afterwards
+        require!(data_account.staked_amount == 0, VestingError::EscrowStaked);
 
-    //Removes a list of beneficiary accounts from the vesting program.
-///
-/// # Arguments
-/// * `ctx` - The execution context containing all the necessary accounts.
-/// * `data_bump` - The bump seed for the data_account PDA.
-/// * `keys` - A vector of public keys representing the beneficiaries to be removed.
-///
-/// The function starts by:
-/// - Fetching the `program_id`, `data_account_key`, and `initializer` (the sender authorized to modify beneficiaries).
-/// - Preparing an iterator over `remaining_accounts`, which may be used to dynamically validate or deserialize the accounts
-///    corresponding to the beneficiary keys being removed.
-///
-/// Note: Actual logic for checking authority, validating keys, and updating state
+        data_account.initializer = Pubkey::default();
+        data_account.release_mode = ReleaseMode::TimeOnly;
+        data_account.is_cancellable = false;
+        data_account.cancel_effective_at = 0;
+        data_account.guardian = None;
+        data_account.claim_approver = None;
+        data_account.on_claim_hook = None;
+        data_account.operator = None;
+        data_account.treasurer = None;
+        emit!(AdminRenounced {
+            data_account: data_account.key(),
+            renounced_by: ctx.accounts.sender.key(),
+        });
+        Ok(())
+    }
 
+    // Permanently locks the schedule's configuration, e.g. for an exchange listing that needs assurance the
+    // vesting terms can't change. Unlike `renounce_admin` the initializer keeps the housekeeping instructions
+    // (deposits, unstaking, fee pool and rent reserve, closing), but every instruction that changes the
+    // schedule, its beneficiaries, releases, cancellation or roles fails with `ConfigFrozen` from then on.
+    pub fn freeze_config(ctx: Context<FreezeConfig>) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(!data_account.is_frozen, VestingError::ConfigFrozen);
 
+        // A scheduled cancellation could no longer be executed
+        data_account.cancel_effective_at = 0;
+        data_account.is_frozen = true;
+        emit!(ScheduleConfigFrozen {
+            data_account: data_account.key(),
+            frozen_by: ctx.accounts.sender.key(),
+        });
+        Ok(())
+    }
 
+    pub fn schedule_cancel(ctx: Context<ScheduleCancel>) -> Result<()> {
+        // Once an approver set is configured, cancellation must go through the multisig flow
+        require!(ctx.accounts.data_account.approvers.is_empty(), VestingError::MultisigRequired);
 
-pub fn remove_beneficiaries(
-    ctx: Context<RemoveBeneficiaries>,
-    data_bump: u8,
-    keys: Vec<Pubkey>,
-) -> Result<()> {
-    let program_id = ctx.program_id;
-    let data_account_key = ctx.accounts.data_account.key();
-    let initializer = &ctx.accounts.sender;
-    let mut remaining = ctx.remaining_accounts.iter();
+        let event = schedule_cancellation(&mut ctx.accounts.data_account, time_source::now(ctx.remaining_accounts)?)?;
+        emit_cpi!(event);
+        Ok(())
+    }
 
-    for key in keys {
-        let beneficiary_info = remaining
-            .next()
-            .ok_or(VestingError::MissingRemainingAccount)?;
+    // Returns what was handed back to the initializer through `set_return_data` (see `TransferResult`).
+    pub fn execute_cancel(
+    ctx: Context<ExecuteCancel>,
+) -> Result<TransferResult> {
+        // Get a mutable reference to the main vesting data account
+    let data_account = &mut ctx.accounts.data_account;
+    require!(data_account.is_cancellable, VestingError::VestingNotCancellable);
+    require!(!data_account.cancelled, VestingError::VestingCancelled);
+    require!(!data_account.frozen, VestingError::VestingFrozen);
+    require!(!data_account.mint_compromised, VestingError::MintCompromised);
+    require!(data_account.staked_amount == 0, VestingError::EscrowStaked);
+    require!(data_account.payout_mint.is_none(), VestingError::PayoutMintUnsupported);
+         // Get the current on-chain timestamp, never earlier than the last one this schedule processed
+    let now = data_account.advance_clock(time_source::now(ctx.remaining_accounts)?)?;
+// Ensure a cancellation was scheduled and its grace period has passed
+    require!(data_account.cancel_effective_at != 0, VestingError::CancelNotScheduled);
+    require!(now >= data_account.cancel_effective_at, VestingError::CancelGracePeriodActive);
+// Ensure vesting is still active (i.e., has not yet fully completed)
+    require!(now < data_account.vesting_end_timestamp(), VestingError::VestingAlreadyCompleted);
+        
+// Freeze the share of each allocation beneficiaries keep: everything vested so far,
+// plus `cancellation_penalty_bps` of the unvested remainder as compensation for the early cancellation
+    let vested_bps = data_account.vested_bps(now) as u64;
+    let final_vested_bps = vested_bps
+        + math::bps_of(10_000 - vested_bps, data_account.cancellation_penalty_bps).ok_or(VestingError::MathOverflow)?;
+// Tokens that stay in escrow so beneficiaries can still claim their frozen share, however it is rounded
+    let retained = data_account
+        .pool_vested_amount(final_vested_bps)?
+        .saturating_sub(data_account.claimed_total);
+
+// Total tokens deposited for vesting
+    let total_deposited = data_account.token_amount;
+        // Total tokens claimed by all beneficiaries so far
+    let total_claimed = data_account.claimed_total;
+        // Calculate unclaimed tokens still in escrow (excluding previously withdrawn unclaimed tokens)
+        // that beneficiaries are no longer entitled to
+    let unclaimed = total_deposited
+        .saturating_sub(total_claimed + data_account.unclaimed_withdrawn + retained);
+
+    data_account.cancelled = true;
+    data_account.final_vested_bps = final_vested_bps as u16;
+    let disposition = data_account.cancel_disposition;
+    let amount = raw_amount(unclaimed, data_account.decimals)?;
+    emit_cpi!(CancelExecuted {
+        data_account: data_account.key(),
+        final_vested_bps: data_account.final_vested_bps,
+        returned: if disposition == Disposition::Burn { 0 } else { amount },
+    });
+// Nothing to return when beneficiaries keep the whole remainder (e.g. a 100% penalty)
+    if unclaimed == 0 {
+        return Ok(data_account.transfer_result(0));
+    }
+    // The tokens go wherever `cancel_disposition` says; `recipient` must belong to the initializer or the treasurer
+    let recipient_owner = ctx.accounts.recipient.owner;
+    match disposition {
+        Disposition::ReturnToInitializer => {
+            require_keys_eq!(recipient_owner, data_account.initializer, VestingError::InvalidRecipient)
+        }
+        Disposition::SendToTreasury => {
+            let treasurer = data_account.treasurer.ok_or(VestingError::TreasurerNotSet)?;
+            require_keys_eq!(recipient_owner, treasurer, VestingError::InvalidRecipient);
+        }
+        Disposition::Burn => {}
+    }
+
+    // Derive the signer PDA seeds for signing the token transfer
+    let token_mint_key = ctx.accounts.token_mint.key();
+    let schedule_seed = data_account.schedule_seed();
+    let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[data_account.bump]];
+    let signer_seeds = &[&seeds[..]];
+
+     // Create a transfer instruction to move tokens from the program's escrow wallet to the recipient's account   
+    let transfer_instruction = TransferChecked {
+        from: ctx.accounts.escrow_wallet.to_account_info(), // Source escrow token account
+        mint: ctx.accounts.token_mint.to_account_info(),
+        to: ctx.accounts.recipient.to_account_info(),      // Destination recipient token account
+        authority: data_account.to_account_info(),     // PDA authority that signs the transfer
+    };
+        
+ // Create a CPI (Cross-Program Invocation) context with signer seeds
+// This context is used to authorize the token transfer using the program-derived address (PDA) as the signer
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(), // SPL Token program being invoked
+        transfer_instruction,      // Transfer instruction created earlier
+        signer_seeds,      // PDA seeds used to sign the CPI on behalf of the program
+    );
+    if disposition == Disposition::Burn {
+        let burn_instruction = Burn {
+            mint: ctx.accounts.token_mint.to_account_info(),
+            from: ctx.accounts.escrow_wallet.to_account_info(),
+            authority: data_account.to_account_info(),
+        };
+        let burn_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            burn_instruction,
+            signer_seeds,
+        );
+        token::burn(burn_ctx, amount)?;
+        emit_cpi!(CancelledTokensBurned {
+            data_account: data_account.key(),
+            burned: amount,
+        });
+    } else {
+// Perform the token transfer from the escrow wallet to the recipient using the CPI context
+        token::transfer_checked(cpi_ctx, amount, data_account.decimals)?;
+    }
+
+    data_account.unclaimed_withdrawn += unclaimed;
+    check_invariants(data_account, None, Some(&ctx.accounts.escrow_wallet.to_account_info()))?;
+
+    Ok(data_account.transfer_result(amount))
+}
+
+// Adds one chunk of a (possibly large) beneficiary batch.
+// `batch_offset` is the index of the chunk's first entry within the batch and must equal `batch_cursor`,
+// so a chunk that already landed can't be processed twice; clients resume from the stored cursor.
+// Chunks hold at most `MAX_BENEFICIARIES_PER_CHUNK` entries; the batch is closed by `finalize_beneficiary_batch`.
+// `remaining_accounts` holds a (beneficiary PDA, beneficiary index entry) pair per entry, the index entries in order
+// from `beneficiary_index_len`.
+pub fn add_beneficiaries<'info>(
+    ctx: Context<'_, '_, '_, 'info, AddBeneficiaries<'info>>,
+    new_beneficiaries: Vec<NewBeneficiary>,
+    batch_offset: u32,
+) -> Result<()> {
+     // Get the current program ID, data account, and payer (usually the authority/owner)
+    let program_id = ctx.program_id;
+    let data_account = &mut ctx.accounts.data_account;
+    let payer = &ctx.accounts.sender;
+    // Iterator over remaining accounts (used to receive dynamically generated PDAs for beneficiaries)
+    let mut remaining = ctx.remaining_accounts.iter();
+    // Tokens taken back by a cancellation can't be allocated to new beneficiaries
+    require!(!data_account.cancelled, VestingError::VestingCancelled);
+    // Fixed chunk size keeps every chunk within the compute and transaction size limits
+    require!(
+        new_beneficiaries.len() <= MAX_BENEFICIARIES_PER_CHUNK,
+        VestingError::BatchChunkTooLarge
+    );
+    require!(batch_offset == data_account.batch_cursor, VestingError::BatchCursorMismatch);
+    data_account.batch_cursor = data_account
+        .batch_cursor
+        .checked_add(new_beneficiaries.len() as u32)
+        .ok_or(VestingError::BatchCursorMismatch)?;
+
+     // Loop through each new beneficiary to add
+    for new in new_beneficiaries {
+        let beneficiary_account_info = remaining
+            .next()
+            .ok_or(VestingError::MissingRemainingAccount)?;
+        let index_account_info = remaining
+            .next()
+            .ok_or(VestingError::MissingRemainingAccount)?;
+        create_beneficiary_account(
+            program_id,
+            data_account,
+            payer,
+            &ctx.accounts.system_program,
+            beneficiary_account_info,
+            index_account_info,
+            &new,
+        )?;
+    }
+
+    emit_schedule_stats(data_account);
+    Ok(())
+}
+
+    // Copies one chunk of the source schedule's beneficiaries into a schedule created by `clone_schedule`.
+    // `remaining_accounts` holds a (source beneficiary account, new beneficiary PDA, new beneficiary index entry)
+    // triple per entry, as in `add_beneficiaries`. Allocations
+    // are scaled to the new amount (shares are copied as they are in shares mode), and terminated beneficiaries
+    // are rejected. Chunks share `batch_cursor` with `add_beneficiaries` and are closed by `finalize_beneficiary_batch`.
+    pub fn clone_beneficiaries<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloneBeneficiaries<'info>>,
+        batch_offset: u32,
+    ) -> Result<()> {
+        let program_id = ctx.program_id;
+        let source = &ctx.accounts.source_data_account;
+        let data_account = &mut ctx.accounts.data_account;
+        let payer = &ctx.accounts.sender;
+        require!(!data_account.cancelled, VestingError::VestingCancelled);
+        require!(ctx.remaining_accounts.len().is_multiple_of(3), VestingError::MissingRemainingAccount);
+        let count = ctx.remaining_accounts.len() / 3;
+        require!(count <= MAX_BENEFICIARIES_PER_CHUNK, VestingError::BatchChunkTooLarge);
+        require!(batch_offset == data_account.batch_cursor, VestingError::BatchCursorMismatch);
+        data_account.batch_cursor = data_account
+            .batch_cursor
+            .checked_add(count as u32)
+            .ok_or(VestingError::BatchCursorMismatch)?;
+
+        for accounts in ctx.remaining_accounts.chunks(3) {
+            let source_beneficiary: Account<'info, BeneficiaryAccount> = Account::try_from(&accounts[0])?;
+            let expected_pda = Pubkey::create_program_address(
+                &[
+                    BENEFICIARY_SEED,
+                    source.key().as_ref(),
+                    source_beneficiary.key.as_ref(),
+                    &[source_beneficiary.bump],
+                ],
+                program_id,
+            )
+            .map_err(|_| VestingError::InvalidBeneficiaryPDA)?;
+            require_keys_eq!(accounts[0].key(), expected_pda, VestingError::InvalidBeneficiaryPDA);
+            require!(!source_beneficiary.terminated, VestingError::BeneficiaryTerminated);
+
+            let allocated_tokens = if data_account.shares_mode {
+                source_beneficiary.shares
+            } else {
+                math::mul_div(source_beneficiary.allocated_tokens, data_account.token_amount, source.token_amount)
+                    .ok_or(VestingError::AllocationExceedsEscrow)?
+            };
+            let new = NewBeneficiary {
+                key: source_beneficiary.key,
+                allocated_tokens,
+                recovery_authority: source_beneficiary.recovery_authority,
+            };
+            create_beneficiary_account(
+                program_id,
+                data_account,
+                payer,
+                &ctx.accounts.system_program,
+                &accounts[1],
+                &accounts[2],
+                &new,
+            )?;
+        }
+
+        emit_schedule_stats(data_account);
+        Ok(())
+    }
+
+    // Adds a single beneficiary. Unlike `add_beneficiaries`, the beneficiary PDA is a regular account of the
+    // instruction, derived from `new_beneficiary.key`, so typed clients resolve it from the IDL. The sender pays its
+    // rent, and the entry doesn't count towards an open beneficiary batch.
+    pub fn add_beneficiary(ctx: Context<AddBeneficiary>, new_beneficiary: NewBeneficiary) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Tokens taken back by a cancellation can't be allocated to new beneficiaries
+        require!(!data_account.cancelled, VestingError::VestingCancelled);
+        let (allocated_tokens, shares) = data_account.register_allocation(new_beneficiary.allocated_tokens)?;
+
+        let beneficiary = &mut ctx.accounts.beneficiary_account;
+        beneficiary.key = new_beneficiary.key;
+        beneficiary.allocated_tokens = allocated_tokens;
+        beneficiary.shares = shares;
+        beneficiary.recovery_authority = new_beneficiary.recovery_authority;
+        beneficiary.bump = ctx.bumps.beneficiary_account;
+        beneficiary.index = data_account.next_beneficiary_index()?;
+        ctx.accounts.beneficiary_index.set_inner(BeneficiaryIndex {
+            data_account: data_account.key(),
+            beneficiary: beneficiary.key,
+            bump: ctx.bumps.beneficiary_index,
+        });
+        emit_schedule_stats(data_account);
+        check_invariants(data_account, Some(beneficiary), None)
+    }
+
+    // Closes the current beneficiary batch once all `expected_count` entries have been added,
+    // and resets `batch_cursor` for the next one.
+    pub fn finalize_beneficiary_batch(ctx: Context<FinalizeBeneficiaryBatch>, expected_count: u32) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        require!(data_account.batch_cursor == expected_count, VestingError::BatchIncomplete);
+
+        data_account.batch_cursor = 0;
+        emit!(BeneficiaryBatchFinalized {
+            data_account: data_account.key(),
+            count: expected_count,
+            total_allocated: data_account.total_allocated,
+        });
+        Ok(())
+    }
+
+    // Creates a single-use invitation for a beneficiary whose wallet isn't known yet ("claim link" onboarding).
+    // `invite_key` is the public key of a keypair handed out off-chain, e.g. in the link; whoever signs
+    // `redeem_invitation` with it becomes the beneficiary of `allocated_tokens`.
+    pub fn create_invitation(
+        ctx: Context<CreateInvitation>,
+        invite_key: Pubkey,
+        allocated_tokens: u64,
+    ) -> Result<()> {
+        let data_account = &ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(!data_account.cancelled, VestingError::VestingCancelled);
+        require!(!data_account.shares_mode, VestingError::SharesModeUnsupported);
+        require!(allocated_tokens > 0, VestingError::ZeroVestingAmount);
+
+        let invitation = &mut ctx.accounts.invitation;
+        invitation.data_account = data_account.key();
+        invitation.invite_key = invite_key;
+        invitation.allocated_tokens = allocated_tokens;
+        invitation.bump = ctx.bumps.invitation;
+        Ok(())
+    }
+
+    // Closes an unredeemed invitation, e.g. when its code leaked.
+    pub fn revoke_invitation(_ctx: Context<RevokeInvitation>) -> Result<()> {
+        // The invitation is closed by the `close = sender` constraint
+        Ok(())
+    }
+
+    // Redeems an invitation: `sender` co-signs with the invitation key and gets its own `BeneficiaryAccount` with the
+    // invitation's allocation. The signature covers `sender`, so a pending redemption can't be replayed for another
+    // wallet. The allocation is checked against the escrow only now, like `add_beneficiaries` does.
+    // The invitation is closed and its rent goes back to the initializer.
+    pub fn redeem_invitation(ctx: Context<RedeemInvitation>) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let invitation = &ctx.accounts.invitation;
+        require!(!data_account.cancelled, VestingError::VestingCancelled);
+        require!(!data_account.shares_mode, VestingError::SharesModeUnsupported);
+
+        // Keep the sum of all allocations within the amount deposited in escrow
+        data_account.total_allocated = data_account
+            .total_allocated
+            .checked_add(invitation.allocated_tokens)
+            .ok_or(VestingError::AllocationExceedsEscrow)?;
+        require!(
+            data_account.total_allocated <= data_account.token_amount,
+            VestingError::AllocationExceedsEscrow
+        );
+        data_account.count_new_beneficiary()?;
+        emit_schedule_stats(data_account);
+
+        let beneficiary = &mut ctx.accounts.beneficiary_account;
+        beneficiary.key = ctx.accounts.sender.key();
+        beneficiary.allocated_tokens = invitation.allocated_tokens;
+        beneficiary.bump = ctx.bumps.beneficiary_account;
+        beneficiary.index = data_account.next_beneficiary_index()?;
+        ctx.accounts.beneficiary_index.set_inner(BeneficiaryIndex {
+            data_account: data_account.key(),
+            beneficiary: beneficiary.key,
+            bump: ctx.bumps.beneficiary_index,
+        });
+        check_invariants(data_account, Some(beneficiary), None)
+    }
+
+    // First phase of removing beneficiaries: marks each of `keys` with `removal_executable_at`, the end of the
+    // schedule's dispute window, after which `execute_removal` can close it. Until then the beneficiary keeps
+    // claiming as usual and the removal arbiter, if any, can `veto_removal`.
+    // The beneficiary accounts are passed through `remaining_accounts` in the same order as `keys`.
+    pub fn schedule_removal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ScheduleRemoval<'info>>,
+        keys: Vec<Pubkey>,
+    ) -> Result<()> {
+        let program_id = ctx.program_id;
+        let data_account = &mut ctx.accounts.data_account;
+        let data_account_key = data_account.key();
+        require!(!data_account.shares_mode, VestingError::SharesModeUnsupported);
+        require!(keys.len() <= MAX_BENEFICIARIES_PER_CHUNK, VestingError::BatchTooLarge);
+        let now = data_account.advance_clock(time_source::now(ctx.remaining_accounts)?)?;
+        let executable_at = now + data_account.removal_dispute_window();
+
+        let mut remaining = ctx.remaining_accounts.iter();
+        for key in keys {
+            let beneficiary_info = remaining.next().ok_or(VestingError::MissingRemainingAccount)?;
+            let (expected_pda, _bump) =
+                Pubkey::find_program_address(&[BENEFICIARY_SEED, data_account_key.as_ref(), key.as_ref()], program_id);
+            require_keys_eq!(beneficiary_info.key(), expected_pda, VestingError::InvalidBeneficiaryPDA);
+            let mut beneficiary: Account<'info, BeneficiaryAccount> = Account::try_from(beneficiary_info)?;
+            require!(beneficiary.removal_executable_at == 0, VestingError::RemovalAlreadyScheduled);
+            beneficiary.removal_executable_at = executable_at;
+            beneficiary.exit(program_id)?;
+            emit!(RemovalScheduled {
+                data_account: data_account_key,
+                beneficiary: key,
+                executable_at,
+            });
+        }
+        Ok(())
+    }
+
+    // Cancels the scheduled removal of `beneficiary_account`. Only the removal arbiter can call it, during the
+    // dispute window or afterwards as long as the removal hasn't been executed.
+    pub fn veto_removal(ctx: Context<VetoRemoval>) -> Result<()> {
+        let beneficiary = &mut ctx.accounts.beneficiary_account;
+        require!(beneficiary.removal_executable_at != 0, VestingError::RemovalNotScheduled);
+        beneficiary.removal_executable_at = 0;
+        emit!(RemovalVetoed {
+            data_account: ctx.accounts.data_account.key(),
+            beneficiary: beneficiary.key,
+            vetoed_by: ctx.accounts.sender.key(),
+        });
+        Ok(())
+    }
+
+    //Removes a list of beneficiary accounts from the vesting program, once their removal was scheduled with
+    //`schedule_removal` and its dispute window has passed.
+///
+/// # Arguments
+/// * `ctx` - The execution context containing all the necessary accounts.
+/// * `keys` - A vector of public keys representing the beneficiaries to be removed.
+/// * `force` - Remove beneficiaries even if they still have vested tokens left to claim.
+///
+/// Each beneficiary account is passed through `remaining_accounts` in the same order as `keys`, followed by its
+/// beneficiary index entry, which is closed too, and by its `Cohort` account if it is in a cohort. Its index isn't
+/// reused.
+/// Accounts are closed with Anchor's `close` semantics: lamports are refunded to the initializer,
+/// the data is truncated and ownership is handed back to the System Program, so the account
+/// cannot be revived later in the same transaction.
+pub fn execute_removal<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExecuteRemoval<'info>>,
+    keys: Vec<Pubkey>,
+    force: bool,
+) -> Result<()> {
+    let program_id = ctx.program_id;
+    let data_account = &mut ctx.accounts.data_account;
+    let data_account_key = data_account.key();
+    let rent_recipient = &ctx.accounts.rent_recipient;
+    let mut remaining = ctx.remaining_accounts.iter();
+    // Dropping shares would grow everyone else's share of the pool, including tokens already paid out
+    require!(!data_account.shares_mode, VestingError::SharesModeUnsupported);
+    let now = data_account.advance_clock(time_source::now(ctx.remaining_accounts)?)?;
+    require!(keys.len() <= MAX_BENEFICIARIES_PER_CHUNK, VestingError::BatchTooLarge);
+
+    for key in keys {
+        let beneficiary_info = remaining
+            .next()
+            .ok_or(VestingError::MissingRemainingAccount)?;
+
+        // Derive the expected PDA for the beneficiary
+        let seeds = &[BENEFICIARY_SEED, data_account_key.as_ref(), key.as_ref()];
+        let (expected_pda, _bump) = Pubkey::find_program_address(seeds, program_id);
+
+        require_keys_eq!(beneficiary_info.key(), expected_pda, VestingError::InvalidBeneficiaryPDA);
+
+        // Deserialize the account, which also checks owner and discriminator
+        let beneficiary: Account<'info, BeneficiaryAccount> = Account::try_from(beneficiary_info)?;
+
+        let index_info = remaining
+            .next()
+            .ok_or(VestingError::MissingRemainingAccount)?;
+        let (expected_index, _bump) = Pubkey::find_program_address(
+            &[BENEFICIARY_INDEX_SEED, data_account_key.as_ref(), &beneficiary.index.to_le_bytes()],
+            program_id,
+        );
+        require_keys_eq!(index_info.key(), expected_index, VestingError::InvalidBeneficiaryIndex);
+        let index_entry: Account<'info, BeneficiaryIndex> = Account::try_from(index_info)?;
+        let cohort = match beneficiary.cohort_id {
+            Some(cohort_id) => {
+                let cohort_info = remaining
+                    .next()
+                    .ok_or(VestingError::MissingRemainingAccount)?;
+                let (expected_cohort, _bump) = Pubkey::find_program_address(
+                    &[COHORT_SEED, data_account_key.as_ref(), &cohort_id.to_le_bytes()],
+                    program_id,
+                );
+                require_keys_eq!(cohort_info.key(), expected_cohort, VestingError::CohortMismatch);
+                Some(Account::<'info, Cohort>::try_from(cohort_info)?)
+            }
+            None => None,
+        };
+
+        // Only removals scheduled with `schedule_removal` whose dispute window has passed
+        require!(beneficiary.removal_executable_at != 0, VestingError::RemovalNotScheduled);
+        require!(now >= beneficiary.removal_executable_at, VestingError::DisputeWindowActive);
+
+        // Refuse to drop a beneficiary who still has vested tokens waiting, unless forced
+        let unclaimed_vested = beneficiary
+            .vested_amount(data_account, beneficiary.vested_bps(data_account, cohort.as_deref(), now)?)
+            .saturating_sub(beneficiary.claimed_tokens);
+        require!(force || unclaimed_vested == 0, VestingError::BeneficiaryHasUnclaimedTokens);
+        // Parked tokens are still in the escrow and would be lost with the account, even when forced
+        require!(beneficiary.parked_tokens == 0, VestingError::ParkedClaimPending);
+
+        // Free the unclaimed part of the allocation. What was claimed stays in the totals and is tracked in
+        // `removed_claimed`, so the pool's vested share only counts the remaining beneficiaries.
+        // `terminate_beneficiary` already took terminated beneficiaries out of the totals.
+        if !beneficiary.terminated {
+            data_account.total_allocated = data_account
+                .total_allocated
+                .saturating_sub(beneficiary.allocated_tokens.saturating_sub(beneficiary.claimed_tokens));
+            data_account.removed_claimed = data_account
+                .removed_claimed
+                .checked_add(beneficiary.claimed_tokens)
+                .ok_or(VestingError::MathOverflow)?;
+            data_account.beneficiary_count = data_account.beneficiary_count.saturating_sub(1);
+        }
+
+        // Close the accounts, refunding lamports to the chosen rent recipient
+        beneficiary.close(rent_recipient.to_account_info())?;
+        index_entry.close(rent_recipient.to_account_info())?;
+    }
+
+    emit_schedule_stats(data_account);
+    Ok(())
+}
+
+    // Attaches another mint to the schedule (e.g. a stablecoin next to the governance token).
+    // `amount` whole tokens of `leg_mint` are deposited into a leg escrow and paid out by `claim`
+    // in proportion to the primary tokens claimed, at the rate fixed here against `token_amount`.
+    // Legs can only be added before the first claim, so every beneficiary gets the same rate.
+    pub fn add_vesting_leg(ctx: Context<AddVestingLeg>, amount: u64) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(amount > 0, VestingError::ZeroVestingAmount);
+        require!(data_account.claimed_total == 0, VestingError::VestingLegsLocked);
+        require!((data_account.leg_count as usize) < MAX_VESTING_LEGS, VestingError::TooManyVestingLegs);
+        require_keys_neq!(
+            ctx.accounts.leg_mint.key(),
+            data_account.token_mint,
+            VestingError::InvalidTokenAccount
+        );
+
+        let leg = &mut ctx.accounts.vesting_leg;
+        leg.data_account = data_account.key();
+        leg.index = data_account.leg_count;
+        leg.mint = ctx.accounts.leg_mint.key();
+        leg.escrow_wallet = ctx.accounts.leg_escrow.key();
+        leg.amount = amount;
+        leg.primary_amount = data_account.token_amount;
+        leg.decimals = ctx.accounts.leg_mint.decimals;
+        leg.bump = ctx.bumps.vesting_leg;
+        leg.escrow_bump = ctx.bumps.leg_escrow;
+        data_account.leg_count += 1;
+
+        let transfer_instruction = TransferChecked {
+            from: ctx.accounts.wallet_to_withdraw_from.to_account_info(),
+            mint: ctx.accounts.leg_mint.to_account_info(),
+            to: ctx.accounts.leg_escrow.to_account_info(),
+            authority: ctx.accounts.sender.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), transfer_instruction);
+        token::transfer_checked(cpi_ctx, raw_amount(amount, leg.decimals)?, leg.decimals)?;
+        Ok(())
+    }
+
+    // Returns what a leg escrow holds beyond the leg's share of the primary tokens still to be claimed, i.e. the
+    // share of tokens withdrawn, cancelled or terminated away, to the initializer's `recipient` account for the leg
+    // mint. Once nothing is left to claim, the schedule's last leg is closed too, with its escrow, refunding their
+    // rent to the initializer; closing every leg this way, last one first, lets `close_vesting` run.
+    pub fn withdraw_leg_remainder(ctx: Context<WithdrawLegRemainder>) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let leg = &ctx.accounts.vesting_leg;
+        require!(!data_account.frozen, VestingError::VestingFrozen);
+        require!(!data_account.mint_compromised, VestingError::MintCompromised);
+
+        // Claims pay the leg pro rata to the primary tokens, rounded down, so this always covers them
+        let outstanding = data_account
+            .token_amount
+            .saturating_sub(data_account.claimed_total + data_account.unclaimed_withdrawn);
+        let amount_raw = ctx.accounts.leg_escrow.amount.saturating_sub(leg.payout_for(outstanding)?);
+        let close_leg = outstanding == 0 && leg.index + 1 == data_account.leg_count;
+        require!(amount_raw > 0 || close_leg, VestingError::NoUnclaimedTokens);
+
+        let token_mint_key = ctx.accounts.token_mint.key();
+        let schedule_seed = data_account.schedule_seed();
+        let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[data_account.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        if amount_raw > 0 {
+            let transfer_instruction = TransferChecked {
+                from: ctx.accounts.leg_escrow.to_account_info(),
+                mint: ctx.accounts.leg_mint.to_account_info(),
+                to: ctx.accounts.recipient.to_account_info(),
+                authority: data_account.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                transfer_instruction,
+                signer_seeds,
+            );
+            token::transfer_checked(cpi_ctx, amount_raw, leg.decimals)?;
+        }
+        if close_leg {
+            let close_instruction = CloseAccount {
+                account: ctx.accounts.leg_escrow.to_account_info(),
+                destination: ctx.accounts.sender.to_account_info(),
+                authority: data_account.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                close_instruction,
+                signer_seeds,
+            );
+            token::close_account(cpi_ctx)?;
+            leg.close(ctx.accounts.sender.to_account_info())?;
+            data_account.leg_count -= 1;
+        }
+
+        emit!(LegRemainderWithdrawn {
+            data_account: data_account.key(),
+            leg_mint: leg.mint,
+            amount: amount_raw,
+            leg_closed: close_leg,
+        });
+        Ok(())
+    }
+
+    pub fn deposit_additional(
+        ctx: Context<DepositAdditional>,
+        amount: u64,
+    ) -> Result<()> {
+        // Ensure the top-up amount is greater than zero
+        require!(amount > 0, VestingError::ZeroVestingAmount);
+        // Mint-vesting schedules have no escrow to top up
+        require!(!ctx.accounts.data_account.mint_vesting, VestingError::MintVestingUnsupported);
+        require!(ctx.accounts.data_account.payout_mint.is_none(), VestingError::PayoutMintUnsupported);
+        // Legs pay out against the `token_amount` they were added at, so the pool can't grow under them
+        require!(ctx.accounts.data_account.leg_count == 0, VestingError::VestingLegsUnsupported);
+        let decimals = ctx.accounts.data_account.decimals;
+
+        // Transfer the additional tokens from the initializer into the escrow wallet
+        let transfer_instruction = TransferChecked {
+            from: ctx.accounts.wallet_to_withdraw_from.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
+            to: ctx.accounts.escrow_wallet.to_account_info(),
+            authority: ctx.accounts.sender.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            transfer_instruction,
+        );
+        token::transfer_checked(cpi_ctx, raw_amount(amount, decimals)?, decimals)?;
+
+        // Grow the vesting pool by the deposited amount; on installment-funded schedules it is funded as well
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.token_amount = data_account.token_amount.saturating_add(amount);
+        if data_account.installment_funding {
+            data_account.funded_amount = data_account.funded_amount.saturating_add(amount);
+        }
+        data_account.sync_pool_allocation();
+
+        // Make sure the escrow actually holds every funded token still owed to beneficiaries
+        ctx.accounts.escrow_wallet.reload()?;
+        let data_account = &ctx.accounts.data_account;
+        let outstanding = data_account
+            .funded_tokens()
+            .saturating_sub(data_account.claimed_total + data_account.unclaimed_withdrawn);
+        require!(
+            ctx.accounts.escrow_wallet.amount >= raw_amount(outstanding, decimals)?,
+            VestingError::EscrowBalanceTooLow
+        );
+
+        Ok(())
+    }
+
+    // Tops up the escrow of an installment-funded schedule (see `initialize_with_installments`) by `amount` whole
+    // tokens toward the committed `token_amount`. Unlike `deposit_additional`, the commitment doesn't change.
+    pub fn fund_installment(ctx: Context<FundInstallment>, amount: u64) -> Result<()> {
+        let data_account = &ctx.accounts.data_account;
+        require!(data_account.installment_funding, VestingError::InstallmentFundingDisabled);
+        require!(
+            amount > 0 && data_account.funded_amount.saturating_add(amount) <= data_account.token_amount,
+            VestingError::InvalidInstallment
+        );
+        let decimals = data_account.decimals;
+
+        let transfer_instruction = TransferChecked {
+            from: ctx.accounts.wallet_to_withdraw_from.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
+            to: ctx.accounts.escrow_wallet.to_account_info(),
+            authority: ctx.accounts.sender.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), transfer_instruction);
+        token::transfer_checked(cpi_ctx, raw_amount(amount, decimals)?, decimals)?;
+
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.funded_amount += amount;
+        emit!(InstallmentFunded {
+            data_account: data_account.key(),
+            amount,
+            funded_amount: data_account.funded_amount,
+            committed_amount: data_account.token_amount,
+        });
+        Ok(())
+    }
+
+    pub fn get_claimable(ctx: Context<GetClaimable>) -> Result<ClaimableAmounts> {
+        let data_account = &ctx.accounts.data_account;
+        let beneficiary = &ctx.accounts.beneficiary_account;
+        let now = data_account.monotonic_now(time_source::now(ctx.remaining_accounts)?);
+
+        // Same math as `claim`, so clients and CPI callers don't have to replicate it
+        let cohort = ctx.accounts.cohort.as_deref();
+        let vested_amount = beneficiary.vested_amount(data_account, beneficiary.vested_bps(data_account, cohort, now)?);
+
+        // Returned to the caller through `set_return_data`
+        let claimable_now = if data_account.claim_window_expired(now) {
+            0
+        } else {
+            data_account
+                .cap_funded(beneficiary, vested_amount)
+                .saturating_sub(beneficiary.claimed_tokens)
+                .saturating_sub(beneficiary.vote_locked(now))
+        };
+        Ok(ClaimableAmounts {
+            vested_amount,
+            claimable_now,
+            next_unlock_timestamp: beneficiary.next_unlock_timestamp(data_account, cohort, now)?,
+        })
+    }
+
+    // Tokens that vested from `period_start` to `period_end` for `beneficiary_account`, or for all active
+    // allocations (`total_allocated`) when it is omitted, returned through `set_return_data`. Uses the same curve
+    // as `claim`, so financial closes don't have to reimplement it off-chain.
+    pub fn report_accruals(ctx: Context<ReportAccruals>, period_start: i64, period_end: i64) -> Result<AccrualReport> {
+        require!(period_start <= period_end, VestingError::InvalidReportPeriod);
+        let data_account = &ctx.accounts.data_account;
+        let schedule = data_account.schedule();
+        // A beneficiary's voluntary extension shifts its curve; schedule totals ignore extensions
+        let (entitlement, extension) = match &ctx.accounts.beneficiary_account {
+            Some(beneficiary) => {
+                // The report takes no cohort account, so it would show a member the schedule's curve
+                require!(beneficiary.cohort_id.is_none(), VestingError::CohortMismatch);
+                (beneficiary.entitlement(data_account), beneficiary.voluntary_extension_seconds as i64)
+            }
+            None => (data_account.total_allocated, 0),
+        };
+        let (period_start, period_end) = (period_start.saturating_sub(extension), period_end.saturating_sub(extension));
+        Ok(AccrualReport {
+            vested_at_start: math::vested_amount(entitlement, schedule.reported_vested_bps(period_start)),
+            vested_at_end: math::vested_amount(entitlement, schedule.reported_vested_bps(period_end)),
+            accrued: schedule.accrued_amount(entitlement, period_start, period_end),
+        })
+    }
+
+    // Unlock table of a hypothetical allocation under the given schedule parameters, computed with the
+    // same math `claim` uses, so front-ends can chart it without re-implementing the schedule.
+    // Only points where the cumulative amount grows are returned.
+    pub fn preview_schedule(
+        _ctx: Context<PreviewSchedule>,
+        allocated_tokens: u64,
+        params: SchedulePreviewParams,
+    ) -> Result<Vec<UnlockPoint>> {
+        require!(params.tge_unlock_bps <= 10_000, VestingError::InvalidTgeUnlock);
+        let schedule = math::Schedule {
+            start_timestamp: params.start_timestamp,
+            vesting_months: params.vesting_months,
+            cliff_months: params.cliff_months,
+            calendar_months: params.calendar_months,
+            tge_unlock_bps: params.tge_unlock_bps,
+            lockup_seconds: params.lockup_seconds,
+            time_vesting: true,
+            ..Default::default()
+        };
+
+        let mut points: Vec<UnlockPoint> = Vec::new();
+        for (timestamp, cumulative_amount) in schedule.unlock_table(allocated_tokens) {
+            if points.last().map_or(0, |point| point.cumulative_amount) < cumulative_amount {
+                points.push(UnlockPoint { timestamp, cumulative_amount });
+            }
+        }
+        // Return data is limited to 1024 bytes
+        require!(points.len() <= MAX_PREVIEW_POINTS, VestingError::PreviewTooLong);
+        Ok(points)
+    }
+
+    // Governance weight of a beneficiary's locked tokens (unvested + vested but unclaimed),
+    // returned through `set_return_data`.
+    pub fn get_voting_weight(ctx: Context<GetClaimable>) -> Result<VotingWeight> {
+        let now = ctx.accounts.data_account.monotonic_now(time_source::now(ctx.remaining_accounts)?);
+        ctx.accounts
+            .beneficiary_account
+            .voting_weight(&ctx.accounts.data_account, ctx.accounts.cohort.as_deref(), now)
+    }
+
+    // Creates the SPL Governance voter weight record of a beneficiary for `realm`.
+    // Anyone can pay for it; its weight is set by `update_voter_weight_record`.
+    pub fn create_voter_weight_record(ctx: Context<CreateVoterWeightRecord>, realm: Pubkey) -> Result<()> {
+        let record = &mut ctx.accounts.voter_weight_record;
+        record.realm = realm;
+        record.governing_token_mint = ctx.accounts.token_mint.key();
+        record.governing_token_owner = ctx.accounts.beneficiary_account.key;
+        Ok(())
+    }
+
+    // Refreshes a voter weight record for use by SPL Governance in the same slot,
+    // as required by the voter-weight addin interface.
+    pub fn update_voter_weight_record(ctx: Context<UpdateVoterWeightRecord>) -> Result<()> {
+        let clock = Clock::get()?;
+        let now = time_source::now(ctx.remaining_accounts)?;
+        let weight = ctx
+            .accounts
+            .beneficiary_account
+            .voting_weight(&ctx.accounts.data_account, ctx.accounts.cohort.as_deref(), now)?;
+
+        let record = &mut ctx.accounts.voter_weight_record;
+        record.voter_weight = weight.voter_weight;
+        record.voter_weight_expiry = Some(clock.slot);
+        // The weight is the same for every governance action
+        record.weight_action = None;
+        record.weight_action_target = None;
+        Ok(())
+    }
+
+    // veToken-style lock: the beneficiary locks everything vested but unclaimed, in place in the escrow, for
+    // `duration` seconds and gets a non-transferable voting weight in its `VoteLockAccount`. The locked tokens
+    // can't be claimed until the lock expires; tokens vesting later stay claimable. Locking again relocks
+    // everything vested but unclaimed by then, and can only move the expiry out.
+    pub fn lock_for_voting(ctx: Context<LockForVoting>, duration: i64) -> Result<()> {
+        let data_account = &ctx.accounts.data_account;
+        let beneficiary = &mut ctx.accounts.beneficiary_account;
+        require!(duration > 0 && duration <= MAX_VOTE_LOCK_DURATION, VestingError::InvalidVoteLockDuration);
+        require!(!data_account.frozen, VestingError::VestingFrozen);
+        require!(!beneficiary.terminated, VestingError::BeneficiaryTerminated);
+        // The NFT holder, not the original wallet, owns an NFT-backed position
+        require!(beneficiary.position_mint.is_none(), VestingError::PositionNftUnsupported);
+
+        let now = data_account.monotonic_now(time_source::now(ctx.remaining_accounts)?);
+        let unlock_at = now.saturating_add(duration);
+        require!(unlock_at >= beneficiary.vote_lock_expires_at, VestingError::InvalidVoteLockDuration);
+        let locked_amount = beneficiary
+            .vested_amount(data_account, beneficiary.vested_bps(data_account, ctx.accounts.cohort.as_deref(), now)?)
+            .saturating_sub(beneficiary.claimed_tokens);
+        require!(locked_amount > 0, VestingError::NothingToLock);
+        beneficiary.vote_locked_amount = locked_amount;
+        beneficiary.vote_lock_expires_at = unlock_at;
+
+        let locked_raw = math::to_raw_amount(locked_amount, data_account.decimals).ok_or(VestingError::MathOverflow)?;
+        let vote_lock = &mut ctx.accounts.vote_lock;
+        vote_lock.data_account = data_account.key();
+        vote_lock.beneficiary = beneficiary.key;
+        vote_lock.locked_amount = locked_amount;
+        vote_lock.locked_at = now;
+        vote_lock.unlock_at = unlock_at;
+        vote_lock.voting_weight = math::mul_div(locked_raw, duration as u64, MAX_VOTE_LOCK_DURATION as u64)
+            .ok_or(VestingError::MathOverflow)?;
+        vote_lock.bump = ctx.bumps.vote_lock;
+
+        emit!(VotesLocked {
+            data_account: data_account.key(),
+            beneficiary: beneficiary.key,
+            locked_amount,
+            unlock_at,
+            voting_weight: vote_lock.voting_weight,
+        });
+        Ok(())
+    }
+
+    // Writes a snapshot of the signer's position to their `StatementAccount`, e.g. to show a lender or auditor
+    // without pointing them at the wallet's history. Only this program can write the account, so its contents
+    // are as trustworthy as the schedule itself. Calling it again refreshes the snapshot in place.
+    pub fn generate_statement(ctx: Context<GenerateStatement>) -> Result<()> {
+        let data_account = &ctx.accounts.data_account;
+        let beneficiary = &ctx.accounts.beneficiary_account;
+        let now = data_account.monotonic_now(time_source::now(ctx.remaining_accounts)?);
+        let allocated_tokens = beneficiary.entitlement(data_account);
+
+        let statement = &mut ctx.accounts.statement;
+        statement.data_account = data_account.key();
+        statement.beneficiary = beneficiary.key;
+        statement.allocated_tokens = allocated_tokens;
+        let vested_bps = beneficiary.vested_bps(data_account, ctx.accounts.cohort.as_deref(), now)?;
+        statement.vested_tokens = beneficiary.vested_amount(data_account, vested_bps);
+        statement.claimed_tokens = beneficiary.claimed_tokens;
+        statement.remaining_tokens = allocated_tokens.saturating_sub(beneficiary.claimed_tokens);
+        statement.as_of = now;
+        statement.bump = ctx.bumps.statement;
+        Ok(())
+    }
+
+    pub fn set_approvers(
+        ctx: Context<SetApprovers>,
+        approvers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // The approver set can only be configured once; afterwards the initializer alone can't change it
+        require!(data_account.approvers.is_empty(), VestingError::ApproversAlreadySet);
+        require!(
+            !approvers.is_empty() && approvers.len() <= MAX_APPROVERS,
+            VestingError::InvalidApprovers
+        );
+        // Reject duplicate approvers so one key can't count twice towards the threshold
+        for (i, approver) in approvers.iter().enumerate() {
+            require!(!approvers[..i].contains(approver), VestingError::InvalidApprovers);
+        }
+        require!(
+            threshold > 0 && threshold as usize <= approvers.len(),
+            VestingError::InvalidThreshold
+        );
+
+        data_account.approvers = approvers;
+        data_account.approval_threshold = threshold;
+        Ok(())
+    }
+
+    pub fn propose_release(
+        ctx: Context<ProposeRelease>,
+        action: ProposalAction,
+    ) -> Result<()> {
+        let data_account = &ctx.accounts.data_account;
+        // Only members of the approver set may open a proposal
+        let index = data_account.approver_index(&ctx.accounts.sender.key())?;
+        if let ProposalAction::Release { percent } = action {
+            require!(percent <= 100, VestingError::InvalidPercentage);
+        }
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.data_account = data_account.key();
+        proposal.proposer = ctx.accounts.sender.key();
+        proposal.action = action;
+        // The proposer's approval is counted right away
+        proposal.approvals = 1 << index;
+        proposal.bump = ctx.bumps.proposal;
+        Ok(())
+    }
+
+    pub fn approve_release(ctx: Context<ApproveRelease>) -> Result<()> {
+        let index = ctx
+            .accounts
+            .data_account
+            .approver_index(&ctx.accounts.sender.key())?;
+        let proposal = &mut ctx.accounts.proposal;
+        // Each approver may only approve once
+        require!(proposal.approvals & (1 << index) == 0, VestingError::AlreadyApproved);
+        proposal.approvals |= 1 << index;
+        Ok(())
+    }
+
+    pub fn execute_release(ctx: Context<ExecuteRelease>) -> Result<()> {
+        // Any approver may execute once enough approvals have been collected
+        ctx.accounts
+            .data_account
+            .approver_index(&ctx.accounts.sender.key())?;
+        let approvals = ctx.accounts.proposal.approvals.count_ones();
+        require!(
+            approvals >= ctx.accounts.data_account.approval_threshold as u32,
+            VestingError::NotEnoughApprovals
+        );
+
+        match ctx.accounts.proposal.action {
+            ProposalAction::Release { percent } => {
+                ctx.accounts.data_account.add_release(percent)?;
+                emit_cpi!(TokensReleased {
+                    data_account: ctx.accounts.data_account.key(),
+                    percent,
+                    percent_available: ctx.accounts.data_account.percent_available,
+                });
+            }
+            ProposalAction::Cancel => {
+                let event = schedule_cancellation(&mut ctx.accounts.data_account, time_source::now(ctx.remaining_accounts)?)?;
+                emit_cpi!(event);
+            }
+            ProposalAction::Accelerate => {
+                trigger_acceleration(&mut ctx.accounts.data_account, time_source::now(ctx.remaining_accounts)?)?;
+            }
+            ProposalAction::Unfreeze => {
+                ctx.accounts.data_account.frozen = false;
+            }
+        }
+
+        // The proposal account is closed by the `close = proposer` constraint, freeing the PDA for the next one
+        Ok(())
+    }
+
+    // Withdraws the open proposal without executing it, e.g. one that can no longer reach the threshold.
+    // Any approver may cancel; the rent goes back to the proposer and the PDA is free for the next proposal.
+    pub fn cancel_release_proposal(ctx: Context<CancelReleaseProposal>) -> Result<()> {
+        ctx.accounts
+            .data_account
+            .approver_index(&ctx.accounts.sender.key())?;
+        // The proposal account is closed by the `close = proposer` constraint
+        Ok(())
+    }
+
+    // Amends a live schedule. Every argument is optional:
+    // - `vesting_months`: extend the vesting duration (it can never be shortened)
+    // - `cliff_months`: move a cliff that has not passed yet
+    // - `allocated_tokens`: change the unvested part of the allocation of the beneficiary passed in
+    //   `beneficiary_account`; reducing it requires the beneficiary to co-sign as `beneficiary`.
+    pub fn amend_schedule(
+        ctx: Context<AmendSchedule>,
+        vesting_months: Option<u8>,
+        cliff_months: Option<u8>,
+        allocated_tokens: Option<u64>,
+    ) -> Result<()> {
+        let program_id = ctx.program_id;
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, amendments must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        // A cancelled schedule is settled; its frozen shares can't be changed any more
+        require!(!data_account.cancelled, VestingError::VestingCancelled);
+        let now = data_account.advance_clock(time_source::now(ctx.remaining_accounts)?)?;
+
+        // Schedule-wide changes slow vesting down for every beneficiary,
+        // so they are only possible on revocable schedules that are still running
+        if vesting_months.is_some() || cliff_months.is_some() {
+            // Interval schedules don't vest by month
+            require!(data_account.interval_seconds == 0, VestingError::IntervalScheduleUnsupported);
+            require!(data_account.is_cancellable, VestingError::VestingNotCancellable);
+            // Custom curves are fixed in months, so a longer schedule wouldn't vest any slower
+            require!(
+                vesting_months.is_none() || !matches!(data_account.curve, CurveType::Custom(_)),
+                VestingError::CurveUnsupported
+            );
+            require!(now < data_account.vesting_end_timestamp(), VestingError::VestingAlreadyCompleted);
+        }
+        if let Some(months) = vesting_months {
+            require!(months >= data_account.vesting_months, VestingError::InvalidAmendment);
+            data_account.vesting_months = months;
+        }
+        if let Some(cliff) = cliff_months {
+            let cliff_pending = now < data_account.vesting_start_timestamp()
+                || data_account.elapsed_months(now) < data_account.cliff_months as i64;
+            require!(cliff_pending, VestingError::InvalidAmendment);
+            data_account.cliff_months = cliff;
+        }
+        require!(
+            data_account.cliff_months <= data_account.vesting_months,
+            VestingError::InvalidAmendment
+        );
+
+        if let Some(allocated_tokens) = allocated_tokens {
+            require!(!data_account.shares_mode, VestingError::SharesModeUnsupported);
+            let beneficiary = ctx
+                .accounts
+                .beneficiary_account
+                .as_mut()
+                .ok_or(VestingError::MissingRemainingAccount)?;
+            let expected_pda = Pubkey::create_program_address(
+                &[BENEFICIARY_SEED, data_account.key().as_ref(), beneficiary.key.as_ref(), &[beneficiary.bump]],
+                program_id,
+            )
+            .map_err(|_| VestingError::InvalidBeneficiaryPDA)?;
+            require_keys_eq!(beneficiary.key(), expected_pda, VestingError::InvalidBeneficiaryPDA);
+
+            // Only the unvested part of an allocation can be changed
+            let vested_bps = beneficiary.vested_bps(data_account, ctx.accounts.cohort.as_deref(), now)?;
+            let vested = beneficiary.vested_amount(data_account, vested_bps);
+            require!(
+                allocated_tokens >= std::cmp::max(vested, beneficiary.claimed_tokens),
+                VestingError::InvalidAmendment
+            );
+            // Taking tokens away needs the beneficiary's consent
+            if allocated_tokens < beneficiary.allocated_tokens {
+                let cosigner = ctx
+                    .accounts
+                    .beneficiary
+                    .as_ref()
+                    .ok_or(VestingError::BeneficiaryConsentRequired)?;
+                require_keys_eq!(cosigner.key(), beneficiary.key, VestingError::BeneficiaryConsentRequired);
+            }
+
+            data_account.total_allocated = data_account
+                .total_allocated
+                .saturating_sub(beneficiary.allocated_tokens)
+                .checked_add(allocated_tokens)
+                .ok_or(VestingError::AllocationExceedsEscrow)?;
+            require!(
+                data_account.total_allocated <= data_account.token_amount,
+                VestingError::AllocationExceedsEscrow
+            );
+            beneficiary.allocated_tokens = allocated_tokens;
+        }
+
+        // Keep an audit trail of amendments
+        data_account.amendment_count = data_account.amendment_count.saturating_add(1);
+        emit!(ScheduleAmended {
+            data_account: data_account.key(),
+            amendment_count: data_account.amendment_count,
+            vesting_months: data_account.vesting_months,
+            cliff_months: data_account.cliff_months,
+        });
+        Ok(())
+    }
+
+    // Corrects a beneficiary's `allocated_tokens` in place, keeping their claim history.
+    // The allocation can never drop below what was already claimed, and the sum of all allocations
+    // has to stay within the escrow. Reducing an allocation requires the beneficiary to co-sign.
+    pub fn update_beneficiary_allocation(
+        ctx: Context<UpdateBeneficiaryAllocation>,
+        allocated_tokens: u64,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let beneficiary = &mut ctx.accounts.beneficiary_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        // A cancelled schedule is settled and a terminated beneficiary was paid out in full
+        require!(!data_account.cancelled, VestingError::VestingCancelled);
+        require!(!beneficiary.terminated, VestingError::BeneficiaryTerminated);
+        // Shares-mode entitlements are derived from the pool, not stored per beneficiary
+        require!(!data_account.shares_mode, VestingError::SharesModeUnsupported);
+        require!(allocated_tokens >= beneficiary.claimed_tokens, VestingError::AllocationBelowClaimed);
+        // Taking tokens away needs the beneficiary's consent
+        if allocated_tokens < beneficiary.allocated_tokens {
+            let cosigner = ctx
+                .accounts
+                .beneficiary
+                .as_ref()
+                .ok_or(VestingError::BeneficiaryConsentRequired)?;
+            require_keys_eq!(cosigner.key(), beneficiary.key, VestingError::BeneficiaryConsentRequired);
+        }
+
+        data_account.total_allocated = data_account
+            .total_allocated
+            .saturating_sub(beneficiary.allocated_tokens)
+            .checked_add(allocated_tokens)
+            .ok_or(VestingError::AllocationExceedsEscrow)?;
+        require!(
+            data_account.total_allocated <= data_account.token_amount,
+            VestingError::AllocationExceedsEscrow
+        );
+
+        emit!(BeneficiaryAllocationUpdated {
+            data_account: data_account.key(),
+            beneficiary: beneficiary.key,
+            previous_allocated_tokens: beneficiary.allocated_tokens,
+            allocated_tokens,
+        });
+        beneficiary.allocated_tokens = allocated_tokens;
+        emit_schedule_stats(data_account);
+        check_invariants(data_account, Some(beneficiary), None)
+    }
+
+    // Carves `amount` unvested tokens off the sender's position into a new position for `new_wallet`,
+    // e.g. for estate planning or a co-founder split. Already claimed tokens are split in the same proportion,
+    // so both positions keep the same vested share. Non-transferable schedules need the initializer to co-sign.
+    pub fn split_position(ctx: Context<SplitPosition>, amount: u64) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let source = &mut ctx.accounts.beneficiary_account;
+        require!(!data_account.cancelled, VestingError::VestingCancelled);
+        require!(!data_account.frozen, VestingError::VestingFrozen);
+        require!(!source.terminated, VestingError::BeneficiaryTerminated);
+        // NFT-backed positions belong to whoever holds the NFT
+        require!(source.position_mint.is_none(), VestingError::PositionNftUnsupported);
+        // Shares-mode entitlements are derived from the pool, not stored per beneficiary
+        require!(!data_account.shares_mode, VestingError::SharesModeUnsupported);
+        if data_account.non_transferable {
+            let initializer = ctx.accounts.initializer.as_ref().ok_or(VestingError::SplitApprovalRequired)?;
+            require_keys_eq!(initializer.key(), data_account.initializer, VestingError::SplitApprovalRequired);
+        }
+        // Blocked wallets can't move tokens out of their position, nor receive a new one
+        data_account.check_blacklist(
+            ctx.accounts.blacklist.as_deref(),
+            &[source.key, ctx.accounts.new_wallet.key()],
+        )?;
+
+        // Only tokens that haven't vested yet can be carved off, and the source keeps part of its allocation
+        let now = data_account.monotonic_now(time_source::now(ctx.remaining_accounts)?);
+        let vested_bps = source.vested_bps(data_account, ctx.accounts.cohort.as_deref(), now)?;
+        let unvested = source
+            .allocated_tokens
+            .saturating_sub(source.vested_amount(data_account, vested_bps));
+        require!(
+            amount > 0 && amount <= unvested && amount < source.allocated_tokens,
+            VestingError::InvalidSplitAmount
+        );
+        let claimed_share = math::mul_div(source.claimed_tokens, amount, source.allocated_tokens)
+            .ok_or(VestingError::MathOverflow)?;
+        source.allocated_tokens -= amount;
+        source.claimed_tokens -= claimed_share;
+
+        let position = &mut ctx.accounts.new_beneficiary_account;
+        position.key = ctx.accounts.new_wallet.key();
+        position.allocated_tokens = amount;
+        position.claimed_tokens = claimed_share;
+        position.bump = ctx.bumps.new_beneficiary_account;
+        // Splitting must not reset the claim cooldown
+        position.last_claim_timestamp = source.last_claim_timestamp;
+        // The new position vests on the same cohort overrides
+        position.cohort_id = source.cohort_id;
+        position.index = data_account.next_beneficiary_index()?;
+        ctx.accounts.beneficiary_index.set_inner(BeneficiaryIndex {
+            data_account: data_account.key(),
+            beneficiary: position.key,
+            bump: ctx.bumps.beneficiary_index,
+        });
+        data_account.count_new_beneficiary()?;
+
+        emit!(PositionSplit {
+            data_account: data_account.key(),
+            beneficiary: source.key,
+            new_beneficiary: position.key,
+            allocated_tokens: amount,
+            claimed_tokens: claimed_share,
+        });
+        emit_schedule_stats(data_account);
+        check_invariants(data_account, Some(source), None)?;
+        check_invariants(data_account, Some(position), None)
+    }
+
+    // Merges the sender's position in `source_data_account` into their position in `data_account`, another schedule
+    // of the same mint with the same initializer and terms (see `DataAccount::can_consolidate_from`). Allocations and
+    // claimed tokens are summed, the unclaimed tokens move to `data_account`'s escrow, and the source position and its
+    // index entry are closed, refunding their rent to the initializer. Call it once per extra position.
+    pub fn consolidate_positions(ctx: Context<ConsolidatePositions>) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let source_data_account = &mut ctx.accounts.source_data_account;
+        let position = &mut ctx.accounts.beneficiary_account;
+        let source = &ctx.accounts.source_beneficiary_account;
+        require!(source_data_account.can_consolidate_from(data_account), VestingError::IncompatibleSchedules);
+        for schedule in [&**data_account, &**source_data_account] {
+            require!(!schedule.cancelled, VestingError::VestingCancelled);
+            require!(!schedule.frozen, VestingError::VestingFrozen);
+            require!(!schedule.is_frozen, VestingError::ConfigFrozen);
+            // Positions and tokens of these schedules aren't plain escrow allocations
+            require!(!schedule.shares_mode, VestingError::SharesModeUnsupported);
+            require!(!schedule.mint_vesting, VestingError::MintVestingUnsupported);
+            require!(schedule.payout_mint.is_none(), VestingError::PayoutMintUnsupported);
+            require!(schedule.leg_count == 0, VestingError::VestingLegsUnsupported);
+            require!(schedule.staked_amount == 0, VestingError::EscrowStaked);
+            require!(schedule.funded_tokens() >= schedule.token_amount, VestingError::IncompatibleSchedules);
+        }
+        // Blacklist entries are per schedule and wouldn't follow the position
+        require!(!source_data_account.blacklist_enabled, VestingError::IncompatibleSchedules);
+        for beneficiary in [&**position, &**source] {
+            require!(!beneficiary.terminated, VestingError::BeneficiaryTerminated);
+            require!(beneficiary.position_mint.is_none(), VestingError::PositionNftUnsupported);
+            // Cohort overrides are per schedule too
+            require!(beneficiary.cohort_id.is_none(), VestingError::CohortMismatch);
+            require!(beneficiary.removal_executable_at == 0, VestingError::RemovalAlreadyScheduled);
+        }
+        require!(source.parked_tokens == 0, VestingError::ParkedClaimPending);
+        require_thawed(&ctx.accounts.source_escrow_wallet, &ctx.accounts.escrow_wallet)?;
+
+        let now = data_account.advance_clock(time_source::now(ctx.remaining_accounts)?)?;
+        let now = std::cmp::max(now, source_data_account.advance_clock(now)?);
+        require!(!data_account.claim_window_expired(now), VestingError::ClaimWindowExpired);
+        // Whatever only the source position carries would otherwise be lost: a vote lock, a pending recovery,
+        // an acceptance still due or a different vesting extension
+        require!(
+            source.vote_locked(now) == 0
+                && source.pending_recovery_wallet.is_none()
+                && (source.accepted || !source_data_account.require_acceptance)
+                && source.voluntary_extension_seconds == position.voluntary_extension_seconds,
+            VestingError::PositionNotConsolidatable
+        );
+
+        let allocated_tokens = source.allocated_tokens;
+        let claimed_tokens = source.claimed_tokens;
+        let unclaimed = allocated_tokens.saturating_sub(claimed_tokens);
+        if unclaimed > 0 {
+            let token_mint_key = ctx.accounts.token_mint.key();
+            let schedule_seed = source_data_account.schedule_seed();
+            let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[source_data_account.bump]];
+            let signer_seeds = &[&seeds[..]];
+            let transfer_instruction = TransferChecked {
+                from: ctx.accounts.source_escrow_wallet.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                to: ctx.accounts.escrow_wallet.to_account_info(),
+                authority: source_data_account.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                transfer_instruction,
+                signer_seeds,
+            );
+            token::transfer_checked(cpi_ctx, raw_amount(unclaimed, data_account.decimals)?, data_account.decimals)?;
+        }
+
+        // The allocation leaves the source's totals the way `terminate_beneficiary` drops one, and joins
+        // `data_account`'s with what was already claimed from it
+        source_data_account.total_allocated = source_data_account.total_allocated.saturating_sub(allocated_tokens);
+        source_data_account.claimed_total = source_data_account.claimed_total.saturating_sub(claimed_tokens);
+        source_data_account.token_amount = source_data_account.token_amount.saturating_sub(allocated_tokens);
+        if source_data_account.installment_funding {
+            source_data_account.funded_amount = source_data_account.funded_amount.saturating_sub(allocated_tokens);
+        }
+        source_data_account.beneficiary_count = source_data_account.beneficiary_count.saturating_sub(1);
+        data_account.total_allocated = data_account
+            .total_allocated
+            .checked_add(allocated_tokens)
+            .ok_or(VestingError::MathOverflow)?;
+        data_account.claimed_total = data_account
+            .claimed_total
+            .checked_add(claimed_tokens)
+            .ok_or(VestingError::MathOverflow)?;
+        data_account.token_amount = data_account
+            .token_amount
+            .checked_add(allocated_tokens)
+            .ok_or(VestingError::MathOverflow)?;
+        if data_account.installment_funding {
+            data_account.funded_amount = data_account
+                .funded_amount
+                .checked_add(allocated_tokens)
+                .ok_or(VestingError::MathOverflow)?;
+        }
+
+        position.allocated_tokens = position
+            .allocated_tokens
+            .checked_add(allocated_tokens)
+            .ok_or(VestingError::MathOverflow)?;
+        position.claimed_tokens = position
+            .claimed_tokens
+            .checked_add(claimed_tokens)
+            .ok_or(VestingError::MathOverflow)?;
+        // Consolidating must not reset the claim cooldown
+        position.last_claim_timestamp = std::cmp::max(position.last_claim_timestamp, source.last_claim_timestamp);
+
+        emit!(PositionsConsolidated {
+            data_account: data_account.key(),
+            source_data_account: source_data_account.key(),
+            beneficiary: position.key,
+            allocated_tokens,
+            claimed_tokens,
+        });
+        emit_schedule_stats(data_account);
+        emit_schedule_stats(source_data_account);
+        check_invariants(data_account, Some(position), Some(&ctx.accounts.escrow_wallet.to_account_info()))?;
+        check_invariants(source_data_account, None, Some(&ctx.accounts.source_escrow_wallet.to_account_info()))
+    }
+
+    // Marks positions of the schedule as non-transferable: `split_position` then needs the initializer's co-signature.
+    pub fn set_non_transferable(ctx: Context<SetNonTransferable>, non_transferable: bool) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        data_account.non_transferable = non_transferable;
+        Ok(())
+    }
+
+    // Switches the schedule to shares mode, for raises whose final size isn't known yet: `add_beneficiaries`
+    // then grants shares instead of tokens, and each beneficiary is entitled to `token_amount * shares / total_shares`,
+    // so later deposits are distributed pro rata. Only possible before anything has been allocated.
+    pub fn enable_shares_mode(ctx: Context<EnableSharesMode>) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(
+            data_account.total_allocated == 0 && data_account.batch_cursor == 0,
+            VestingError::SharesLocked
+        );
+        data_account.shares_mode = true;
+        Ok(())
+    }
+
+    // Switches the schedule to interval-based vesting: once the lockup (if any) ends, the part above the TGE unlock
+    // vests in `total_intervals` equal tranches, one every `interval_seconds` (e.g. 10% every 90 days). The month and
+    // cliff settings are then ignored. Passing zero for both switches back to monthly vesting.
+    // Only possible before the start and before anything has been allocated.
+    pub fn set_interval_schedule(
+        ctx: Context<SetIntervalSchedule>,
+        interval_seconds: u32,
+        total_intervals: u16,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(
+            (interval_seconds == 0) == (total_intervals == 0),
+            VestingError::InvalidIntervalSchedule
+        );
+        let now = time_source::now(ctx.remaining_accounts)?;
+        require!(
+            now < data_account.start_timestamp
+                && data_account.total_allocated == 0
+                && data_account.batch_cursor == 0,
+            VestingError::IntervalScheduleLocked
+        );
+        data_account.interval_seconds = interval_seconds;
+        data_account.total_intervals = total_intervals;
+        Ok(())
+    }
+
+    // Assigns a role. Granting `Owner` hands the schedule over to `key`, which becomes the `initializer`;
+    // granting `Operator` or `Treasurer` replaces the current holder of that role.
+    pub fn grant_role(ctx: Context<ManageRole>, role: Role, key: Pubkey) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        // Giving up ownership is `renounce_admin`, which also makes the schedule irrevocable
+        require!(key != Pubkey::default(), VestingError::InvalidRole);
+        match role {
+            Role::Owner => data_account.initializer = key,
+            Role::Operator => data_account.operator = Some(key),
+            Role::Treasurer => data_account.treasurer = Some(key),
+        }
+        emit!(RoleGranted {
+            data_account: data_account.key(),
+            role,
+            key,
+        });
+        Ok(())
+    }
+
+    // Removes the operator or treasurer. The owner can't be revoked, only handed over or renounced.
+    pub fn revoke_role(ctx: Context<ManageRole>, role: Role) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        match role {
+            Role::Owner => return err!(VestingError::InvalidRole),
+            Role::Operator => data_account.operator = None,
+            Role::Treasurer => data_account.treasurer = None,
+        }
+        emit!(RoleRevoked {
+            data_account: data_account.key(),
+            role,
+        });
+        Ok(())
+    }
+
+    // Adds `lamports` to the schedule's rent reserve, held by the data account. While the reserve covers it,
+    // `add_beneficiaries` pays the rent of new beneficiary accounts from the reserve instead of the sender.
+    pub fn fund_rent_reserve(ctx: Context<FundRentReserve>, lamports: u64) -> Result<()> {
+        anchor_lang::solana_program::program::invoke(
+            &system_instruction::transfer(&ctx.accounts.sender.key(), &ctx.accounts.data_account.key(), lamports),
+            &[
+                ctx.accounts.sender.to_account_info(),
+                ctx.accounts.data_account.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.rent_reserve_lamports = data_account
+            .rent_reserve_lamports
+            .checked_add(lamports)
+            .ok_or(VestingError::MathOverflow)?;
+        emit!(RentReserveChanged {
+            data_account: data_account.key(),
+            rent_reserve_lamports: data_account.rent_reserve_lamports,
+        });
+        Ok(())
+    }
+
+    // Takes `lamports` back out of the rent reserve.
+    pub fn withdraw_rent_reserve(ctx: Context<WithdrawRentReserve>, lamports: u64) -> Result<()> {
+        let recipient = ctx.accounts.sender.to_account_info();
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        draw_rent_reserve(data_account, &recipient, lamports)?;
+        emit!(RentReserveChanged {
+            data_account: data_account.key(),
+            rent_reserve_lamports: data_account.rent_reserve_lamports,
+        });
+        Ok(())
+    }
+
+    // Adds `lamports` to the schedule's SOL fee pool, held by the data account, and sets how much of it
+    // a single claim can be refunded. Typically bundled with `initialize`.
+    pub fn fund_fee_pool(ctx: Context<FundFeePool>, lamports: u64, max_per_claim: u64) -> Result<()> {
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(ctx.accounts.data_account.approvers.is_empty(), VestingError::MultisigRequired);
+
+        anchor_lang::solana_program::program::invoke(
+            &system_instruction::transfer(&ctx.accounts.sender.key(), &ctx.accounts.data_account.key(), lamports),
+            &[
+                ctx.accounts.sender.to_account_info(),
+                ctx.accounts.data_account.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.fee_pool_lamports = data_account
+            .fee_pool_lamports
+            .checked_add(lamports)
+            .ok_or(VestingError::MathOverflow)?;
+        data_account.fee_pool_max_per_claim = max_per_claim;
+        Ok(())
+    }
+
+    // Takes `lamports` back out of the SOL fee pool.
+    pub fn withdraw_fee_pool(ctx: Context<WithdrawFeePool>, lamports: u64) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(lamports <= data_account.fee_pool_lamports, VestingError::FeePoolInsufficient);
+
+        data_account.fee_pool_lamports -= lamports;
+        pay_from_fee_pool(data_account, &ctx.accounts.sender.to_account_info(), lamports)
+    }
+
+    // Rewards beneficiaries for leaving vested tokens unclaimed: each claim earns `bonus_rate_bps_per_year` on the
+    // claimed tokens, compounded monthly (`math::compound_bonus`), for the time since the beneficiary's last claim,
+    // or since vesting started for the first one. Bonuses are paid from the schedule's bonus escrow, which is created
+    // on first use and topped up with `deposit` tokens from the initializer; once it runs dry, claims pay what is
+    // left. Setting the rate again changes it for every later claim.
+    pub fn set_bonus_rate(ctx: Context<SetBonusRate>, bonus_rate_bps_per_year: u16, deposit: u64) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(bonus_rate_bps_per_year <= 10_000, VestingError::InvalidBonusRate);
+        // Bonuses are paid in the vesting mint
+        require!(data_account.payout_mint.is_none(), VestingError::PayoutMintUnsupported);
+        data_account.bonus_rate_bps_per_year = bonus_rate_bps_per_year;
+        data_account.bonus_escrow = ctx.accounts.bonus_escrow.key();
+
+        let deposit_raw = raw_amount(deposit, data_account.decimals)?;
+        if deposit_raw > 0 {
+            let deposit_instruction = TransferChecked {
+                from: ctx.accounts.wallet_to_withdraw_from.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                to: ctx.accounts.bonus_escrow.to_account_info(),
+                authority: ctx.accounts.sender.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), deposit_instruction);
+            token::transfer_checked(cpi_ctx, deposit_raw, data_account.decimals)?;
+        }
+        emit!(BonusRateSet {
+            data_account: data_account.key(),
+            bonus_rate_bps_per_year,
+            bonus_escrow_balance: ctx.accounts.bonus_escrow.amount.saturating_add(deposit_raw),
+        });
+        Ok(())
+    }
+
+    // Ends the bonus: returns what is left in the bonus escrow to the initializer and closes it.
+    // Frozen schedules keep their bonus until every token has been claimed or withdrawn.
+    pub fn withdraw_bonus(ctx: Context<WithdrawBonus>) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(
+            !data_account.is_frozen
+                || data_account.claimed_total + data_account.unclaimed_withdrawn == data_account.token_amount,
+            VestingError::ConfigFrozen
+        );
+        require!(!data_account.mint_compromised, VestingError::MintCompromised);
+
+        let token_mint_key = ctx.accounts.token_mint.key();
+        let schedule_seed = data_account.schedule_seed();
+        let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[data_account.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let remaining = ctx.accounts.bonus_escrow.amount;
+        if remaining > 0 {
+            let transfer_instruction = TransferChecked {
+                from: ctx.accounts.bonus_escrow.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                to: ctx.accounts.recipient.to_account_info(),
+                authority: data_account.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                transfer_instruction,
+                signer_seeds,
+            );
+            token::transfer_checked(cpi_ctx, remaining, data_account.decimals)?;
+        }
+        let close_instruction = CloseAccount {
+            account: ctx.accounts.bonus_escrow.to_account_info(),
+            destination: ctx.accounts.sender.to_account_info(),
+            authority: data_account.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            close_instruction,
+            signer_seeds,
+        );
+        token::close_account(cpi_ctx)?;
+
+        data_account.bonus_rate_bps_per_year = 0;
+        data_account.bonus_escrow = Pubkey::default();
+        emit!(BonusRateSet {
+            data_account: data_account.key(),
+            bonus_rate_bps_per_year: 0,
+            bonus_escrow_balance: 0,
+        });
+        Ok(())
+    }
+
+    pub fn close_vesting(ctx: Context<CloseVesting>) -> Result<()> {
+        // Get a reference to the main vesting data account
+        let data_account = &ctx.accounts.data_account;
+        require!(data_account.staked_amount == 0, VestingError::EscrowStaked);
+        // The data account PDA stays the mint authority of a mint-vesting schedule, so it can't be closed
+        require!(!data_account.mint_vesting, VestingError::MintVestingUnsupported);
+        require!(data_account.payout_mint.is_none(), VestingError::PayoutMintUnsupported);
+        // Leg escrows are not closed here; `withdraw_leg_remainder` closes them once nothing is left to claim
+        require!(data_account.leg_count == 0, VestingError::VestingLegsUnsupported);
+        // Nor is the bonus escrow; `withdraw_bonus` closes it
+        require!(data_account.bonus_escrow == Pubkey::default(), VestingError::BonusUnsupported);
+        // Parked claims are still paid from the escrow
+        require!(data_account.parked_total == 0, VestingError::ParkedClaimPending);
+        // Only allow closing once every vested token has either been claimed or withdrawn
+        require!(
+            data_account.claimed_total + data_account.unclaimed_withdrawn == data_account.token_amount,
+            VestingError::VestingNotSettled
+        );
+
+        // Prepare signer seeds for PDA authority
+        let token_mint_key = ctx.accounts.token_mint.key();
+        let schedule_seed = data_account.schedule_seed();
+        let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[data_account.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        // Close the escrow token account, refunding its rent to the chosen rent recipient
+        let close_instruction = CloseAccount {
+            account: ctx.accounts.escrow_wallet.to_account_info(),
+            destination: ctx.accounts.rent_recipient.to_account_info(),
+            authority: data_account.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            close_instruction,
+            signer_seeds,
+        );
+        token::close_account(cpi_ctx)?;
+
+        // The data account itself is closed by the `close = rent_recipient` constraint once the instruction returns
+        Ok(())
+    }
+
+    // Program retirement exit (see `set_sunset_mode`): once the sunset has taken effect, anyone can push what is left
+    // of a beneficiary's allocation to its ATA and close its account and index entry, refunding their rent to the
+    // initializer. The allocation counts as fully vested, or as vested at the frozen share on a cancelled schedule;
+    // parked tokens are paid too. No protocol fee is taken.
+    pub fn sunset_claim(ctx: Context<SunsetClaim>) -> Result<()> {
+        let now = time_source::now(ctx.remaining_accounts)?;
+        require!(ctx.accounts.program_status.load()?.is_sunset(now), VestingError::SunsetNotActive);
+        let data_account = &mut ctx.accounts.data_account;
+        let beneficiary = &mut ctx.accounts.beneficiary_account;
+        // Schedules whose tokens aren't all in the escrow, or not in the vested mint
+        require!(data_account.staked_amount == 0, VestingError::EscrowStaked);
+        require!(!data_account.mint_vesting, VestingError::MintVestingUnsupported);
+        require!(data_account.payout_mint.is_none(), VestingError::PayoutMintUnsupported);
+        require!(data_account.leg_count == 0, VestingError::VestingLegsUnsupported);
+        // Dropping shares would grow everyone else's share of the pool
+        require!(!data_account.shares_mode, VestingError::SharesModeUnsupported);
+        // The NFT holder, not the original wallet, owns an NFT-backed position
+        require!(beneficiary.position_mint.is_none(), VestingError::PositionNftUnsupported);
+        require_thawed(&ctx.accounts.escrow_wallet, &ctx.accounts.wallet_to_deposit_to)?;
+
+        // `terminate_beneficiary` already settled a terminated allocation
+        let final_bps = if data_account.cancelled { data_account.final_vested_bps } else { 10_000 };
+        let owed = if beneficiary.terminated {
+            0
+        } else {
+            data_account
+                .cap_funded(beneficiary, beneficiary.vested_amount(data_account, final_bps))
+                .saturating_sub(beneficiary.claimed_tokens)
+        };
+        let amount_raw = raw_amount(owed + beneficiary.parked_tokens, data_account.decimals)?;
+        if amount_raw > 0 {
+            let token_mint_key = ctx.accounts.token_mint.key();
+            let schedule_seed = data_account.schedule_seed();
+            let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[data_account.bump]];
+            let signer_seeds = &[&seeds[..]];
+            let transfer_instruction = TransferChecked {
+                from: ctx.accounts.escrow_wallet.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                to: ctx.accounts.wallet_to_deposit_to.to_account_info(),
+                authority: data_account.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                transfer_instruction,
+                signer_seeds,
+            );
+            token::transfer_checked(cpi_ctx, amount_raw, data_account.decimals)?;
+        }
+
+        // Drop the beneficiary from the pool totals. Everything it claimed, and what was just paid, has left the
+        // escrow, so it comes out of `token_amount` too.
+        data_account.parked_total = data_account.parked_total.saturating_sub(beneficiary.parked_tokens);
+        if !beneficiary.terminated {
+            data_account.total_allocated = data_account
+                .total_allocated
+                .saturating_sub(beneficiary.allocated_tokens);
+            data_account.claimed_total = data_account
+                .claimed_total
+                .saturating_sub(beneficiary.claimed_tokens);
+            data_account.token_amount = data_account
+                .token_amount
+                .saturating_sub(beneficiary.claimed_tokens.saturating_add(owed));
+            data_account.beneficiary_count = data_account.beneficiary_count.saturating_sub(1);
+        }
+
+        emit_cpi!(SunsetClaimed {
+            data_account: data_account.key(),
+            beneficiary: beneficiary.key,
+            amount: amount_raw,
+        });
+        emit_schedule_stats(data_account);
+        // The beneficiary account and its index entry are closed by their `close = rent_recipient` constraints
+        Ok(())
+    }
+
+    // Closes a schedule after the sunset, once `sunset_claim` has paid out every beneficiary: the tokens left in the
+    // escrow (unallocated or returned ones) go to the initializer's `recipient`, and the rent of the escrow and data
+    // account to the initializer. Anyone can call it.
+    pub fn sunset_close_schedule(ctx: Context<SunsetCloseSchedule>) -> Result<()> {
+        let now = time_source::now(ctx.remaining_accounts)?;
+        require!(ctx.accounts.program_status.load()?.is_sunset(now), VestingError::SunsetNotActive);
+        let data_account = &ctx.accounts.data_account;
+        // Registry entries count too; the initializer removes them with `registry_remove_beneficiaries`
+        require!(data_account.beneficiary_count == 0, VestingError::BeneficiariesRemaining);
+        require!(data_account.staked_amount == 0, VestingError::EscrowStaked);
+        // The data account PDA stays the mint authority of a mint-vesting schedule, so it can't be closed
+        require!(!data_account.mint_vesting, VestingError::MintVestingUnsupported);
+        require!(data_account.payout_mint.is_none(), VestingError::PayoutMintUnsupported);
+        // Leg and bonus escrows are not closed here; `withdraw_leg_remainder` and `withdraw_bonus` close them
+        require!(data_account.leg_count == 0, VestingError::VestingLegsUnsupported);
+        require!(data_account.bonus_escrow == Pubkey::default(), VestingError::BonusUnsupported);
+        // Parked claims are still paid from the escrow
+        require!(data_account.parked_total == 0, VestingError::ParkedClaimPending);
+        require_thawed(&ctx.accounts.escrow_wallet, &ctx.accounts.recipient)?;
+
+        let token_mint_key = ctx.accounts.token_mint.key();
+        let schedule_seed = data_account.schedule_seed();
+        let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[data_account.bump]];
+        let signer_seeds = &[&seeds[..]];
+        let returned = ctx.accounts.escrow_wallet.amount;
+        if returned > 0 {
+            let transfer_instruction = TransferChecked {
+                from: ctx.accounts.escrow_wallet.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                to: ctx.accounts.recipient.to_account_info(),
+                authority: data_account.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                transfer_instruction,
+                signer_seeds,
+            );
+            token::transfer_checked(cpi_ctx, returned, data_account.decimals)?;
+        }
+        let close_instruction = CloseAccount {
+            account: ctx.accounts.escrow_wallet.to_account_info(),
+            destination: ctx.accounts.initializer.to_account_info(),
+            authority: data_account.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            close_instruction,
+            signer_seeds,
+        );
+        token::close_account(cpi_ctx)?;
+
+        emit!(SunsetScheduleClosed { data_account: data_account.key(), returned });
+        // The data account itself is closed by the `close = initializer` constraint once the instruction returns
+        Ok(())
+    }
+
+    // Upgrades a data account created with an older layout to the current one, in place.
+    // The account is grown to `DataAccount::LEN` with zeroed bytes, which the fields added since read
+    // as their defaults, then re-serialized with the current `version`. Running it again is a no-op.
+    pub fn migrate_data_account(ctx: Context<MigrateDataAccount>) -> Result<()> {
+        let data_info = ctx.accounts.data_account.to_account_info();
+        require_keys_eq!(*data_info.owner, crate::ID, VestingError::InvalidDataAccount);
+
+        let grown = data_info.data_len() < DataAccount::LEN;
+        if grown {
+            data_info.resize(DataAccount::LEN)?;
+        }
+
+        // Deserializing checks the discriminator
+        let mut data_account = DataAccount::try_deserialize(&mut &data_info.data.borrow()[..])?;
+        require_keys_eq!(data_account.initializer, ctx.accounts.sender.key(), VestingError::InvalidSender);
+        if grown {
+            // The fee pool and rent reserve are held for their own purposes, so they don't count towards the rent
+            // of the grown account
+            let rent_lamports = data_info
+                .lamports()
+                .saturating_sub(data_account.fee_pool_lamports)
+                .saturating_sub(data_account.rent_reserve_lamports);
+            let rent_due = Rent::get()?
+                .minimum_balance(DataAccount::LEN)
+                .saturating_sub(rent_lamports);
+            if rent_due > 0 {
+                let transfer_instruction = system_instruction::transfer(
+                    &ctx.accounts.sender.key(),
+                    &data_info.key(),
+                    rent_due,
+                );
+                anchor_lang::solana_program::program::invoke(
+                    &transfer_instruction,
+                    &[
+                        ctx.accounts.sender.to_account_info(),
+                        data_info.clone(),
+                        ctx.accounts.system_program.to_account_info(),
+                    ],
+                )?;
+            }
+        }
+        if data_account.version >= DataAccount::VERSION {
+            return Ok(());
+        }
+
+        // v1 accounts predate `release_mode`, and always applied both release gates
+        if data_account.version < 2 {
+            data_account.release_mode = ReleaseMode::Both;
+        }
+        // Older accounts may not have stored their canonical bumps
+        data_account.bump = ctx.bumps.data_account;
+        data_account.escrow_bump = Pubkey::find_program_address(
+            &[ESCROW_WALLET_SEED, ctx.accounts.token_mint.key().as_ref()],
+            &crate::ID,
+        )
+        .1;
+        // Schedules from before v28 didn't record the mint's authorities; start from the current ones
+        if data_account.version < 28 {
+            data_account.snapshot_mint(&ctx.accounts.token_mint);
+        }
+        // Schedules from before v30 were all derived from the v1 seeds
+        if data_account.version < 30 {
+            data_account.seed_version = SEED_VERSION_V1;
+        }
+        data_account.version = DataAccount::VERSION;
+        data_account.try_serialize(&mut &mut data_info.data.borrow_mut()[..])?;
+        Ok(())
+    }
+
+    // Permissionless: compares the mint's current mint and freeze authorities with the ones recorded when the
+    // schedule was created. If either changed, the schedule is flagged `mint_compromised` for good, which blocks
+    // the initializer's withdrawal paths while beneficiaries keep claiming.
+    pub fn verify_mint_integrity(ctx: Context<VerifyMintIntegrity>) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let token_mint = &ctx.accounts.token_mint;
+        if data_account.mint_compromised || data_account.mint_matches_snapshot(token_mint) {
+            return Ok(());
+        }
+
+        data_account.mint_compromised = true;
+        emit!(MintCompromised {
+            data_account: data_account.key(),
+            mint_authority: token_mint.mint_authority.into(),
+            freeze_authority: token_mint.freeze_authority.into(),
+        });
+        Ok(())
+    }
+
+    // Fully accelerates vesting after an exit event (e.g. a token listing): from now on the schedule
+    // counts as 100% time-vested, while manual releases via `percent_available` still apply.
+    pub fn accelerate_vesting(ctx: Context<AccelerateVesting>) -> Result<()> {
+        // Once an approver set is configured, acceleration must go through the multisig flow
+        require!(ctx.accounts.data_account.approvers.is_empty(), VestingError::MultisigRequired);
+
+        trigger_acceleration(&mut ctx.accounts.data_account, time_source::now(ctx.remaining_accounts)?)
+    }
+
+    // Kill switch for incident response: blocks claims, cancellation and unclaimed withdrawals.
+    // Only the guardian can freeze; it gets no other power over the schedule or its funds.
+    pub fn emergency_freeze(ctx: Context<EmergencyFreeze>) -> Result<()> {
+        ctx.accounts.data_account.frozen = true;
+        Ok(())
+    }
+
+    // Lifts an emergency freeze. Goes through an `Unfreeze` proposal once an approver set is configured.
+    pub fn unfreeze(ctx: Context<Unfreeze>) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        data_account.frozen = false;
+        Ok(())
+    }
+
+    // Rate-limits claims to smooth sell pressure: each beneficiary can claim at most once every
+    // `min_claim_interval_seconds`, and at most `max_claim_per_period` whole tokens per claim
+    // (the rest stays claimable later). Zero disables either limit.
+    pub fn set_claim_limits(
+        ctx: Context<SetClaimLimits>,
+        min_claim_interval_seconds: u32,
+        max_claim_per_period: u64,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        // The period of `max_claim_per_period` is the claim interval
+        require!(
+            max_claim_per_period == 0 || min_claim_interval_seconds > 0,
+            VestingError::InvalidClaimLimits
+        );
+        data_account.min_claim_interval_seconds = min_claim_interval_seconds;
+        data_account.max_claim_per_period = max_claim_per_period;
+        Ok(())
+    }
+
+    // Sets the time after which unclaimed entitlements are forfeited (0 = no deadline).
+    // The deadline can't fall before the end of vesting. On irrevocable schedules it can only be
+    // extended or removed, since introducing or shortening it takes tokens away from beneficiaries.
+    pub fn set_claim_deadline(ctx: Context<SetClaimDeadline>, claim_deadline: i64) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        let now = time_source::now(ctx.remaining_accounts)?;
+        require!(!data_account.claim_window_expired(now), VestingError::ClaimWindowExpired);
+        if claim_deadline != 0 {
+            require!(
+                claim_deadline >= std::cmp::max(now, data_account.latest_vesting_end_timestamp()),
+                VestingError::InvalidClaimDeadline
+            );
+            let extends = data_account.claim_deadline != 0 && claim_deadline >= data_account.claim_deadline;
+            require!(extends || data_account.is_cancellable, VestingError::VestingNotCancellable);
+        }
+        data_account.claim_deadline = claim_deadline;
+        Ok(())
+    }
+
+    // Sets how far behind the last processed timestamp the validator clock may be before time-dependent
+    // instructions are rejected with `ClockWentBackwards`.
+    pub fn set_clock_tolerance(ctx: Context<SetClockTolerance>, clock_tolerance_seconds: u32) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(clock_tolerance_seconds <= MAX_CLOCK_TOLERANCE, VestingError::InvalidClockTolerance);
+        data_account.clock_tolerance_seconds = clock_tolerance_seconds;
+        Ok(())
+    }
+
+    // Turns the acceptance step on or off: while it is on, beneficiaries can't claim before calling `accept_grant`.
+    pub fn set_require_acceptance(ctx: Context<SetRequireAcceptance>, require_acceptance: bool) -> Result<()> {
+        ctx.accounts.data_account.require_acceptance = require_acceptance;
+        Ok(())
+    }
+
+    // Turns the per-claim `ClaimRecord` history on or off. While it is on, claims must go through `claim`.
+    pub fn set_record_claims(ctx: Context<SetRecordClaims>, record_claims: bool) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        data_account.record_claims = record_claims;
+        Ok(())
+    }
+
+    // Caps the schedule's active beneficiaries (PDAs and registry entries), bounding the rent and the accounts
+    // the initializer and operator can create. 0 removes the cap. A cap below the current count only blocks
+    // further additions.
+    pub fn set_max_beneficiaries(ctx: Context<SetMaxBeneficiaries>, max_beneficiaries: u32) -> Result<()> {
+        ctx.accounts.data_account.max_beneficiaries = max_beneficiaries;
+        Ok(())
+    }
+
+    // Chooses how claims round each beneficiary's vested share to whole tokens (see `RoundingPolicy`). Only before
+    // the first claim, so every beneficiary's claims are rounded the same way.
+    pub fn set_rounding_policy(ctx: Context<SetRoundingPolicy>, rounding: RoundingPolicy) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(data_account.claimed_total == 0, VestingError::RoundingLocked);
+        data_account.rounding = rounding;
+        Ok(())
+    }
+
+    // Chooses the curve the part above the TGE unlock vests along after the cliff (see `CurveType`), e.g. back-weighted
+    // to vest 10/20/30/40% over four years. Month-based schedules only, and only before the start, so no
+    // beneficiary's vested share ever changes.
+    pub fn set_vesting_curve(ctx: Context<SetVestingCurve>, curve: CurveType) -> Result<()> {
+        require_feature!(ctx.accounts.program_status, FEATURE_VESTING_CURVES);
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(data_account.interval_seconds == 0, VestingError::CurveUnsupported);
+        let now = time_source::now(ctx.remaining_accounts)?;
+        require!(now < data_account.start_timestamp, VestingError::CurveLocked);
+        curve.to_curve()?;
+        data_account.curve = curve;
+        Ok(())
+    }
+
+    // Turns permissioned claim mode on or off: when `claim_approver` is set,
+    // every claim has to be co-signed by it. Pass `None` to allow unrestricted claims again.
+    pub fn set_claim_approver(ctx: Context<SetClaimApprover>, claim_approver: Option<Pubkey>) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        data_account.claim_approver = claim_approver;
+        Ok(())
+    }
+
+    // Gates claims on identity verification: every claimer must pass `gate_program`'s `verify` instruction
+    // against `gate_config` (see `verify_claim_gate`). Passing None removes the gate.
+    pub fn set_claim_gate(ctx: Context<SetClaimGate>, gate_program: Option<Pubkey>, gate_config: Pubkey) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        data_account.gate_program = gate_program;
+        data_account.gate_config = if gate_program.is_some() { gate_config } else { Pubkey::default() };
+        Ok(())
+    }
+
+    // Notifies `hook_program` of every claim: after paying out, `claim` invokes its `notify` instruction
+    // (see `notify_claim_hook`), so payroll ledgers and similar programs can react in the same transaction.
+    // Passing None removes the hook.
+    pub fn set_claim_hook(ctx: Context<SetClaimHook>, hook_program: Option<Pubkey>) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        // A failing hook blocks every claim, which irrevocable schedules must not allow
+        require!(
+            hook_program.is_none() || data_account.is_cancellable,
+            VestingError::VestingNotCancellable
+        );
+        data_account.on_claim_hook = hook_program;
+        Ok(())
+    }
+
+    // Withholds `withholding_bps` of every claim (after the protocol fee) for tax remittance: `claim` pays that share
+    // into `withholding_account`, a token account of the vesting mint, and the rest to the beneficiary.
+    // Passing 0 and None stops withholding.
+    pub fn set_withholding(
+        ctx: Context<SetWithholding>,
+        withholding_bps: u16,
+        withholding_account: Option<Pubkey>,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(withholding_bps <= 10_000, VestingError::InvalidWithholding);
+        require!((withholding_bps > 0) == withholding_account.is_some(), VestingError::InvalidWithholding);
+        // Withheld tokens are paid in the vesting mint
+        require!(data_account.payout_mint.is_none(), VestingError::PayoutMintUnsupported);
+        data_account.withholding_bps = withholding_bps;
+        data_account.withholding_account = withholding_account;
+        Ok(())
+    }
+
+    // Relabels the schedule, e.g. "Series A lockup". Purely informational: nothing in the program reads it.
+    pub fn update_metadata(ctx: Context<UpdateMetadata>, name: [u8; 32], uri: [u8; 128]) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.name = name;
+        data_account.uri = uri;
+        emit!(ScheduleMetadataUpdated {
+            data_account: data_account.key(),
+            name,
+            uri,
+        });
+        Ok(())
+    }
+
+    // Publishes a commitment to everything that determines the schedule: its parameters (see
+    // `DataAccount::schedule_commitment`) and `beneficiaries_root`, the Merkle root of the beneficiaries' allocations
+    // computed off-chain with `beneficiaries_merkle_root`. Exchanges and auditors record the hash at listing time and
+    // later check with `verify_schedule` that nothing has changed. Committing again replaces the previous hash.
+    pub fn commit_schedule_hash(ctx: Context<CommitScheduleHash>, beneficiaries_root: [u8; 32]) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let schedule_hash = data_account.schedule_commitment(&beneficiaries_root)?;
+        data_account.schedule_hash = schedule_hash;
+        data_account.beneficiaries_root = beneficiaries_root;
+        data_account.schedule_committed_at = time_source::now(ctx.remaining_accounts)?;
+        emit!(ScheduleCommitted {
+            data_account: data_account.key(),
+            schedule_hash,
+            beneficiaries_root,
+            committed_at: data_account.schedule_committed_at,
+        });
+        Ok(())
+    }
+
+    // Recomputes the schedule hash from the current parameters and a beneficiaries root the caller computed from
+    // the current `BeneficiaryAccount`s, and returns it alongside the committed one through `set_return_data`.
+    pub fn verify_schedule(ctx: Context<VerifySchedule>, beneficiaries_root: [u8; 32]) -> Result<ScheduleVerification> {
+        let data_account = &ctx.accounts.data_account;
+        require!(data_account.schedule_committed_at != 0, VestingError::ScheduleNotCommitted);
+        let current_hash = data_account.schedule_commitment(&beneficiaries_root)?;
+        Ok(ScheduleVerification {
+            committed_hash: data_account.schedule_hash,
+            current_hash,
+            committed_at: data_account.schedule_committed_at,
+            matches: current_hash == data_account.schedule_hash,
+        })
+    }
+
+    // Blocks `wallets` from claiming, e.g. for sanctions compliance. The schedule's `Blacklist` is created
+    // on first use; from then on every claim has to pass it.
+    pub fn add_to_blacklist(ctx: Context<AddToBlacklist>, wallets: Vec<Pubkey>) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Once an approver set is configured, schedule changes must go through the multisig flow
+        require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+
+        let blacklist = &mut ctx.accounts.blacklist;
+        blacklist.data_account = data_account.key();
+        blacklist.bump = ctx.bumps.blacklist;
+        for wallet in wallets {
+            if !blacklist.contains(&wallet) {
+                require!(blacklist.entries.len() < MAX_BLACKLIST_ENTRIES, VestingError::BlacklistFull);
+                blacklist.entries.push(wallet);
+            }
+        }
+        data_account.blacklist_enabled = true;
+        Ok(())
+    }
+
+    // Lets `wallets` claim again. The (possibly empty) blacklist still has to be passed to `claim`.
+    pub fn remove_from_blacklist(ctx: Context<RemoveFromBlacklist>, wallets: Vec<Pubkey>) -> Result<()> {
+        require!(ctx.accounts.data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        ctx.accounts.blacklist.entries.retain(|entry| !wallets.contains(entry));
+        Ok(())
+    }
+
+    // Creates the program-wide `Config` PDA. Only the program's upgrade authority can call it, and only once.
+    pub fn initialize_config(ctx: Context<InitializeConfig>, fee_recipient: Pubkey, fee_bps: u16) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, VestingError::InvalidFee);
+
+        let config = &mut ctx.accounts.config;
+        config.fee_recipient = fee_recipient;
+        config.fee_bps = fee_bps;
+        config.staking_program = None;
+        config.bump = ctx.bumps.config;
+        Ok(())
+    }
+
+    // Whitelists the staking/yield program escrows may be delegated to with `stake_escrow`.
+    // Upgrade authority only. Open stakes can only be unstaked while their program stays whitelisted.
+    pub fn set_staking_program(ctx: Context<UpdateConfig>, staking_program: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.config.staking_program = staking_program;
+        Ok(())
+    }
+
+    // Halts (or resumes) the whole program, e.g. while a critical bug is being fixed: every instruction that changes
+    // state checks the `ProgramState` PDA and fails with `ProgramHalted` while it is set, for every schedule at
+    // once. Upgrade authority only; creates the program state on first use, before which nothing is halted. Views
+    // and the upgrade authority's configuration instructions keep working.
+    pub fn set_program_halted(ctx: Context<SetProgramHalted>, is_halted: bool) -> Result<()> {
+        let program_state = &mut ctx.accounts.program_state;
+        program_state.is_halted = is_halted;
+        program_state.bump = ctx.bumps.program_state;
+        emit!(ProgramHaltChanged { is_halted });
+        Ok(())
+    }
+
+    // Enables the features whose `features::FEATURE_*` bits are set in `features` on this cluster, and disables
+    // the others. Upgrade authority only. Also grows a program state created by an older version (before the
+    // feature set or the sunset existed), which other instructions can't read until this has run once after the
+    // upgrade.
+    pub fn set_program_features(ctx: Context<SetProgramFeatures>, features: u64) -> Result<()> {
+        require!(features & !KNOWN_FEATURES == 0, VestingError::UnknownFeature);
+        let state_info = ctx.accounts.program_state.to_account_info();
+        let mut program_state = grow_program_state(&state_info, &ctx.accounts.sender, &ctx.accounts.system_program)?;
+        program_state.features = features;
+        program_state.try_serialize(&mut &mut state_info.data.borrow_mut()[..])?;
+        emit!(ProgramFeaturesChanged { features });
+        Ok(())
+    }
+
+    // Schedules the program's retirement: `SUNSET_DELAY` (180 days) from now, `sunset_claim` lets anyone pay every
+    // beneficiary what is left of its allocation and close its accounts, so nothing is stranded once the program
+    // stops being maintained. Enabling it again keeps the date already set. It can be called off until it takes
+    // effect, but not after (`SunsetActive`). Upgrade authority only; grows an older program state like
+    // `set_program_features`.
+    pub fn set_sunset_mode(ctx: Context<SetSunsetMode>, enabled: bool) -> Result<()> {
+        let now = time_source::now(ctx.remaining_accounts)?;
+        let state_info = ctx.accounts.program_state.to_account_info();
+        let mut program_state = grow_program_state(&state_info, &ctx.accounts.sender, &ctx.accounts.system_program)?;
+        require!(!program_state.is_sunset(now), VestingError::SunsetActive);
+        if !enabled {
+            program_state.sunset_at = 0;
+        } else if program_state.sunset_at == 0 {
+            program_state.sunset_at = now.checked_add(SUNSET_DELAY).ok_or(VestingError::MathOverflow)?;
+        }
+        program_state.try_serialize(&mut &mut state_info.data.borrow_mut()[..])?;
+        emit!(SunsetModeChanged { sunset_at: program_state.sunset_at });
+        Ok(())
+    }
+
+    // Sets how far in the past and in the future a new schedule may start, in seconds; 0 restores the default
+    // (`MAX_START_IN_PAST`, `MAX_START_IN_FUTURE`). Existing schedules are unaffected. Upgrade authority only;
+    // grows an older program state like `set_program_features`.
+    pub fn set_start_bounds(
+        ctx: Context<SetStartBounds>,
+        max_start_in_past: u32,
+        max_start_in_future: u32,
+    ) -> Result<()> {
+        let state_info = ctx.accounts.program_state.to_account_info();
+        let mut program_state = grow_program_state(&state_info, &ctx.accounts.sender, &ctx.accounts.system_program)?;
+        program_state.max_start_in_past = max_start_in_past;
+        program_state.max_start_in_future = max_start_in_future;
+        program_state.try_serialize(&mut &mut state_info.data.borrow_mut()[..])?;
+        Ok(())
+    }
+
+    // Interface version of the deployed program, the oldest client version it still supports and the features
+    // enabled on this cluster, returned through `set_return_data`. Integrators check it before relying on
+    // newer instructions.
+    pub fn get_program_version(ctx: Context<GetProgramVersion>) -> Result<ProgramVersion> {
+        Ok(ProgramVersion {
+            version: PROGRAM_VERSION,
+            min_supported_version: MIN_SUPPORTED_VERSION,
+            features: read_program_state(&ctx.accounts.program_state)?.features,
+        })
+    }
+
+    // Whitelists the swap program `claim_and_swap` routes claims through (e.g. Jupiter), creating the swap config
+    // on first use. Upgrade authority only; `None` disables swaps.
+    pub fn set_swap_program(ctx: Context<SetSwapProgram>, swap_program: Option<Pubkey>) -> Result<()> {
+        let swap_config = &mut ctx.accounts.swap_config;
+        swap_config.swap_program = swap_program;
+        swap_config.bump = ctx.bumps.swap_config;
+        Ok(())
+    }
+
+    // Sets the time read by builds with the `mock-clock` feature (see `time_source`), creating the mock clock on
+    // first use. Upgrade authority only. Moving it backwards makes schedules that already saw a later time fail
+    // with `ClockWentBackwards`.
+    #[cfg(feature = "mock-clock")]
+    pub fn set_mock_clock(ctx: Context<SetMockClock>, unix_timestamp: i64) -> Result<()> {
+        let mock_clock = &mut ctx.accounts.mock_clock;
+        mock_clock.unix_timestamp = unix_timestamp;
+        mock_clock.bump = ctx.bumps.mock_clock;
+        Ok(())
+    }
+
+    // Delegates `amount` whole tokens of the escrow to the whitelisted staking program.
+    // `instruction_data` and `remaining_accounts` form the staking program's instruction; the data account
+    // PDA signs it as escrow authority. The CPI must move exactly `amount` out of the escrow, and the
+    // escrow must still cover everything that is claimable right now.
+    pub fn stake_escrow<'info>(
+        ctx: Context<'_, '_, '_, 'info, StakeEscrow<'info>>,
+        amount: u64,
+        instruction_data: Vec<u8>,
+    ) -> Result<()> {
+        require!(ctx.accounts.data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(!ctx.accounts.data_account.frozen, VestingError::VestingFrozen);
+        require!(!ctx.accounts.data_account.mint_compromised, VestingError::MintCompromised);
+        require!(ctx.accounts.data_account.payout_mint.is_none(), VestingError::PayoutMintUnsupported);
+        require!(amount > 0, VestingError::ZeroVestingAmount);
+        let decimals = ctx.accounts.data_account.decimals;
+        let amount_raw = raw_amount(amount, decimals)?;
+
+        let balance_before = ctx.accounts.escrow_wallet.amount;
+        invoke_staking_program(
+            &ctx.accounts.staking_program,
+            &ctx.accounts.data_account,
+            ctx.remaining_accounts,
+            instruction_data,
+        )?;
+        ctx.accounts.escrow_wallet.reload()?;
+        // The staking program signs with the escrow authority, so it could have approved a delegate
+        require_plain_token_account(&ctx.accounts.escrow_wallet)?;
+        let balance_after = ctx.accounts.escrow_wallet.amount;
+        require!(
+            balance_before.checked_sub(balance_after) == Some(amount_raw),
+            VestingError::StakingAmountMismatch
+        );
+
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.staked_amount = data_account.staked_amount.saturating_add(amount);
+
+        // Vested tokens must stay liquid so beneficiaries can always claim them
+        let now = time_source::now(ctx.remaining_accounts)?;
+        let claimable_now = data_account
+            .pool_vested_amount(data_account.vested_bps(now) as u64)?
+            .saturating_sub(data_account.claimed_total);
+        require!(
+            balance_after >= raw_amount(claimable_now, decimals)?,
+            VestingError::EscrowBalanceTooLow
+        );
+        Ok(())
+    }
+
+    // Brings `amount` whole tokens back from the staking program, in the same way as `stake_escrow`.
+    // The CPI must return at least `amount`; any yield on top is sent to `recipient`.
+    pub fn unstake_escrow<'info>(
+        ctx: Context<'_, '_, '_, 'info, UnstakeEscrow<'info>>,
+        amount: u64,
+        instruction_data: Vec<u8>,
+    ) -> Result<()> {
+        require!(ctx.accounts.data_account.approvers.is_empty(), VestingError::MultisigRequired);
+        require!(amount > 0, VestingError::ZeroVestingAmount);
+        require!(
+            amount <= ctx.accounts.data_account.staked_amount,
+            VestingError::StakingAmountMismatch
+        );
+        let decimals = ctx.accounts.data_account.decimals;
+        let amount_raw = raw_amount(amount, decimals)?;
+
+        let balance_before = ctx.accounts.escrow_wallet.amount;
+        invoke_staking_program(
+            &ctx.accounts.staking_program,
+            &ctx.accounts.data_account,
+            ctx.remaining_accounts,
+            instruction_data,
+        )?;
+        ctx.accounts.escrow_wallet.reload()?;
+        require_plain_token_account(&ctx.accounts.escrow_wallet)?;
+        let returned = ctx
+            .accounts
+            .escrow_wallet
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(VestingError::StakingAmountMismatch)?;
+        require!(returned >= amount_raw, VestingError::StakingAmountMismatch);
+
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.staked_amount -= amount;
+
+        // Pay out the yield so the escrow holds exactly what the schedule accounts for
+        let yield_raw = returned - amount_raw;
+        if yield_raw > 0 {
+            let token_mint_key = ctx.accounts.token_mint.key();
+            let schedule_seed = data_account.schedule_seed();
+            let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[data_account.bump]];
+            let signer_seeds = &[&seeds[..]];
+            let transfer_instruction = TransferChecked {
+                from: ctx.accounts.escrow_wallet.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                to: ctx.accounts.recipient.to_account_info(),
+                authority: data_account.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                transfer_instruction,
+                signer_seeds,
+            );
+            token::transfer_checked(cpi_ctx, yield_raw, data_account.decimals)?;
+        }
+        Ok(())
+    }
+
+    // Sets up an empty zero-copy `BeneficiaryRegistry` for a schedule. The client creates the account
+    // (owned by this program, `REGISTRY_ACCOUNT_SPACE` bytes) in the same transaction.
+    pub fn init_registry(ctx: Context<InitRegistry>) -> Result<()> {
+        let mut registry = ctx.accounts.registry.load_init()?;
+        registry.data_account = ctx.accounts.data_account.key();
+        Ok(())
+    }
+
+    // Registry counterpart of `add_beneficiaries`: appends entries instead of creating one PDA per beneficiary.
+    pub fn registry_add_beneficiaries(
+        ctx: Context<ModifyRegistry>,
+        new_beneficiaries: Vec<NewBeneficiary>,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        // Tokens taken back by a cancellation can't be allocated to new beneficiaries
+        require!(!data_account.cancelled, VestingError::VestingCancelled);
+        require!(!data_account.shares_mode, VestingError::SharesModeUnsupported);
+        require!(new_beneficiaries.len() <= MAX_BENEFICIARIES_PER_CHUNK, VestingError::BatchTooLarge);
+        let mut registry = ctx.accounts.registry.load_mut()?;
+
+        for new in new_beneficiaries {
+            require!(registry.position(&new.key).is_none(), VestingError::BeneficiaryAlreadyExists);
+            // Registry entries have no room for a recovery authority
+            require!(new.recovery_authority.is_none(), VestingError::RecoveryUnsupported);
+
+            // Never allocate more tokens than the escrow holds
+            data_account.total_allocated = data_account
+                .total_allocated
+                .checked_add(new.allocated_tokens)
+                .ok_or(VestingError::AllocationExceedsEscrow)?;
+            require!(
+                data_account.total_allocated <= data_account.token_amount,
+                VestingError::AllocationExceedsEscrow
+            );
+
+            registry.push(RegistryEntry {
+                key: new.key,
+                allocated_tokens: new.allocated_tokens,
+                claimed_tokens: 0,
+            })?;
+            data_account.count_new_beneficiary()?;
+        }
+
+        emit_schedule_stats(data_account);
+        Ok(())
+    }
+
+    // Registry counterpart of `execute_removal`, with the same accounting but without a dispute window.
+    pub fn registry_remove_beneficiaries(
+        ctx: Context<ModifyRegistry>,
+        keys: Vec<Pubkey>,
+        force: bool,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        require!(keys.len() <= MAX_BENEFICIARIES_PER_CHUNK, VestingError::BatchTooLarge);
+        let vested_bps = data_account.vested_bps(time_source::now(ctx.remaining_accounts)?);
+        let mut registry = ctx.accounts.registry.load_mut()?;
+
+        for key in keys {
+            let index = registry.position(&key).ok_or(VestingError::BeneficiaryNotFound)?;
+            let entry = registry.entries[index];
+
+            // Refuse to drop a beneficiary who still has vested tokens waiting, unless forced
+            let unclaimed_vested = entry
+                .vested_amount(data_account, vested_bps)
+                .saturating_sub(entry.claimed_tokens);
+            require!(force || unclaimed_vested == 0, VestingError::BeneficiaryHasUnclaimedTokens);
+
+            data_account.total_allocated = data_account.total_allocated.saturating_sub(entry.allocated_tokens);
+            data_account.claimed_total = data_account.claimed_total.saturating_sub(entry.claimed_tokens);
+            data_account.token_amount = data_account.token_amount.saturating_sub(entry.claimed_tokens);
+            data_account.beneficiary_count = data_account.beneficiary_count.saturating_sub(1);
+
+            registry.swap_remove(index);
+        }
+
+        emit_schedule_stats(data_account);
+        Ok(())
+    }
+
+    // Registry counterpart of `claim`: pays the signer's registry entry into their ATA.
+    // Payout accounts and claim delegates are only supported for `BeneficiaryAccount` PDAs.
+    pub fn registry_claim(ctx: Context<RegistryClaim>) -> Result<()> {
+        let sender = ctx.accounts.sender.key();
+        let data_account = &mut ctx.accounts.data_account;
+        let config = ctx.accounts.config.as_ref();
+        data_account.check_claim_approval(ctx.accounts.claim_approver.as_ref())?;
+        require!(!data_account.frozen, VestingError::VestingFrozen);
+        data_account.require_plain_claim()?;
+        // Registry entries don't track the last claim, so rate-limited schedules can't use them
+        require!(!data_account.has_claim_limits(), VestingError::ClaimLimitsUnsupported);
+        // Nor can they record an acceptance
+        require!(!data_account.require_acceptance, VestingError::GrantNotAccepted);
+
+        let now = data_account.advance_clock(time_source::now(ctx.remaining_accounts)?)?;
+        require!(now >= data_account.start_timestamp, VestingError::VestingNotStarted);
+        require!(!data_account.claim_window_expired(now), VestingError::ClaimWindowExpired);
+
+        let mut registry = ctx.accounts.registry.load_mut()?;
+        let index = registry.position(&sender).ok_or(VestingError::BeneficiaryNotFound)?;
+        let entry = &mut registry.entries[index];
+        let claimable_amount = entry.claimable_amount(data_account, now);
+        require!(claimable_amount > 0, VestingError::ClaimNotAllowed);
+        require_thawed(&ctx.accounts.escrow_wallet, &ctx.accounts.wallet_to_deposit_to)?;
+
+        let token_mint_key = ctx.accounts.token_mint.key();
+        let schedule_seed = data_account.schedule_seed();
+        let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[data_account.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let amount_raw = math::to_raw_amount(claimable_amount, data_account.decimals).ok_or(VestingError::MathOverflow)?;
+        let (fee_raw, fee_account) = protocol_fee(config, ctx.accounts.fee_token_account.as_ref(), amount_raw)?;
+        pay_claim(
+            data_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.token_mint,
+            Some(&ctx.accounts.escrow_wallet),
+            [(ctx.accounts.wallet_to_deposit_to.to_account_info(), amount_raw - fee_raw)]
+                .into_iter()
+                .chain(fee_account.map(|fee_account| (fee_account, fee_raw))),
+            signer_seeds,
+        )?;
+
+        data_account.book_registry_claim(entry, claimable_amount);
+        check_invariants(data_account, None, Some(&ctx.accounts.escrow_wallet.to_account_info()))?;
+        emit_schedule_stats(data_account);
+        emit_cpi!(TokensClaimed {
+            data_account: data_account.key(),
+            beneficiary: sender,
+            amount: amount_raw - fee_raw,
+            fee: fee_raw,
+            fee_recipient: fee_recipient(config),
+            secondary_amount: 0,
+            memo_hash: None,
+        });
+        Ok(())
+    }
+
+}
+
+// Claims everything claimable by `sender` from one schedule of a `claim_many` or `claim_all_for_signer` batch.
+// `accounts` is a (data_account, escrow_wallet, beneficiary_account, wallet_to_deposit_to, fee_token_account,
+// token_mint) tuple. Returns the raw amount paid out and the fee part of it, or None if the schedule was skipped.
+// Nothing is written before the checks have passed, so a failed position leaves no state behind.
+fn claim_schedule_position<'info>(
+    program_id: &Pubkey,
+    sender: &Signer<'info>,
+    config: Option<&Account<'info, Config>>,
+    claim_approver: Option<&Signer<'info>>,
+    token_program: &Program<'info, Token>,
+    accounts: &'info [AccountInfo<'info>],
+    now: i64,
+) -> Result<Option<(u64, u64)>> {
+    let (data_info, escrow_info, beneficiary_info, ata_info, fee_info, mint_info) =
+        (&accounts[0], &accounts[1], &accounts[2], &accounts[3], &accounts[4], &accounts[5]);
+
+    // Deserializing checks owner and discriminator of each account
+    let mut data_account: Account<'info, DataAccount> = Account::try_from(data_info)?;
+    let mut beneficiary: Account<'info, BeneficiaryAccount> = Account::try_from(beneficiary_info)?;
+    let deposit_wallet: Account<'info, TokenAccount> = Account::try_from(ata_info)?;
+    let token_mint: Account<'info, Mint> = Account::try_from(mint_info)?;
+
+    // The data account must be the PDA for its mint, derived with its stored canonical bump
+    let token_mint_key = data_account.token_mint;
+    let expected_data = Pubkey::create_program_address(
+        &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &data_account.schedule_seed(), &[data_account.bump]],
+        program_id,
+    )
+    .map_err(|_| VestingError::InvalidDataAccount)?;
+    require_keys_eq!(data_info.key(), expected_data, VestingError::InvalidDataAccount);
+    require_keys_eq!(escrow_info.key(), data_account.escrow_wallet, VestingError::InvalidEscrowWallet);
+    require_keys_eq!(mint_info.key(), token_mint_key, VestingError::InvalidTokenAccount);
+    data_account.check_claim_approval(claim_approver)?;
+    require!(!data_account.frozen, VestingError::VestingFrozen);
+    data_account.require_plain_claim()?;
+    let now = data_account.advance_clock(now)?;
+
+    // The beneficiary account must belong to the signer in this schedule
+    let expected_beneficiary = Pubkey::create_program_address(
+        &[BENEFICIARY_SEED, data_info.key.as_ref(), sender.key.as_ref(), &[beneficiary.bump]],
+        program_id,
+    )
+    .map_err(|_| VestingError::InvalidBeneficiaryPDA)?;
+    require_keys_eq!(beneficiary_info.key(), expected_beneficiary, VestingError::InvalidBeneficiaryPDA);
+    require_keys_eq!(beneficiary.key, sender.key(), VestingError::BeneficiaryNotFound);
+    beneficiary.require_plain_position(&data_account)?;
+    // The tuple has no room for a cohort account, so members claim through `claim`
+    require!(beneficiary.cohort_id.is_none(), VestingError::CohortMismatch);
+
+    // Tokens may only go to the signer's own account for this mint
+    require_keys_eq!(deposit_wallet.mint, token_mint_key, VestingError::InvalidTokenAccount);
+    require_keys_eq!(deposit_wallet.owner, sender.key(), VestingError::InvalidTokenAccount);
+    require_plain_token_account(&deposit_wallet)?;
+    let escrow_wallet: Account<'info, TokenAccount> = Account::try_from(escrow_info)?;
+    require_thawed(&escrow_wallet, &deposit_wallet)?;
+    // The fee goes to the configured fee recipient's account for this mint; the slot is unused without a `Config`
+    let fee_wallet = match config {
+        Some(config) => {
+            let fee_wallet: Account<'info, TokenAccount> = Account::try_from(fee_info)?;
+            require_keys_eq!(fee_wallet.mint, token_mint_key, VestingError::InvalidTokenAccount);
+            require_keys_eq!(fee_wallet.owner, config.fee_recipient, VestingError::InvalidFeeAccount);
+            Some(fee_wallet)
+        }
+        None => None,
+    };
+
+    // Schedules still in their claim cooldown or past their claim deadline are skipped
+    // like ones with nothing vested
+    if now < data_account.next_claim_allowed_at(beneficiary.last_claim_timestamp)
+        || data_account.claim_window_expired(now)
+    {
+        return Ok(None);
+    }
+    let claimable_amount = beneficiary.claimable_amount(&data_account, None, now)?;
+    if claimable_amount == 0 {
+        return Ok(None);
+    }
+
+    let schedule_seed = data_account.schedule_seed();
+    let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[data_account.bump]];
+    let signer_seeds = &[&seeds[..]];
+    let amount_raw = math::to_raw_amount(claimable_amount, data_account.decimals).ok_or(VestingError::MathOverflow)?;
+    let (fee_raw, fee_account) = protocol_fee(config, fee_wallet.as_ref(), amount_raw)?;
+    pay_claim(
+        &data_account,
+        token_program,
+        &token_mint,
+        Some(&escrow_wallet),
+        [(ata_info.clone(), amount_raw - fee_raw)].into_iter().chain(fee_account.map(|fee_account| (fee_account, fee_raw))),
+        signer_seeds,
+    )?;
+
+    data_account.book_claim(&mut beneficiary, claimable_amount, now);
+    check_invariants(&data_account, Some(&beneficiary), Some(escrow_info))?;
+    emit_schedule_stats(&data_account);
+
+    // Accounts loaded from `remaining_accounts` are not persisted automatically
+    beneficiary.exit(program_id)?;
+    data_account.exit(program_id)?;
+    Ok(Some((amount_raw, fee_raw)))
+}
+
+// Numeric code of a program or Anchor error, as reported to clients. Builtin program errors keep their full
+// `u64` code, which only `ProgramError::Custom` fits into 32 bits.
+fn error_code_number(error: &Error) -> u64 {
+    match error {
+        Error::AnchorError(error) => u64::from(error.error_code_number),
+        Error::ProgramError(error) => u64::from(error.program_error.clone()),
+    }
+}
+
+// Shared by `claim` and `claim_with_memo`; `memo_hash` is reported in `TokensClaimed`.
+fn process_claim<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Claim<'info>>,
+    amount: u64,
+    memo_hash: Option<[u8; 32]>,
+    dry_run: bool,
+    claim_nonce: Option<u64>,
+) -> Result<TransferResult> {
+     // Get a reference to the signer account (the beneficiary or their claim delegate).
+    let sender = &ctx.accounts.sender;
+     // Get the beneficiary wallet the grant is bound to.
+    let beneficiary_wallet = ctx.accounts.beneficiary_wallet.key();
+     // Get a reference to the escrow wallet holding the vested tokens.
+    let escrow_wallet = ctx.accounts.escrow_wallet.as_ref();
+    // Get a mutable reference to the main vesting state account (PDA).
+    let data_account = &mut ctx.accounts.data_account;
+     // Get a reference to the SPL Token program account, needed for CPI.
+    let token_program = &ctx.accounts.token_program;
+     // Get the public key of the token mint used for vesting (e.g., USDC, custom SPL token).
+    let token_mint_key = ctx.accounts.token_mint.key();
+     // Get the associated token account (ATA) of the beneficiary — this is where tokens will be sent.
+    let beneficiary_ata = &ctx.accounts.wallet_to_deposit_to;
+    // Get the decimals (precision) used by the token (e.g., 6 decimals means 1 token = 1_000_000 units).
+    let decimals = data_account.decimals;
+ // Get a mutable reference to the beneficiary's vesting tracking account.
+    let beneficiary = &mut ctx.accounts.beneficiary_account;
+    
+    // Ensure the beneficiary account belongs to the given beneficiary wallet.
+  // If they don't match, return a custom error: `BeneficiaryNotFound`
+    require_keys_eq!(beneficiary.key, beneficiary_wallet, VestingError::BeneficiaryNotFound);
+    // Terminated beneficiaries were paid out in full by `terminate_beneficiary`.
+    require!(!beneficiary.terminated, VestingError::BeneficiaryTerminated);
+    // Grants of schedules with an acceptance step only pay out once accepted
+    require!(!data_account.require_acceptance || beneficiary.accepted, VestingError::GrantNotAccepted);
+    // A nonce at or below the last one used belongs to a claim that already went through.
+    if let Some(nonce) = claim_nonce {
+        require!(nonce > beneficiary.last_claim_nonce, VestingError::DuplicateClaim);
+    }
+    // Positions backed by an NFT are claimed by whoever holds the NFT, into their own token account;
+    // the original wallet's delegate and payout account no longer apply.
+    // Otherwise only the beneficiary or their claim delegate may trigger a claim.
+    let nft_holder = match beneficiary.position_mint {
+        Some(position_mint) => {
+            let position = ctx
+                .accounts
+                .position_token_account
+                .as_ref()
+                .ok_or(VestingError::PositionNftRequired)?;
+            require_keys_eq!(position.mint, position_mint, VestingError::PositionNftRequired);
+            require_keys_eq!(position.owner, sender.key(), VestingError::PositionNftRequired);
+            require!(position.amount == 1, VestingError::PositionNftRequired);
+            Some(sender.key())
+        }
+        None => {
+            require!(
+                sender.key() == beneficiary_wallet || beneficiary.claim_delegate == Some(sender.key()),
+                VestingError::UnauthorizedClaimer
+            );
+            None
+        }
+    };
+    // In permissioned mode the claim must also be co-signed by the compliance authority.
+    data_account.check_claim_approval(ctx.accounts.claim_approver.as_ref())?;
+    require!(!data_account.frozen, VestingError::VestingFrozen);
+    // Blocked wallets can't claim, neither as the beneficiary nor as the holder of its position NFT.
+    let claimants: Vec<Pubkey> = std::iter::once(beneficiary_wallet).chain(nft_holder).collect();
+    data_account.check_blacklist(ctx.accounts.blacklist.as_deref(), &claimants)?;
+    // Identity-gated schedules have the gate program verify the beneficiary wallet, and the NFT holder claiming for it.
+    if let Some(gate_program_key) = data_account.gate_program {
+        let gate_program = ctx.accounts.gate_program.as_ref().ok_or(VestingError::GateCheckFailed)?;
+        let gate_config = ctx.accounts.gate_config.as_ref().ok_or(VestingError::GateCheckFailed)?;
+        require_keys_eq!(gate_program.key(), gate_program_key, VestingError::GateCheckFailed);
+        require_keys_eq!(gate_config.key(), data_account.gate_config, VestingError::GateCheckFailed);
+        verify_claim_gate(gate_program, gate_config, &ctx.accounts.beneficiary_wallet)?;
+        if nft_holder.is_some() {
+            verify_claim_gate(gate_program, gate_config, &sender.to_account_info())?;
+        }
+    }
+     // Get the current on-chain UNIX timestamp from the Solana clock sysvar.
+    // It is never taken earlier than the last time this schedule processed, so vested shares can't go backwards.
+    // A dry run doesn't record it.
+    let now = if dry_run {
+        data_account.check_clock(time_source::now(ctx.remaining_accounts)?)?
+    } else {
+        data_account.advance_clock(time_source::now(ctx.remaining_accounts)?)?
+    };
+     // Check that the vesting has started.
+// If current time is before the `start_timestamp`, throw `VestingNotStarted` error.
+    require!(now >= data_account.start_timestamp, VestingError::VestingNotStarted);
+    // Entitlements not claimed by the claim deadline are forfeited.
+    require!(!data_account.claim_window_expired(now), VestingError::ClaimWindowExpired);
+    // Determine the effective claimable percentage for the beneficiary.
+//
+// Take the lesser of:
+// - the time-vested share: the TGE unlock plus linear vesting after the lockup (e.g., 50% after 18 months of a 36-month vesting)
+// - `data_account.percent_available`: how much has been manually released (e.g., via `release()`)
+// This ensures both time-based and manual vesting constraints are respected.
+// Once the schedule is cancelled, the share frozen by `execute_cancel` applies instead.
+// The math lives in `DataAccount::vested_bps` so `get_claimable` reports the same numbers.
+
+    let effective_claim_bps = beneficiary.vested_bps(data_account, ctx.accounts.cohort.as_deref(), now)?;
+      // Calculate the total number of tokens the beneficiary is eligible to claim at this point.
+// Formula:
+// (allocated_tokens * effective_bps) / 10_000
+// Once fully vested this is exactly `allocated_tokens`, so the final claim leaves no remainder.
+
+    let total_eligible = beneficiary.vested_amount(data_account, effective_claim_bps);
+    // Installment-funded schedules pay out no more than the beneficiary's share of the funded tokens
+    let funded_eligible = data_account.cap_funded(beneficiary, total_eligible);
+    if funded_eligible < total_eligible && !dry_run {
+        emit_cpi!(FundingShortfall {
+            data_account: data_account.key(),
+            beneficiary: beneficiary_wallet,
+            vested_amount: total_eligible,
+            funded_amount: funded_eligible,
+        });
+    }
+    // Enforce the schedule's claim cooldown, if any.
+    require!(
+        now >= data_account.next_claim_allowed_at(beneficiary.last_claim_timestamp),
+        VestingError::ClaimTooSoon
+    );
+    // Calculate the remaining claimable amount by subtracting already claimed tokens.
+    // Tokens locked for voting stay in escrow until the lock expires, and the per-period cap applies.
+    let claimable_amount = data_account.claimable_amount(beneficiary, funded_eligible, now);
+    // A partial claim leaves the rest claimable later
+    require!(amount <= claimable_amount, VestingError::ClaimAmountTooHigh);
+    let claimable_amount = if amount == 0 { claimable_amount } else { amount };
+     // Prepare the signer seeds for invoking CPI as the data_account PDA.
+   // Seeds used to generate the PDA:
+// - "data_account": a static string prefix
+// - token_mint_key: identifies the specific vesting mint
+// - data_account.bump: canonical bump stored at initialization
+
+    let schedule_seed = data_account.schedule_seed();
+    let seeds = &[DATA_ACCOUNT_SEED, token_mint_key.as_ref(), &schedule_seed, &[data_account.bump]];
+      // Wrap the seeds in the required nested format for CPI signer support.
+    let signer_seeds = &[&seeds[..]];
+
+ // Convert the human-readable token amount to raw amount by applying the token's decimal places
+    let amount_to_transfer_raw = math::to_raw_amount(claimable_amount, decimals).ok_or(VestingError::MathOverflow)?;
+     // Ensure that the effective claim share is greater than 0 before proceeding
+
+    require!(effective_claim_bps > 0, VestingError::ClaimNotAllowed);
+    // Mint-vesting schedules mint every claim, never beyond the `token_amount` cap
+    require!(
+        !data_account.mint_vesting
+            || data_account.claimed_total.saturating_add(claimable_amount) <= data_account.token_amount,
+        VestingError::MintCapExceeded
+    );
+     // Skim the protocol fee off the claimed amount; the beneficiary receives the rest
+    let config = ctx.accounts.config.as_ref();
+    let (fee_raw, fee_account) =
+        protocol_fee(config, ctx.accounts.fee_token_account.as_ref(), amount_to_transfer_raw)?;
+    // Withhold the schedule's tax share of what is left after the fee
+    let withheld_raw = data_account.withholding_share(amount_to_transfer_raw - fee_raw)?;
+    // Split off the secondary recipient's share (e.g. a recruiter's cut) of what is left after the fee and withholding
+    let secondary_raw = beneficiary.secondary_share(amount_to_transfer_raw - fee_raw - withheld_raw)?;
+    if dry_run {
+        // The claim record account would already have been created for the next index
+        require!(ctx.accounts.claim_record.is_none(), VestingError::DryRunClaimRecord);
+        if let Some(hook_program_key) = data_account.on_claim_hook {
+            let hook_program = ctx.accounts.claim_hook_program.as_ref().ok_or(VestingError::ClaimHookRequired)?;
+            require_keys_eq!(hook_program.key(), hook_program_key, VestingError::ClaimHookRequired);
+        }
+        let claimed_total = data_account.claimed_total.saturating_add(claimable_amount);
+        return Ok(TransferResult {
+            amount: amount_to_transfer_raw - fee_raw - withheld_raw - secondary_raw,
+            claimed_total,
+            remaining_tokens: data_account
+                .token_amount
+                .saturating_sub(claimed_total + data_account.unclaimed_withdrawn),
+        });
+    }
+    if let Some(payout_mint_key) = data_account.payout_mint {
+        // After `set_payout_mint` the claim is paid in the payout mint, at the stored ratio, to the recipient's
+        // and the fee recipient's token accounts for that mint. Payout accounts and secondary recipients
+        // were set up for the vesting mint, so they can't be used any more.
+        require!(
+            beneficiary.payout_token_account.is_none() && beneficiary.secondary_recipient.is_none(),
+            VestingError::PayoutMintUnsupported
+        );
+        require!(data_account.withholding_account.is_none(), VestingError::PayoutMintUnsupported);
+        let payout_mint = ctx.accounts.payout_mint.as_ref().ok_or(VestingError::PayoutMintAccountMismatch)?;
+        let payout_escrow = ctx.accounts.payout_escrow.as_ref().ok_or(VestingError::PayoutMintAccountMismatch)?;
+        let payout_destination = ctx
+            .accounts
+            .payout_destination
+            .as_ref()
+            .ok_or(VestingError::PayoutMintAccountMismatch)?;
+        require_keys_eq!(payout_mint.key(), payout_mint_key, VestingError::PayoutMintAccountMismatch);
+        require_keys_eq!(payout_escrow.key(), data_account.payout_escrow, VestingError::PayoutMintAccountMismatch);
+        require_keys_eq!(
+            payout_destination.owner,
+            nft_holder.unwrap_or(beneficiary_wallet),
+            VestingError::PayoutMintAccountMismatch
+        );
+        require_keys_eq!(payout_destination.mint, payout_mint_key, VestingError::PayoutMintAccountMismatch);
+        require_plain_token_account(payout_destination)?;
+        require_thawed(payout_escrow, payout_destination)?;
+        pay_out_in_payout_mint(
+            data_account,
+            token_program,
+            payout_mint,
+            payout_escrow,
+            payout_destination.to_account_info(),
+            amount_to_transfer_raw - fee_raw,
+            signer_seeds,
+        )?;
+        if fee_raw > 0 {
+            let payout_fee_account = ctx
+                .accounts
+                .payout_fee_token_account
+                .as_ref()
+                .ok_or(VestingError::PayoutMintAccountMismatch)?;
+            require_keys_eq!(payout_fee_account.owner, fee_recipient(config), VestingError::PayoutMintAccountMismatch);
+            require_keys_eq!(payout_fee_account.mint, payout_mint_key, VestingError::PayoutMintAccountMismatch);
+            pay_out_in_payout_mint(
+                data_account,
+                token_program,
+                payout_mint,
+                payout_escrow,
+                payout_fee_account.to_account_info(),
+                fee_raw,
+                signer_seeds,
+            )?;
+        }
+    } else {
+        // Pay out to the NFT holder's token account, or to the beneficiary's chosen payout account if one is set,
+        // otherwise to their ATA.
+        let destination = match (nft_holder, beneficiary.payout_token_account) {
+            (Some(holder), _) => {
+                let payout_account = ctx
+                    .accounts
+                    .payout_token_account
+                    .as_ref()
+                    .ok_or(VestingError::PayoutAccountMismatch)?;
+                require_keys_eq!(payout_account.owner, holder, VestingError::PayoutAccountMismatch);
+                require_keys_eq!(payout_account.mint, token_mint_key, VestingError::PayoutAccountMismatch);
+                payout_account
+            }
+            (None, Some(payout)) => {
+                let payout_account = ctx
+                    .accounts
+                    .payout_token_account
+                    .as_ref()
+                    .ok_or(VestingError::PayoutAccountMismatch)?;
+                require_keys_eq!(payout_account.key(), payout, VestingError::PayoutAccountMismatch);
+                payout_account
+            }
+            (None, None) => beneficiary_ata,
+        };
+        require_plain_token_account(destination)?;
+        // Mint-vesting claims are minted, so their escrow is never touched
+        require!(
+            data_account.mint_vesting || !escrow_wallet.is_some_and(|escrow_wallet| escrow_wallet.is_frozen()),
+            VestingError::EscrowFrozen
+        );
+        require!(!destination.is_frozen(), VestingError::RecipientFrozen);
+         // Pay the beneficiary, and the fee to the fee recipient's ATA, from the escrow (or mint to them),
+         // signed by the `data_account` PDA
+        pay_claim(
+            data_account,
+            token_program,
+            &ctx.accounts.token_mint,
+            escrow_wallet,
+            [(destination.to_account_info(), amount_to_transfer_raw - fee_raw - withheld_raw - secondary_raw)]
+                .into_iter()
+                .chain(fee_account.map(|fee_account| (fee_account, fee_raw))),
+            signer_seeds,
+        )?;
+        // Reward the tokens for staying unclaimed, as far as the bonus escrow allows; no fee is taken from the bonus
+        if data_account.bonus_rate_bps_per_year > 0 {
+            let bonus_escrow = ctx.accounts.bonus_escrow.as_ref().ok_or(VestingError::BonusEscrowMismatch)?;
+            require_keys_eq!(bonus_escrow.key(), data_account.bonus_escrow, VestingError::BonusEscrowMismatch);
+            let bonus_raw = data_account
+                .unclaimed_bonus(amount_to_transfer_raw, beneficiary.last_claim_timestamp, now)?
+                .min(bonus_escrow.amount);
+            if bonus_raw > 0 {
+                let transfer_instruction = TransferChecked {
+                    from: bonus_escrow.to_account_info(),
+                    mint: ctx.accounts.token_mint.to_account_info(),
+                    to: destination.to_account_info(),
+                    authority: data_account.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    transfer_instruction,
+                    signer_seeds,
+                );
+                token::transfer_checked(cpi_ctx, bonus_raw, decimals)?;
+                data_account.bonus_paid_total = data_account.bonus_paid_total.saturating_add(bonus_raw);
+                emit!(BonusPaid {
+                    data_account: data_account.key(),
+                    beneficiary: beneficiary_wallet,
+                    amount: bonus_raw,
+                    bonus_paid_total: data_account.bonus_paid_total,
+                });
+            }
+        }
+        if let Some(withholding_account_key) = data_account.withholding_account {
+            let withholding_account = ctx
+                .accounts
+                .withholding_token_account
+                .as_ref()
+                .ok_or(VestingError::WithholdingAccountMismatch)?;
+            require_keys_eq!(withholding_account.key(), withholding_account_key, VestingError::WithholdingAccountMismatch);
+            require_keys_eq!(withholding_account.mint, token_mint_key, VestingError::WithholdingAccountMismatch);
+            require_plain_token_account(withholding_account)?;
+            if withheld_raw > 0 {
+                pay_out(
+                    data_account,
+                    token_program,
+                    &ctx.accounts.token_mint,
+                    escrow_wallet,
+                    withholding_account.to_account_info(),
+                    withheld_raw,
+                    signer_seeds,
+                )?;
+            }
+        }
+        if let Some(secondary_recipient) = beneficiary.secondary_recipient {
+            let secondary_account = ctx
+                .accounts
+                .secondary_token_account
+                .as_ref()
+                .ok_or(VestingError::SecondaryAccountMismatch)?;
+            require_keys_eq!(secondary_account.owner, secondary_recipient, VestingError::SecondaryAccountMismatch);
+            require_keys_eq!(secondary_account.mint, token_mint_key, VestingError::SecondaryAccountMismatch);
+            require_plain_token_account(secondary_account)?;
+            if secondary_raw > 0 {
+                pay_out(
+                    data_account,
+                    token_program,
+                    &ctx.accounts.token_mint,
+                    escrow_wallet,
+                    secondary_account.to_account_info(),
+                    secondary_raw,
+                    signer_seeds,
+                )?;
+            }
+        }
+    }
+    // The first claim is the one that pays for the beneficiary's token account
+    let first_claim = beneficiary.last_claim_timestamp == 0;
+    // Update the beneficiary's and the schedule's claimed amounts (in whole tokens)
+    data_account.book_claim(beneficiary, claimable_amount, now);
+    if let Some(nonce) = claim_nonce {
+        beneficiary.last_claim_nonce = nonce;
+    }
+    // Pay out the beneficiary's share of every additional vesting leg alongside the primary mint
+    pay_vesting_legs(
+        data_account,
+        time_source::instruction_accounts(ctx.remaining_accounts),
+        nft_holder.unwrap_or(beneficiary_wallet),
+        beneficiary
+            .secondary_recipient
+            .map(|secondary_recipient| (secondary_recipient, beneficiary.secondary_bps)),
+        claimable_amount,
+        token_program,
+        signer_seeds,
+    )?;
+    // Append to the beneficiary's claim history when the schedule keeps one
+    require!(
+        !data_account.record_claims || ctx.accounts.claim_record.is_some(),
+        VestingError::ClaimRecordRequired
+    );
+    if let Some(claim_record) = ctx.accounts.claim_record.as_mut() {
+        claim_record.timestamp = now;
+        claim_record.amount = claimable_amount;
+        claim_record.cumulative_claimed = beneficiary.claimed_tokens;
+        claim_record.bump = ctx.bumps.claim_record.unwrap_or_default();
+        beneficiary.claim_count = beneficiary.claim_count.saturating_add(1);
+    }
+    // Tell the claim hook, if any, once the tokens have been paid
+    if let Some(hook_program_key) = data_account.on_claim_hook {
+        let hook_program = ctx.accounts.claim_hook_program.as_ref().ok_or(VestingError::ClaimHookRequired)?;
+        require_keys_eq!(hook_program.key(), hook_program_key, VestingError::ClaimHookRequired);
+        notify_claim_hook(
+            hook_program,
+            &data_account.to_account_info(),
+            beneficiary_wallet,
+            amount_to_transfer_raw,
+            now,
+            signer_seeds,
+        )?;
+    }
+    if withheld_raw > 0 {
+        data_account.withheld_total = data_account.withheld_total.saturating_add(withheld_raw);
+        emit!(ClaimWithheld {
+            data_account: data_account.key(),
+            beneficiary: beneficiary_wallet,
+            amount: withheld_raw,
+            withheld_total: data_account.withheld_total,
+        });
+    }
+    check_invariants(
+        data_account,
+        Some(beneficiary),
+        escrow_wallet.map(|escrow_wallet| escrow_wallet.to_account_info()).as_ref(),
+    )?;
+    emit_schedule_stats(data_account);
+    emit_cpi!(TokensClaimed {
+        data_account: data_account.key(),
+        beneficiary: beneficiary_wallet,
+        amount: amount_to_transfer_raw - fee_raw - withheld_raw - secondary_raw,
+        fee: fee_raw,
+        fee_recipient: fee_recipient(config),
+        secondary_amount: secondary_raw,
+        memo_hash,
+    });
+    // Refund the sender's first-claim costs (token account rent, transaction fee) from the SOL fee pool,
+    // so a relayer acting as claim delegate can onboard beneficiaries who hold no SOL
+    if first_claim && claimable_amount > 0 {
+        let lamports = data_account.take_fee_reimbursement();
+        if lamports > 0 {
+            pay_from_fee_pool(data_account, &sender.to_account_info(), lamports)?;
+            emit!(FeePoolReimbursed {
+                data_account: data_account.key(),
+                beneficiary: beneficiary_wallet,
+                recipient: sender.key(),
+                lamports,
+            });
+        }
+    }
+
+    Ok(data_account.transfer_result(amount_to_transfer_raw - fee_raw - withheld_raw - secondary_raw))
+}
+
+// Creates the `["beneficiary", data_account, new.key]` PDA passed as `beneficiary_account_info` and books its
+// allocation, for `add_beneficiaries` and `clone_beneficiaries`. `index_account_info` becomes its beneficiary
+// index entry.
+fn create_beneficiary_account<'info>(
+    program_id: &Pubkey,
+    data_account: &mut Account<'info, DataAccount>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    beneficiary_account_info: &AccountInfo<'info>,
+    index_account_info: &AccountInfo<'info>,
+    new: &NewBeneficiary,
+) -> Result<()> {
+    let beneficiary_pubkey = new.key;
+// Seeds used to generate the PDA for the beneficiary
+    let data_account_key = data_account.key();
+    let beneficiary_seeds = &[
+        BENEFICIARY_SEED,
+        data_account_key.as_ref(),
+        beneficiary_pubkey.as_ref(),
+    ];
+// Derive the PDA and its bump for the beneficiary
+    let (beneficiary_pda, bump) =
+        Pubkey::find_program_address(beneficiary_seeds, program_id);
+    // The account passed in has to be that PDA, or the data would be written to an arbitrary account
+    require_keys_eq!(
+        beneficiary_account_info.key(),
+        beneficiary_pda,
+        VestingError::InvalidBeneficiaryPDA
+    );
+     // Ensure the beneficiary account is still owned by the System Program (i.e., not yet initialized)
+    require!(
+        beneficiary_account_info.owner == &System::id(),
+        VestingError::BeneficiaryAlreadyExists
+    );
+
+    let (allocated_tokens, shares) = data_account.register_allocation(new.allocated_tokens)?;
+    let index = create_beneficiary_index(program_id, data_account, payer, system_program, index_account_info, new.key)?;
+
+    // Skip creation if already initialized
+    if beneficiary_account_info.owner == &System::id() {
+        let rent = Rent::get()?;
+        let space = std::mem::size_of::<BeneficiaryAccount>() + 8; // add discriminator
+        let lamports = rent.minimum_balance(space);
+        let beneficiary_signer_seeds: &[&[u8]] = &[
+            BENEFICIARY_SEED,
+            data_account_key.as_ref(),
+            beneficiary_pubkey.as_ref(),
+            &[bump],
+        ];
+
+        if data_account.rent_reserve_lamports >= lamports {
+            // Fund the account from the rent reserve, then allocate it and assign it to the program,
+            // signed by the beneficiary PDA itself
+            let top_up = lamports.saturating_sub(beneficiary_account_info.lamports());
+            draw_rent_reserve(data_account, beneficiary_account_info, top_up)?;
+            invoke_signed(
+                &system_instruction::allocate(&beneficiary_pda, space as u64),
+                &[beneficiary_account_info.clone(), system_program.to_account_info()],
+                &[beneficiary_signer_seeds],
+            )?;
+            invoke_signed(
+                &system_instruction::assign(&beneficiary_pda, program_id),
+                &[beneficiary_account_info.clone(), system_program.to_account_info()],
+                &[beneficiary_signer_seeds],
+            )?;
+        } else {
+            invoke_signed(
+                &system_instruction::create_account(
+                    payer.key,
+                    &beneficiary_pda,
+                    lamports,
+                    space as u64,
+                    program_id,
+                ),
+                &[
+                    payer.to_account_info(),
+                    beneficiary_account_info.clone(),
+                    system_program.to_account_info(),
+                ],
+                &[beneficiary_signer_seeds],
+            )?;
+        }
+
+        let account_data = BeneficiaryAccount {
+            key: beneficiary_pubkey,
+            allocated_tokens,
+            claimed_tokens: 0,
+            bump,
+            claim_delegate: None,
+            payout_token_account: None,
+            shares,
+            recovery_authority: new.recovery_authority,
+            index,
+            ..Default::default()
+        };
+        // Anchor account layout: the 8-byte discriminator, then the Borsh-serialized fields
+        let mut data = beneficiary_account_info.try_borrow_mut_data()?;
+        data[..8].copy_from_slice(BeneficiaryAccount::DISCRIMINATOR);
+        account_data.serialize(&mut &mut data[8..])?;
+    }
+    Ok(())
+}
+
+// Creates the next entry of the schedule's beneficiary index, the `["beneficiary_index", data_account, index]` PDA
+// passed as `index_account_info`, pointing at `beneficiary`. Returns its index.
+fn create_beneficiary_index<'info>(
+    program_id: &Pubkey,
+    data_account: &mut Account<'info, DataAccount>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    index_account_info: &AccountInfo<'info>,
+    beneficiary: Pubkey,
+) -> Result<u32> {
+    let data_account_key = data_account.key();
+    let index = data_account.next_beneficiary_index()?;
+    let index_bytes = index.to_le_bytes();
+    let (index_pda, bump) =
+        Pubkey::find_program_address(&[BENEFICIARY_INDEX_SEED, data_account_key.as_ref(), &index_bytes], program_id);
+    require_keys_eq!(index_account_info.key(), index_pda, VestingError::InvalidBeneficiaryIndex);
+
+    let lamports = Rent::get()?.minimum_balance(BeneficiaryIndex::LEN);
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            &index_pda,
+            lamports,
+            BeneficiaryIndex::LEN as u64,
+            program_id,
+        ),
+        &[
+            payer.to_account_info(),
+            index_account_info.clone(),
+            system_program.to_account_info(),
+        ],
+        &[&[BENEFICIARY_INDEX_SEED, data_account_key.as_ref(), &index_bytes, &[bump]]],
+    )?;
+
+    let entry = BeneficiaryIndex {
+        data_account: data_account_key,
+        beneficiary,
+        bump,
+    };
+    let mut data = index_account_info.try_borrow_mut_data()?;
+    data[..8].copy_from_slice(BeneficiaryIndex::DISCRIMINATOR);
+    entry.serialize(&mut &mut data[8..])?;
+    Ok(index)
+}
+
+// Reports the schedule's aggregates after beneficiaries are added, removed or terminated, allocations change or
+// tokens are claimed, so dashboards don't have to scan every beneficiary account.
+fn emit_schedule_stats(data_account: &Account<DataAccount>) {
+    emit!(ScheduleStatsUpdated {
+        data_account: data_account.key(),
+        beneficiary_count: data_account.beneficiary_count,
+        total_allocated: data_account.total_allocated,
+        claimed_total: data_account.claimed_total,
+    });
+}
+
+// Moves fee pool lamports out of the data account, which always keeps its rent-exempt minimum.
+fn pay_from_fee_pool<'info>(
+    data_account: &Account<'info, DataAccount>,
+    recipient: &AccountInfo<'info>,
+    lamports: u64,
+) -> Result<()> {
+    let data_info = data_account.to_account_info();
+    let rent_exempt = Rent::get()?.minimum_balance(data_info.data_len());
+    require!(
+        data_info.lamports().saturating_sub(lamports) >= rent_exempt,
+        VestingError::FeePoolInsufficient
+    );
+    **data_info.try_borrow_mut_lamports()? -= lamports;
+    **recipient.try_borrow_mut_lamports()? += lamports;
+    Ok(())
+}
+
+// Moves `lamports` of the rent reserve out of the data account, e.g. to fund an account the program creates or
+// grows. Growing the data account itself only needs `rent_reserve_lamports` lowered by the extra rent, since
+// the lamports are already there.
+fn draw_rent_reserve<'info>(
+    data_account: &mut Account<'info, DataAccount>,
+    recipient: &AccountInfo<'info>,
+    lamports: u64,
+) -> Result<()> {
+    require!(lamports <= data_account.rent_reserve_lamports, VestingError::RentReserveInsufficient);
+    let data_info = data_account.to_account_info();
+    let rent_exempt = Rent::get()?.minimum_balance(data_info.data_len());
+    require!(
+        data_info.lamports().saturating_sub(lamports) >= rent_exempt,
+        VestingError::RentReserveInsufficient
+    );
+    data_account.rent_reserve_lamports -= lamports;
+    **data_info.try_borrow_mut_lamports()? -= lamports;
+    **recipient.try_borrow_mut_lamports()? += lamports;
+    Ok(())
+}
+
+// Pays a beneficiary's share of every vesting leg for a claim of `claimed_amount` primary tokens.
+// `leg_accounts` holds one (vesting_leg, leg_escrow, leg_mint, beneficiary token account for the leg mint)
+// tuple per leg, in leg order; all of them are required so no leg can be skipped.
+// `recipient` is the wallet that must own the leg token accounts. With a `secondary` (recipient, bps) split, each
+// tuple ends with the secondary recipient's token account for the leg mint, which receives its share of the leg.
+fn pay_vesting_legs<'info>(
+    data_account: &Account<'info, DataAccount>,
+    leg_accounts: &'info [AccountInfo<'info>],
+    recipient: Pubkey,
+    secondary: Option<(Pubkey, u16)>,
+    claimed_amount: u64,
+    token_program: &Program<'info, Token>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let tuple_len = if secondary.is_some() { 5 } else { 4 };
+    require!(
+        leg_accounts.len() == data_account.leg_count as usize * tuple_len,
+        VestingError::MissingRemainingAccount
+    );
+    for (index, accounts) in leg_accounts.chunks(tuple_len).enumerate() {
+        let (leg_info, escrow_info, mint_info, destination_info) =
+            (&accounts[0], &accounts[1], &accounts[2], &accounts[3]);
+        // Deserializing checks owner and discriminator, so the leg was created by `add_vesting_leg`
+        let leg: Account<'info, VestingLeg> = Account::try_from(leg_info)?;
+        let destination: Account<'info, TokenAccount> = Account::try_from(destination_info)?;
+        require_keys_eq!(leg.data_account, data_account.key(), VestingError::InvalidVestingLeg);
+        require!(leg.index as usize == index, VestingError::InvalidVestingLeg);
+        require_keys_eq!(escrow_info.key(), leg.escrow_wallet, VestingError::InvalidEscrowWallet);
+        require_keys_eq!(mint_info.key(), leg.mint, VestingError::InvalidTokenAccount);
+        require_keys_eq!(destination.mint, leg.mint, VestingError::InvalidTokenAccount);
+        require_keys_eq!(destination.owner, recipient, VestingError::InvalidTokenAccount);
+        require_plain_token_account(&destination)?;
+
+        let amount_raw = leg.payout_for(claimed_amount)?;
+        let mut payouts = vec![(destination_info, amount_raw)];
+        if let Some((secondary_recipient, secondary_bps)) = secondary {
+            let secondary_info = &accounts[4];
+            let secondary_account: Account<'info, TokenAccount> = Account::try_from(secondary_info)?;
+            require_keys_eq!(secondary_account.owner, secondary_recipient, VestingError::SecondaryAccountMismatch);
+            require_keys_eq!(secondary_account.mint, leg.mint, VestingError::SecondaryAccountMismatch);
+            require_plain_token_account(&secondary_account)?;
+            let secondary_raw = math::bps_of(amount_raw, secondary_bps).ok_or(VestingError::MathOverflow)?;
+            payouts = vec![(destination_info, amount_raw - secondary_raw), (secondary_info, secondary_raw)];
+        }
+        for (to, amount_raw) in payouts {
+            if amount_raw == 0 {
+                continue;
+            }
+            let transfer_instruction = TransferChecked {
+                from: escrow_info.clone(),
+                mint: mint_info.clone(),
+                to: to.clone(),
+                authority: data_account.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                transfer_instruction,
+                signer_seeds,
+            );
+            token::transfer_checked(cpi_ctx, amount_raw, leg.decimals)?;
+        }
+    }
+    Ok(())
+}
+
+// Raw units of `tokens` whole tokens of a mint with `decimals` decimals, failing instead of wrapping on overflow.
+// Stores a cohort's overrides for `create_cohort` and `update_cohort`. The overrides can't make members vest faster
+// than the schedule, but a longer vesting moves the schedule's latest end, which withdrawals wait for.
+fn set_cohort_overrides(
+    data_account: &mut DataAccount,
+    cohort: &mut Cohort,
+    cliff_months: Option<u8>,
+    vesting_months: Option<u8>,
+    acceleration_exempt: bool,
+) -> Result<()> {
+    // Once an approver set is configured, schedule changes must go through the multisig flow
+    require!(data_account.approvers.is_empty(), VestingError::MultisigRequired);
+    require!(!data_account.cancelled, VestingError::VestingCancelled);
+    // The overrides are in months
+    require!(data_account.interval_seconds == 0, VestingError::IntervalScheduleUnsupported);
+    cohort.cliff_months = cliff_months;
+    cohort.vesting_months = vesting_months;
+    cohort.acceleration_exempt = acceleration_exempt;
+
+    let mut schedule = data_account.schedule();
+    cohort.apply(&mut schedule);
+    require!(schedule.cliff_months <= schedule.vesting_months, VestingError::InvalidCohort);
+    data_account.max_cohort_vesting_months =
+        std::cmp::max(data_account.max_cohort_vesting_months, schedule.vesting_months);
+    // Tokens vesting after the claim deadline could never be claimed
+    require!(
+        data_account.claim_deadline == 0 || data_account.latest_vesting_end_timestamp() <= data_account.claim_deadline,
+        VestingError::InvalidCohort
+    );
+    emit!(CohortUpdated {
+        data_account: cohort.data_account,
+        cohort_id: cohort.cohort_id,
+        cliff_months,
+        vesting_months,
+        acceleration_exempt,
+    });
+    Ok(())
+}
+
+// Terms of a new schedule, gathered by `initialize` and its variants for `init_schedule`.
+struct ScheduleParams {
+    // Committed total, in whole tokens
+    amount: u64,
+    decimals: u8,
+    start_timestamp: i64,
+    // Accept a start in the past (within the program's start bounds), e.g. for back-dated grants
+    retroactive: bool,
+    vesting_months: u8,
+    cliff_months: u8,
+    calendar_months: bool,
+    is_cancellable: bool,
+    cancellation_penalty_bps: u16,
+    guardian: Option<Pubkey>,
+    tge_unlock_bps: u16,
+    lockup_seconds: u32,
+    release_mode: ReleaseMode,
+    name: [u8; 32],
+    uri: [u8; 128],
+    // Derive the PDAs from the v2 seeds with this id (see `initialize_v2`) instead of the mint alone
+    schedule_id: Option<u64>,
+    funding: ScheduleFunding,
+}
+
+// How the escrow of a new schedule is funded.
+enum ScheduleFunding {
+    // The whole amount is deposited up front
+    Full,
+    // Only `first_installment` is deposited now, the rest with `fund_installment`
+    Installments { first_installment: u64 },
+    // Nothing is escrowed: the mint authority goes to the data account, which mints every claim
+    Minted,
+}
+
+// Accounts of the creating instruction that `init_schedule` uses, with their canonical bumps.
+struct NewScheduleAccounts<'a, 'info> {
+    sender: &'a Signer<'info>,
+    token_mint: &'a Account<'info, Mint>,
+    // The escrow and its bump; None for mint-vesting schedules, which have no escrow
+    escrow: Option<(&'a Account<'info, TokenAccount>, u8)>,
+    // None for mint-vesting schedules, which take no deposit
+    wallet_to_withdraw_from: Option<&'a Account<'info, TokenAccount>>,
+    token_program: &'a Program<'info, Token>,
+    system_program: &'a Program<'info, System>,
+    creator_registry: &'a mut Account<'info, CreatorRegistry>,
+    // Holds the bounds on `start_timestamp`
+    program_state: ProgramState,
+    bump: u8,
+    creator_registry_bump: u8,
+}
+
+// Validates `params`, fills in a freshly created `data_account`, funds its escrow from the sender (or hands it the
+// mint authority) and lists the schedule under its creator. `initialize` and every variant creating a schedule
+// call this, adding only what is specific to them.
+fn init_schedule<'info>(
+    data_account: &mut Account<'info, DataAccount>,
+    params: ScheduleParams,
+    accounts: NewScheduleAccounts<'_, 'info>,
+    now: i64,
+) -> Result<()> {
+    require!(params.amount > 0, VestingError::ZeroVestingAmount);
+    // All amounts are scaled by `decimals`, so it has to be the mint's
+    require!(params.decimals == accounts.token_mint.decimals, VestingError::DecimalMismatch);
+    // A start in the past is only accepted for retroactive grants
+    let (earliest_start, latest_start) = accounts.program_state.start_range(now);
+    require!(
+        (earliest_start..=latest_start).contains(&params.start_timestamp),
+        VestingError::StartTimestampOutOfRange
+    );
+    require!(params.retroactive || params.start_timestamp >= now, VestingError::StartTimestampInPast);
+    // The cancellation penalty is a share of the unvested remainder, and the TGE unlock one of each allocation
+    require!(params.cancellation_penalty_bps <= 10_000, VestingError::InvalidCancellationPenalty);
+    require!(params.tge_unlock_bps <= 10_000, VestingError::InvalidTgeUnlock);
+    require!(params.cliff_months <= params.vesting_months, VestingError::InvalidCliffMonths);
+    let deposit = match params.funding {
+        ScheduleFunding::Full => Some(params.amount),
+        ScheduleFunding::Installments { first_installment } => {
+            require!(
+                first_installment > 0 && first_installment <= params.amount,
+                VestingError::InvalidInstallment
+            );
+            Some(first_installment)
+        }
+        ScheduleFunding::Minted => None,
+    };
+    let deposit = match deposit {
+        Some(deposit) => {
+            let (escrow_wallet, _) = accounts.escrow.ok_or(VestingError::InvalidEscrowWallet)?;
+            let wallet_to_withdraw_from = accounts.wallet_to_withdraw_from.ok_or(VestingError::InvalidTokenAccount)?;
+            // Fail with a clear error, not in the middle of the transfer, if the funding account can't cover it
+            require_funding(wallet_to_withdraw_from, deposit, params.decimals)?;
+            Some((escrow_wallet, wallet_to_withdraw_from, deposit))
+        }
+        None => None,
+    };
+
+    data_account.percent_available = 0;
+    data_account.token_amount = params.amount;
+    data_account.decimals = params.decimals;
+    data_account.initializer = accounts.sender.key();
+    // Mint-vesting schedules have no escrow, which leaves `escrow_wallet` at the default key
+    if let Some((escrow_wallet, escrow_bump)) = accounts.escrow {
+        data_account.escrow_wallet = escrow_wallet.key();
+        data_account.escrow_bump = escrow_bump;
+    }
+    data_account.token_mint = accounts.token_mint.key();
+    data_account.start_timestamp = params.start_timestamp;
+    data_account.vesting_months = params.vesting_months;
+    data_account.cliff_months = params.cliff_months;
+    data_account.calendar_months = params.calendar_months;
+    data_account.is_cancellable = params.is_cancellable;
+    data_account.cancellation_penalty_bps = params.cancellation_penalty_bps;
+    data_account.guardian = params.guardian;
+    data_account.tge_unlock_bps = params.tge_unlock_bps;
+    data_account.lockup_seconds = params.lockup_seconds;
+    data_account.release_mode = params.release_mode;
+    data_account.name = params.name;
+    data_account.uri = params.uri;
+    // Remember who controls the mint, so `verify_mint_integrity` can tell when that changes
+    data_account.snapshot_mint(accounts.token_mint);
+    // Store the canonical bumps so later instructions never take them from the client
+    data_account.bump = accounts.bump;
+    data_account.version = DataAccount::VERSION;
+    match params.schedule_id {
+        Some(schedule_id) => {
+            data_account.seed_version = SEED_VERSION_V2;
+            data_account.schedule_id = schedule_id;
+        }
+        None => data_account.seed_version = SEED_VERSION_V1,
+    }
+    match params.funding {
+        ScheduleFunding::Full => {}
+        ScheduleFunding::Installments { first_installment } => {
+            data_account.installment_funding = true;
+            data_account.funded_amount = first_installment;
+        }
+        ScheduleFunding::Minted => {
+            data_account.mint_vesting = true;
+            // The mint authority is handed to the data account PDA below
+            data_account.mint_authority_snapshot = Some(data_account.key());
+        }
+    }
+
+    match deposit {
+        Some((escrow_wallet, wallet_to_withdraw_from, deposit)) => {
+            let transfer_instruction = TransferChecked {
+                from: wallet_to_withdraw_from.to_account_info(),
+                mint: accounts.token_mint.to_account_info(),
+                to: escrow_wallet.to_account_info(),
+                authority: accounts.sender.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(accounts.token_program.to_account_info(), transfer_instruction);
+            token::transfer_checked(cpi_ctx, raw_amount(deposit, params.decimals)?, params.decimals)?;
+        }
+        None => {
+            let set_authority_instruction = SetAuthority {
+                current_authority: accounts.sender.to_account_info(),
+                account_or_mint: accounts.token_mint.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(accounts.token_program.to_account_info(), set_authority_instruction);
+            token::set_authority(cpi_ctx, AuthorityType::MintTokens, Some(data_account.key()))?;
+        }
+    }
+
+    emit!(ScheduleMetadataUpdated {
+        data_account: data_account.key(),
+        name: data_account.name,
+        uri: data_account.uri,
+    });
+    if let ScheduleFunding::Installments { first_installment } = params.funding {
+        emit!(InstallmentFunded {
+            data_account: data_account.key(),
+            amount: first_installment,
+            funded_amount: data_account.funded_amount,
+            committed_amount: data_account.token_amount,
+        });
+    }
+
+    // List the new schedule under its creator
+    let entry = CreatorRegistryEntry {
+        data_account: data_account.key(),
+        mint: data_account.token_mint,
+        created_at: now,
+    };
+    append_creator_registry(
+        accounts.creator_registry,
+        accounts.creator_registry_bump,
+        accounts.sender,
+        accounts.system_program,
+        entry,
+    )
+}
+
+// Checks that the funding account of a new schedule holds the `amount` whole tokens it deposits, before anything is
+// written, rather than letting the transfer fail with a generic token program error.
+fn require_funding(wallet_to_withdraw_from: &TokenAccount, amount: u64, decimals: u8) -> Result<()> {
+    require!(
+        wallet_to_withdraw_from.amount >= raw_amount(amount, decimals)?,
+        VestingError::InsufficientFunding
+    );
+    Ok(())
+}
+
+// Grows a program state created by an older version to `ProgramState::LEN`, the sender topping up its rent, and
+// deserializes it. For the upgrade authority's instructions that write fields added since; they serialize the
+// state back into `state_info` themselves.
+fn grow_program_state<'info>(
+    state_info: &AccountInfo<'info>,
+    sender: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<ProgramState> {
+    // The program state is created by `set_program_halted`
+    require_keys_eq!(*state_info.owner, crate::ID, anchor_lang::error::ErrorCode::AccountNotInitialized);
+    if state_info.data_len() < ProgramState::LEN {
+        let rent_due = Rent::get()?
+            .minimum_balance(ProgramState::LEN)
+            .saturating_sub(state_info.lamports());
+        if rent_due > 0 {
+            let transfer_instruction = system_instruction::transfer(&sender.key(), &state_info.key(), rent_due);
+            anchor_lang::solana_program::program::invoke(
+                &transfer_instruction,
+                &[sender.to_account_info(), state_info.clone(), system_program.to_account_info()],
+            )?;
+        }
+        state_info.resize(ProgramState::LEN)?;
+    }
+    // Deserializing checks the discriminator
+    ProgramState::try_deserialize(&mut &state_info.data.borrow()[..])
+}
+
+fn raw_amount(tokens: u64, decimals: u8) -> Result<u64> {
+    Ok(math::to_raw_amount(tokens, decimals).ok_or(VestingError::MathOverflow)?)
+}
+
+// Accounting invariants, asserted after the instructions that move tokens or change allocations when the program is
+// built with the `strict-invariants` feature, so devnet and staging deployments fail on any drift before mainnet:
+// - `claimed_total` never exceeds `token_amount`
+// - a beneficiary never claims more than its entitlement
+// - the escrow holds every token still owed (not checked for mint-vesting and payout-mint schedules)
+#[cfg(feature = "strict-invariants")]
+fn check_invariants(
+    data_account: &DataAccount,
+    beneficiary: Option<&BeneficiaryAccount>,
+    escrow_wallet: Option<&AccountInfo>,
+) -> Result<()> {
+    require!(data_account.claimed_total <= data_account.token_amount, VestingError::InvariantViolated);
+    if let Some(beneficiary) = beneficiary {
+        require!(
+            beneficiary.claimed_tokens <= beneficiary.entitlement(data_account),
+            VestingError::InvariantViolated
+        );
+    }
+    if let Some(escrow_wallet) = escrow_wallet {
+        if !data_account.mint_vesting && data_account.payout_mint.is_none() {
+            // Staked tokens are delegated, not held, by the escrow; parked claims are still held
+            let outstanding = data_account.funded_tokens().saturating_sub(
+                data_account.claimed_total + data_account.unclaimed_withdrawn + data_account.staked_amount,
+            ) + data_account.parked_total;
+            // Read from the account data: deserialized accounts don't see transfers made earlier in the instruction
+            require!(
+                token::accessor::amount(escrow_wallet)? >= raw_amount(outstanding, data_account.decimals)?,
+                VestingError::InvariantViolated
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "strict-invariants"))]
+#[inline(always)]
+fn check_invariants(_: &DataAccount, _: Option<&BeneficiaryAccount>, _: Option<&AccountInfo>) -> Result<()> {
+    Ok(())
+}
+
+// Accounts frozen by the mint's freeze authority reject every transfer, so claims fail up front with a dedicated
+// error instead of the token program's; `redirect_frozen_claim` can park the claim until they are thawed.
+fn require_thawed(escrow_wallet: &TokenAccount, recipient: &TokenAccount) -> Result<()> {
+    require!(!escrow_wallet.is_frozen(), VestingError::EscrowFrozen);
+    require!(!recipient.is_frozen(), VestingError::RecipientFrozen);
+    Ok(())
+}
+
+// Rejects token accounts that someone besides their owner can still empty or close: escrows the program pays
+// from and accounts it pays into.
+fn require_plain_token_account(account: &TokenAccount) -> Result<()> {
+    require!(account.delegate.is_none(), VestingError::TokenAccountHasDelegate);
+    require!(account.close_authority.is_none(), VestingError::TokenAccountHasCloseAuthority);
+    Ok(())
+}
+
+// Pays `amount_raw` primary tokens out of a schedule, signed by the data account PDA: transferred from the
+// escrow with `transfer_checked`, or minted with `mint_to` on mint-vesting schedules, which need no escrow.
+fn pay_out<'info>(
+    data_account: &Account<'info, DataAccount>,
+    token_program: &Program<'info, Token>,
+    token_mint: &Account<'info, Mint>,
+    escrow_wallet: Option<&Account<'info, TokenAccount>>,
+    to: AccountInfo<'info>,
+    amount_raw: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if data_account.mint_vesting {
+        let mint_instruction = MintTo {
+            mint: token_mint.to_account_info(),
+            to,
+            authority: data_account.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), mint_instruction, signer_seeds);
+        return token::mint_to(cpi_ctx, amount_raw);
+    }
+    let escrow_wallet = escrow_wallet.ok_or(VestingError::InvalidEscrowWallet)?;
+    let transfer_instruction = TransferChecked {
+        from: escrow_wallet.to_account_info(),
+        mint: token_mint.to_account_info(),
+        to,
+        authority: data_account.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), transfer_instruction, signer_seeds);
+    token::transfer_checked(cpi_ctx, amount_raw, data_account.decimals)
+}
+
+// Protocol fee on a claim of `amount_raw` tokens, and the fee recipient's token account it is paid into. Claims are
+// free, and need no fee accounts, while the program has no `Config`; the account is only returned for a non-zero fee.
+fn protocol_fee<'info>(
+    config: Option<&Account<'info, Config>>,
+    fee_token_account: Option<&Account<'info, TokenAccount>>,
+    amount_raw: u64,
+) -> Result<(u64, Option<AccountInfo<'info>>)> {
+    let fee_raw = match config {
+        Some(config) => config.fee_for(amount_raw)?,
+        None => 0,
+    };
+    if fee_raw == 0 {
+        return Ok((0, None));
+    }
+    let fee_token_account = fee_token_account.ok_or(VestingError::InvalidFeeAccount)?;
+    Ok((fee_raw, Some(fee_token_account.to_account_info())))
+}
+
+// Fee recipient reported in `TokensClaimed`; the default key while the program has no `Config`.
+fn fee_recipient(config: Option<&Account<Config>>) -> Pubkey {
+    config.map_or_else(Pubkey::default, |config| config.fee_recipient)
+}
+
+// Pays a claim in the primary mint: every `(destination, amount_raw)` of `payouts`, e.g. the claimant's share and the
+// protocol fee, through `pay_out`. Zero amounts are skipped. Every claim path pays through here.
+fn pay_claim<'info>(
+    data_account: &Account<'info, DataAccount>,
+    token_program: &Program<'info, Token>,
+    token_mint: &Account<'info, Mint>,
+    escrow_wallet: Option<&Account<'info, TokenAccount>>,
+    payouts: impl IntoIterator<Item = (AccountInfo<'info>, u64)>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    for (to, amount_raw) in payouts {
+        if amount_raw > 0 {
+            pay_out(data_account, token_program, token_mint, escrow_wallet, to, amount_raw, signer_seeds)?;
+        }
+    }
+    Ok(())
+}
+
+// Pays `amount_raw` (raw units of the vesting mint) out of the payout escrow, converted to the payout mint
+// at the ratio stored by `set_payout_mint`.
+fn pay_out_in_payout_mint<'info>(
+    data_account: &Account<'info, DataAccount>,
+    token_program: &Program<'info, Token>,
+    payout_mint: &Account<'info, Mint>,
+    payout_escrow: &Account<'info, TokenAccount>,
+    to: AccountInfo<'info>,
+    amount_raw: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let payout_raw = data_account.to_payout_amount(amount_raw)?;
+    let transfer_instruction = TransferChecked {
+        from: payout_escrow.to_account_info(),
+        mint: payout_mint.to_account_info(),
+        to,
+        authority: data_account.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), transfer_instruction, signer_seeds);
+    token::transfer_checked(cpi_ctx, payout_raw, payout_mint.decimals)
+}
+
+// Appends a schedule to its creator's registry, growing the account by one entry.
+// The creator pays the additional rent. Once the registry is full, the oldest entry is dropped instead.
+fn append_creator_registry<'info>(
+    registry: &mut Account<'info, CreatorRegistry>,
+    bump: u8,
+    creator: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    entry: CreatorRegistryEntry,
+) -> Result<()> {
+    registry.creator = creator.key();
+    registry.bump = bump;
+
+    if registry.entries.len() >= MAX_CREATOR_REGISTRY_ENTRIES {
+        registry.entries.remove(0);
+        registry.entries.push(entry);
+        return Ok(());
+    }
+
+    let registry_info = registry.to_account_info();
+    let new_len = CreatorRegistry::space(registry.entries.len() + 1);
+    let rent_due = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(registry_info.lamports());
+    if rent_due > 0 {
+        anchor_lang::solana_program::program::invoke(
+            &system_instruction::transfer(&creator.key(), &registry_info.key(), rent_due),
+            &[
+                creator.to_account_info(),
+                registry_info.clone(),
+                system_program.to_account_info(),
+            ],
+        )?;
+    }
+    registry_info.resize(new_len)?;
+
+    registry.entries.push(entry);
+    Ok(())
+}
+
+// Invokes the whitelisted staking program with `remaining_accounts`, signing as the data account PDA
+// (the escrow authority) wherever it appears in the account list.
+fn invoke_staking_program<'info>(
+    staking_program: &AccountInfo<'info>,
+    data_account: &Account<'info, DataAccount>,
+    remaining_accounts: &[AccountInfo<'info>],
+    instruction_data: Vec<u8>,
+) -> Result<()> {
+    let data_account_key = data_account.key();
+    let accounts = remaining_accounts
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: account.key(),
+            is_signer: account.is_signer || account.key() == data_account_key,
+            is_writable: account.is_writable,
+        })
+        .collect();
+    let instruction = anchor_lang::solana_program::instruction::Instruction {
+        program_id: staking_program.key(),
+        accounts,
+        data: instruction_data,
+    };
+
+    let mut account_infos = remaining_accounts.to_vec();
+    account_infos.push(staking_program.clone());
+    let schedule_seed = data_account.schedule_seed();
+    let seeds = &[DATA_ACCOUNT_SEED, data_account.token_mint.as_ref(), &schedule_seed, &[data_account.bump]];
+    invoke_signed(&instruction, &account_infos, &[&seeds[..]])?;
+    Ok(())
+}
+
+// Invokes the whitelisted swap program with `remaining_accounts`, signing as the swap authority PDA of
+// `claim_and_swap`'s temporary account wherever it appears in the account list.
+fn invoke_swap_program<'info>(
+    swap_program: &AccountInfo<'info>,
+    swap_authority: &AccountInfo<'info>,
+    swap_authority_seeds: &[&[u8]],
+    remaining_accounts: &[AccountInfo<'info>],
+    instruction_data: Vec<u8>,
+) -> Result<()> {
+    let swap_authority_key = swap_authority.key();
+    let accounts = remaining_accounts
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: account.key(),
+            is_signer: account.is_signer || account.key() == swap_authority_key,
+            is_writable: account.is_writable,
+        })
+        .collect();
+    let instruction = anchor_lang::solana_program::instruction::Instruction {
+        program_id: swap_program.key(),
+        accounts,
+        data: instruction_data,
+    };
+
+    let mut account_infos = remaining_accounts.to_vec();
+    account_infos.push(swap_program.clone());
+    invoke_signed(&instruction, &account_infos, &[swap_authority_seeds])?;
+    Ok(())
+}
+
+// Asks the schedule's identity gate whether `wallet` may claim.
+// The gate program's `verify` instruction (Anchor discriminator of `global:verify`) gets `[gate_config, wallet]`,
+// both read-only, and has to set the return data to `[1]`; anything else fails with `GateCheckFailed`.
+fn verify_claim_gate<'info>(
+    gate_program: &AccountInfo<'info>,
+    gate_config: &AccountInfo<'info>,
+    wallet: &AccountInfo<'info>,
+) -> Result<()> {
+    let instruction = anchor_lang::solana_program::instruction::Instruction {
+        program_id: gate_program.key(),
+        accounts: vec![
+            AccountMeta::new_readonly(gate_config.key(), false),
+            AccountMeta::new_readonly(wallet.key(), false),
+        ],
+        data: anchor_lang::solana_program::hash::hash(b"global:verify").to_bytes()[..8].to_vec(),
+    };
+    anchor_lang::solana_program::program::invoke(
+        &instruction,
+        &[gate_config.clone(), wallet.clone(), gate_program.clone()],
+    )?;
+
+    let (program_id, verdict) = get_return_data().ok_or(VestingError::GateCheckFailed)?;
+    require_keys_eq!(program_id, gate_program.key(), VestingError::GateCheckFailed);
+    require!(verdict == [1], VestingError::GateCheckFailed);
+    Ok(())
+}
+
+// Notifies the schedule's claim hook of a claim.
+// The hook program's `notify` instruction (Anchor discriminator of `global:notify`) gets `[data_account]`, read-only
+// and signed by the data account PDA so the hook can tell real claims from forged calls, and the Borsh-encoded
+// arguments `(beneficiary: Pubkey, amount: u64, timestamp: i64)`; `amount` is the claimed amount in raw token units,
+// before the fee. The data account's changes from this claim are not visible to the hook yet.
+// A failing hook fails the claim.
+fn notify_claim_hook<'info>(
+    hook_program: &AccountInfo<'info>,
+    data_account: &AccountInfo<'info>,
+    beneficiary: Pubkey,
+    amount: u64,
+    timestamp: i64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let mut data = anchor_lang::solana_program::hash::hash(b"global:notify").to_bytes()[..8].to_vec();
+    (beneficiary, amount, timestamp).serialize(&mut data)?;
+    let instruction = anchor_lang::solana_program::instruction::Instruction {
+        program_id: hook_program.key(),
+        accounts: vec![AccountMeta::new_readonly(data_account.key(), true)],
+        data,
+    };
+    invoke_signed(&instruction, &[data_account.clone(), hook_program.clone()], signer_seeds)?;
+    Ok(())
+}
+
+// Marks the schedule as fully time-vested from now on.
+// Shared by `accelerate_vesting` and multisig-approved accelerations in `execute_release`.
+fn trigger_acceleration(data_account: &mut Account<DataAccount>, now: i64) -> Result<()> {
+    require!(!data_account.cancelled, VestingError::VestingCancelled);
+    require!(data_account.accelerated_at == 0, VestingError::AlreadyAccelerated);
+
+    data_account.accelerated_at = now;
+    emit!(AccelerationTriggered {
+        data_account: data_account.key(),
+        accelerated_at: data_account.accelerated_at,
+    });
+    Ok(())
+}
+
+// Starts the grace period of a cancellation; `execute_cancel` can only run once it has passed.
+// Shared by `schedule_cancel` and multisig-approved cancellations in `execute_release`,
+// which emit the returned event through `emit_cpi!`.
+fn schedule_cancellation(data_account: &mut Account<DataAccount>, now: i64) -> Result<CancelScheduled> {
+    let now = data_account.advance_clock(now)?;
+    require!(data_account.is_cancellable, VestingError::VestingNotCancellable);
+    require!(data_account.cancel_effective_at == 0, VestingError::CancelAlreadyScheduled);
+    // Ensure vesting is still active (i.e., has not yet fully completed)
+    require!(now < data_account.vesting_end_timestamp(), VestingError::VestingAlreadyCompleted);
+
+    data_account.cancel_effective_at = now + data_account.cancel_grace_period();
+    Ok(CancelScheduled {
+        data_account: data_account.key(),
+        cancel_effective_at: data_account.cancel_effective_at,
+    })
+}
+
+/// Accounts required to initialize the vesting contract.
+///
+/// This instruction creates and initializes two PDA accounts:
+/// 1. `data_account` - A PDA that stores metadata/configuration for the vesting logic.
+/// 2. `escrow_wallet` - A PDA token account that will hold the escrowed SPL tokens to be vested.
+/// The main data account storing vesting configuration.
+    /// 
+    /// Seeds: ["data_account", token_mint.key()]
+    /// Bump: Auto-calculated
+    /// Space: Enough to store the serialized `DataAccount` structure:
+    /// - 8   (discriminator)
+    /// - 1   (percent_available: u8)
+    /// - 8   (token_amount: u64)
+    /// - 32  (initializer: Pubkey)
+    /// - 32  (escrow_wallet: Pubkey)
+    /// - 32  (token_mint: Pubkey)
+    /// - 1   (decimals: u8)
+    /// - 8   (start_timestamp: i64)
+    /// - 1   (vesting_months: u8)
+    /// - 8   (claimed_total: u64)
+    /// - 8   (unclaimed_withdrawn: u64)
+    /// - 8   (total_allocated: u64)
+    /// - 4 + 32 * MAX_APPROVERS (approvers: Vec<Pubkey>)
+    /// - 1   (approval_threshold: u8)
+    /// - 8   (cancel_effective_at: i64)
+    /// - 1   (is_cancellable: bool)
+    /// - 1   (bump: u8)
+    /// - 1   (escrow_bump: u8)
+    /// - 1   (calendar_months: bool)
+    /// - 1   (cliff_months: u8)
+    /// - 4   (amendment_count: u32)
+    /// - 2   (cancellation_penalty_bps: u16)
+    /// - 1   (cancelled: bool)
+    /// - 2   (final_vested_bps: u16)
+    /// - 33  (claim_approver: Option<Pubkey>)
+    /// - 8   (accelerated_at: i64)
+    /// - 1   (record_claims: bool)
+    /// - 33  (guardian: Option<Pubkey>)
+    /// - 1   (frozen: bool)
+    /// - 2   (tge_unlock_bps: u16)
+    /// - 4   (lockup_seconds: u32)
+    /// - 1   (release_mode: ReleaseMode)
+    /// - 8   (staked_amount: u64)
+    /// - 4   (min_claim_interval_seconds: u32)
+    /// - 8   (max_claim_per_period: u64)
+    /// - 33  (forfeiture_pool: Option<Pubkey>)
+    /// - 2   (forfeiture_bps: u16)
+    /// - 1   (leg_count: u8)
+    /// - 1   (version: u8)
+    /// - 4   (batch_cursor: u32)
+    /// - 1   (blacklist_enabled: bool)
+    /// - 8   (claim_deadline: i64)
+    /// - 1   (mint_vesting: bool)
+    /// - 8   (last_processed_timestamp: i64)
+    /// - 4   (clock_tolerance_seconds: u32)
+    /// - 33  (gate_program: Option<Pubkey>)
+    /// - 32  (gate_config: Pubkey)
+    /// - 32  (name: [u8; 32])
+    /// - 128 (uri: [u8; 128])
+    /// - 1   (non_transferable: bool)
+    /// - 1   (shares_mode: bool)
+    /// - 8   (total_shares: u64)
+    /// - 33  (operator: Option<Pubkey>)
+    /// - 33  (treasurer: Option<Pubkey>)
+    /// - 8   (fee_pool_lamports: u64)
+    /// - 8   (fee_pool_max_per_claim: u64)
+    /// - 4   (interval_seconds: u32)
+    /// - 2   (total_intervals: u16)
+    /// - 33  (on_claim_hook: Option<Pubkey>)
+    /// - 8   (rent_reserve_lamports: u64)
+    /// - 33  (payout_mint: Option<Pubkey>)
+    /// - 32  (payout_escrow: Pubkey)
+    /// - 8   (payout_ratio_numerator: u64)
+    /// - 8   (payout_ratio_denominator: u64)
+    /// - 32  (schedule_hash: [u8; 32])
+    /// - 32  (beneficiaries_root: [u8; 32])
+    /// - 8   (schedule_committed_at: i64)
+    /// - 4   (max_voluntary_extension_seconds: u32)
+    /// - 2   (withholding_bps: u16)
+    /// - 33  (withholding_account: Option<Pubkey>)
+    /// - 8   (withheld_total: u64)
+    /// - 1   (is_frozen: bool)
+    /// - 33  (release_veto_authority: Option<Pubkey>)
+    /// - 4   (next_release_id: u32)
+    /// - 13 * MAX_ANNOUNCED_RELEASES (announced_releases: [AnnouncedRelease; MAX_ANNOUNCED_RELEASES])
+    /// - 2   (bonus_rate_bps_per_year: u16)
+    /// - 32  (bonus_escrow: Pubkey)
+    /// - 8   (bonus_paid_total: u64)
+    /// - 4   (beneficiary_count: u32)
+    /// - 33  (cloned_from: Option<Pubkey>)
+    /// - 1   (require_acceptance: bool)
+    /// - 8   (parked_total: u64)
+    /// - 33  (mint_authority_snapshot: Option<Pubkey>)
+    /// - 33  (freeze_authority_snapshot: Option<Pubkey>)
+    /// - 1   (mint_compromised: bool)
+    /// - 4   (beneficiary_index_len: u32)
+    /// - 1   (seed_version: u8)
+    /// - 8   (schedule_id: u64)
+    /// - 4   (max_beneficiaries: u32)
+    /// - 1   (completed: bool)
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = sender,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref()],
+        bump,
+        space = DataAccount::LEN
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+/// The escrow wallet PDA that holds SPL tokens for vesting.
+    ///
+    /// Seeds: ["escrow_wallet", token_mint.key()]
+    /// Authority: The `data_account` PDA
+    /// Token Mint: Must match the `token_mint` passed into the instruction
+    
+    #[account(
+        init,
+        payer = sender,
+        seeds = [ESCROW_WALLET_SEED, token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = data_account
+    )]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    // The sender's token account funding the deposit.
+    #[account(
+        mut,
+        constraint = wallet_to_withdraw_from.mint == token_mint.key() @ VestingError::FundingMintMismatch,
+        constraint = wallet_to_withdraw_from.owner == sender.key() @ VestingError::InvalidTokenAccount,
+    )]
+    pub wallet_to_withdraw_from: Account<'info, TokenAccount>,
+
+    // The sender's list of schedules; created with their first schedule and grown by one entry per schedule.
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = CreatorRegistry::space(0),
+        seeds = [CREATOR_REGISTRY_SEED, sender.key().as_ref()],
+        bump,
+    )]
+    pub creator_registry: Account<'info, CreatorRegistry>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(schedule_id: u64)]
+pub struct InitializeV2<'info> {
+    #[account(
+        init,
+        payer = sender,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &schedule_id.to_le_bytes()],
+        bump,
+        space = DataAccount::LEN
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        init,
+        payer = sender,
+        seeds = [ESCROW_WALLET_SEED, token_mint.key().as_ref(), &schedule_id.to_le_bytes()],
+        bump,
+        token::mint = token_mint,
+        token::authority = data_account
+    )]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    // The sender's token account funding the deposit.
+    #[account(
+        mut,
+        constraint = wallet_to_withdraw_from.mint == token_mint.key() @ VestingError::FundingMintMismatch,
+        constraint = wallet_to_withdraw_from.owner == sender.key() @ VestingError::InvalidTokenAccount,
+    )]
+    pub wallet_to_withdraw_from: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = CreatorRegistry::space(0),
+        seeds = [CREATOR_REGISTRY_SEED, sender.key().as_ref()],
+        bump,
+    )]
+    pub creator_registry: Account<'info, CreatorRegistry>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Claim<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        // Mint-vesting schedules mint every claim and may have no escrow
+        constraint = escrow_wallet.as_ref().map_or(data_account.mint_vesting, |escrow_wallet| {
+            escrow_wallet.key() == data_account.escrow_wallet
+        }) @ VestingError::InvalidEscrowWallet,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), beneficiary_wallet.key().as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    #[account(
+        mut,
+        constraint = escrow_wallet.delegate.is_none() @ VestingError::TokenAccountHasDelegate,
+        constraint = escrow_wallet.close_authority.is_none() @ VestingError::TokenAccountHasCloseAuthority,
+    )]
+    pub escrow_wallet: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Only used as PDA seed and ATA authority; the seeds tie it to `beneficiary_account`.
+    pub beneficiary_wallet: UncheckedAccount<'info>,
+
+    // The beneficiary or their claim delegate; pays for the ATA if it has to be created.
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    // Writable because mint-vesting schedules mint claims.
+    #[account(mut)]
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = sender,
+        associated_token::mint = token_mint,
+        associated_token::authority = beneficiary_wallet,
+    )]
+    pub wallet_to_deposit_to: Account<'info, TokenAccount>,
+
+    // Required when the beneficiary has set a payout account; receives the tokens instead of the ATA.
+    #[account(mut)]
+    pub payout_token_account: Option<Account<'info, TokenAccount>>,
+
+    // Absent until `initialize_config` has run; claims are free until then.
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Option<Account<'info, Config>>,
+
+    /// CHECK: Only used as the fee ATA authority; must match `config.fee_recipient`.
+    #[account(
+        constraint = config.as_ref().is_some_and(|config| config.fee_recipient == fee_recipient.key())
+            @ VestingError::InvalidFeeAccount,
+    )]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+
+    // Receives the protocol fee; created by the claimer if it doesn't exist yet.
+    #[account(
+        init_if_needed,
+        payer = sender,
+        associated_token::mint = token_mint,
+        associated_token::authority = fee_recipient,
+    )]
+    pub fee_token_account: Option<Account<'info, TokenAccount>>,
+
+    // Required when the schedule has a `claim_approver`; must be that key.
+    pub claim_approver: Option<Signer<'info>>,
+
+    // Required by `claim_with_memo`.
+    pub memo_program: Option<Program<'info, Memo>>,
+
+    // Required for NFT-backed positions: the sender's token account holding the position NFT.
+    pub position_token_account: Option<Account<'info, TokenAccount>>,
+
+    // Required when the schedule has `record_claims` set; created at the beneficiary's next claim index.
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + 8 + 8 + 8 + 1,
+        seeds = [
+            CLAIM_RECORD_SEED,
+            beneficiary_account.key().as_ref(),
+            &beneficiary_account.claim_count.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub claim_record: Option<Account<'info, ClaimRecord>>,
+
+    // Required when the beneficiary has a secondary recipient: its token account for this mint.
+    #[account(mut)]
+    pub secondary_token_account: Option<Account<'info, TokenAccount>>,
+
+    // Required once the schedule has a blacklist.
+    #[account(
+        seeds = [BLACKLIST_SEED, data_account.key().as_ref()],
+        bump = blacklist.bump,
+    )]
+    pub blacklist: Option<Account<'info, Blacklist>>,
+
+    /// CHECK: Required once the schedule has an identity gate; must match `data_account.gate_program`.
+    pub gate_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Required once the schedule has an identity gate; must match `data_account.gate_config`.
+    pub gate_config: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Required once the schedule has a claim hook; must match `data_account.on_claim_hook`.
+    pub claim_hook_program: Option<UncheckedAccount<'info>>,
+
+    // Required once the schedule pays out in a payout mint: the mint and `data_account.payout_escrow`.
+    pub payout_mint: Option<Account<'info, Mint>>,
+    #[account(mut)]
+    pub payout_escrow: Option<Account<'info, TokenAccount>>,
+
+    // Required once the schedule pays out in a payout mint: the recipient's token account for it,
+    // and the fee recipient's when a fee is charged.
+    #[account(mut)]
+    pub payout_destination: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub payout_fee_token_account: Option<Account<'info, TokenAccount>>,
+
+    // Required once the schedule withholds tax: `data_account.withholding_account`.
+    #[account(mut)]
+    pub withholding_token_account: Option<Account<'info, TokenAccount>>,
+
+    // Required while the schedule pays a bonus: `data_account.bonus_escrow`.
+    #[account(mut)]
+    pub bonus_escrow: Option<Account<'info, TokenAccount>>,
+
+    // Required when the beneficiary is in a cohort: its `Cohort` account.
+    #[account(
+        seeds = [COHORT_SEED, data_account.key().as_ref(), &cohort.cohort_id.to_le_bytes()],
+        bump = cohort.bump,
+    )]
+    pub cohort: Option<Account<'info, Cohort>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Release<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.can_operate(&sender.key()) @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetReleaseVetoAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRemovalDispute<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AnnounceRelease<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.can_operate(&sender.key()) @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteAnnouncedRelease<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VetoRelease<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.release_veto_authority == Some(sender.key()) @ VestingError::InvalidSender,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReleaseMany<'info> {
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+
+    // (data_account, token_mint) pairs are passed in remaining_accounts[]
+}
+
+#[derive(Accounts)]
+pub struct SetReleaseSchedule<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = ReleaseCalendar::LEN,
+        seeds = [RELEASE_CALENDAR_SEED, data_account.key().as_ref()],
+        bump,
+    )]
+    pub release_calendar: Account<'info, ReleaseCalendar>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FinalizeExpired<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    // Anyone; receives the bounty
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CrankRelease<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        seeds = [RELEASE_CALENDAR_SEED, data_account.key().as_ref()],
+        bump = release_calendar.bump,
+    )]
+    pub release_calendar: Account<'info, ReleaseCalendar>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ModifyBeneficiaries<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddBeneficiaries<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.can_operate(&sender.key()) @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+
+    // BeneficiaryAccount and BeneficiaryIndex PDAs will be passed dynamically via remaining_accounts
+}
+
+#[derive(Accounts)]
+#[instruction(new_beneficiary: NewBeneficiary)]
+pub struct AddBeneficiary<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.can_operate(&sender.key()) @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    // Fails if `new_beneficiary.key` is already a beneficiary of this schedule.
+    #[account(
+        init,
+        payer = sender,
+        space = std::mem::size_of::<BeneficiaryAccount>() + 8,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), new_beneficiary.key.as_ref()],
+        bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    // Next entry of the beneficiary index.
+    #[account(
+        init,
+        payer = sender,
+        space = BeneficiaryIndex::LEN,
+        seeds = [BENEFICIARY_INDEX_SEED, data_account.key().as_ref(), &data_account.beneficiary_index_len.to_le_bytes()],
+        bump,
+    )]
+    pub beneficiary_index: Account<'info, BeneficiaryIndex>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawUnclaimed<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key()
+            || data_account.treasurer == Some(sender.key()) @ VestingError::InvalidSender,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = recipient.mint == token_mint.key() @ VestingError::InvalidTokenAccount,
+        constraint = recipient.owner == sender.key() @ VestingError::InvalidRecipient,
+        constraint = recipient.delegate.is_none() @ VestingError::TokenAccountHasDelegate,
+        constraint = recipient.close_authority.is_none() @ VestingError::TokenAccountHasCloseAuthority,
+    )]
+    pub recipient: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+// The offsets of the leading fields are published in `vesting_common::schema` for indexers; only append fields.
+#[account]
+pub struct DataAccount {
+    pub percent_available: u8,
+    pub token_amount: u64,
+    pub initializer: Pubkey,
+    pub escrow_wallet: Pubkey,
+    pub token_mint: Pubkey,
+    pub decimals: u8,
+    pub start_timestamp: i64,
+    pub vesting_months: u8,
+    pub claimed_total: u64,
+    pub unclaimed_withdrawn: u64,
+    pub total_allocated: u64,
+    pub approvers: Vec<Pubkey>,
+    pub approval_threshold: u8,
+    // Timestamp after which a scheduled cancellation can be executed (0 if none is scheduled)
+    pub cancel_effective_at: i64,
+    // Revocable grant (true) or irrevocable lockup (false)
+    pub is_cancellable: bool,
+    // Canonical bumps of the data account and escrow wallet PDAs
+    pub bump: u8,
+    pub escrow_bump: u8,
+    // Count real calendar months instead of 30-day months
+    pub calendar_months: bool,
+    // Months after `start_timestamp` before anything vests
+    pub cliff_months: u8,
+    // Number of times the schedule has been amended via `amend_schedule`
+    pub amendment_count: u32,
+    // Share of the unvested remainder, in basis points, paid to beneficiaries on cancellation
+    pub cancellation_penalty_bps: u16,
+    // Set by `execute_cancel`; from then on every allocation vests at `final_vested_bps`
+    pub cancelled: bool,
+    pub final_vested_bps: u16,
+    // Compliance authority that must co-sign every claim, if set
+    pub claim_approver: Option<Pubkey>,
+    // Timestamp from which the schedule counts as fully time-vested (0 if never accelerated)
+    pub accelerated_at: i64,
+    // Write a `ClaimRecord` for every claim
+    pub record_claims: bool,
+    // Key allowed to call `emergency_freeze`
+    pub guardian: Option<Pubkey>,
+    // Set by `emergency_freeze`; blocks claims, cancellation and withdrawals until `unfreeze`
+    pub frozen: bool,
+    // Share of each allocation unlocked at `start_timestamp` (the TGE)
+    pub tge_unlock_bps: u16,
+    // Lockup after `start_timestamp` before linear vesting (and the cliff) starts counting
+    pub lockup_seconds: u32,
+    pub release_mode: ReleaseMode,
+    // Whole tokens of the escrow currently delegated to the whitelisted staking program
+    pub staked_amount: u64,
+    // Minimum time between two claims of the same beneficiary (0 = no cooldown)
+    pub min_claim_interval_seconds: u32,
+    // Most whole tokens a beneficiary can claim per interval (0 = no cap)
+    pub max_claim_per_period: u64,
+    // Token account receiving `forfeiture_bps` of a terminated beneficiary's unvested tokens
+    pub forfeiture_pool: Option<Pubkey>,
+    pub forfeiture_bps: u16,
+    // Number of `VestingLeg`s attached via `add_vesting_leg`
+    pub leg_count: u8,
+    // Layout version (`DataAccount::VERSION`); older accounts are upgraded by `migrate_data_account`
+    pub version: u8,
+    // Entries of the open beneficiary batch added so far; reset by `finalize_beneficiary_batch`
+    pub batch_cursor: u32,
+    // Set by the first `add_to_blacklist`; claims must then pass the schedule's `Blacklist`
+    pub blacklist_enabled: bool,
+    // Unclaimed entitlements are forfeited after this time (0 = no deadline)
+    pub claim_deadline: i64,
+    // Created by `initialize_mint_vesting`: claims are minted (up to `token_amount`) instead of paid from escrow
+    pub mint_vesting: bool,
+    // Latest clock time a time-dependent instruction ran at; the clock is never taken as earlier than this
+    pub last_processed_timestamp: i64,
+    // How far behind `last_processed_timestamp` the clock may be before instructions fail
+    pub clock_tolerance_seconds: u32,
+    // Program whose `verify` instruction must approve every claimer (None = no identity gate)
+    pub gate_program: Option<Pubkey>,
+    // Account passed to `gate_program`, e.g. the gatekeeper network of a Civic-style pass
+    pub gate_config: Pubkey,
+    // Human-readable label, UTF-8 padded with zeros (e.g. "Team 2024")
+    pub name: [u8; 32],
+    // Off-chain metadata URI, UTF-8 padded with zeros
+    pub uri: [u8; 128],
+    // Positions can only be split with the initializer's co-signature
+    pub non_transferable: bool,
+    // Beneficiaries hold shares of the whole pool instead of fixed token allocations
+    pub shares_mode: bool,
+    // Sum of all beneficiaries' shares in shares mode
+    pub total_shares: u64,
+    // Can add and remove beneficiaries and release, alongside the initializer (the owner)
+    pub operator: Option<Pubkey>,
+    // Can withdraw unclaimed tokens once the claim window has expired
+    pub treasurer: Option<Pubkey>,
+    // Lamports held by the data account, above its rent, to refund the costs of beneficiaries' first claims
+    pub fee_pool_lamports: u64,
+    // Most lamports refunded for one claim
+    pub fee_pool_max_per_claim: u64,
+    // Length of one unlock interval; when non-zero, the schedule vests in `total_intervals` equal tranches
+    // instead of monthly
+    pub interval_seconds: u32,
+    pub total_intervals: u16,
+    // Program whose `notify` instruction `claim` invokes after every payout (None = no hook)
+    pub on_claim_hook: Option<Pubkey>,
+    // Lamports held by the data account, above its rent and the fee pool, to pay the rent of accounts the program
+    // creates or grows (see `draw_rent_reserve`)
+    pub rent_reserve_lamports: u64,
+    // Mint claims are paid in after `set_payout_mint` swapped the escrow (None = the vesting mint)
+    pub payout_mint: Option<Pubkey>,
+    // PDA token account ["payout_escrow", data_account] holding the payout mint
+    pub payout_escrow: Pubkey,
+    // Raw payout mint units paid per `payout_ratio_denominator` raw units of the vesting mint
+    pub payout_ratio_numerator: u64,
+    pub payout_ratio_denominator: u64,
+    // Set by `commit_schedule_hash`: hash of the schedule parameters and `beneficiaries_root` (zeros if never
+    // committed), the committed root and when it was committed
+    pub schedule_hash: [u8; 32],
+    pub beneficiaries_root: [u8; 32],
+    pub schedule_committed_at: i64,
+    // Longest `voluntary_extension_seconds` of any beneficiary, so withdrawals and claim deadlines respect the
+    // latest end date
+    pub max_voluntary_extension_seconds: u32,
+    // Share of each claim, after the protocol fee, paid into `withholding_account` for tax remittance
+    pub withholding_bps: u16,
+    pub withholding_account: Option<Pubkey>,
+    // Raw tokens withheld from claims so far
+    pub withheld_total: u64,
+    // Set for good by `freeze_config`: the schedule's terms, beneficiaries and roles can no longer change.
+    // Unlike `frozen`, claims are unaffected.
+    pub is_frozen: bool,
+    // Wallet that can veto announced releases; once set, manual releases must be announced ahead
+    pub release_veto_authority: Option<Pubkey>,
+    // Id of the next `announce_release`
+    pub next_release_id: u32,
+    // Pending announcements, in slot `id % MAX_ANNOUNCED_RELEASES`
+    pub announced_releases: [AnnouncedRelease; MAX_ANNOUNCED_RELEASES],
+    // Yearly bonus on vested tokens left unclaimed, compounded monthly and paid from `bonus_escrow`; 0 if none
+    pub bonus_rate_bps_per_year: u16,
+    pub bonus_escrow: Pubkey,
+    // Raw bonus tokens paid so far
+    pub bonus_paid_total: u64,
+    // Active beneficiaries (PDAs and registry entries): added, minus removed and terminated ones. Together with
+    // `total_allocated` and `claimed_total`, which also only count active beneficiaries, it lets dashboards
+    // skip scanning every beneficiary account. Schedules migrated from layouts before v24 start counting at 0.
+    pub beneficiary_count: u32,
+    // Schedule this one was cloned from by `clone_schedule`; `clone_beneficiaries` copies its beneficiaries
+    pub cloned_from: Option<Pubkey>,
+    // Claims are blocked until the beneficiary accepts the grant with `accept_grant`
+    pub require_acceptance: bool,
+    // Claimed tokens parked in the escrow by `redirect_frozen_claim` and not yet paid out; included in `claimed_total`
+    pub parked_total: u64,
+    // The mint's mint and freeze authorities when the schedule was created (or migrated to v28)
+    pub mint_authority_snapshot: Option<Pubkey>,
+    pub freeze_authority_snapshot: Option<Pubkey>,
+    // Set by `verify_mint_integrity` once either authority changed; blocks the initializer's withdrawal paths
+    pub mint_compromised: bool,
+    // Beneficiary index entries created so far; the next beneficiary gets this index. Entries of removed
+    // beneficiaries are closed and their indexes not reused.
+    pub beneficiary_index_len: u32,
+    // Which seeds the data account and escrow PDAs are derived from: `SEED_VERSION_V1` (mint only) or
+    // `SEED_VERSION_V2` (mint and `schedule_id`, see `initialize_v2`)
+    pub seed_version: u8,
+    // Only part of the seeds for v2 schedules
+    pub schedule_id: u64,
+    // Most active beneficiaries (`beneficiary_count`) the schedule may have; 0 = no limit
+    pub max_beneficiaries: u32,
+    // Set by `finalize_expired` once vesting has ended; `percent_available` is then 100
+    pub completed: bool,
+    // Created by `initialize_with_installments`: only `funded_amount` of `token_amount` has been deposited so far
+    pub installment_funding: bool,
+    pub funded_amount: u64,
+    // Dispute window of `schedule_removal`, in seconds; 0 = `REMOVAL_DISPUTE_WINDOW_DEFAULT`
+    pub removal_dispute_seconds: u32,
+    // Wallet that can veto scheduled beneficiary removals, see `set_removal_arbiter`
+    pub removal_arbiter: Option<Pubkey>,
+    // How claims round vested shares to whole tokens, see `set_rounding_policy`
+    pub rounding: RoundingPolicy,
+    // How the part above the TGE unlock vests over `vesting_months`, see `set_vesting_curve`
+    pub curve: CurveType,
+    // How `withdraw_unclaimed` divides the residual; empty = all to the withdrawing wallet
+    pub withdraw_split: Vec<WithdrawShare>,
+    // What `execute_cancel` does with the tokens beneficiaries lose, see `set_cancel_disposition`
+    pub cancel_disposition: Disposition,
+    // Longest vesting, in months, any cohort has set for its members; never decreases
+    pub max_cohort_vesting_months: u8,
+    // Grace period of `schedule_cancel`, in seconds; 0 = `CANCEL_GRACE_PERIOD`
+    pub cancel_grace_seconds: u32,
+    // Tokens claimed by beneficiaries `execute_removal` removed; they stay in `total_allocated` and `claimed_total`
+    pub removed_claimed: u64,
+}
+
+// Which gates decide how much of an allocation is claimable.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReleaseMode {
+    // Time vesting only; `percent_available` is ignored and `release` is disabled
+    TimeOnly,
+    // Only what the initializer (or multisig) has released via `release`
+    ManualOnly,
+    // The lesser of the time-vested share and `percent_available`
+    #[default]
+    Both,
+}
+
+// How a beneficiary's vested share is rounded to whole tokens. A fully vested allocation is always paid in full.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingPolicy {
+    // Down, so the remainders stay in the escrow until the allocation fully vests
+    #[default]
+    Floor,
+    // To the nearest whole token, halves up
+    Nearest,
+    // Down while vesting, and up for the share frozen by a cancellation
+    CeilFinal,
+}
+
+// Shape of a month-based schedule after the cliff; see `vesting_common::curve::Curve` for the formulas.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Default)]
+pub enum CurveType {
+    // Evenly, in whole percent steps every month
+    #[default]
+    Linear,
+    // A year's share at once on every anniversary
+    Stepped,
+    // Each year vests more than the one before, e.g. 10/20/30/40% over four years
+    BackWeighted,
+    // Linear between (0, 0) and up to `MAX_CURVE_POINTS` points, the last one at 10_000 bps
+    Custom(Vec<CurvePoint>),
+}
+
+// A point of a custom curve: `cumulative_bps` of the part above the TGE unlock has vested after `month` months.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct CurvePoint {
+    pub month: u8,
+    pub cumulative_bps: u16,
+}
+
+impl CurveType {
+    // The curve evaluated by `math::Schedule`; fails for invalid custom points.
+    pub fn to_curve(&self) -> Result<curve::Curve> {
+        Ok(match self {
+            CurveType::Linear => curve::Curve::Linear,
+            CurveType::Stepped => curve::Curve::Stepped,
+            CurveType::BackWeighted => curve::Curve::BackWeighted,
+            CurveType::Custom(points) => {
+                let points: Vec<curve::CurvePoint> = points
+                    .iter()
+                    .map(|point| curve::CurvePoint { month: point.month, cumulative_bps: point.cumulative_bps })
+                    .collect();
+                curve::Curve::custom(&points).ok_or(VestingError::InvalidCurve)?
+            }
+        })
+    }
+}
+
+// Where `execute_cancel` sends the unvested tokens taken back from beneficiaries.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Disposition {
+    // To a token account of the initializer
+    #[default]
+    ReturnToInitializer,
+    // Burned from the escrow, reducing the mint's supply
+    Burn,
+    // To a token account of the schedule's treasurer
+    SendToTreasury,
+}
+
+// Administrative roles of a schedule, managed by `grant_role` and `revoke_role`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    // The `initializer`: manages roles, cancels and configures the schedule
+    Owner,
+    // Adds and removes beneficiaries and releases tokens
+    Operator,
+    // Withdraws unclaimed tokens once the claim window has expired
+    Treasurer,
+}
+
+impl DataAccount {
+    // Serialized size including the discriminator; see the layout documented on `Initialize`.
+    // Defined in `vesting_common::layout` so clients can size and parse the account too.
+    pub const LEN: usize = DATA_ACCOUNT_LEN;
+
+    // Layout version written by `initialize` and `migrate_data_account`.
+    // Accounts created before `version` existed (v1) read it as 0.
+    pub const VERSION: u8 = DATA_ACCOUNT_VERSION;
+
+    // Increase `percent_available` by `percent`, capped at 100%.
+    //
+    // `saturating_add` prevents overflow.
+    // `std::cmp::min` ensures the cap at 100.
+    // Fails in `TimeOnly` mode, where `percent_available` has no effect.
+    pub fn add_release(&mut self, percent: u8) -> Result<()> {
+        require!(self.release_mode != ReleaseMode::TimeOnly, VestingError::ManualReleaseDisabled);
+        self.percent_available = std::cmp::min(self.percent_available.saturating_add(percent), 100);
+        Ok(())
+    }
+
+    // Removes pending announcement `id` from the ring buffer and returns it.
+    pub fn take_announced_release(&mut self, id: u32) -> Result<AnnouncedRelease> {
+        let slot = &mut self.announced_releases[id as usize % MAX_ANNOUNCED_RELEASES];
+        require!(
+            slot.effective_at != 0 && slot.id == id,
+            VestingError::ReleaseAnnouncementNotFound
+        );
+        Ok(std::mem::take(slot))
+    }
+
+    // Books a new beneficiary's `amount` and returns its (allocated_tokens, shares). In shares mode `amount` is a
+    // number of shares, and the whole pool is split among them; otherwise it's a token allocation, and the sum of
+    // all allocations has to stay within the amount deposited in escrow.
+    pub fn register_allocation(&mut self, amount: u64) -> Result<(u64, u64)> {
+        self.count_new_beneficiary()?;
+        if self.shares_mode {
+            // New shares would dilute tokens that were already paid out
+            require!(self.claimed_total == 0, VestingError::SharesLocked);
+            self.total_shares = self.total_shares.checked_add(amount).ok_or(VestingError::SharesLocked)?;
+            self.sync_pool_allocation();
+            Ok((0, amount))
+        } else {
+            self.total_allocated = self
+                .total_allocated
+                .checked_add(amount)
+                .ok_or(VestingError::AllocationExceedsEscrow)?;
+            require!(self.total_allocated <= self.token_amount, VestingError::AllocationExceedsEscrow);
+            Ok((amount, 0))
+        }
+    }
+
+    // Rounding of beneficiaries' vested amounts under the schedule's `RoundingPolicy`.
+    pub fn rounding(&self) -> math::Rounding {
+        match self.rounding {
+            RoundingPolicy::Floor => math::Rounding::Down,
+            RoundingPolicy::Nearest => math::Rounding::Nearest,
+            RoundingPolicy::CeilFinal if self.cancelled => math::Rounding::Up,
+            RoundingPolicy::CeilFinal => math::Rounding::Down,
+        }
+    }
+
+    // Most tokens all active allocations together can have vested at `bps`: the pool's share rounded down, plus a
+    // token per beneficiary when the rounding policy can round individual shares up, never more than the pool, plus
+    // what removed beneficiaries claimed.
+    pub fn pool_vested_amount(&self, bps: u64) -> Result<u64> {
+        // Claims of removed beneficiaries count as fully vested
+        let active_allocated = self.total_allocated.saturating_sub(self.removed_claimed);
+        let vested = math::mul_div(active_allocated, bps, 10_000).ok_or(VestingError::MathOverflow)?;
+        let vested = if self.rounding == RoundingPolicy::Floor {
+            vested
+        } else {
+            std::cmp::min(vested.saturating_add(self.beneficiary_count as u64), active_allocated)
+        };
+        Ok(vested.saturating_add(self.removed_claimed))
+    }
+
+    // Seconds between `schedule_removal` and the earliest `execute_removal`.
+    pub fn removal_dispute_window(&self) -> i64 {
+        if self.removal_dispute_seconds == 0 {
+            REMOVAL_DISPUTE_WINDOW_DEFAULT
+        } else {
+            self.removal_dispute_seconds as i64
+        }
+    }
+
+    // Seconds between `schedule_cancel` and the earliest `execute_cancel`.
+    pub fn cancel_grace_period(&self) -> i64 {
+        if self.cancel_grace_seconds == 0 {
+            CANCEL_GRACE_PERIOD
+        } else {
+            self.cancel_grace_seconds as i64
+        }
+    }
+
+    // Tokens deposited into the escrow for the schedule: `funded_amount` while it is funded in installments.
+    pub fn funded_tokens(&self) -> u64 {
+        if self.installment_funding {
+            self.funded_amount
+        } else {
+            self.token_amount
+        }
+    }
+
+    // Caps `vested` tokens of `beneficiary` at its share of the funded tokens, rounded down, while the schedule is
+    // still behind on its installments.
+    pub fn cap_funded(&self, beneficiary: &BeneficiaryAccount, vested: u64) -> u64 {
+        self.cap_funded_entitlement(beneficiary.entitlement(self), vested)
+    }
+
+    // `cap_funded` for a position entitled to `entitlement` tokens.
+    pub fn cap_funded_entitlement(&self, entitlement: u64, vested: u64) -> u64 {
+        if self.funded_tokens() >= self.token_amount {
+            return vested;
+        }
+        let funded_share = math::mul_div(entitlement, self.funded_amount, self.token_amount).unwrap_or(0);
+        std::cmp::min(vested, funded_share)
+    }
+
+    // Tokens of the pool that `shares` are entitled to in shares mode, rounded down.
+    pub fn pool_share(&self, shares: u64) -> u64 {
+        // `shares` are part of `total_shares`, so this never exceeds the pool; an empty pool has no shares
+        math::mul_div(self.token_amount, shares, self.total_shares).unwrap_or(0)
+    }
+
+    // In shares mode the whole pool is allocated, so `total_allocated` follows `token_amount`.
+    pub fn sync_pool_allocation(&mut self) {
+        if self.shares_mode && self.total_shares > 0 {
+            self.total_allocated = self.token_amount;
+        }
+    }
+
+    // Fails with `WalletBlacklisted` if any of `wallets` is on the schedule's blacklist, which has to be passed
+    // once the schedule has one (`BlacklistRequired`).
+    pub fn check_blacklist(&self, blacklist: Option<&Blacklist>, wallets: &[Pubkey]) -> Result<()> {
+        if self.blacklist_enabled {
+            let blacklist = blacklist.ok_or(VestingError::BlacklistRequired)?;
+            require!(!wallets.iter().any(|wallet| blacklist.contains(wallet)), VestingError::WalletBlacklisted);
+        }
+        Ok(())
+    }
+
+    // Fails with `ClaimApprovalRequired` unless the configured claim approver (if any) signed.
+    pub fn check_claim_approval(&self, signer: Option<&Signer>) -> Result<()> {
+        if let Some(claim_approver) = self.claim_approver {
+            let signer = signer.ok_or(VestingError::ClaimApprovalRequired)?;
+            require_keys_eq!(signer.key(), claim_approver, VestingError::ClaimApprovalRequired);
+        }
+        Ok(())
+    }
+
+    // Fails unless plain escrow claims are all the schedule needs. Claim history, blacklists, identity gates, claim
+    // hooks, payout mints, withholding, bonuses, vesting legs and mint vesting are only handled by `claim`, so the
+    // other claim paths call this first.
+    pub fn require_plain_claim(&self) -> Result<()> {
+        require!(!self.record_claims, VestingError::ClaimRecordRequired);
+        require!(!self.blacklist_enabled, VestingError::BlacklistRequired);
+        require!(self.gate_program.is_none(), VestingError::ClaimGateUnsupported);
+        require!(self.on_claim_hook.is_none(), VestingError::ClaimHookUnsupported);
+        require!(self.payout_mint.is_none(), VestingError::PayoutMintUnsupported);
+        require!(self.withholding_account.is_none(), VestingError::WithholdingUnsupported);
+        require!(self.bonus_rate_bps_per_year == 0, VestingError::BonusUnsupported);
+        require!(self.leg_count == 0, VestingError::VestingLegsUnsupported);
+        require!(!self.mint_vesting, VestingError::MintVestingUnsupported);
+        Ok(())
+    }
+
+    // Earliest time a beneficiary whose last claim was at `last_claim_timestamp` may claim again.
+    pub fn next_claim_allowed_at(&self, last_claim_timestamp: i64) -> i64 {
+        if self.min_claim_interval_seconds == 0 || last_claim_timestamp == 0 {
+            return 0;
+        }
+        last_claim_timestamp + self.min_claim_interval_seconds as i64
+    }
+
+    // Caps a claimable amount at `max_claim_per_period`, if set.
+    pub fn cap_claim(&self, claimable_amount: u64) -> u64 {
+        if self.max_claim_per_period == 0 {
+            return claimable_amount;
+        }
+        std::cmp::min(claimable_amount, self.max_claim_per_period)
+    }
+
+    // Whole tokens `beneficiary` can claim at `now` out of `funded_eligible`, its vested tokens already capped by
+    // `cap_funded`: what is neither claimed nor locked for voting, within the per-period cap.
+    pub fn claimable_amount(&self, beneficiary: &BeneficiaryAccount, funded_eligible: u64, now: i64) -> u64 {
+        self.cap_claim(
+            funded_eligible
+                .saturating_sub(beneficiary.claimed_tokens)
+                .saturating_sub(beneficiary.vote_locked(now)),
+        )
+    }
+
+    // Books a claim of `amount` whole tokens by `beneficiary` at `now`, once it has been paid.
+    pub fn book_claim(&mut self, beneficiary: &mut BeneficiaryAccount, amount: u64, now: i64) {
+        beneficiary.claimed_tokens = beneficiary.claimed_tokens.saturating_add(amount);
+        beneficiary.last_claim_timestamp = now;
+        self.claimed_total = self.claimed_total.saturating_add(amount);
+    }
+
+    // `book_claim` for a registry entry, which doesn't track its last claim.
+    pub fn book_registry_claim(&mut self, entry: &mut RegistryEntry, amount: u64) {
+        entry.claimed_tokens = entry.claimed_tokens.saturating_add(amount);
+        self.claimed_total = self.claimed_total.saturating_add(amount);
+    }
+
+    // Result of an instruction that transferred `amount` raw units out of the escrow.
+    pub fn transfer_result(&self, amount: u64) -> TransferResult {
+        TransferResult {
+            amount,
+            claimed_total: self.claimed_total,
+            remaining_tokens: self
+                .token_amount
+                .saturating_sub(self.claimed_total + self.unclaimed_withdrawn),
+        }
+    }
+
+    // Part of a claim of `amount_raw` raw tokens (after the fee) that is withheld for tax, rounded down.
+    pub fn withholding_share(&self, amount_raw: u64) -> Result<u64> {
+        Ok(math::bps_of(amount_raw, self.withholding_bps).ok_or(VestingError::MathOverflow)?)
+    }
+
+    // `amount_raw` units of the vesting mint converted to the payout mint, rounded down.
+    pub fn to_payout_amount(&self, amount_raw: u64) -> Result<u64> {
+        Ok(math::mul_div(amount_raw, self.payout_ratio_numerator, self.payout_ratio_denominator)
+            .ok_or(VestingError::MathOverflow)?)
+    }
+
+    // Lamports the fee pool refunds for one claim, taken off the pool.
+    pub fn take_fee_reimbursement(&mut self) -> u64 {
+        let lamports = std::cmp::min(self.fee_pool_lamports, self.fee_pool_max_per_claim);
+        self.fee_pool_lamports -= lamports;
+        lamports
+    }
+
+    // Whether `key` may manage beneficiaries and release: the owner or the operator.
+    pub fn can_operate(&self, key: &Pubkey) -> bool {
+        *key == self.initializer || self.operator == Some(*key)
+    }
+
+    // Records the mint's current authorities for `verify_mint_integrity`.
+    pub fn snapshot_mint(&mut self, mint: &Mint) {
+        self.mint_authority_snapshot = mint.mint_authority.into();
+        self.freeze_authority_snapshot = mint.freeze_authority.into();
+    }
+
+    // Whether the mint's authorities are still the recorded ones.
+    pub fn mint_matches_snapshot(&self, mint: &Mint) -> bool {
+        Option::<Pubkey>::from(mint.mint_authority) == self.mint_authority_snapshot
+            && Option::<Pubkey>::from(mint.freeze_authority) == self.freeze_authority_snapshot
+    }
+
+    // Seed following the mint in this schedule's data account and escrow PDAs: empty for v1 schedules, the
+    // little-endian schedule id for v2. An empty seed doesn't change the derived address, so derivations and
+    // signers append it whatever the version.
+    pub fn schedule_seed(&self) -> Vec<u8> {
+        if self.seed_version >= SEED_VERSION_V2 {
+            self.schedule_id.to_le_bytes().to_vec()
+        } else {
+            Vec::new()
+        }
+    }
+
+    // Counts a new active beneficiary, failing once the schedule has `max_beneficiaries` of them.
+    pub fn count_new_beneficiary(&mut self) -> Result<()> {
+        require!(
+            self.max_beneficiaries == 0 || self.beneficiary_count < self.max_beneficiaries,
+            VestingError::BeneficiaryLimitReached
+        );
+        self.beneficiary_count = self.beneficiary_count.saturating_add(1);
+        Ok(())
+    }
+
+    // Takes the next index of the beneficiary index.
+    pub fn next_beneficiary_index(&mut self) -> Result<u32> {
+        let index = self.beneficiary_index_len;
+        self.beneficiary_index_len = index.checked_add(1).ok_or(VestingError::MathOverflow)?;
+        Ok(index)
+    }
+
+    pub fn has_claim_limits(&self) -> bool {
+        self.min_claim_interval_seconds > 0 || self.max_claim_per_period > 0
+    }
+
+    // Checks the validator clock against the last time this schedule processed a time-dependent instruction,
+    // and records it. A clock at most `clock_tolerance_seconds` behind (validator skew) is treated as the last
+    // processed time, so vested shares never go backwards and nothing unlocks early; a clock further behind is rejected.
+    pub fn advance_clock(&mut self, now: i64) -> Result<i64> {
+        self.last_processed_timestamp = self.check_clock(now)?;
+        Ok(self.last_processed_timestamp)
+    }
+
+    // The time `advance_clock` would record at `now`, without recording it.
+    pub fn check_clock(&self, now: i64) -> Result<i64> {
+        require!(
+            now.saturating_add(self.clock_tolerance_seconds as i64) >= self.last_processed_timestamp,
+            VestingError::ClockWentBackwards
+        );
+        Ok(self.monotonic_now(now))
+    }
+
+    // `now`, but never earlier than the last processed timestamp; for read-only views.
+    pub fn monotonic_now(&self, now: i64) -> i64 {
+        std::cmp::max(now, self.last_processed_timestamp)
+    }
+
+    // Whether the claim deadline, if any, has passed at `now`.
+    pub fn claim_window_expired(&self, now: i64) -> bool {
+        self.claim_deadline != 0 && now > self.claim_deadline
+    }
+
+    // Position of `key` in the approver set, used as its bit in `ReleaseProposal::approvals`.
+    pub fn approver_index(&self, key: &Pubkey) -> Result<usize> {
+        self.approvers
+            .iter()
+            .position(|approver| approver == key)
+            .ok_or_else(|| error!(VestingError::NotAnApprover))
+    }
+
+    // The fields the vesting formula depends on; see `math::Schedule`.
+    pub fn schedule(&self) -> math::Schedule {
+        math::Schedule {
+            start_timestamp: self.start_timestamp,
+            vesting_months: self.vesting_months,
+            cliff_months: self.cliff_months,
+            percent_available: self.percent_available,
+            calendar_months: self.calendar_months,
+            accelerated_at: self.accelerated_at,
+            cancelled: self.cancelled,
+            final_vested_bps: self.final_vested_bps,
+            tge_unlock_bps: self.tge_unlock_bps,
+            lockup_seconds: self.lockup_seconds,
+            time_vesting: self.release_mode != ReleaseMode::ManualOnly,
+            manual_release: self.release_mode != ReleaseMode::TimeOnly,
+            interval_seconds: self.interval_seconds,
+            total_intervals: self.total_intervals,
+            // Only valid curves are ever stored
+            curve: self.curve.to_curve().unwrap_or_default(),
+        }
+    }
+
+    // Whether `consolidate_positions` can merge positions of `other` into this schedule: the same initializer and the
+    // same terms, so the merged position vests, is claimed and can be revoked exactly as both did.
+    pub fn can_consolidate_from(&self, other: &DataAccount) -> bool {
+        self.schedule() == other.schedule()
+            && self.initializer == other.initializer
+            && self.rounding == other.rounding
+            && self.is_cancellable == other.is_cancellable
+            && self.cancellation_penalty_bps == other.cancellation_penalty_bps
+            && self.claim_deadline == other.claim_deadline
+            && self.min_claim_interval_seconds == other.min_claim_interval_seconds
+            && self.max_claim_per_period == other.max_claim_per_period
+            && self.claim_approver == other.claim_approver
+            && self.gate_program == other.gate_program
+            && self.gate_config == other.gate_config
+            && self.on_claim_hook == other.on_claim_hook
+            && self.require_acceptance == other.require_acceptance
+            && self.bonus_rate_bps_per_year == other.bonus_rate_bps_per_year
+            && self.withholding_bps == other.withholding_bps
+            && self.withholding_account == other.withholding_account
+    }
+
+    // SHA-256 of the Borsh-encoded `ScheduleCommitment` of the current parameters and `beneficiaries_root`.
+    pub fn schedule_commitment(&self, beneficiaries_root: &[u8; 32]) -> Result<[u8; 32]> {
+        let commitment = ScheduleCommitment {
+            token_mint: self.token_mint,
+            decimals: self.decimals,
+            initializer: self.initializer,
+            token_amount: self.token_amount,
+            start_timestamp: self.start_timestamp,
+            vesting_months: self.vesting_months,
+            cliff_months: self.cliff_months,
+            calendar_months: self.calendar_months,
+            tge_unlock_bps: self.tge_unlock_bps,
+            lockup_seconds: self.lockup_seconds,
+            release_mode: self.release_mode,
+            interval_seconds: self.interval_seconds,
+            total_intervals: self.total_intervals,
+            accelerated_at: self.accelerated_at,
+            amendment_count: self.amendment_count,
+            leg_count: self.leg_count,
+            is_cancellable: self.is_cancellable,
+            cancellation_penalty_bps: self.cancellation_penalty_bps,
+            forfeiture_pool: self.forfeiture_pool,
+            forfeiture_bps: self.forfeiture_bps,
+            claim_deadline: self.claim_deadline,
+            min_claim_interval_seconds: self.min_claim_interval_seconds,
+            max_claim_per_period: self.max_claim_per_period,
+            mint_vesting: self.mint_vesting,
+            shares_mode: self.shares_mode,
+            non_transferable: self.non_transferable,
+            claim_approver: self.claim_approver,
+            gate_program: self.gate_program,
+            on_claim_hook: self.on_claim_hook,
+            payout_mint: self.payout_mint,
+            payout_ratio_numerator: self.payout_ratio_numerator,
+            payout_ratio_denominator: self.payout_ratio_denominator,
+            beneficiaries_root: *beneficiaries_root,
+        };
+        let mut data = Vec::new();
+        commitment.serialize(&mut data)?;
+        Ok(hash(&data).to_bytes())
+    }
+
+    // Share (0–10_000 basis points) of each allocation that is claimable at `now`.
+    // Once the schedule is cancelled this is the share frozen by `execute_cancel`.
+    pub fn vested_bps(&self, now: i64) -> u16 {
+        self.schedule().vested_bps(now)
+    }
+
+    pub fn next_unlock_timestamp(&self, now: i64) -> i64 {
+        self.schedule().next_unlock_timestamp(now)
+    }
+
+    pub fn vesting_end_timestamp(&self) -> i64 {
+        self.schedule().vesting_end_timestamp()
+    }
+
+    // End of vesting for the beneficiary vesting the longest: in the cohort with the longest vesting, and having
+    // extended theirs the furthest via `extend_my_vesting`.
+    pub fn latest_vesting_end_timestamp(&self) -> i64 {
+        let mut schedule = self.schedule();
+        schedule.vesting_months = std::cmp::max(schedule.vesting_months, self.max_cohort_vesting_months);
+        schedule
+            .vesting_end_timestamp()
+            .saturating_add(self.max_voluntary_extension_seconds as i64)
+    }
+
+    pub fn elapsed_months(&self, now: i64) -> i64 {
+        self.schedule().elapsed_months(now)
+    }
+
+    pub fn vesting_start_timestamp(&self) -> i64 {
+        self.schedule().vesting_start_timestamp()
+    }
+
+    // Bonus on `amount_raw` vested tokens claimed at `now` by a beneficiary who last claimed at `last_claim_timestamp`,
+    // or who never claimed (0), in which case the tokens have been left unclaimed since vesting started.
+    pub fn unclaimed_bonus(&self, amount_raw: u64, last_claim_timestamp: i64, now: i64) -> Result<u64> {
+        let since = if last_claim_timestamp == 0 { self.vesting_start_timestamp() } else { last_claim_timestamp };
+        Ok(math::compound_bonus(amount_raw, self.bonus_rate_bps_per_year, now.saturating_sub(since))
+            .ok_or(VestingError::MathOverflow)?)
+    }
+}
+
+// Bounds on `start_timestamp` relative to the time `initialize` runs (10 years back, 5 years ahead), unless the
+// upgrade authority set others with `set_start_bounds`.
+pub const MAX_START_IN_PAST: i64 = 10 * 365 * SECONDS_PER_DAY;
+pub const MAX_START_IN_FUTURE: i64 = 5 * 365 * SECONDS_PER_DAY;
+
+// Time beneficiaries have to claim vested tokens between `schedule_cancel` and `execute_cancel` unless the schedule
+// sets its own (7 days), and the bounds of `set_cancel_grace_period` (1 to 90 days).
+pub const CANCEL_GRACE_PERIOD: i64 = 7 * 24 * 60 * 60;
+pub const CANCEL_GRACE_PERIOD_MIN: i64 = SECONDS_PER_DAY;
+pub const CANCEL_GRACE_PERIOD_MAX: i64 = 90 * SECONDS_PER_DAY;
+
+// Time a scheduled beneficiary removal stays open to dispute unless the schedule sets its own (7 days),
+// and the bounds of `set_removal_dispute_window` (1 to 90 days).
+pub const REMOVAL_DISPUTE_WINDOW_DEFAULT: i64 = 7 * SECONDS_PER_DAY;
+pub const REMOVAL_DISPUTE_WINDOW_MIN: i64 = SECONDS_PER_DAY;
+pub const REMOVAL_DISPUTE_WINDOW_MAX: i64 = 90 * SECONDS_PER_DAY;
+
+// Time after the latest end of vesting before anyone can `finalize_expired` a schedule (30 days).
+pub const FINALIZE_GRACE_PERIOD: i64 = 30 * SECONDS_PER_DAY;
+
+// Notice `set_sunset_mode` gives before the program is retired (180 days), so beneficiaries and integrators can
+// claim and move on through the usual instructions first.
+pub const SUNSET_DELAY: i64 = 180 * SECONDS_PER_DAY;
+
+// Most lamports `finalize_expired` pays its caller out of the rent reserve.
+pub const FINALIZE_BOUNTY_LAMPORTS: u64 = 100_000;
+
+// Time the original key has to `cancel_recovery` before `execute_recovery` can run (14 days).
+pub const RECOVERY_DELAY: i64 = 14 * SECONDS_PER_DAY;
+
+// Shortest notice `announce_release` accepts between the announcement and `effective_at` (1 day).
+pub const MIN_RELEASE_NOTICE: i64 = SECONDS_PER_DAY;
+
+// Largest clock tolerance `set_clock_tolerance` accepts.
+pub const MAX_CLOCK_TOLERANCE: u32 = 60 * 60;
+
+// Longest total `extend_my_vesting` extension of a position (5 years).
+pub const MAX_VOLUNTARY_EXTENSION: u32 = (5 * 365 * SECONDS_PER_DAY) as u32;
+
+// Emitted by `terminate_beneficiary`; amounts are in raw token units.
+#[event]
+pub struct BeneficiaryTerminated {
+    pub data_account: Pubkey,
+    pub beneficiary: Pubkey,
+    // Vested but unclaimed tokens pushed to the beneficiary
+    pub vested_paid: u64,
+    // Unvested tokens sent to the forfeiture pool
+    pub forfeited: u64,
+    // Unvested tokens returned to the initializer
+    pub returned: u64,
+}
+
+// Maximum number of entries `add_beneficiaries`, `clone_beneficiaries`, `schedule_removal`, `execute_removal` and the
+// registry counterparts process per call.
+pub const MAX_BENEFICIARIES_PER_CHUNK: usize = 10;
+
+// Emitted by `clone_schedule`. `token_amount` is in whole tokens.
+#[event]
+pub struct ScheduleCloned {
+    pub data_account: Pubkey,
+    pub source_data_account: Pubkey,
+    pub token_amount: u64,
+    pub start_timestamp: i64,
+}
+
+// Emitted when a schedule is created and by `update_metadata`, so indexers can label schedules.
+#[event]
+pub struct ScheduleMetadataUpdated {
+    pub data_account: Pubkey,
+    pub name: [u8; 32],
+    pub uri: [u8; 128],
+}
+
+// Emitted by `lock_for_voting`. `locked_amount` is in whole tokens, `voting_weight` in raw units.
+#[event]
+pub struct VotesLocked {
+    pub data_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub locked_amount: u64,
+    pub unlock_at: i64,
+    pub voting_weight: u64,
+}
+
+// Emitted by `redirect_frozen_claim`. `amount` and `parked_tokens`, the beneficiary's total parked tokens, are in
+// whole tokens.
+#[event]
+pub struct ClaimParked {
+    pub data_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub parked_tokens: u64,
+}
+
+// Emitted by `verify_mint_integrity` when it flags a schedule whose mint authorities changed.
+#[event]
+pub struct MintCompromised {
+    pub data_account: Pubkey,
+    pub mint_authority: Option<Pubkey>,
+    pub freeze_authority: Option<Pubkey>,
+}
+
+// Emitted by `claim_and_swap`, in raw token units: `amount_in` claimed tokens were traded for `amount_out` tokens of
+// `output_mint`.
+#[event]
+pub struct ClaimSwapped {
+    pub data_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount_in: u64,
+    pub output_mint: Pubkey,
+    pub amount_out: u64,
+}
+
+// Emitted by `set_program_halted`.
+#[event]
+pub struct ProgramHaltChanged {
+    pub is_halted: bool,
+}
+
+// Emitted by `set_sunset_mode`; `sunset_at` is 0 when the sunset was called off.
+#[event]
+pub struct SunsetModeChanged {
+    pub sunset_at: i64,
+}
+
+// Emitted by `sunset_claim`; `amount` is the raw amount paid to the beneficiary's ATA.
+#[event]
+pub struct SunsetClaimed {
+    pub data_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+}
+
+// Emitted by `sunset_close_schedule`; `returned` is the raw amount left in the escrow and sent to the initializer.
+#[event]
+pub struct SunsetScheduleClosed {
+    pub data_account: Pubkey,
+    pub returned: u64,
+}
+
+// Emitted by `set_program_features` with the new feature bitmask.
+#[event]
+pub struct ProgramFeaturesChanged {
+    pub features: u64,
+}
+
+// Emitted by `accept_grant`.
+#[event]
+pub struct GrantAccepted {
+    pub data_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub agreement_hash: [u8; 32],
+}
+
+// Emitted by `extend_my_vesting`. `voluntary_extension_seconds` is the beneficiary's total extension and
+// `end_timestamp` their new end of vesting.
+#[event]
+pub struct VestingExtended {
+    pub data_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub extension_seconds: u32,
+    pub voluntary_extension_seconds: u32,
+    pub end_timestamp: i64,
+}
+
+// Emitted by `commit_schedule_hash`.
+#[event]
+pub struct ScheduleCommitted {
+    pub data_account: Pubkey,
+    pub schedule_hash: [u8; 32],
+    pub beneficiaries_root: [u8; 32],
+    pub committed_at: i64,
+}
+
+// Emitted by `sync_escrow`. Balances and `deficit` are in raw token units, `absorbed_tokens` in whole tokens.
+#[event]
+pub struct EscrowSynced {
+    pub data_account: Pubkey,
+    pub escrow_balance: u64,
+    pub expected_balance: u64,
+    pub absorbed_tokens: u64,
+    pub deficit: u64,
+}
+
+// Emitted by `migrate_escrow`. `amount` is in raw token units.
+#[event]
+pub struct EscrowMigrated {
+    pub data_account: Pubkey,
+    pub old_escrow_wallet: Pubkey,
+    pub new_escrow_wallet: Pubkey,
+    pub amount: u64,
+}
+
+// Emitted by `set_payout_mint`. `burned` is in raw units of the vesting mint, `deposited` in raw payout mint units.
+#[event]
+pub struct PayoutMintSet {
+    pub data_account: Pubkey,
+    pub payout_mint: Pubkey,
+    pub ratio_numerator: u64,
+    pub ratio_denominator: u64,
+    pub burned: u64,
+    pub deposited: u64,
+}
+
+// Emitted by `initialize_with_installments` and `fund_installment`, with the funded and committed totals.
+#[event]
+pub struct InstallmentFunded {
+    pub data_account: Pubkey,
+    pub amount: u64,
+    pub funded_amount: u64,
+    pub committed_amount: u64,
+}
+
+// Emitted by `claim` when the beneficiary's vested tokens exceed its share of the funded tokens, with both amounts.
+#[event]
+pub struct FundingShortfall {
+    pub data_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub vested_amount: u64,
+    pub funded_amount: u64,
+}
+
+// Emitted by `finalize_expired`, with the lamports paid to the cranker.
+#[event]
+pub struct VestingCompleted {
+    pub data_account: Pubkey,
+    pub cranker: Pubkey,
+    pub bounty: u64,
+}
+
+// Emitted by `fund_rent_reserve` and `withdraw_rent_reserve` with the new reserve balance.
+#[event]
+pub struct RentReserveChanged {
+    pub data_account: Pubkey,
+    pub rent_reserve_lamports: u64,
+}
+
+// Emitted by `initiate_recovery`.
+#[event]
+pub struct RecoveryInitiated {
+    pub data_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub new_wallet: Pubkey,
+    pub executable_at: i64,
+}
+
+// Emitted by `cancel_recovery`.
+#[event]
+pub struct RecoveryCancelled {
+    pub data_account: Pubkey,
+    pub beneficiary: Pubkey,
+}
+
+// Emitted by `execute_recovery`.
+#[event]
+pub struct RecoveryExecuted {
+    pub data_account: Pubkey,
+    pub previous_wallet: Pubkey,
+    pub new_wallet: Pubkey,
+}
+
+// Emitted by `grant_role`; for `Owner`, `key` is the new initializer.
+#[event]
+pub struct RoleGranted {
+    pub data_account: Pubkey,
+    pub role: Role,
+    pub key: Pubkey,
+}
+
+// Emitted by `revoke_role`.
+#[event]
+pub struct RoleRevoked {
+    pub data_account: Pubkey,
+    pub role: Role,
+}
+
+// Emitted by `claim` when part of the claim is withheld. Amounts are in raw token units.
+#[event]
+pub struct ClaimWithheld {
+    pub data_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub withheld_total: u64,
+}
+
+// Emitted by `withdraw_leg_remainder`; `amount` is in raw units of the leg mint.
+#[event]
+pub struct LegRemainderWithdrawn {
+    pub data_account: Pubkey,
+    pub leg_mint: Pubkey,
+    pub amount: u64,
+    pub leg_closed: bool,
+}
+
+// Emitted by `set_bonus_rate` and `withdraw_bonus`. The balance is in raw token units.
+#[event]
+pub struct BonusRateSet {
+    pub data_account: Pubkey,
+    pub bonus_rate_bps_per_year: u16,
+    pub bonus_escrow_balance: u64,
+}
+
+// Emitted by `claim` when it pays a bonus on top of the claimed tokens. Amounts are in raw token units.
+#[event]
+pub struct BonusPaid {
+    pub data_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub bonus_paid_total: u64,
+}
+
+// Emitted when a claim is refunded from the SOL fee pool.
+#[event]
+pub struct FeePoolReimbursed {
+    pub data_account: Pubkey,
+    pub beneficiary: Pubkey,
+    // The claim's sender, who paid for it
+    pub recipient: Pubkey,
+    pub lamports: u64,
+}
+
+// Emitted by `split_position`; amounts are in whole tokens and describe the new position.
+#[event]
+pub struct PositionSplit {
+    pub data_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub new_beneficiary: Pubkey,
+    pub allocated_tokens: u64,
+    pub claimed_tokens: u64,
+}
+
+// Emitted by `consolidate_positions`; amounts are in whole tokens and describe the position merged in.
+#[event]
+pub struct PositionsConsolidated {
+    pub data_account: Pubkey,
+    pub source_data_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub allocated_tokens: u64,
+    pub claimed_tokens: u64,
+}
+
+// Emitted by `update_beneficiary_allocation`; amounts are in whole tokens.
+#[event]
+pub struct BeneficiaryAllocationUpdated {
+    pub data_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub previous_allocated_tokens: u64,
+    pub allocated_tokens: u64,
+}
+
+// Emitted whenever the aggregates below change: beneficiaries added, removed or terminated, allocations updated
+// and claims. `claimed_total` only counts active beneficiaries, in whole tokens like `total_allocated`.
+#[event]
+pub struct ScheduleStatsUpdated {
+    pub data_account: Pubkey,
+    pub beneficiary_count: u32,
+    pub total_allocated: u64,
+    pub claimed_total: u64,
+}
+
+// Emitted by `finalize_beneficiary_batch`.
+#[event]
+pub struct BeneficiaryBatchFinalized {
+    pub data_account: Pubkey,
+    pub count: u32,
+    pub total_allocated: u64,
+}
+
+// Emitted by `renounce_admin`; from then on the schedule is an irrevocable, time-only lockup.
+#[event]
+pub struct AdminRenounced {
+    pub data_account: Pubkey,
+    pub renounced_by: Pubkey,
+}
+
+// Emitted by `freeze_config`; from then on the schedule's configuration can't change.
+#[event]
+pub struct ScheduleConfigFrozen {
+    pub data_account: Pubkey,
+    pub frozen_by: Pubkey,
+}
+
+// Emitted by `announce_release`; the release can be executed from `effective_at` unless it is vetoed.
+#[event]
+pub struct ReleaseAnnounced {
+    pub data_account: Pubkey,
+    pub id: u32,
+    pub percent: u8,
+    pub effective_at: i64,
+}
+
+// Emitted by `schedule_removal` for each beneficiary, with the time from which it can be removed.
+#[event]
+pub struct RemovalScheduled {
+    pub data_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub executable_at: i64,
+}
+
+// Emitted by `veto_removal`.
+#[event]
+pub struct RemovalVetoed {
+    pub data_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub vetoed_by: Pubkey,
+}
+
+// Emitted by `veto_release`.
+#[event]
+pub struct ReleaseVetoed {
+    pub data_account: Pubkey,
+    pub id: u32,
+    pub percent: u8,
+    pub vetoed_by: Pubkey,
+}
+
+// Emitted by `release` and multisig-approved releases.
+#[event]
+pub struct TokensReleased {
+    pub data_account: Pubkey,
+    pub percent: u8,
+    // `percent_available` after the release
+    pub percent_available: u8,
+}
+
+// Emitted by `execute_cancel`; `returned` is in raw token units.
+#[event]
+pub struct CancelExecuted {
+    pub data_account: Pubkey,
+    pub final_vested_bps: u16,
+    // Unclaimed tokens sent back to the initializer, or to the treasurer (see `set_cancel_disposition`)
+    pub returned: u64,
+}
+
+// Emitted by `create_cohort` and `update_cohort` with the cohort's new overrides.
+#[event]
+pub struct CohortUpdated {
+    pub data_account: Pubkey,
+    pub cohort_id: u32,
+    pub cliff_months: Option<u8>,
+    pub vesting_months: Option<u8>,
+    pub acceleration_exempt: bool,
+}
+
+// Emitted by `assign_to_cohort`; `cohort_id` is `None` when the beneficiary left its cohort.
+#[event]
+pub struct CohortAssigned {
+    pub data_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub cohort_id: Option<u32>,
+}
+
+// Emitted by `execute_cancel` when the schedule's `cancel_disposition` is `Burn`; `burned` is in raw token units.
+#[event]
+pub struct CancelledTokensBurned {
+    pub data_account: Pubkey,
+    pub burned: u64,
+}
+
+// Emitted when a cancellation is scheduled, so beneficiaries can claim before it takes effect.
+#[event]
+pub struct CancelScheduled {
+    pub data_account: Pubkey,
+    pub cancel_effective_at: i64,
+}
+
+// Emitted when a schedule is fully accelerated.
+#[event]
+pub struct AccelerationTriggered {
+    pub data_account: Pubkey,
+    pub accelerated_at: i64,
+}
+
+// Emitted by `withdraw_unclaimed` for every share of a withdrawal split, with the raw amount paid
+// (`recipient` is `None` for a burned share).
+#[event]
+pub struct UnclaimedShareWithdrawn {
+    pub data_account: Pubkey,
+    pub recipient: Option<Pubkey>,
+    pub bps: u16,
+    pub amount: u64,
+}
+
+// Emitted by `amend_schedule` with the schedule parameters after the amendment.
+#[event]
+pub struct ScheduleAmended {
+    pub data_account: Pubkey,
+    pub amendment_count: u32,
+    pub vesting_months: u8,
+    pub cliff_months: u8,
+}
+
+// A pending multisig proposal for a sensitive operation on a vesting schedule.
+// Seeds: ["release_proposal", data_account]; only one proposal can be open per schedule.
+#[account]
+pub struct ReleaseProposal {
+    pub data_account: Pubkey,
+    pub proposer: Pubkey,
+    pub action: ProposalAction,
+    // Bitmask of approvals, indexed by position in `DataAccount::approvers`
+    pub approvals: u8,
+    pub bump: u8,
+}
+
+// A manual release announced with `announce_release`; an empty slot has `effective_at == 0`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AnnouncedRelease {
+    pub id: u32,
+    pub percent: u8,
+    pub effective_at: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalAction {
+    // Increase `percent_available` by `percent`
+    Release { percent: u8 },
+    // Schedule a cancellation; it still has to be executed with `execute_cancel` after the grace period
+    Cancel,
+    // Fully accelerate the schedule, see `accelerate_vesting`
+    Accelerate,
+    // Lift an emergency freeze, see `unfreeze`
+    Unfreeze,
+}
+
+// The offsets of the leading fields are published in `vesting_common::schema` for indexers; only append fields.
+#[account]
+#[derive(Default)]
+pub struct BeneficiaryAccount {
+    pub key: Pubkey,
+    pub allocated_tokens: u64,
+    pub claimed_tokens: u64,
+    // Canonical bump of the beneficiary PDA
+    pub bump: u8,
+    // Optional wallet allowed to call `claim` on the beneficiary's behalf
+    pub claim_delegate: Option<Pubkey>,
+    // Optional token account claims are paid to instead of the beneficiary's ATA
+    pub payout_token_account: Option<Pubkey>,
+    // Number of `ClaimRecord`s written so far; the index of the next one
+    pub claim_count: u32,
+    // Time of the last claim (0 if never claimed), for the schedule's claim cooldown
+    pub last_claim_timestamp: i64,
+    // Set by `terminate_beneficiary`; the allocation was settled and no further claims are possible
+    pub terminated: bool,
+    // Set by `mint_position_nft`; the holder of this NFT is the one who can claim
+    pub position_mint: Option<Pubkey>,
+    // Set by `set_secondary_recipient`: a wallet (e.g. a recruiter) receiving `secondary_bps` of every claim
+    pub secondary_recipient: Option<Pubkey>,
+    pub secondary_bps: u16,
+    // Shares of the pool when the schedule is in shares mode (`allocated_tokens` is then unused)
+    pub shares: u64,
+    // Wallet allowed to move the position to a new wallet if the key is lost
+    pub recovery_authority: Option<Pubkey>,
+    // Wallet a recovery in progress will move the position to, and when it was started
+    pub pending_recovery_wallet: Option<Pubkey>,
+    pub recovery_initiated_at: i64,
+    // Total time the beneficiary pushed their vesting out by via `extend_my_vesting`
+    pub voluntary_extension_seconds: u32,
+    // Vested tokens locked for voting by `lock_for_voting`, unclaimable until `vote_lock_expires_at`
+    pub vote_locked_amount: u64,
+    pub vote_lock_expires_at: i64,
+    // Highest `claim_nonce` of a claim so far (0 if none was given)
+    pub last_claim_nonce: u64,
+    // Set by `accept_grant`, with the hash of the accepted agreement (all zeroes if none was given)
+    pub accepted: bool,
+    pub agreement_hash: [u8; 32],
+    // Claimed tokens parked in the escrow by `redirect_frozen_claim`, until `claim_parked` pays them out
+    pub parked_tokens: u64,
+    // Index of the position's `BeneficiaryIndex` entry
+    pub index: u32,
+    // Set by `schedule_removal`: time from which `execute_removal` can remove the position (0 = none pending)
+    pub removal_executable_at: i64,
+    // Cohort the beneficiary belongs to (see `assign_to_cohort`), whose overrides `claim` applies
+    pub cohort_id: Option<u32>,
+}
+
+// One entry of a schedule's beneficiary index, so clients can enumerate the beneficiaries at deterministic
+// addresses (indexes 0 to `beneficiary_index_len - 1`, skipping closed entries) instead of scanning program accounts.
+// Seeds: ["beneficiary_index", data_account, index as u32 little-endian]
+#[account]
+pub struct BeneficiaryIndex {
+    pub data_account: Pubkey,
+    // Beneficiary wallet; its account is ["beneficiary", data_account, beneficiary]
+    pub beneficiary: Pubkey,
+    pub bump: u8,
+}
+
+impl BeneficiaryIndex {
+    pub const LEN: usize = 8 + 32 + 32 + 1;
+}
+
+// Schedule overrides shared by a group of beneficiaries, e.g. a hiring batch with a longer cliff. Members only
+// reference the cohort by `cohort_id`, and `claim` applies its overrides when it computes their vested share, so
+// `update_cohort` changes every member at once without touching their accounts.
+// Overrides can only hold vesting back: a cohort's cliff and vesting months apply when they are longer than the
+// schedule's, so the schedule's own share (which the escrow and cancellation accounting use) still covers every
+// member. A cancellation freezes members at the schedule's share like everyone else.
+// Seeds: ["cohort", data_account, cohort_id as u32 little-endian]
+#[account]
+pub struct Cohort {
+    pub data_account: Pubkey,
+    pub cohort_id: u32,
+    pub cliff_months: Option<u8>,
+    pub vesting_months: Option<u8>,
+    // Members keep vesting over time when the schedule is accelerated
+    pub acceleration_exempt: bool,
+    pub bump: u8,
+}
+
+impl Cohort {
+    pub const LEN: usize = 8 + 32 + 4 + 2 + 2 + 1 + 1;
+
+    // Applies the overrides to the schedule of a member.
+    pub fn apply(&self, schedule: &mut math::Schedule) {
+        if let Some(cliff_months) = self.cliff_months {
+            schedule.cliff_months = std::cmp::max(schedule.cliff_months, cliff_months);
+        }
+        if let Some(vesting_months) = self.vesting_months {
+            schedule.vesting_months = std::cmp::max(schedule.vesting_months, vesting_months);
+        }
+        if self.acceleration_exempt {
+            schedule.accelerated_at = 0;
+        }
+    }
+}
+
+// `vesting_common::schema` hardcodes the discriminators for clients without Anchor; keep them in sync.
+const _: () = assert!(discriminator_matches(DataAccount::DISCRIMINATOR, &schema::DATA_ACCOUNT_DISCRIMINATOR));
+const _: () = assert!(discriminator_matches(
+    BeneficiaryAccount::DISCRIMINATOR,
+    &schema::BENEFICIARY_ACCOUNT_DISCRIMINATOR
+));
+
+const fn discriminator_matches(discriminator: &[u8], expected: &[u8; 8]) -> bool {
+    if discriminator.len() != expected.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < expected.len() {
+        if discriminator[i] != expected[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+impl BeneficiaryAccount {
+    // Share (0–10_000 basis points) of this allocation that is claimable at `now`: the schedule's share under the
+    // overrides of the beneficiary's cohort, with time vesting running `voluntary_extension_seconds` late.
+    // `cohort` has to be the beneficiary's cohort account, or `None` if it is in none.
+    pub fn vested_bps(&self, data_account: &DataAccount, cohort: Option<&Cohort>, now: i64) -> Result<u16> {
+        Ok(self
+            .schedule(data_account, cohort)?
+            .vested_bps(now.saturating_sub(self.voluntary_extension_seconds as i64)))
+    }
+
+    pub fn next_unlock_timestamp(&self, data_account: &DataAccount, cohort: Option<&Cohort>, now: i64) -> Result<i64> {
+        let extension = self.voluntary_extension_seconds as i64;
+        Ok(match self.schedule(data_account, cohort)?.next_unlock_timestamp(now.saturating_sub(extension)) {
+            0 => 0,
+            timestamp => timestamp.saturating_add(extension),
+        })
+    }
+
+    // The schedule this beneficiary vests on: the data account's, with its cohort's overrides applied.
+    fn schedule(&self, data_account: &DataAccount, cohort: Option<&Cohort>) -> Result<math::Schedule> {
+        require!(self.cohort_id == cohort.map(|cohort| cohort.cohort_id), VestingError::CohortMismatch);
+        let mut schedule = data_account.schedule();
+        if let Some(cohort) = cohort {
+            cohort.apply(&mut schedule);
+        }
+        Ok(schedule)
+    }
+
+    // Whole tokens the position can claim at `now`, see `DataAccount::claimable_amount`.
+    pub fn claimable_amount(&self, data_account: &DataAccount, cohort: Option<&Cohort>, now: i64) -> Result<u64> {
+        let vested = self.vested_amount(data_account, self.vested_bps(data_account, cohort, now)?);
+        Ok(data_account.claimable_amount(self, data_account.cap_funded(self, vested), now))
+    }
+
+    // Fails unless the position can be claimed by the plain claim paths (see `DataAccount::require_plain_claim`):
+    // it is still active and accepted, not backed by an NFT and not split with a secondary recipient.
+    pub fn require_plain_position(&self, data_account: &DataAccount) -> Result<()> {
+        require!(!self.terminated, VestingError::BeneficiaryTerminated);
+        // Grants of schedules with an acceptance step only pay out once accepted
+        require!(!data_account.require_acceptance || self.accepted, VestingError::GrantNotAccepted);
+        // NFT-backed positions are only claimable by the NFT holder through `claim`
+        require!(self.position_mint.is_none(), VestingError::PositionNftUnsupported);
+        // Payouts are only split with a secondary recipient by `claim`
+        require!(self.secondary_recipient.is_none(), VestingError::SecondaryRecipientUnsupported);
+        Ok(())
+    }
+
+    // Whole tokens locked for voting at `now` (0 once the lock has expired).
+    pub fn vote_locked(&self, now: i64) -> u64 {
+        if now < self.vote_lock_expires_at {
+            self.vote_locked_amount
+        } else {
+            0
+        }
+    }
+
+    pub fn vesting_end_timestamp(&self, data_account: &DataAccount) -> i64 {
+        data_account
+            .vesting_end_timestamp()
+            .saturating_add(self.voluntary_extension_seconds as i64)
+    }
+
+    // Leaf of the beneficiaries Merkle tree: SHA-256 of 0x00, the wallet and its allocation and shares (little-endian).
+    pub fn commitment_leaf(&self) -> [u8; 32] {
+        hashv(&[&[0], self.key.as_ref(), &self.allocated_tokens.to_le_bytes(), &self.shares.to_le_bytes()]).to_bytes()
+    }
+
+    // Part of a payout of `amount_raw` raw tokens that goes to the secondary recipient, rounded down.
+    pub fn secondary_share(&self, amount_raw: u64) -> Result<u64> {
+        match self.secondary_recipient {
+            Some(_) => Ok(math::bps_of(amount_raw, self.secondary_bps).ok_or(VestingError::MathOverflow)?),
+            None => Ok(0),
+        }
+    }
+
+    // Tokens the beneficiary is entitled to in total: its allocation, or in shares mode its share of the pool.
+    pub fn entitlement(&self, data_account: &DataAccount) -> u64 {
+        if data_account.shares_mode {
+            data_account.pool_share(self.shares)
+        } else {
+            self.allocated_tokens
+        }
+    }
+
+    // Tokens of this entitlement that have vested at the given share, in basis points.
+    pub fn vested_amount(&self, data_account: &DataAccount, bps: u16) -> u64 {
+        math::vested_amount_rounded(self.entitlement(data_account), bps, data_account.rounding())
+    }
+
+    // Tokens of this allocation still held in escrow for the beneficiary at `now`, for governance.
+    // After a cancellation the forfeited unvested part no longer counts.
+    pub fn voting_weight(&self, data_account: &DataAccount, cohort: Option<&Cohort>, now: i64) -> Result<VotingWeight> {
+        // A terminated beneficiary has nothing left in escrow
+        if self.terminated {
+            return Ok(VotingWeight::default());
+        }
+        let vested = self.vested_amount(data_account, self.vested_bps(data_account, cohort, now)?);
+        let unvested = if data_account.cancelled {
+            0
+        } else {
+            self.entitlement(data_account).saturating_sub(vested)
+        };
+        let vested_unclaimed = vested.saturating_sub(self.claimed_tokens);
+        Ok(VotingWeight {
+            unvested: raw_amount(unvested, data_account.decimals)?,
+            vested_unclaimed: raw_amount(vested_unclaimed, data_account.decimals)?,
+            voter_weight: raw_amount(unvested.saturating_add(vested_unclaimed), data_account.decimals)?,
+        })
+    }
+}
+
+// Maximum number of entries in a `BeneficiaryRegistry`.
+pub const MAX_REGISTRY_ENTRIES: usize = 2048;
+
+// Size of a `BeneficiaryRegistry` account including the discriminator.
+// It is larger than a CPI can allocate, so clients create the account themselves before `init_registry`.
+pub const REGISTRY_ACCOUNT_SPACE: usize = 8 + std::mem::size_of::<BeneficiaryRegistry>();
+
+// Zero-copy alternative to one `BeneficiaryAccount` PDA per beneficiary: a packed array of entries
+// in a single account, so large grants don't pay rent and (de)serialization per beneficiary.
+#[account(zero_copy)]
+pub struct BeneficiaryRegistry {
+    pub data_account: Pubkey,
+    // Number of used slots at the front of `entries`
+    pub count: u32,
+    pub _padding: [u8; 4],
+    pub entries: [RegistryEntry; MAX_REGISTRY_ENTRIES],
+}
+
+#[zero_copy]
+pub struct RegistryEntry {
+    pub key: Pubkey,
+    pub allocated_tokens: u64,
+    pub claimed_tokens: u64,
+}
+
+impl BeneficiaryRegistry {
+    // Index of the entry for `key` among the used slots.
+    pub fn position(&self, key: &Pubkey) -> Option<usize> {
+        self.entries[..self.count as usize]
+            .iter()
+            .position(|entry| entry.key == *key)
+    }
+
+    pub fn push(&mut self, entry: RegistryEntry) -> Result<()> {
+        let count = self.count as usize;
+        require!(count < MAX_REGISTRY_ENTRIES, VestingError::RegistryFull);
+        self.entries[count] = entry;
+        self.count += 1;
+        Ok(())
+    }
+
+    // Removes the entry at `index` by moving the last entry into its slot.
+    pub fn swap_remove(&mut self, index: usize) {
+        let last = self.count as usize - 1;
+        self.entries[index] = self.entries[last];
+        self.entries[last] = RegistryEntry {
+            key: Pubkey::default(),
+            allocated_tokens: 0,
+            claimed_tokens: 0,
+        };
+        self.count -= 1;
+    }
+}
+
+impl RegistryEntry {
+    // Same as `BeneficiaryAccount::vested_amount`.
+    pub fn vested_amount(&self, data_account: &DataAccount, bps: u16) -> u64 {
+        math::vested_amount_rounded(self.allocated_tokens, bps, data_account.rounding())
+    }
+
+    // Whole tokens the entry can claim at `now`, see `DataAccount::claimable_amount`. Entries can't lock tokens for
+    // voting.
+    pub fn claimable_amount(&self, data_account: &DataAccount, now: i64) -> u64 {
+        let vested = self.vested_amount(data_account, data_account.vested_bps(now));
+        data_account.cap_claim(
+            data_account
+                .cap_funded_entitlement(self.allocated_tokens, vested)
+                .saturating_sub(self.claimed_tokens),
+        )
+    }
+}
+
+// Longest lock `lock_for_voting` accepts; a lock this long gets the full voting weight of the locked tokens.
+pub const MAX_VOTE_LOCK_DURATION: i64 = 4 * 365 * SECONDS_PER_DAY;
+
+// Snapshot of a beneficiary's position written by `generate_statement`, in whole tokens.
+// Seeds: ["statement", beneficiary_account]
+#[account]
+pub struct StatementAccount {
+    pub data_account: Pubkey,
+    // Beneficiary wallet the statement is about
+    pub beneficiary: Pubkey,
+    pub allocated_tokens: u64,
+    pub vested_tokens: u64,
+    pub claimed_tokens: u64,
+    // Allocated but not yet claimed, vested or not
+    pub remaining_tokens: u64,
+    // Time the snapshot was taken
+    pub as_of: i64,
+    pub bump: u8,
+}
+
+impl StatementAccount {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 1;
+}
+
+// A beneficiary's vote lock, created by `lock_for_voting`. Governance programs read `voting_weight_at`.
+// Seeds: ["vote_lock", beneficiary_account]
+#[account]
+pub struct VoteLockAccount {
+    pub data_account: Pubkey,
+    // Beneficiary wallet the weight belongs to
+    pub beneficiary: Pubkey,
+    // Whole tokens locked
+    pub locked_amount: u64,
+    pub locked_at: i64,
+    pub unlock_at: i64,
+    // Weight at `locked_at`, in raw token units: `locked_amount * (unlock_at - locked_at) / MAX_VOTE_LOCK_DURATION`
+    pub voting_weight: u64,
+    pub bump: u8,
+}
+
+impl VoteLockAccount {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 1;
+
+    // Weight at `now`, decaying linearly to 0 at `unlock_at`.
+    pub fn voting_weight_at(&self, now: i64) -> u64 {
+        let total = self.unlock_at.saturating_sub(self.locked_at);
+        if total <= 0 {
+            return 0;
+        }
+        let remaining = self.unlock_at.saturating_sub(now).clamp(0, total);
+        math::mul_div(self.voting_weight, remaining as u64, total as u64).unwrap_or(0)
+    }
+}
+
+// A pending beneficiary slot created by `create_invitation`.
+// Seeds: ["invitation", data_account, invite_key]
+#[account]
+pub struct Invitation {
+    pub data_account: Pubkey,
+    // Key that has to co-sign `redeem_invitation`; its secret key is handed out off-chain
+    pub invite_key: Pubkey,
+    pub allocated_tokens: u64,
+    pub bump: u8,
+}
+
+// Reusable schedule parameters created by `create_template`.
+// Seeds: ["schedule_template", authority, template_id as u64 little-endian]
+#[account]
+pub struct ScheduleTemplate {
+    // Only this key can create pools from the template
+    pub authority: Pubkey,
+    pub template_id: u64,
+    pub vesting_months: u8,
+    pub cliff_months: u8,
+    pub calendar_months: bool,
+    pub is_cancellable: bool,
+    pub tge_unlock_bps: u16,
+    pub lockup_seconds: u32,
+    pub cancellation_penalty_bps: u16,
+    pub release_mode: ReleaseMode,
+    pub bump: u8,
+}
+
+// Maximum number of schedules a creator registry holds; older ones are dropped to make room.
+pub const MAX_CREATOR_REGISTRY_ENTRIES: usize = 64;
+
+// Schedules created by one initializer, so wallets can list "my vestings" without scanning program accounts.
+// Seeds: ["creator_registry", creator]
+// Entries are fixed-size and appended in creation order, so clients can read a page of them with
+// a `dataSlice` starting at `entry_offset(first_index)`. Entries stay after a schedule is closed, until
+// `MAX_CREATOR_REGISTRY_ENTRIES` newer schedules push them out.
+#[account]
+pub struct CreatorRegistry {
+    pub creator: Pubkey,
+    pub bump: u8,
+    pub entries: Vec<CreatorRegistryEntry>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct CreatorRegistryEntry {
+    pub data_account: Pubkey,
+    pub mint: Pubkey,
+    pub created_at: i64,
+}
+
+impl CreatorRegistry {
+    // Discriminator, `creator`, `bump` and the length prefix of `entries`.
+    pub const HEADER_LEN: usize = 8 + 32 + 1 + 4;
+    pub const ENTRY_LEN: usize = 32 + 32 + 8;
+
+    // Account size holding `entries` entries.
+    pub fn space(entries: usize) -> usize {
+        Self::HEADER_LEN + entries * Self::ENTRY_LEN
+    }
+
+    // Byte offset of entry `index` within the account data.
+    pub fn entry_offset(index: usize) -> usize {
+        Self::space(index)
+    }
+}
+
+// Maximum number of wallets a schedule's blacklist can hold.
+pub const MAX_BLACKLIST_ENTRIES: usize = 64;
+
+// Wallets blocked from claiming from a schedule, maintained by the initializer.
+// Seeds: ["blacklist", data_account]
+#[account]
+pub struct Blacklist {
+    pub data_account: Pubkey,
+    pub entries: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl Blacklist {
+    pub fn contains(&self, wallet: &Pubkey) -> bool {
+        self.entries.contains(wallet)
+    }
+}
+
+// Maximum number of steps in a release calendar, e.g. monthly releases over four years.
+pub const MAX_RELEASE_STEPS: usize = 48;
+
+// One share of a withdrawal split: `bps` of the residual go to the `recipient` token account, or are burned
+// without one.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct WithdrawShare {
+    pub recipient: Option<Pubkey>,
+    pub bps: u16,
+}
+
+// One step of a release calendar: from `timestamp` on, `percent_available` is at least `cumulative_bps`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ReleaseStep {
+    pub timestamp: i64,
+    pub cumulative_bps: u16,
+}
+
+// Pre-committed releases applied by `crank_release`, set by the initializer.
+// Seeds: ["release_calendar", data_account]
+#[account]
+pub struct ReleaseCalendar {
+    pub data_account: Pubkey,
+    pub steps: Vec<ReleaseStep>,
+    pub bump: u8,
+}
+
+impl ReleaseCalendar {
+    pub const LEN: usize = 8 + 32 + 4 + (8 + 2) * MAX_RELEASE_STEPS + 1;
+
+    // `percent_available` the calendar has reached at `now`.
+    pub fn released_percent(&self, now: i64) -> u8 {
+        self.steps
+            .iter()
+            .take_while(|step| step.timestamp <= now)
+            .last()
+            .map_or(0, |step| (step.cumulative_bps / 100) as u8)
+    }
+}
+
+// Maximum number of additional mints (legs) a schedule can pay out.
+pub const MAX_VESTING_LEGS: usize = 4;
+
+// An additional mint paid out alongside the schedule's primary mint.
+// Seeds: ["vesting_leg", data_account, index as u8]
+#[account]
+pub struct VestingLeg {
+    pub data_account: Pubkey,
+    // Position among the schedule's legs; legs are passed to `claim` in this order
+    pub index: u8,
+    pub mint: Pubkey,
+    // PDA token account ["leg_escrow", data_account, mint] owned by the data account
+    pub escrow_wallet: Pubkey,
+    // Whole leg tokens deposited, paid out pro rata against `primary_amount` primary tokens
+    pub amount: u64,
+    pub primary_amount: u64,
+    pub decimals: u8,
+    pub bump: u8,
+    pub escrow_bump: u8,
+}
+
+impl VestingLeg {
+    // Raw leg tokens owed for a claim of `claimed_amount` whole primary tokens.
+    // Formula: claimed_amount * amount * 10^decimals / primary_amount
+    pub fn payout_for(&self, claimed_amount: u64) -> Result<u64> {
+        let amount_raw = raw_amount(self.amount, self.decimals)?;
+        Ok(math::mul_div(claimed_amount, amount_raw, self.primary_amount).ok_or(VestingError::MathOverflow)?)
+    }
+}
+
+// One entry of a beneficiary's claim history, amounts in whole tokens.
+// Seeds: ["claim_record", beneficiary_account, claim_index as u32 little-endian]
+#[account]
+pub struct ClaimRecord {
+    pub timestamp: i64,
+    pub amount: u64,
+    // `claimed_tokens` of the beneficiary right after this claim
+    pub cumulative_claimed: u64,
+    pub bump: u8,
+}
+
+// Return value of `get_claimable`, in whole tokens.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ClaimableAmounts {
+    pub vested_amount: u64,
+    pub claimable_now: u64,
+    pub next_unlock_timestamp: i64,
+}
+
+// Return value of `report_accruals`, in whole tokens: vested by the start and the end of the period, and the
+// difference between them.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AccrualReport {
+    pub vested_at_start: u64,
+    pub vested_at_end: u64,
+    pub accrued: u64,
+}
+
+// Return value of `claim`, `withdraw_unclaimed` and `execute_cancel`. `amount` is the raw amount the instruction
+// transferred to the beneficiary or recipient (after fees); `claimed_total` and `remaining_tokens` are the schedule's
+// totals afterwards, in whole tokens. `remaining_tokens` is what the escrow still holds for beneficiaries and the
+// initializer: `token_amount - claimed_total - unclaimed_withdrawn`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TransferResult {
+    pub amount: u64,
+    pub claimed_total: u64,
+    pub remaining_tokens: u64,
+}
+
+// Outcome of one position in `claim_all_for_signer`. `amount` is the raw amount paid to the signer,
+// `error_code` the code of the error that skipped the position (0 if it was claimed or had nothing claimable).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PositionClaimResult {
+    pub data_account: Pubkey,
+    pub amount: u64,
+    pub error_code: u64,
+}
+
+// Most positions `claim_all_for_signer` can report within the 1024-byte return data limit.
+pub const MAX_CLAIM_ALL_POSITIONS: usize = 20;
+
+// Schedule parameters for `preview_schedule`; same meaning as the `initialize` arguments.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SchedulePreviewParams {
+    pub start_timestamp: i64,
+    pub vesting_months: u8,
+    pub cliff_months: u8,
+    pub calendar_months: bool,
+    pub tge_unlock_bps: u16,
+    pub lockup_seconds: u32,
+}
+
+// One row of the `preview_schedule` unlock table, amounts in whole tokens.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UnlockPoint {
+    pub timestamp: i64,
+    pub cumulative_amount: u64,
+}
+
+// Most rows `preview_schedule` can return within the 1024-byte return data limit.
+pub const MAX_PREVIEW_POINTS: usize = 60;
+
+// Everything `commit_schedule_hash` commits to. Fields that move as the schedule runs (releases, claims,
+// cancellation, freezes) are left out, so the hash only changes when a term of the schedule does.
+#[derive(AnchorSerialize)]
+pub struct ScheduleCommitment {
+    pub token_mint: Pubkey,
+    pub decimals: u8,
+    pub initializer: Pubkey,
+    pub token_amount: u64,
+    pub start_timestamp: i64,
+    pub vesting_months: u8,
+    pub cliff_months: u8,
+    pub calendar_months: bool,
+    pub tge_unlock_bps: u16,
+    pub lockup_seconds: u32,
+    pub release_mode: ReleaseMode,
+    pub interval_seconds: u32,
+    pub total_intervals: u16,
+    pub accelerated_at: i64,
+    pub amendment_count: u32,
+    pub leg_count: u8,
+    pub is_cancellable: bool,
+    pub cancellation_penalty_bps: u16,
+    pub forfeiture_pool: Option<Pubkey>,
+    pub forfeiture_bps: u16,
+    pub claim_deadline: i64,
+    pub min_claim_interval_seconds: u32,
+    pub max_claim_per_period: u64,
+    pub mint_vesting: bool,
+    pub shares_mode: bool,
+    pub non_transferable: bool,
+    pub claim_approver: Option<Pubkey>,
+    pub gate_program: Option<Pubkey>,
+    pub on_claim_hook: Option<Pubkey>,
+    pub payout_mint: Option<Pubkey>,
+    pub payout_ratio_numerator: u64,
+    pub payout_ratio_denominator: u64,
+    pub beneficiaries_root: [u8; 32],
+}
+
+// Merkle root committed by `commit_schedule_hash`, over the `commitment_leaf`s of all of a schedule's
+// `BeneficiaryAccount`s. Leaves are sorted, each parent is SHA-256 of 0x01 and its two children in ascending order,
+// and an unpaired node moves up a level unchanged. No beneficiaries give a root of zeros.
+pub fn beneficiaries_merkle_root(mut leaves: Vec<[u8; 32]>) -> [u8; 32] {
+    if leaves.is_empty() {
+        return [0; 32];
+    }
+    leaves.sort_unstable();
+    while leaves.len() > 1 {
+        leaves = leaves
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => hashv(&[&[1], a.min(b), a.max(b)]).to_bytes(),
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    leaves[0]
+}
+
+// Return value of `verify_schedule`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ScheduleVerification {
+    pub committed_hash: [u8; 32],
+    pub current_hash: [u8; 32],
+    pub committed_at: i64,
+    pub matches: bool,
+}
+
+// Return value of `get_program_version`; versions are [major, minor, patch].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProgramVersion {
+    pub version: [u16; 3],
+    pub min_supported_version: [u16; 3],
+    // `features::FEATURE_*` bits enabled on this cluster
+    pub features: u64,
+}
+
+// Return value of `get_voting_weight`, in raw token units like SPL Governance voter weights.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct VotingWeight {
+    pub unvested: u64,
+    pub vested_unclaimed: u64,
+    // unvested + vested_unclaimed
+    pub voter_weight: u64,
+}
+
+// Voter weight record of the SPL Governance voter-weight addin interface.
+// The layout (including the "account:VoterWeightRecord" discriminator) matches `spl-governance-addin-api`,
+// so a realm configured with this program as voter weight addin can read it directly.
+// Seeds: ["voter-weight-record", realm, governing_token_mint, governing_token_owner]
+#[account]
+pub struct VoterWeightRecord {
+    pub realm: Pubkey,
+    pub governing_token_mint: Pubkey,
+    pub governing_token_owner: Pubkey,
+    pub voter_weight: u64,
+    // Slot the weight is valid for; SPL Governance rejects stale records
+    pub voter_weight_expiry: Option<u64>,
+    pub weight_action: Option<VoterWeightAction>,
+    pub weight_action_target: Option<Pubkey>,
+    pub reserved: [u8; 8],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum VoterWeightAction {
+    CastVote,
+    CommentProposal,
+    CreateGovernance,
+    CreateProposal,
+    SignOffProposal,
+}
+
+// Program-wide settings for running the program as a hosted vesting service.
+// Seeds: ["config"]
+#[account]
+pub struct Config {
+    // Owner of the token accounts that receive claim fees
+    pub fee_recipient: Pubkey,
+    // Fee skimmed from every claim, in basis points
+    pub fee_bps: u16,
+    // Program escrows may be staked with via `stake_escrow`, if any
+    pub staking_program: Option<Pubkey>,
+    pub bump: u8,
+}
+
+impl Config {
+    // Fee owed on a claim of `amount` raw token units, rounded down.
+    pub fn fee_for(&self, amount: u64) -> Result<u64> {
+        Ok(math::bps_of(amount, self.fee_bps).ok_or(VestingError::MathOverflow)?)
+    }
+}
+
+// Program-wide kill switch, set by the upgrade authority with `set_program_halted`. Every instruction that changes
+// state takes it through `ProgramStatus` and fails with `ProgramHalted` while `is_halted` is set. Until the
+// upgrade authority first creates it, the program runs on the defaults: not halted, no features, no sunset.
+// Seeds: ["program_state"]
+#[account]
+#[derive(Default)]
+pub struct ProgramState {
+    pub is_halted: bool,
+    pub bump: u8,
+    // `features::FEATURE_*` bits enabled with `set_program_features`
+    pub features: u64,
+    // Time from which the program is retired and `sunset_claim` is open, set by `set_sunset_mode` (0 = none)
+    pub sunset_at: i64,
+    // How far before and after its creation a schedule may start, in seconds, set by `set_start_bounds`
+    // (0 = `MAX_START_IN_PAST` / `MAX_START_IN_FUTURE`)
+    pub max_start_in_past: u32,
+    pub max_start_in_future: u32,
+}
+
+impl ProgramState {
+    pub const LEN: usize = 8 + 1 + 1 + 8 + 8 + 4 + 4;
+
+    pub fn has_feature(&self, feature: u64) -> bool {
+        self.features & feature == feature
+    }
+
+    pub fn is_sunset(&self, now: i64) -> bool {
+        self.sunset_at != 0 && now >= self.sunset_at
+    }
+
+    // Earliest and latest `start_timestamp` a schedule created at `now` may have.
+    pub fn start_range(&self, now: i64) -> (i64, i64) {
+        let past = match self.max_start_in_past {
+            0 => MAX_START_IN_PAST,
+            seconds => seconds as i64,
+        };
+        let future = match self.max_start_in_future {
+            0 => MAX_START_IN_FUTURE,
+            seconds => seconds as i64,
+        };
+        (now - past, now + future)
+    }
+}
+
+// The program state account of every instruction that changes state, failing with `ProgramHalted` while the
+// program is halted. The PDA is optional on chain: as long as it doesn't exist, nothing is halted.
+#[derive(Accounts)]
+pub struct ProgramStatus<'info> {
+    /// CHECK: The `ProgramState` PDA, which may not exist yet; read with `read_program_state`.
+    #[account(
+        seeds = [PROGRAM_STATE_SEED],
+        bump,
+        constraint = !read_program_state(&program_state)?.is_halted @ VestingError::ProgramHalted,
+    )]
+    pub program_state: UncheckedAccount<'info>,
+}
+
+impl ProgramStatus<'_> {
+    // The program state, or its defaults while the PDA doesn't exist.
+    pub fn load(&self) -> Result<ProgramState> {
+        read_program_state(&self.program_state)
+    }
+}
+
+// Reads the program state PDA, falling back to `ProgramState::default()` while it hasn't been created.
+pub fn read_program_state(state_info: &AccountInfo) -> Result<ProgramState> {
+    if *state_info.owner != crate::ID {
+        return Ok(ProgramState::default());
+    }
+    // Deserializing checks the discriminator
+    ProgramState::try_deserialize(&mut &state_info.data.borrow()[..])
+}
+
+// Swap program `claim_and_swap` may route claims through, set by the upgrade authority with `set_swap_program`.
+// Kept out of `Config` so the existing config account doesn't have to grow.
+// Seeds: ["swap_config"]
+#[account]
+pub struct SwapConfig {
+    pub swap_program: Option<Pubkey>,
+    pub bump: u8,
+}
+
+impl SwapConfig {
+    pub const LEN: usize = 8 + 33 + 1;
+}
+
+// Upper bound on `Config::fee_bps` (10%).
+pub const MAX_FEE_BPS: u16 = 1_000;
+
+// Emitted for every claim, in raw token units: `amount` reached the beneficiary and `fee` went to `fee_recipient`.
+#[event]
+pub struct TokensClaimed {
+    pub data_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub fee_recipient: Pubkey,
+    // Share of the claim paid to the beneficiary's secondary recipient
+    pub secondary_amount: u64,
+    // SHA-256 of the memo written by `claim_with_memo`
+    pub memo_hash: Option<[u8; 32]>,
+}
+
+// Longest memo `claim_with_memo` accepts, in bytes.
+pub const MAX_MEMO_LEN: usize = 256;
+
+// `VestingError` is generated from the list in `vesting_common::error`, which clients share.
+macro_rules! anchor_vesting_error {
+    ($(#[msg($msg:literal)] $name:ident,)*) => {
+        #[error_code]
+        pub enum VestingError {
+            $(#[msg($msg)] $name,)*
+        }
+    };
+}
+
+vesting_common::with_vesting_errors!(anchor_vesting_error);
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct NewBeneficiary {
+    pub key: Pubkey,
+    pub allocated_tokens: u64,
+    // Wallet that can move the position to a new wallet if the key is lost (see `initiate_recovery`)
+    pub recovery_authority: Option<Pubkey>,
+}
+#[derive(Accounts)]
+pub struct ScheduleRemoval<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.can_operate(&sender.key()) @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+
+    // Pass each BeneficiaryAccount (writable) in remaining_accounts[]
+}
+
+#[derive(Accounts)]
+pub struct VetoRemoval<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.removal_arbiter == Some(sender.key()) @ VestingError::InvalidSender,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), beneficiary_account.key.as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteRemoval<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.can_operate(&sender.key()) @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    /// CHECK: Only receives the rent of the closed accounts: the original payer, a treasury or the beneficiary.
+    #[account(mut)]
+    pub rent_recipient: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+
+    // Pass each BeneficiaryAccount and its BeneficiaryIndex in remaining_accounts[]
+}
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteCancel<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut)]
+    // The program-owned escrow token account that temporarily holds tokens until conditions are met.
+    pub escrow_wallet: Account<'info, TokenAccount>,
+    
+ // The recipient's token account where tokens will be sent once escrow conditions are fulfilled.
+    #[account(
+        mut,
+        constraint = recipient.mint == token_mint.key() @ VestingError::InvalidTokenAccount,
+        constraint = recipient.delegate.is_none() @ VestingError::TokenAccountHasDelegate,
+        constraint = recipient.close_authority.is_none() @ VestingError::TokenAccountHasCloseAuthority,
+    )]
+    pub recipient: Account<'info, TokenAccount>,
+
+      // The signer (payer/initiator) of the transaction, usually the one depositing tokens into escrow.
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    // The SPL token mint for the token being escrowed (e.g., USDC, custom token).
+    pub token_mint: Account<'info, Mint>,
+    // The SPL Token Program — required to perform token transfers and account operations.
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVesting<'info> {
+    #[account(
+        mut,
+        close = rent_recipient,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    /// CHECK: Only receives the rent of both closed accounts, e.g. the initializer or a treasury.
+    #[account(mut)]
+    pub rent_recipient: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SunsetClaim<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = rent_recipient,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), beneficiary_wallet.key().as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    #[account(
+        mut,
+        close = rent_recipient,
+        seeds = [BENEFICIARY_INDEX_SEED, data_account.key().as_ref(), &beneficiary_account.index.to_le_bytes()],
+        bump = beneficiary_index.bump,
+    )]
+    pub beneficiary_index: Account<'info, BeneficiaryIndex>,
+
+    /// CHECK: Only used as PDA seed and ATA authority; the seeds tie it to `beneficiary_account`.
+    pub beneficiary_wallet: UncheckedAccount<'info>,
+
+    // Receives what is left of the allocation; created by the sender if needed.
+    #[account(
+        init_if_needed,
+        payer = sender,
+        associated_token::mint = token_mint,
+        associated_token::authority = beneficiary_wallet,
+    )]
+    pub wallet_to_deposit_to: Account<'info, TokenAccount>,
+
+    /// CHECK: Only receives the rent of the closed accounts; must be the initializer, who paid it.
+    #[account(mut, address = data_account.initializer @ VestingError::InvalidSender)]
+    pub rent_recipient: UncheckedAccount<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+    // Anyone; pays for the ATA if it has to be created.
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SunsetCloseSchedule<'info> {
+    #[account(
+        mut,
+        close = initializer,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    // The initializer's token account receiving the tokens left in the escrow.
+    #[account(
+        mut,
+        constraint = recipient.mint == token_mint.key() @ VestingError::InvalidTokenAccount,
+        constraint = recipient.owner == data_account.initializer @ VestingError::InvalidRecipient,
+        constraint = recipient.delegate.is_none() @ VestingError::TokenAccountHasDelegate,
+        constraint = recipient.close_authority.is_none() @ VestingError::TokenAccountHasCloseAuthority,
+    )]
+    pub recipient: Account<'info, TokenAccount>,
+
+    /// CHECK: Only receives the rent of the closed accounts; must be the initializer.
+    #[account(mut, address = data_account.initializer @ VestingError::InvalidSender)]
+    pub initializer: UncheckedAccount<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+    // Anyone.
+    pub sender: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawLegRemainder<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [VESTING_LEG_SEED, data_account.key().as_ref(), &vesting_leg.index.to_le_bytes()],
+        bump = vesting_leg.bump,
+    )]
+    pub vesting_leg: Account<'info, VestingLeg>,
+
+    #[account(mut, address = vesting_leg.escrow_wallet @ VestingError::InvalidEscrowWallet)]
+    pub leg_escrow: Account<'info, TokenAccount>,
+
+    #[account(address = vesting_leg.mint @ VestingError::InvalidTokenAccount)]
+    pub leg_mint: Account<'info, Mint>,
+
+    // The initializer's token account for the leg mint.
+    #[account(
+        mut,
+        constraint = recipient.mint == leg_mint.key() @ VestingError::InvalidTokenAccount,
+        constraint = recipient.owner == sender.key() @ VestingError::InvalidRecipient,
+    )]
+    pub recipient: Account<'info, TokenAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DepositAdditional<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    // The initializer's token account the top-up is taken from.
+    #[account(
+        mut,
+        constraint = wallet_to_withdraw_from.mint == token_mint.key() @ VestingError::InvalidTokenAccount,
+    )]
+    pub wallet_to_withdraw_from: Account<'info, TokenAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FundInstallment<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    // The initializer's token account the installment is taken from.
+    #[account(
+        mut,
+        constraint = wallet_to_withdraw_from.mint == token_mint.key() @ VestingError::InvalidTokenAccount,
+    )]
+    pub wallet_to_withdraw_from: Account<'info, TokenAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetApprovers<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeRelease<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    // Space: 8 (discriminator) + 32 (data_account) + 32 (proposer) + 2 (action) + 1 (approvals) + 1 (bump)
+    #[account(
+        init,
+        payer = sender,
+        seeds = [RELEASE_PROPOSAL_SEED, data_account.key().as_ref()],
+        bump,
+        space = 8 + 32 + 32 + 2 + 1 + 1
+    )]
+    pub proposal: Account<'info, ReleaseProposal>,
+
+    pub token_mint: Account<'info, Mint>,
+    // The approver opening the proposal; pays for the proposal account.
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveRelease<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [RELEASE_PROPOSAL_SEED, data_account.key().as_ref()],
+        bump = proposal.bump,
+        has_one = data_account,
+    )]
+    pub proposal: Account<'info, ReleaseProposal>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteRelease<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [RELEASE_PROPOSAL_SEED, data_account.key().as_ref()],
+        bump = proposal.bump,
+        has_one = data_account,
+        has_one = proposer,
+    )]
+    pub proposal: Account<'info, ReleaseProposal>,
+
+    // Receives the rent of the closed proposal.
+    #[account(mut)]
+    pub proposer: SystemAccount<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelReleaseProposal<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [RELEASE_PROPOSAL_SEED, data_account.key().as_ref()],
+        bump = proposal.bump,
+        has_one = data_account,
+        has_one = proposer,
+    )]
+    pub proposal: Account<'info, ReleaseProposal>,
+
+    // Receives the rent of the closed proposal.
+    #[account(mut)]
+    pub proposer: SystemAccount<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ScheduleCancel<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetClaimable<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), beneficiary_account.key.as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    // Required when the beneficiary is in a cohort: its `Cohort` account.
+    #[account(
+        seeds = [COHORT_SEED, data_account.key().as_ref(), &cohort.cohort_id.to_le_bytes()],
+        bump = cohort.bump,
+    )]
+    pub cohort: Option<Account<'info, Cohort>>,
+
+    pub token_mint: Account<'info, Mint>,
+}
+
+#[derive(Accounts)]
+pub struct ReportAccruals<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    // Omit to report on the whole schedule
+    #[account(
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), beneficiary_account.key.as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Option<Account<'info, BeneficiaryAccount>>,
+
+    pub token_mint: Account<'info, Mint>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimAllForSigner<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    // Absent until `initialize_config` has run; claims are free until then.
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Option<Account<'info, Config>>,
+    // Co-signs for every schedule in the batch that has a `claim_approver`.
+    pub claim_approver: Option<Signer<'info>>,
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+
+    // Same remaining_accounts[] tuples as `ClaimMany`
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimMany<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    // Absent until `initialize_config` has run; claims are free until then.
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Option<Account<'info, Config>>,
+    // Co-signs for every schedule in the batch that has a `claim_approver`.
+    pub claim_approver: Option<Signer<'info>>,
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+
+    // (data_account, escrow_wallet, beneficiary_account, wallet_to_deposit_to, fee_token_account) tuples
+    // are passed in remaining_accounts[]
+}
+
+#[derive(Accounts)]
+pub struct SetRecoveryAuthority<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), sender.key().as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    // The beneficiary wallet (the original key).
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitiateRecovery<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), beneficiary_account.key.as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    // The position's recovery authority.
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteRecovery<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    // Closed once the position has moved; the rent goes to the recovery authority paying for the new account.
+    #[account(
+        mut,
+        close = sender,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), beneficiary_account.key.as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    // Fails if the new wallet is already a beneficiary of this schedule.
+    #[account(
+        init,
+        payer = sender,
+        space = std::mem::size_of::<BeneficiaryAccount>() + 8,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), new_wallet.key().as_ref()],
+        bump,
+    )]
+    pub new_beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    // The position's beneficiary index entry, moved to the new wallet.
+    #[account(
+        mut,
+        seeds = [BENEFICIARY_INDEX_SEED, data_account.key().as_ref(), &beneficiary_account.index.to_le_bytes()],
+        bump = beneficiary_index.bump,
+    )]
+    pub beneficiary_index: Account<'info, BeneficiaryIndex>,
+
+    /// CHECK: Must be the pending recovery wallet; only used as the PDA seed and key of the new position.
+    pub new_wallet: UncheckedAccount<'info>,
+
+    // Required once the schedule has a blacklist.
+    #[account(
+        seeds = [BLACKLIST_SEED, data_account.key().as_ref()],
+        bump = blacklist.bump,
+    )]
+    pub blacklist: Option<Account<'info, Blacklist>>,
+
+    pub token_mint: Account<'info, Mint>,
+    // The position's recovery authority.
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendMyVesting<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), sender.key().as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    // The beneficiary wallet; only it can extend its own vesting.
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetClaimDelegate<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), sender.key().as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    // The beneficiary wallet; only it can change its delegate.
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptGrant<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), sender.key().as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    // The beneficiary wallet; only it can accept its grant.
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPayoutAccount<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), sender.key().as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    #[account(constraint = payout_token_account.mint == token_mint.key() @ VestingError::InvalidTokenAccount)]
+    pub payout_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_mint: Account<'info, Mint>,
+    // The beneficiary wallet; only it can change where its tokens are paid.
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AmendSchedule<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    // Only needed when amending a beneficiary's allocation; its PDA is checked in the handler.
+    #[account(mut)]
+    pub beneficiary_account: Option<Account<'info, BeneficiaryAccount>>,
+
+    // Required when `beneficiary_account` is in a cohort: its `Cohort` account.
+    #[account(
+        seeds = [COHORT_SEED, data_account.key().as_ref(), &cohort.cohort_id.to_le_bytes()],
+        bump = cohort.bump,
+    )]
+    pub cohort: Option<Account<'info, Cohort>>,
+
+    // The beneficiary's co-signature, required when their allocation is reduced.
+    pub beneficiary: Option<Signer<'info>>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + 32 + 2 + 33 + 1,
+        seeds = [CONFIG_SEED],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ VestingError::InvalidSender)]
+    pub program: Program<'info, crate::program::TokenVesting>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(sender.key()) @ VestingError::InvalidSender)]
+    pub program_data: Account<'info, ProgramData>,
+
+    // The program's upgrade authority
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(feature = "mock-clock")]
+#[derive(Accounts)]
+pub struct SetMockClock<'info> {
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = MockClock::LEN,
+        seeds = [MOCK_CLOCK_SEED],
+        bump,
+    )]
+    pub mock_clock: Account<'info, MockClock>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ VestingError::InvalidSender)]
+    pub program: Program<'info, crate::program::TokenVesting>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(sender.key()) @ VestingError::InvalidSender)]
+    pub program_data: Account<'info, ProgramData>,
+
+    // The program's upgrade authority
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetClaimApprover<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetClaimGate<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetClaimHook<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetWithholding<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMetadata<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CommitScheduleHash<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifySchedule<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+}
+
+#[derive(Accounts)]
+pub struct AccelerateVesting<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRecordClaims<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRoundingPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetVestingCurve<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxBeneficiaries<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRequireAcceptance<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitRegistry<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(zero)]
+    pub registry: AccountLoader<'info, BeneficiaryRegistry>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ModifyRegistry<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut, constraint = registry.load()?.data_account == data_account.key() @ VestingError::InvalidRegistry)]
+    pub registry: AccountLoader<'info, BeneficiaryRegistry>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RedirectFrozenClaim<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), beneficiary_account.key.as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    // Required when the beneficiary is in a cohort: its `Cohort` account.
+    #[account(
+        seeds = [COHORT_SEED, data_account.key().as_ref(), &cohort.cohort_id.to_le_bytes()],
+        bump = cohort.bump,
+    )]
+    pub cohort: Option<Account<'info, Cohort>>,
+
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    // The account the beneficiary's claims are paid into: their payout account if set, otherwise one they own.
+    #[account(
+        constraint = recipient_token_account.mint == token_mint.key() @ VestingError::InvalidTokenAccount,
+        constraint = match beneficiary_account.payout_token_account {
+            Some(payout) => recipient_token_account.key() == payout,
+            None => recipient_token_account.owner == beneficiary_account.key,
+        } @ VestingError::InvalidTokenAccount,
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimParkedTokens<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), beneficiary_account.key.as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    #[account(mut)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    // The beneficiary or their claim delegate; pays for the fee ATA if it has to be created.
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    // The beneficiary's payout account if set, otherwise a token account they own.
+    #[account(
+        mut,
+        constraint = wallet_to_deposit_to.mint == token_mint.key() @ VestingError::InvalidTokenAccount,
+        constraint = match beneficiary_account.payout_token_account {
+            Some(payout) => wallet_to_deposit_to.key() == payout,
+            None => wallet_to_deposit_to.owner == beneficiary_account.key,
+        } @ VestingError::InvalidTokenAccount,
+        constraint = wallet_to_deposit_to.delegate.is_none() @ VestingError::TokenAccountHasDelegate,
+        constraint = wallet_to_deposit_to.close_authority.is_none() @ VestingError::TokenAccountHasCloseAuthority,
+    )]
+    pub wallet_to_deposit_to: Account<'info, TokenAccount>,
+
+    // Absent until `initialize_config` has run; claims are free until then.
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Option<Account<'info, Config>>,
+
+    /// CHECK: Only used as the fee ATA authority; must match `config.fee_recipient`.
+    #[account(
+        constraint = config.as_ref().is_some_and(|config| config.fee_recipient == fee_recipient.key())
+            @ VestingError::InvalidFeeAccount,
+    )]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        init_if_needed,
+        payer = sender,
+        associated_token::mint = token_mint,
+        associated_token::authority = fee_recipient,
+    )]
+    pub fee_token_account: Option<Account<'info, TokenAccount>>,
+
+    // Required when the schedule has a `claim_approver`; must be that key.
+    pub claim_approver: Option<Signer<'info>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RegistryClaim<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut, constraint = registry.load()?.data_account == data_account.key() @ VestingError::InvalidRegistry)]
+    pub registry: AccountLoader<'info, BeneficiaryRegistry>,
+
+    #[account(mut)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    // The beneficiary; pays for the ATAs if they have to be created.
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = sender,
+        associated_token::mint = token_mint,
+        associated_token::authority = sender,
+        constraint = wallet_to_deposit_to.delegate.is_none() @ VestingError::TokenAccountHasDelegate,
+        constraint = wallet_to_deposit_to.close_authority.is_none() @ VestingError::TokenAccountHasCloseAuthority,
+    )]
+    pub wallet_to_deposit_to: Account<'info, TokenAccount>,
+
+    // Absent until `initialize_config` has run; claims are free until then.
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Option<Account<'info, Config>>,
+
+    /// CHECK: Only used as the fee ATA authority; must match `config.fee_recipient`.
+    #[account(
+        constraint = config.as_ref().is_some_and(|config| config.fee_recipient == fee_recipient.key())
+            @ VestingError::InvalidFeeAccount,
+    )]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        init_if_needed,
+        payer = sender,
+        associated_token::mint = token_mint,
+        associated_token::authority = fee_recipient,
+    )]
+    pub fee_token_account: Option<Account<'info, TokenAccount>>,
+
+    // Required when the schedule has a `claim_approver`; must be that key.
+    pub claim_approver: Option<Signer<'info>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyFreeze<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.guardian == Some(sender.key()) @ VestingError::NotGuardian,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    // The schedule's guardian
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Unfreeze<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimAndUnwrap<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), beneficiary_wallet.key().as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    // Required when the beneficiary is in a cohort: its `Cohort` account.
+    #[account(
+        seeds = [COHORT_SEED, data_account.key().as_ref(), &cohort.cohort_id.to_le_bytes()],
+        bump = cohort.bump,
+    )]
+    pub cohort: Option<Account<'info, Cohort>>,
+
+    #[account(mut)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    // Receives the unwrapped SOL.
+    #[account(mut)]
+    pub beneficiary_wallet: SystemAccount<'info>,
+
+    // The beneficiary or their claim delegate; pays for the temporary wSOL account.
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    #[account(address = token::spl_token::native_mint::ID @ VestingError::NotNativeMint)]
+    pub token_mint: Account<'info, Mint>,
+
+    // Temporary wSOL account, closed again before the instruction returns.
+    #[account(
+        init,
+        payer = sender,
+        seeds = [UNWRAP_SEED, beneficiary_account.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = data_account,
+    )]
+    pub unwrap_account: Account<'info, TokenAccount>,
+
+    // Absent until `initialize_config` has run; claims are free until then.
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Option<Account<'info, Config>>,
+
+    /// CHECK: Only used as the fee ATA authority; must match `config.fee_recipient`.
+    #[account(
+        constraint = config.as_ref().is_some_and(|config| config.fee_recipient == fee_recipient.key())
+            @ VestingError::InvalidFeeAccount,
+    )]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        init_if_needed,
+        payer = sender,
+        associated_token::mint = token_mint,
+        associated_token::authority = fee_recipient,
+    )]
+    pub fee_token_account: Option<Account<'info, TokenAccount>>,
+
+    // Required when the schedule has a `claim_approver`; must be that key.
+    pub claim_approver: Option<Signer<'info>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimAndSwap<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), beneficiary_wallet.key().as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    // Required when the beneficiary is in a cohort: its `Cohort` account.
+    #[account(
+        seeds = [COHORT_SEED, data_account.key().as_ref(), &cohort.cohort_id.to_le_bytes()],
+        bump = cohort.bump,
+    )]
+    pub cohort: Option<Account<'info, Cohort>>,
+
+    #[account(mut)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    /// CHECK: Only used as the PDA seed and the owner of `output_token_account`.
+    pub beneficiary_wallet: UncheckedAccount<'info>,
+
+    // Receives the swapped tokens; the beneficiary's account of another mint.
+    #[account(
+        mut,
+        token::authority = beneficiary_wallet,
+        constraint = output_token_account.mint != token_mint.key() @ VestingError::InvalidTokenAccount,
+    )]
+    pub output_token_account: Account<'info, TokenAccount>,
+
+    // The beneficiary or their claim delegate; pays for the temporary account.
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    /// CHECK: PDA owning `swap_account` and signing the swap; it has authority over nothing else.
+    #[account(seeds = [SWAP_AUTHORITY_SEED, beneficiary_account.key().as_ref()], bump)]
+    pub swap_authority: UncheckedAccount<'info>,
+
+    // Temporary account the swap trades from, closed again before the instruction returns.
+    #[account(
+        init,
+        payer = sender,
+        seeds = [SWAP_SEED, beneficiary_account.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = swap_authority,
+    )]
+    pub swap_account: Account<'info, TokenAccount>,
+
+    #[account(seeds = [SWAP_CONFIG_SEED], bump = swap_config.bump)]
+    pub swap_config: Account<'info, SwapConfig>,
+
+    /// CHECK: Only invoked; must be the swap program whitelisted in `swap_config`.
+    #[account(
+        executable,
+        constraint = swap_config.swap_program == Some(swap_program.key()) @ VestingError::SwapProgramNotWhitelisted,
+    )]
+    pub swap_program: UncheckedAccount<'info>,
+
+    // Absent until `initialize_config` has run; claims are free until then.
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Option<Account<'info, Config>>,
+
+    /// CHECK: Only used as the fee ATA authority; must match `config.fee_recipient`.
+    #[account(
+        constraint = config.as_ref().is_some_and(|config| config.fee_recipient == fee_recipient.key())
+            @ VestingError::InvalidFeeAccount,
+    )]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        init_if_needed,
+        payer = sender,
+        associated_token::mint = token_mint,
+        associated_token::authority = fee_recipient,
+    )]
+    pub fee_token_account: Option<Account<'info, TokenAccount>>,
+
+    // Required when the schedule has a `claim_approver`; must be that key.
+    pub claim_approver: Option<Signer<'info>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+
+    // The swap program's accounts (the route) are passed in remaining_accounts[]
+}
+
+#[derive(Accounts)]
+pub struct SetProgramHalted<'info> {
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = ProgramState::LEN,
+        seeds = [PROGRAM_STATE_SEED],
+        bump,
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ VestingError::InvalidSender)]
+    pub program: Program<'info, crate::program::TokenVesting>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(sender.key()) @ VestingError::InvalidSender)]
+    pub program_data: Account<'info, ProgramData>,
+
+    // The program's upgrade authority
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetProgramFeatures<'info> {
+    /// CHECK: A program state created before `features` existed doesn't deserialize as the current `ProgramState`,
+    /// so the handler checks the owner, grows the account, and only then deserializes it.
+    #[account(
+        mut,
+        seeds = [PROGRAM_STATE_SEED],
+        bump,
+    )]
+    pub program_state: UncheckedAccount<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ VestingError::InvalidSender)]
+    pub program: Program<'info, crate::program::TokenVesting>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(sender.key()) @ VestingError::InvalidSender)]
+    pub program_data: Account<'info, ProgramData>,
+
+    // The program's upgrade authority
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetSunsetMode<'info> {
+    /// CHECK: Checked, grown and deserialized by the handler, as in `SetProgramFeatures`.
+    #[account(
+        mut,
+        seeds = [PROGRAM_STATE_SEED],
+        bump,
+    )]
+    pub program_state: UncheckedAccount<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ VestingError::InvalidSender)]
+    pub program: Program<'info, crate::program::TokenVesting>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(sender.key()) @ VestingError::InvalidSender)]
+    pub program_data: Account<'info, ProgramData>,
+
+    // The program's upgrade authority
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetStartBounds<'info> {
+    /// CHECK: Checked, grown and deserialized by the handler, as in `SetProgramFeatures`.
+    #[account(
+        mut,
+        seeds = [PROGRAM_STATE_SEED],
+        bump,
+    )]
+    pub program_state: UncheckedAccount<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ VestingError::InvalidSender)]
+    pub program: Program<'info, crate::program::TokenVesting>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(sender.key()) @ VestingError::InvalidSender)]
+    pub program_data: Account<'info, ProgramData>,
+
+    // The program's upgrade authority
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetProgramVersion<'info> {
+    /// CHECK: The `ProgramState` PDA, which may not exist yet; read with `read_program_state`.
+    #[account(seeds = [PROGRAM_STATE_SEED], bump)]
+    pub program_state: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSwapProgram<'info> {
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = SwapConfig::LEN,
+        seeds = [SWAP_CONFIG_SEED],
+        bump,
+    )]
+    pub swap_config: Account<'info, SwapConfig>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ VestingError::InvalidSender)]
+    pub program: Program<'info, crate::program::TokenVesting>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(sender.key()) @ VestingError::InvalidSender)]
+    pub program_data: Account<'info, ProgramData>,
+
+    // The program's upgrade authority
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(mut, seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ VestingError::InvalidSender)]
+    pub program: Program<'info, crate::program::TokenVesting>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(sender.key()) @ VestingError::InvalidSender)]
+    pub program_data: Account<'info, ProgramData>,
+
+    // The program's upgrade authority
+    pub sender: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct StakeEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Only invoked; must be the staking program whitelisted in `config`.
+    #[account(
+        executable,
+        constraint = config.staking_program == Some(staking_program.key()) @ VestingError::StakingProgramNotWhitelisted,
+    )]
+    pub staking_program: UncheckedAccount<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+
+    // The staking program's accounts are passed in remaining_accounts[]
+}
+
+#[derive(Accounts)]
+pub struct UnstakeEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Only invoked; must be the staking program whitelisted in `config`.
+    #[account(
+        executable,
+        constraint = config.staking_program == Some(staking_program.key()) @ VestingError::StakingProgramNotWhitelisted,
+    )]
+    pub staking_program: UncheckedAccount<'info>,
+
+    // Receives the staking yield
+    #[account(
+        mut,
+        constraint = recipient.mint == token_mint.key() @ VestingError::InvalidTokenAccount,
+        constraint = recipient.owner == sender.key() @ VestingError::InvalidRecipient,
+        constraint = recipient.delegate.is_none() @ VestingError::TokenAccountHasDelegate,
+        constraint = recipient.close_authority.is_none() @ VestingError::TokenAccountHasCloseAuthority,
+    )]
+    pub recipient: Account<'info, TokenAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(realm: Pubkey)]
+pub struct CreateVoterWeightRecord<'info> {
+    // Voter weight records are keyed by mint, which only identifies v1 schedules
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.seed_version == SEED_VERSION_V1 @ VestingError::SeedVersionUnsupported,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), beneficiary_account.key.as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 32 + 32 + 32 + 8 + 9 + 2 + 33 + 8,
+        seeds = [
+            VOTER_WEIGHT_RECORD_SEED,
+            realm.as_ref(),
+            token_mint.key().as_ref(),
+            beneficiary_account.key.as_ref(),
+        ],
+        bump,
+    )]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateVoterWeightRecord<'info> {
+    // Voter weight records are keyed by mint, which only identifies v1 schedules
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.seed_version == SEED_VERSION_V1 @ VestingError::SeedVersionUnsupported,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), beneficiary_account.key.as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    // Required when the beneficiary is in a cohort: its `Cohort` account.
+    #[account(
+        seeds = [COHORT_SEED, data_account.key().as_ref(), &cohort.cohort_id.to_le_bytes()],
+        bump = cohort.bump,
+    )]
+    pub cohort: Option<Account<'info, Cohort>>,
+
+    #[account(
+        mut,
+        seeds = [
+            VOTER_WEIGHT_RECORD_SEED,
+            voter_weight_record.realm.as_ref(),
+            token_mint.key().as_ref(),
+            beneficiary_account.key.as_ref(),
+        ],
+        bump,
+    )]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct LockForVoting<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), sender.key().as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    // Required when the beneficiary is in a cohort: its `Cohort` account.
+    #[account(
+        seeds = [COHORT_SEED, data_account.key().as_ref(), &cohort.cohort_id.to_le_bytes()],
+        bump = cohort.bump,
+    )]
+    pub cohort: Option<Account<'info, Cohort>>,
+
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = VoteLockAccount::LEN,
+        seeds = [VOTE_LOCK_SEED, beneficiary_account.key().as_ref()],
+        bump,
+    )]
+    pub vote_lock: Account<'info, VoteLockAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    // The beneficiary wallet; pays for the vote lock account on the first lock.
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GenerateStatement<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), sender.key().as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    // Required when the beneficiary is in a cohort: its `Cohort` account.
+    #[account(
+        seeds = [COHORT_SEED, data_account.key().as_ref(), &cohort.cohort_id.to_le_bytes()],
+        bump = cohort.bump,
+    )]
+    pub cohort: Option<Account<'info, Cohort>>,
+
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = StatementAccount::LEN,
+        seeds = [STATEMENT_SEED, beneficiary_account.key().as_ref()],
+        bump,
+    )]
+    pub statement: Account<'info, StatementAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    // The beneficiary wallet; pays for the statement account the first time.
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+// `preview_schedule` is a pure calculation. Anchor's CPI wrappers need an account struct with a lifetime, so it takes
+// the system program, which Anchor clients resolve on their own.
+#[derive(Accounts)]
+pub struct PreviewSchedule<'info> {
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetClaimLimits<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCancelDisposition<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cohort_id: u32)]
+pub struct CreateCohort<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        init,
+        payer = sender,
+        space = Cohort::LEN,
+        seeds = [COHORT_SEED, data_account.key().as_ref(), &cohort_id.to_le_bytes()],
+        bump,
+    )]
+    pub cohort: Account<'info, Cohort>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCohort<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [COHORT_SEED, data_account.key().as_ref(), &cohort.cohort_id.to_le_bytes()],
+        bump = cohort.bump,
+    )]
+    pub cohort: Account<'info, Cohort>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AssignToCohort<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), beneficiary_account.key.as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    // The cohort to put the beneficiary in; omitted to take it out of its cohort.
+    #[account(
+        seeds = [COHORT_SEED, data_account.key().as_ref(), &cohort.cohort_id.to_le_bytes()],
+        bump = cohort.bump,
+    )]
+    pub cohort: Option<Account<'info, Cohort>>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetWithdrawSplit<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetForfeiturePolicy<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TerminateBeneficiary<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), beneficiary_wallet.key().as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    /// CHECK: Only used as PDA seed and ATA authority; the seeds tie it to `beneficiary_account`.
+    pub beneficiary_wallet: UncheckedAccount<'info>,
+
+    // Receives the beneficiary's vested but unclaimed tokens; created by the initializer if needed.
+    #[account(
+        init_if_needed,
+        payer = sender,
+        associated_token::mint = token_mint,
+        associated_token::authority = beneficiary_wallet,
+    )]
+    pub wallet_to_deposit_to: Account<'info, TokenAccount>,
+
+    // Required when anything is forfeited; must be the schedule's `forfeiture_pool`.
+    #[account(
+        mut,
+        constraint = Some(forfeiture_pool.key()) == data_account.forfeiture_pool @ VestingError::InvalidForfeiture,
+    )]
+    pub forfeiture_pool: Option<Account<'info, TokenAccount>>,
+
+    // The initializer's token account receiving the unforfeited unvested tokens.
+    #[account(
+        mut,
+        constraint = recipient.mint == token_mint.key() @ VestingError::InvalidTokenAccount,
+        constraint = recipient.owner == sender.key() @ VestingError::InvalidRecipient,
+        constraint = recipient.delegate.is_none() @ VestingError::TokenAccountHasDelegate,
+        constraint = recipient.close_authority.is_none() @ VestingError::TokenAccountHasCloseAuthority,
+    )]
+    pub recipient: Account<'info, TokenAccount>,
+
+    // Required when the beneficiary is in a cohort: its `Cohort` account.
+    #[account(
+        seeds = [COHORT_SEED, data_account.key().as_ref(), &cohort.cohort_id.to_le_bytes()],
+        bump = cohort.bump,
+    )]
+    pub cohort: Option<Account<'info, Cohort>>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddVestingLeg<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + 32 + 1 + 32 + 32 + 8 + 8 + 1 + 1 + 1,
+        seeds = [VESTING_LEG_SEED, data_account.key().as_ref(), &data_account.leg_count.to_le_bytes()],
+        bump,
+    )]
+    pub vesting_leg: Account<'info, VestingLeg>,
+
+    #[account(
+        init,
+        payer = sender,
+        seeds = [LEG_ESCROW_SEED, data_account.key().as_ref(), leg_mint.key().as_ref()],
+        bump,
+        token::mint = leg_mint,
+        token::authority = data_account,
+    )]
+    pub leg_escrow: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = wallet_to_withdraw_from.mint == leg_mint.key() @ VestingError::InvalidTokenAccount,
+    )]
+    pub wallet_to_withdraw_from: Account<'info, TokenAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub leg_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateDataAccount<'info> {
+    /// CHECK: Older layouts don't deserialize as the current `DataAccount`, so the handler checks the owner,
+    /// grows the account, and only then deserializes it (checking the discriminator and initializer).
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref()],
+        bump,
+    )]
+    pub data_account: UncheckedAccount<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyMintIntegrity<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(invite_key: Pubkey)]
+pub struct CreateInvitation<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + 32 + 32 + 8 + 1,
+        seeds = [INVITATION_SEED, data_account.key().as_ref(), invite_key.as_ref()],
+        bump,
+    )]
+    pub invitation: Account<'info, Invitation>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeInvitation<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        close = sender,
+        seeds = [INVITATION_SEED, data_account.key().as_ref(), invitation.invite_key.as_ref()],
+        bump = invitation.bump,
+    )]
+    pub invitation: Account<'info, Invitation>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemInvitation<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    // Closed on redemption, so each code works once; the rent goes back to the initializer who paid it.
+    #[account(
+        mut,
+        close = initializer,
+        seeds = [INVITATION_SEED, data_account.key().as_ref(), invitation.invite_key.as_ref()],
+        bump = invitation.bump,
+    )]
+    pub invitation: Account<'info, Invitation>,
+
+    // The invitation's key.
+    #[account(address = invitation.invite_key @ VestingError::InvalidInvitationCode)]
+    pub invite_signer: Signer<'info>,
+
+    // Fails if `sender` is already a beneficiary of this schedule.
+    #[account(
+        init,
+        payer = sender,
+        space = std::mem::size_of::<BeneficiaryAccount>() + 8,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), sender.key().as_ref()],
+        bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    // Next entry of the beneficiary index.
+    #[account(
+        init,
+        payer = sender,
+        space = BeneficiaryIndex::LEN,
+        seeds = [BENEFICIARY_INDEX_SEED, data_account.key().as_ref(), &data_account.beneficiary_index_len.to_le_bytes()],
+        bump,
+    )]
+    pub beneficiary_index: Account<'info, BeneficiaryIndex>,
+
+    #[account(mut, address = data_account.initializer @ VestingError::InvalidSender)]
+    pub initializer: SystemAccount<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+    // The new beneficiary; pays the rent of its beneficiary account.
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SweepDust<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = recipient.mint == token_mint.key() @ VestingError::InvalidTokenAccount,
+        constraint = recipient.owner == sender.key() @ VestingError::InvalidRecipient,
+        constraint = recipient.delegate.is_none() @ VestingError::TokenAccountHasDelegate,
+        constraint = recipient.close_authority.is_none() @ VestingError::TokenAccountHasCloseAuthority,
+    )]
+    pub recipient: Account<'info, TokenAccount>,
+
+    pub sender: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SyncEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    // Created here at the address of its signer, a keypair or a custodian's PDA.
+    #[account(
+        init,
+        payer = sender,
+        token::mint = token_mint,
+        token::authority = data_account
+    )]
+    pub new_escrow_wallet: Account<'info, TokenAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    // Pays the rent of the new escrow and gets the old one's back.
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPayoutMint<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    // Writable because the placeholder tokens are burned.
+    #[account(mut)]
+    pub token_mint: Account<'info, Mint>,
+
+    pub payout_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = sender,
+        seeds = [PAYOUT_ESCROW_SEED, data_account.key().as_ref()],
+        bump,
+        token::mint = payout_mint,
+        token::authority = data_account
+    )]
+    pub payout_escrow: Account<'info, TokenAccount>,
+
+    // The initializer's token account the payout tokens are taken from.
+    #[account(
+        mut,
+        constraint = wallet_to_withdraw_from.mint == payout_mint.key() @ VestingError::InvalidTokenAccount,
+    )]
+    pub wallet_to_withdraw_from: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RenounceAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FreezeConfig<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MintPositionNft<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), sender.key().as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    // One position NFT per beneficiary account; `init` fails if it was already minted.
+    #[account(
+        init,
+        payer = sender,
+        seeds = [POSITION_MINT_SEED, beneficiary_account.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = data_account,
+    )]
+    pub position_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = sender,
+        associated_token::mint = position_mint,
+        associated_token::authority = sender,
+    )]
+    pub position_token_account: Account<'info, TokenAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    // The beneficiary the position belongs to.
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(template_id: u64)]
+pub struct CreateTemplate<'info> {
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + 32 + 8 + 1 + 1 + 1 + 1 + 2 + 4 + 2 + 1 + 1,
+        seeds = [SCHEDULE_TEMPLATE_SEED, sender.key().as_ref(), template_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub template: Account<'info, ScheduleTemplate>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeFromTemplate<'info> {
+    #[account(
+        seeds = [SCHEDULE_TEMPLATE_SEED, template.authority.as_ref(), template.template_id.to_le_bytes().as_ref()],
+        bump = template.bump,
+        constraint = template.authority == sender.key() @ VestingError::InvalidSender,
+    )]
+    pub template: Account<'info, ScheduleTemplate>,
+
+    #[account(
+        init,
+        payer = sender,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref()],
+        bump,
+        space = DataAccount::LEN
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        init,
+        payer = sender,
+        seeds = [ESCROW_WALLET_SEED, token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = data_account
+    )]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    // The sender's token account funding the deposit.
+    #[account(
+        mut,
+        constraint = wallet_to_withdraw_from.mint == token_mint.key() @ VestingError::FundingMintMismatch,
+        constraint = wallet_to_withdraw_from.owner == sender.key() @ VestingError::InvalidTokenAccount,
+    )]
+    pub wallet_to_withdraw_from: Account<'info, TokenAccount>,
+
+    // The sender's list of schedules; created with their first schedule and grown by one entry per schedule.
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = CreatorRegistry::space(0),
+        seeds = [CREATOR_REGISTRY_SEED, sender.key().as_ref()],
+        bump,
+    )]
+    pub creator_registry: Account<'info, CreatorRegistry>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloneSchedule<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, source_data_account.token_mint.as_ref(), &source_data_account.schedule_seed()],
+        bump = source_data_account.bump,
+        constraint = source_data_account.initializer == sender.key() @ VestingError::InvalidSender,
+    )]
+    pub source_data_account: Account<'info, DataAccount>,
+
+    #[account(
+        init,
+        payer = sender,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref()],
+        bump,
+        space = DataAccount::LEN
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        init,
+        payer = sender,
+        seeds = [ESCROW_WALLET_SEED, token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = data_account
+    )]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    // The sender's token account funding the deposit.
+    #[account(
+        mut,
+        constraint = wallet_to_withdraw_from.mint == token_mint.key() @ VestingError::FundingMintMismatch,
+        constraint = wallet_to_withdraw_from.owner == sender.key() @ VestingError::InvalidTokenAccount,
+    )]
+    pub wallet_to_withdraw_from: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = CreatorRegistry::space(0),
+        seeds = [CREATOR_REGISTRY_SEED, sender.key().as_ref()],
+        bump,
+    )]
+    pub creator_registry: Account<'info, CreatorRegistry>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloneBeneficiaries<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, source_data_account.token_mint.as_ref(), &source_data_account.schedule_seed()],
+        bump = source_data_account.bump,
+    )]
+    pub source_data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.can_operate(&sender.key()) @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+        constraint = data_account.cloned_from == Some(source_data_account.key()) @ VestingError::NotClonedFrom,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+
+    // (source BeneficiaryAccount, new BeneficiaryAccount PDA) pairs are passed via remaining_accounts
+}
+
+#[derive(Accounts)]
+pub struct FinalizeBeneficiaryBatch<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.can_operate(&sender.key()) @ VestingError::InvalidSender,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddToBlacklist<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + 32 + 4 + 32 * MAX_BLACKLIST_ENTRIES + 1,
+        seeds = [BLACKLIST_SEED, data_account.key().as_ref()],
+        bump,
+    )]
+    pub blacklist: Account<'info, Blacklist>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveFromBlacklist<'info> {
+    #[account(
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [BLACKLIST_SEED, data_account.key().as_ref()],
+        bump = blacklist.bump,
+    )]
+    pub blacklist: Account<'info, Blacklist>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetClaimDeadline<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
 
-        // Derive the expected PDA for the beneficiary
-        let seeds = &[b"beneficiary", data_account_key.as_ref(), key.as_ref()];
-        let (expected_pda, bump) = Pubkey::find_program_address(seeds, program_id);
+    pub program_status: ProgramStatus<'info>,
+}
 
-        require_keys_eq!(beneficiary_info.key(), expected_pda, VestingError::InvalidBeneficiaryPDA);
+#[derive(Accounts)]
+pub struct UpdateBeneficiaryAllocation<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
 
-        // Close the account, refunding lamports to initializer
-        **initializer.to_account_info().try_borrow_mut_lamports()? += beneficiary_info.lamports();
-        **beneficiary_info.try_borrow_mut_lamports()? = 0;
-        let mut data = beneficiary_info.try_borrow_mut_data()?;
-        for byte in data.iter_mut() {
-            *byte = 0;
-        }
-    }
+    #[account(
+        mut,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), beneficiary_account.key.as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
 
-    Ok(())
-}
+    // The beneficiary's co-signature, required when their allocation is reduced.
+    pub beneficiary: Option<Signer<'info>>,
 
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
 
+    pub program_status: ProgramStatus<'info>,
 }
 
-/// Accounts required to initialize the vesting contract.
-///
-/// This instruction creates and initializes two PDA accounts:
-/// 1. `data_account` - A PDA that stores metadata/configuration for the vesting logic.
-/// 2. `escrow_wallet` - A PDA token account that will hold the escrowed SPL tokens to be vested.
-/// The main data account storing vesting configuration.
-    /// 
-    /// Seeds: ["data_account", token_mint.key()]
-    /// Bump: Auto-calculated
-    /// Space: Enough to store the serialized `DataAccount` structure:
-    /// - 8   (discriminator)
-    /// - 1   (is_initialized: bool)
-    /// - 8   (start_time: i64)
-    /// - 32  (token_mint: Pubkey)
-    /// - 32  (admin: Pubkey)
-    /// - 32  (creator: Pubkey)
-    /// - 1   (has_cliff: bool)
-    /// - 8   (cliff_duration: i64)
-    /// - 1   (is_cancellable: bool)
-    /// - 8   (total_duration: i64)
-    /// - 8   (created_at: i64)
-
 #[derive(Accounts)]
-pub struct Initialize<'info> {
+pub struct SplitPosition<'info> {
+    // Writable for `beneficiary_count` and `beneficiary_index_len`
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), sender.key().as_ref()],
+        bump = beneficiary_account.bump,
+    )]
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    // Required when the beneficiary is in a cohort: its `Cohort` account.
+    #[account(
+        seeds = [COHORT_SEED, data_account.key().as_ref(), &cohort.cohort_id.to_le_bytes()],
+        bump = cohort.bump,
+    )]
+    pub cohort: Option<Account<'info, Cohort>>,
+
+    // Fails if `new_wallet` is already a beneficiary of this schedule.
     #[account(
         init,
         payer = sender,
-        seeds = [b"data_account", token_mint.key().as_ref()],
+        space = std::mem::size_of::<BeneficiaryAccount>() + 8,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), new_wallet.key().as_ref()],
         bump,
-        space = 8 + 1 + 8 + 32 + 32 + 32 + 1 + 8 + 1 + 8 + 8
     )]
-    pub data_account: Account<'info, DataAccount>,
+    pub new_beneficiary_account: Account<'info, BeneficiaryAccount>,
 
-/// The escrow wallet PDA that holds SPL tokens for vesting.
-    ///
-    /// Seeds: ["escrow_wallet", token_mint.key()]
-    /// Authority: The `data_account` PDA
-    /// Token Mint: Must match the `token_mint` passed into the instruction
-    
+    // Next entry of the beneficiary index.
     #[account(
         init,
         payer = sender,
-        seeds = [b"escrow_wallet", token_mint.key().as_ref()],
+        space = BeneficiaryIndex::LEN,
+        seeds = [BENEFICIARY_INDEX_SEED, data_account.key().as_ref(), &data_account.beneficiary_index_len.to_le_bytes()],
         bump,
-        token::mint = token_mint,
-        token::authority = data_account
     )]
-    pub escrow_wallet: Account<'info, TokenAccount>,
+    pub beneficiary_index: Account<'info, BeneficiaryIndex>,
 
-    #[account(mut)]
-    pub wallet_to_withdraw_from: Account<'info, TokenAccount>,
+    /// CHECK: Only used as the PDA seed and key of the new position.
+    pub new_wallet: UncheckedAccount<'info>,
+
+    // The initializer's co-signature, required when the schedule is non-transferable.
+    pub initializer: Option<Signer<'info>>,
+
+    // Required once the schedule has a blacklist.
+    #[account(
+        seeds = [BLACKLIST_SEED, data_account.key().as_ref()],
+        bump = blacklist.bump,
+    )]
+    pub blacklist: Option<Account<'info, Blacklist>>,
 
     pub token_mint: Account<'info, Mint>,
+    // The beneficiary whose position is split; pays the rent of the new position.
     #[account(mut)]
     pub sender: Signer<'info>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
 }
 
 #[derive(Accounts)]
-#[instruction(data_bump: u8, beneficiary_bump: u8)]
-pub struct Claim<'info> {
+pub struct ConsolidatePositions<'info> {
+    // Schedule the position is merged into
     #[account(
         mut,
-        seeds = [b"data_account", token_mint.key().as_ref()],
-        bump = data_bump,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        has_one = escrow_wallet @ VestingError::InvalidEscrowWallet,
     )]
     pub data_account: Account<'info, DataAccount>,
 
+    #[account(mut)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
     #[account(
         mut,
-        seeds = [b"beneficiary", data_account.key().as_ref(), sender.key().as_ref()],
-        bump = beneficiary_bump,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), sender.key().as_ref()],
+        bump = beneficiary_account.bump,
     )]
     pub beneficiary_account: Account<'info, BeneficiaryAccount>,
 
-    #[account(mut)]
-    pub escrow_wallet: Account<'info, TokenAccount>,
+    // Schedule of the same mint whose position is merged and closed
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &source_data_account.schedule_seed()],
+        bump = source_data_account.bump,
+        constraint = source_data_account.key() != data_account.key() @ VestingError::IncompatibleSchedules,
+        constraint = source_data_account.escrow_wallet == source_escrow_wallet.key()
+            @ VestingError::InvalidEscrowWallet,
+    )]
+    pub source_data_account: Account<'info, DataAccount>,
 
     #[account(mut)]
+    pub source_escrow_wallet: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = initializer,
+        seeds = [BENEFICIARY_SEED, source_data_account.key().as_ref(), sender.key().as_ref()],
+        bump = source_beneficiary_account.bump,
+    )]
+    pub source_beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    #[account(
+        mut,
+        close = initializer,
+        seeds = [
+            BENEFICIARY_INDEX_SEED,
+            source_data_account.key().as_ref(),
+            &source_beneficiary_account.index.to_le_bytes(),
+        ],
+        bump = source_beneficiary_index.bump,
+    )]
+    pub source_beneficiary_index: Account<'info, BeneficiaryIndex>,
+
+    /// CHECK: Only receives the rent of the closed source accounts; both schedules' initializer.
+    #[account(mut, address = source_data_account.initializer @ VestingError::InvalidSender)]
+    pub initializer: UncheckedAccount<'info>,
+
+    // The beneficiary holding both positions
+    pub sender: Signer<'info>,
+    pub token_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EnableSharesMode<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
     pub sender: Signer<'info>,
 
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetIntervalSchedule<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
     pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
 
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageRole<'info> {
     #[account(
-        init_if_needed,
-        payer = sender,
-        associated_token::mint = token_mint,
-        associated_token::authority = sender,
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
     )]
-    pub wallet_to_deposit_to: Account<'info, TokenAccount>,
+    pub data_account: Account<'info, DataAccount>,
 
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
 }
 
 #[derive(Accounts)]
-#[instruction(data_bump: u8)]
-pub struct Release<'info> {
+pub struct FundRentReserve<'info> {
     #[account(
         mut,
-        seeds = [b"data_account", token_mint.key().as_ref()],
-        bump = data_bump,
-        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.can_operate(&sender.key()) @ VestingError::InvalidSender,
     )]
     pub data_account: Account<'info, DataAccount>,
 
@@ -605,178 +11556,218 @@ pub struct Release<'info> {
     #[account(mut)]
     pub sender: Signer<'info>,
     pub system_program: Program<'info, System>,
+
+    pub program_status: ProgramStatus<'info>,
 }
 
 #[derive(Accounts)]
-#[instruction(data_bump: u8)]
-pub struct ModifyBeneficiaries<'info> {
+pub struct WithdrawRentReserve<'info> {
     #[account(
         mut,
-        seeds = [b"data_account", token_mint.key().as_ref()],
-        bump = data_bump,
-        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
     )]
     pub data_account: Account<'info, DataAccount>,
 
     pub token_mint: Account<'info, Mint>,
     #[account(mut)]
     pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
 }
 
 #[derive(Accounts)]
-#[instruction()]
+pub struct FundFeePool<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
 
-#[account(init, seeds = [...], bump, payer = sender, space = ...)]
+    pub program_status: ProgramStatus<'info>,
+}
 
-pub struct AddBeneficiaries<'info> {
+#[derive(Accounts)]
+pub struct WithdrawFeePool<'info> {
     #[account(
         mut,
-        seeds = [b"data_account", token_mint.key().as_ref()],
-        bump,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
         constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
     )]
     pub data_account: Account<'info, DataAccount>,
 
+    pub token_mint: Account<'info, Mint>,
     #[account(mut)]
     pub sender: Signer<'info>,
 
-    pub token_mint: Account<'info, Mint>,
-    pub system_program: Program<'info, System>,
-    // BeneficiaryAccount PDAs will be passed dynamically via remaining_accounts
+    pub program_status: ProgramStatus<'info>,
 }
 
 #[derive(Accounts)]
-#[instruction(data_bump: u8, escrow_bump: u8)]
-pub struct WithdrawUnclaimed<'info> {
+pub struct SetBonusRate<'info> {
     #[account(
         mut,
-        seeds = [b"data_account", token_mint.key().as_ref()],
-        bump = data_bump,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
         constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
     )]
     pub data_account: Account<'info, DataAccount>,
 
+    #[account(
+        init_if_needed,
+        payer = sender,
+        seeds = [BONUS_ESCROW_SEED, data_account.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = data_account
+    )]
+    pub bonus_escrow: Account<'info, TokenAccount>,
+
+    // The initializer's token account the deposit is taken from.
     #[account(
         mut,
-        seeds = [b"escrow_wallet", token_mint.key().as_ref()],
-        bump = escrow_bump,
+        constraint = wallet_to_withdraw_from.mint == token_mint.key() @ VestingError::InvalidTokenAccount,
     )]
-    pub escrow_wallet: Account<'info, TokenAccount>,
+    pub wallet_to_withdraw_from: Account<'info, TokenAccount>,
 
     pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+
+    pub program_status: ProgramStatus<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawBonus<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = data_account.bonus_escrow == bonus_escrow.key() @ VestingError::BonusEscrowMismatch,
+    )]
+    pub data_account: Account<'info, DataAccount>,
 
     #[account(mut)]
+    pub bonus_escrow: Account<'info, TokenAccount>,
+
+    // The initializer's token account the remaining bonus tokens are returned to.
+    #[account(
+        mut,
+        constraint = recipient.mint == token_mint.key() @ VestingError::InvalidTokenAccount,
+    )]
     pub recipient: Account<'info, TokenAccount>,
 
+    pub token_mint: Account<'info, Mint>,
     #[account(mut)]
     pub sender: Signer<'info>,
     pub token_program: Program<'info, Token>,
-}
 
-#[account]
-#[derive(Default)]
-pub struct DataAccount {
-    pub percent_available: u8,
-    pub token_amount: u64,
-    pub initializer: Pubkey,
-    pub escrow_wallet: Pubkey,
-    pub token_mint: Pubkey,
-    pub decimals: u8,
-    pub start_timestamp: i64,
-    pub vesting_months: u8,
-    pub claimed_total: u64,
-    pub unclaimed_withdrawn: u64,
+    pub program_status: ProgramStatus<'info>,
 }
 
-#[account]
-#[derive(Default)]
-pub struct BeneficiaryAccount {
-    pub key: Pubkey,
-    pub allocated_tokens: u64,
-    pub claimed_tokens: u64,
-}
+#[derive(Accounts)]
+pub struct SetNonTransferable<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
 
-#[error_code]
-pub enum VestingError {
-    #[msg("Sender is not owner of Data Account")]
-    InvalidSender,
-    #[msg("Not allowed to claim new tokens currently")]
-    ClaimNotAllowed,
-    #[msg("Beneficiary does not exist in account")]
-    BeneficiaryNotFound,
-    #[msg("Vesting period has not started yet")]
-    VestingNotStarted,
-    #[msg("Invalid percentage provided (must be between 0 and 100)")]
-    InvalidPercentage,
-    #[msg("Total vesting amount must be greater than 0")]
-    ZeroVestingAmount,
-    #[msg("Unclaimed tokens are not yet withdrawable")]
-    VestingStillActive,
-    #[msg("No unclaimed tokens available for withdrawal")]
-    NoUnclaimedTokens,
-    #[msg("Missing account in remaining_accounts")]
-MissingRemainingAccount,
-#[msg("Provided account does not match expected beneficiary PDA")]
-InvalidBeneficiaryPDA,
-#[msg("Provided account does not match expected beneficiary PDA")]
-BeneficiaryAlreadyExists,
-#[msg("Vesting already completed, cannot cancel")]
-VestingAlreadyCompleted,
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
 
+    pub program_status: ProgramStatus<'info>,
 }
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct NewBeneficiary {
-    pub key: Pubkey,
-    pub allocated_tokens: u64,
-}
+
 #[derive(Accounts)]
-#[instruction(data_bump: u8)]
-pub struct RemoveBeneficiaries<'info> {
+pub struct InitializeMintVesting<'info> {
     #[account(
-        mut,
-        seeds = [b"data_account", token_mint.key().as_ref()],
-        bump = data_bump,
-        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        init,
+        payer = sender,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref()],
+        bump,
+        space = DataAccount::LEN
     )]
     pub data_account: Account<'info, DataAccount>,
 
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = CreatorRegistry::space(0),
+        seeds = [CREATOR_REGISTRY_SEED, sender.key().as_ref()],
+        bump,
+    )]
+    pub creator_registry: Account<'info, CreatorRegistry>,
+
+    #[account(
+        mut,
+        constraint = token_mint.mint_authority == COption::Some(sender.key()) @ VestingError::InvalidMintAuthority,
+    )]
     pub token_mint: Account<'info, Mint>,
+    // The mint's current mint authority.
     #[account(mut)]
     pub sender: Signer<'info>,
     pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
 
-    // Pass each BeneficiaryAccount in remaining_accounts[]
+    pub program_status: ProgramStatus<'info>,
 }
+
 #[derive(Accounts)]
-#[instruction(data_bump: u8, escrow_bump: u8)]
-pub struct CancelVesting<'info> {
+pub struct SetSecondaryRecipient<'info> {
     #[account(
-        mut,
-        seeds = [b"data_account", token_mint.key().as_ref()],
-        bump = data_bump,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
         constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
     )]
     pub data_account: Account<'info, DataAccount>,
 
     #[account(
         mut,
-        seeds = [b"escrow_wallet", token_mint.key().as_ref()],
-        bump = escrow_bump,
+        seeds = [BENEFICIARY_SEED, data_account.key().as_ref(), beneficiary.key().as_ref()],
+        bump = beneficiary_account.bump,
     )]
-    // The program-owned escrow token account that temporarily holds tokens until conditions are met.
-    pub escrow_wallet: Account<'info, TokenAccount>,
-    
- // The recipient's token account where tokens will be sent once escrow conditions are fulfilled.
-    #[account(mut)]
-    pub recipient: Account<'info, TokenAccount>,
+    pub beneficiary_account: Account<'info, BeneficiaryAccount>,
+
+    // The beneficiary, whose claims are split.
+    pub beneficiary: Signer<'info>,
 
-      // The signer (payer/initiator) of the transaction, usually the one depositing tokens into escrow.
-    #[account(mut)]
-    pub sender: Signer<'info>,
-    
-    // The SPL token mint for the token being escrowed (e.g., USDC, custom token).
     pub token_mint: Account<'info, Mint>,
-    // The SPL Token Program — required to perform token transfers and account operations.
-    pub token_program: Program<'info, Token>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
 }
+
+#[derive(Accounts)]
+pub struct SetClockTolerance<'info> {
+    #[account(
+        mut,
+        seeds = [DATA_ACCOUNT_SEED, token_mint.key().as_ref(), &data_account.schedule_seed()],
+        bump = data_account.bump,
+        constraint = data_account.initializer == sender.key() @ VestingError::InvalidSender,
+        constraint = !data_account.is_frozen @ VestingError::ConfigFrozen,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+    pub sender: Signer<'info>,
+
+    pub program_status: ProgramStatus<'info>,
 }
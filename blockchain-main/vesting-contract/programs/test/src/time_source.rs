@@ -0,0 +1,88 @@
+// Where instructions read the current time from.
+//
+// Production builds read the `Clock` sysvar. Builds with the `mock-clock` feature, for devnet only, read the
+// `["mock_clock"]` PDA instead when it is passed as the last of an instruction's remaining accounts, so a whole
+// 36-month schedule can be rehearsed end to end in minutes by moving it with `set_mock_clock`:
+//
+//     anchor build -- --features mock-clock
+//
+// Instructions that parse their remaining accounts read them through `instruction_accounts`, which leaves a
+// trailing mock clock out. Without the feature, a passed mock clock is ignored like any other extra account.
+
+use anchor_lang::prelude::*;
+#[cfg(feature = "mock-clock")]
+use vesting_common::seeds::MOCK_CLOCK_SEED;
+
+pub trait TimeSource {
+    // Current unix timestamp, in seconds.
+    fn unix_timestamp(&self) -> Result<i64>;
+}
+
+// The cluster's `Clock` sysvar.
+pub struct SysvarClock;
+
+impl TimeSource for SysvarClock {
+    fn unix_timestamp(&self) -> Result<i64> {
+        Ok(Clock::get()?.unix_timestamp)
+    }
+}
+
+// Time set by the program's upgrade authority with `set_mock_clock`.
+#[account]
+#[derive(Default)]
+pub struct MockClock {
+    pub unix_timestamp: i64,
+    pub bump: u8,
+}
+
+impl MockClock {
+    pub const LEN: usize = 8 + 8 + 1;
+}
+
+impl TimeSource for MockClock {
+    fn unix_timestamp(&self) -> Result<i64> {
+        Ok(self.unix_timestamp)
+    }
+}
+
+// Current time for an instruction with `remaining_accounts`.
+#[cfg(not(feature = "mock-clock"))]
+#[inline(always)]
+pub fn now(_remaining_accounts: &[AccountInfo]) -> Result<i64> {
+    SysvarClock.unix_timestamp()
+}
+
+#[cfg(feature = "mock-clock")]
+pub fn now(remaining_accounts: &[AccountInfo]) -> Result<i64> {
+    match mock_clock(remaining_accounts) {
+        Some(info) => {
+            // `mock_clock` checked the address and owner; deserializing checks the discriminator
+            let data = info.try_borrow_data()?;
+            MockClock::try_deserialize(&mut &data[..])?.unix_timestamp()
+        }
+        None => SysvarClock.unix_timestamp(),
+    }
+}
+
+// `remaining_accounts` without a trailing mock clock.
+#[cfg(not(feature = "mock-clock"))]
+#[inline(always)]
+pub fn instruction_accounts<'a, 'info>(remaining_accounts: &'a [AccountInfo<'info>]) -> &'a [AccountInfo<'info>] {
+    remaining_accounts
+}
+
+#[cfg(feature = "mock-clock")]
+pub fn instruction_accounts<'a, 'info>(remaining_accounts: &'a [AccountInfo<'info>]) -> &'a [AccountInfo<'info>] {
+    match mock_clock(remaining_accounts) {
+        Some(_) => &remaining_accounts[..remaining_accounts.len() - 1],
+        None => remaining_accounts,
+    }
+}
+
+// The mock clock, if it is the last remaining account.
+#[cfg(feature = "mock-clock")]
+fn mock_clock<'a, 'info>(remaining_accounts: &'a [AccountInfo<'info>]) -> Option<&'a AccountInfo<'info>> {
+    let last = remaining_accounts.last()?;
+    let (address, _) = Pubkey::find_program_address(&[MOCK_CLOCK_SEED], &crate::ID);
+    (last.key() == address && last.owner == &crate::ID).then_some(last)
+}
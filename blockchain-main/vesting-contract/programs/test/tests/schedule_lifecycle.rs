@@ -0,0 +1,1455 @@
+// Time-based behavior of a schedule, run against the built program in `solana-program-test`
+// with the `Clock` sysvar set explicitly, so every scenario is deterministic:
+//
+//     anchor build && SBF_OUT_DIR=target/deploy cargo test -p token_vesting --test schedule_lifecycle
+//
+// Every schedule vests over 36 30-day months from `START`, in `TimeOnly` mode, funded with `DEPOSIT` tokens.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{Instruction, InstructionError};
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::{system_program, InstructionData};
+use anchor_spl::associated_token::{self, get_associated_token_address};
+use anchor_spl::token::spl_token;
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account as SolanaAccount;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+use token_vesting::math::{SECONDS_PER_DAY, SECONDS_PER_MONTH};
+use token_vesting::{
+    accounts, instruction, BeneficiaryAccount, BeneficiaryIndex, Config, CurveType, DataAccount, Disposition,
+    NewBeneficiary, ProgramState, ReleaseMode, RoundingPolicy, VestingError, WithdrawShare,
+};
+use vesting_common::features::{FEATURE_VESTING_CURVES, KNOWN_FEATURES};
+use vesting_common::schema;
+use vesting_common::seeds::{
+    BENEFICIARY_INDEX_SEED, BENEFICIARY_SEED, COHORT_SEED, CONFIG_SEED, CREATOR_REGISTRY_SEED, DATA_ACCOUNT_SEED,
+    ESCROW_WALLET_SEED, PROGRAM_STATE_SEED, SEED_VERSION_V1, SEED_VERSION_V2,
+};
+
+const DECIMALS: u8 = 6;
+const UNIT: u64 = 1_000_000;
+const DEPOSIT: u64 = 1_000;
+// Clock time every test starts at, and the schedules' start a minute later
+const GENESIS: i64 = 1_700_000_000;
+const START: i64 = GENESIS + 60;
+const SOL: u64 = 1_000_000_000;
+
+struct Harness {
+    context: ProgramTestContext,
+    authority: Keypair,
+    mint: Pubkey,
+    authority_tokens: Pubkey,
+    data_account: Pubkey,
+    escrow_wallet: Pubkey,
+    beneficiaries: Vec<Keypair>,
+}
+
+impl Harness {
+    // Loads the program with a fee-free `Config`, a mint, and an authority holding `2 * DEPOSIT` tokens.
+    async fn new(beneficiary_count: usize) -> Self {
+        let mut program_test = ProgramTest::new("token_vesting", token_vesting::ID, None);
+        program_test.prefer_bpf(true);
+
+        let authority = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let authority_tokens = Pubkey::new_unique();
+        let beneficiaries: Vec<Keypair> = (0..beneficiary_count).map(|_| Keypair::new()).collect();
+
+        for wallet in std::iter::once(&authority).chain(beneficiaries.iter()) {
+            program_test.add_account(
+                wallet.pubkey(),
+                SolanaAccount::new(10 * SOL, 0, &system_program::ID),
+            );
+        }
+        program_test.add_account(
+            mint,
+            token_program_account(spl_token::state::Mint {
+                mint_authority: COption::Some(authority.pubkey()),
+                supply: 2 * DEPOSIT * UNIT,
+                decimals: DECIMALS,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            }),
+        );
+        program_test.add_account(
+            authority_tokens,
+            token_program_account(spl_token::state::Account {
+                mint,
+                owner: authority.pubkey(),
+                amount: 2 * DEPOSIT * UNIT,
+                state: spl_token::state::AccountState::Initialized,
+                ..Default::default()
+            }),
+        );
+
+        // The config is normally created by the upgrade authority; here it is written directly
+        let (config, bump) = Pubkey::find_program_address(&[CONFIG_SEED], &token_vesting::ID);
+        let mut data = Vec::new();
+        Config {
+            fee_recipient: authority.pubkey(),
+            fee_bps: 0,
+            staking_program: None,
+            bump,
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+        program_test.add_account(
+            config,
+            SolanaAccount {
+                lamports: SOL,
+                data,
+                owner: token_vesting::ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+
+        let (program_state, bump) = Pubkey::find_program_address(&[PROGRAM_STATE_SEED], &token_vesting::ID);
+        program_test.add_account(program_state, program_state_account(false, KNOWN_FEATURES, 0, bump));
+
+        let mut context = program_test.start_with_context().await;
+        let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+        clock.unix_timestamp = GENESIS;
+        context.set_sysvar(&clock);
+
+        Self {
+            context,
+            authority,
+            mint,
+            authority_tokens,
+            data_account: pda(&[DATA_ACCOUNT_SEED, mint.as_ref()]),
+            escrow_wallet: pda(&[ESCROW_WALLET_SEED, mint.as_ref()]),
+            beneficiaries,
+        }
+    }
+
+    async fn send(&mut self, instruction: Instruction, signers: &[&Keypair]) -> std::result::Result<(), BanksClientError> {
+        // A fresh blockhash each time, so repeating an identical instruction is a new transaction
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        self.context.banks_client.process_transaction(transaction).await
+    }
+
+    async fn warp_to(&mut self, unix_timestamp: i64) {
+        let mut clock = self.context.banks_client.get_sysvar::<Clock>().await.unwrap();
+        clock.unix_timestamp = unix_timestamp;
+        self.context.set_sysvar(&clock);
+    }
+
+    async fn initialize(&mut self, amount: u64, is_cancellable: bool) -> std::result::Result<(), BanksClientError> {
+        let ix = build_instruction(
+            accounts::Initialize {
+                data_account: self.data_account,
+                escrow_wallet: self.escrow_wallet,
+                wallet_to_withdraw_from: self.authority_tokens,
+                creator_registry: pda(&[CREATOR_REGISTRY_SEED, self.authority.pubkey().as_ref()]),
+                token_mint: self.mint,
+                sender: self.authority.pubkey(),
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                program_status: program_status(),
+            },
+            instruction::Initialize {
+                amount,
+                decimals: DECIMALS,
+                start_timestamp: START,
+                is_cancellable,
+                calendar_months: false,
+                retroactive: false,
+                cancellation_penalty_bps: 0,
+                guardian: None,
+                tge_unlock_bps: 0,
+                lockup_seconds: 0,
+                release_mode: ReleaseMode::TimeOnly,
+                name: [0; 32],
+                uri: [0; 128],
+            },
+        );
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    // Creates the schedule and its first beneficiaries in one instruction.
+    async fn initialize_with_beneficiaries(
+        &mut self,
+        amount: u64,
+        allocations: &[(usize, u64)],
+    ) -> std::result::Result<(), BanksClientError> {
+        let new_beneficiaries = allocations
+            .iter()
+            .map(|&(index, allocated_tokens)| NewBeneficiary {
+                key: self.beneficiaries[index].pubkey(),
+                allocated_tokens,
+                recovery_authority: None,
+            })
+            .collect();
+        let mut ix = build_instruction(
+            accounts::Initialize {
+                data_account: self.data_account,
+                escrow_wallet: self.escrow_wallet,
+                wallet_to_withdraw_from: self.authority_tokens,
+                creator_registry: pda(&[CREATOR_REGISTRY_SEED, self.authority.pubkey().as_ref()]),
+                token_mint: self.mint,
+                sender: self.authority.pubkey(),
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                program_status: program_status(),
+            },
+            instruction::InitializeWithBeneficiaries {
+                amount,
+                decimals: DECIMALS,
+                start_timestamp: START,
+                is_cancellable: false,
+                calendar_months: false,
+                retroactive: false,
+                cancellation_penalty_bps: 0,
+                guardian: None,
+                tge_unlock_bps: 0,
+                lockup_seconds: 0,
+                release_mode: ReleaseMode::TimeOnly,
+                name: [0; 32],
+                uri: [0; 128],
+                new_beneficiaries,
+            },
+        );
+        for (&(index, _), entry) in allocations.iter().zip(0..) {
+            ix.accounts.push(AccountMeta::new(self.beneficiary_account(index), false));
+            ix.accounts.push(AccountMeta::new(self.beneficiary_index(entry), false));
+        }
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    // Commits to `amount` tokens but only deposits `first_installment` of them.
+    async fn initialize_with_installments(
+        &mut self,
+        amount: u64,
+        first_installment: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        let ix = build_instruction(
+            accounts::Initialize {
+                data_account: self.data_account,
+                escrow_wallet: self.escrow_wallet,
+                wallet_to_withdraw_from: self.authority_tokens,
+                creator_registry: pda(&[CREATOR_REGISTRY_SEED, self.authority.pubkey().as_ref()]),
+                token_mint: self.mint,
+                sender: self.authority.pubkey(),
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                program_status: program_status(),
+            },
+            instruction::InitializeWithInstallments {
+                amount,
+                decimals: DECIMALS,
+                start_timestamp: START,
+                is_cancellable: false,
+                calendar_months: false,
+                retroactive: false,
+                cancellation_penalty_bps: 0,
+                guardian: None,
+                tge_unlock_bps: 0,
+                lockup_seconds: 0,
+                release_mode: ReleaseMode::TimeOnly,
+                name: [0; 32],
+                uri: [0; 128],
+                first_installment,
+            },
+        );
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn fund_installment(&mut self, amount: u64) -> std::result::Result<(), BanksClientError> {
+        let ix = build_instruction(
+            accounts::FundInstallment {
+                data_account: self.data_account,
+                escrow_wallet: self.escrow_wallet,
+                wallet_to_withdraw_from: self.authority_tokens,
+                token_mint: self.mint,
+                sender: self.authority.pubkey(),
+                token_program: spl_token::ID,
+                program_status: program_status(),
+            },
+            instruction::FundInstallment { amount },
+        );
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    // Creates a v2 schedule with `schedule_id` for the same mint and points the harness at it.
+    async fn initialize_v2(&mut self, schedule_id: u64, amount: u64) -> std::result::Result<(), BanksClientError> {
+        self.data_account = pda(&[DATA_ACCOUNT_SEED, self.mint.as_ref(), &schedule_id.to_le_bytes()]);
+        self.escrow_wallet = pda(&[ESCROW_WALLET_SEED, self.mint.as_ref(), &schedule_id.to_le_bytes()]);
+        let ix = build_instruction(
+            accounts::InitializeV2 {
+                data_account: self.data_account,
+                escrow_wallet: self.escrow_wallet,
+                wallet_to_withdraw_from: self.authority_tokens,
+                creator_registry: pda(&[CREATOR_REGISTRY_SEED, self.authority.pubkey().as_ref()]),
+                token_mint: self.mint,
+                sender: self.authority.pubkey(),
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                program_status: program_status(),
+            },
+            instruction::InitializeV2 {
+                schedule_id,
+                amount,
+                decimals: DECIMALS,
+                start_timestamp: START,
+                is_cancellable: false,
+                calendar_months: false,
+                retroactive: false,
+                cancellation_penalty_bps: 0,
+                guardian: None,
+                tge_unlock_bps: 0,
+                lockup_seconds: 0,
+                release_mode: ReleaseMode::TimeOnly,
+                name: [0; 32],
+                uri: [0; 128],
+            },
+        );
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn add_beneficiaries(&mut self, allocations: &[(usize, u64)]) -> std::result::Result<(), BanksClientError> {
+        let data_account: DataAccount = self.account(self.data_account).await;
+        let new_beneficiaries: Vec<NewBeneficiary> = allocations
+            .iter()
+            .map(|&(index, allocated_tokens)| NewBeneficiary {
+                key: self.beneficiaries[index].pubkey(),
+                allocated_tokens,
+                recovery_authority: None,
+            })
+            .collect();
+        let mut ix = build_instruction(
+            accounts::AddBeneficiaries {
+                data_account: self.data_account,
+                sender: self.authority.pubkey(),
+                token_mint: self.mint,
+                system_program: system_program::ID,
+                program_status: program_status(),
+            },
+            instruction::AddBeneficiaries {
+                new_beneficiaries,
+                batch_offset: data_account.batch_cursor,
+            },
+        );
+        for (&(index, _), entry) in allocations.iter().zip(data_account.beneficiary_index_len..) {
+            ix.accounts.push(AccountMeta::new(self.beneficiary_account(index), false));
+            ix.accounts.push(AccountMeta::new(self.beneficiary_index(entry), false));
+        }
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn set_max_beneficiaries(&mut self, max_beneficiaries: u32) -> std::result::Result<(), BanksClientError> {
+        let ix = build_instruction(
+            accounts::SetMaxBeneficiaries {
+                data_account: self.data_account,
+                token_mint: self.mint,
+                sender: self.authority.pubkey(),
+                program_status: program_status(),
+            },
+            instruction::SetMaxBeneficiaries { max_beneficiaries },
+        );
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn set_rounding_policy(&mut self, rounding: RoundingPolicy) -> std::result::Result<(), BanksClientError> {
+        let ix = build_instruction(
+            accounts::SetRoundingPolicy {
+                data_account: self.data_account,
+                token_mint: self.mint,
+                sender: self.authority.pubkey(),
+                program_status: program_status(),
+            },
+            instruction::SetRoundingPolicy { rounding },
+        );
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn set_vesting_curve(&mut self, curve: CurveType) -> std::result::Result<(), BanksClientError> {
+        let ix = build_instruction(
+            accounts::SetVestingCurve {
+                data_account: self.data_account,
+                token_mint: self.mint,
+                sender: self.authority.pubkey(),
+                program_status: program_status(),
+            },
+            instruction::SetVestingCurve { curve },
+        );
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn set_cancel_disposition(&mut self, disposition: Disposition) -> std::result::Result<(), BanksClientError> {
+        let ix = build_instruction(
+            accounts::SetCancelDisposition {
+                data_account: self.data_account,
+                token_mint: self.mint,
+                sender: self.authority.pubkey(),
+                program_status: program_status(),
+            },
+            instruction::SetCancelDisposition { disposition },
+        );
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn create_cohort(
+        &mut self,
+        cohort_id: u32,
+        cliff_months: Option<u8>,
+        vesting_months: Option<u8>,
+    ) -> std::result::Result<(), BanksClientError> {
+        let ix = build_instruction(
+            accounts::CreateCohort {
+                data_account: self.data_account,
+                cohort: self.cohort(cohort_id),
+                token_mint: self.mint,
+                sender: self.authority.pubkey(),
+                system_program: system_program::ID,
+                program_status: program_status(),
+            },
+            instruction::CreateCohort { cohort_id, cliff_months, vesting_months, acceleration_exempt: false },
+        );
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn update_cohort(
+        &mut self,
+        cohort_id: u32,
+        cliff_months: Option<u8>,
+        vesting_months: Option<u8>,
+    ) -> std::result::Result<(), BanksClientError> {
+        let ix = build_instruction(
+            accounts::UpdateCohort {
+                data_account: self.data_account,
+                cohort: self.cohort(cohort_id),
+                token_mint: self.mint,
+                sender: self.authority.pubkey(),
+                program_status: program_status(),
+            },
+            instruction::UpdateCohort { cliff_months, vesting_months, acceleration_exempt: false },
+        );
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn assign_to_cohort(
+        &mut self,
+        index: usize,
+        cohort_id: Option<u32>,
+    ) -> std::result::Result<(), BanksClientError> {
+        let ix = build_instruction(
+            accounts::AssignToCohort {
+                data_account: self.data_account,
+                beneficiary_account: self.beneficiary_account(index),
+                cohort: cohort_id.map(|cohort_id| self.cohort(cohort_id)),
+                token_mint: self.mint,
+                sender: self.authority.pubkey(),
+                program_status: program_status(),
+            },
+            instruction::AssignToCohort {},
+        );
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn set_withdraw_split(&mut self, shares: Vec<WithdrawShare>) -> std::result::Result<(), BanksClientError> {
+        let ix = build_instruction(
+            accounts::SetWithdrawSplit {
+                data_account: self.data_account,
+                token_mint: self.mint,
+                sender: self.authority.pubkey(),
+                program_status: program_status(),
+            },
+            instruction::SetWithdrawSplit { shares },
+        );
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn set_removal_arbiter(&mut self, arbiter: Pubkey) -> std::result::Result<(), BanksClientError> {
+        let ix = build_instruction(
+            accounts::SetRemovalDispute {
+                data_account: self.data_account,
+                token_mint: self.mint,
+                sender: self.authority.pubkey(),
+                program_status: program_status(),
+            },
+            instruction::SetRemovalArbiter { arbiter },
+        );
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn schedule_removal(&mut self, index: usize) -> std::result::Result<(), BanksClientError> {
+        let mut ix = build_instruction(
+            accounts::ScheduleRemoval {
+                data_account: self.data_account,
+                token_mint: self.mint,
+                sender: self.authority.pubkey(),
+                program_status: program_status(),
+            },
+            instruction::ScheduleRemoval { keys: vec![self.beneficiaries[index].pubkey()] },
+        );
+        ix.accounts.push(AccountMeta::new(self.beneficiary_account(index), false));
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn veto_removal(&mut self, index: usize, arbiter: &Keypair) -> std::result::Result<(), BanksClientError> {
+        let ix = build_instruction(
+            accounts::VetoRemoval {
+                data_account: self.data_account,
+                beneficiary_account: self.beneficiary_account(index),
+                token_mint: self.mint,
+                sender: arbiter.pubkey(),
+                program_status: program_status(),
+            },
+            instruction::VetoRemoval {},
+        );
+        self.send(ix, &[arbiter]).await
+    }
+
+    async fn execute_removal(&mut self, index: usize, force: bool) -> std::result::Result<(), BanksClientError> {
+        let beneficiary_account = self.beneficiary_account(index);
+        let beneficiary: BeneficiaryAccount = self.account(beneficiary_account).await;
+        let mut ix = build_instruction(
+            accounts::ExecuteRemoval {
+                data_account: self.data_account,
+                token_mint: self.mint,
+                sender: self.authority.pubkey(),
+                rent_recipient: self.authority.pubkey(),
+                system_program: system_program::ID,
+                program_status: program_status(),
+            },
+            instruction::ExecuteRemoval { keys: vec![self.beneficiaries[index].pubkey()], force },
+        );
+        ix.accounts.push(AccountMeta::new(beneficiary_account, false));
+        ix.accounts.push(AccountMeta::new(self.beneficiary_index(beneficiary.index), false));
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn finalize_expired(&mut self) -> std::result::Result<(), BanksClientError> {
+        let ix = build_instruction(
+            accounts::FinalizeExpired {
+                data_account: self.data_account,
+                token_mint: self.mint,
+                cranker: self.context.payer.pubkey(),
+                program_status: program_status(),
+                event_authority: event_authority(),
+                program: token_vesting::ID,
+            },
+            instruction::FinalizeExpired {},
+        );
+        self.send(ix, &[]).await
+    }
+
+    async fn claim(&mut self, index: usize, amount: u64) -> std::result::Result<(), BanksClientError> {
+        self.claim_in_cohort(index, amount, None).await
+    }
+
+    // `claim` passing the `Cohort` account of `cohort_id`.
+    async fn claim_in_cohort(
+        &mut self,
+        index: usize,
+        amount: u64,
+        cohort_id: Option<u32>,
+    ) -> std::result::Result<(), BanksClientError> {
+        let wallet = self.beneficiaries[index].insecure_clone();
+        let ix = build_instruction(
+            accounts::Claim {
+                data_account: self.data_account,
+                beneficiary_account: self.beneficiary_account(index),
+                escrow_wallet: Some(self.escrow_wallet),
+                beneficiary_wallet: wallet.pubkey(),
+                sender: wallet.pubkey(),
+                token_mint: self.mint,
+                wallet_to_deposit_to: get_associated_token_address(&wallet.pubkey(), &self.mint),
+                payout_token_account: None,
+                config: Some(pda(&[CONFIG_SEED])),
+                fee_recipient: Some(self.authority.pubkey()),
+                fee_token_account: Some(get_associated_token_address(&self.authority.pubkey(), &self.mint)),
+                claim_approver: None,
+                memo_program: None,
+                position_token_account: None,
+                claim_record: None,
+                secondary_token_account: None,
+                blacklist: None,
+                gate_program: None,
+                gate_config: None,
+                claim_hook_program: None,
+                payout_mint: None,
+                payout_escrow: None,
+                payout_destination: None,
+                payout_fee_token_account: None,
+                withholding_token_account: None,
+                bonus_escrow: None,
+                cohort: cohort_id.map(|cohort_id| self.cohort(cohort_id)),
+                associated_token_program: associated_token::ID,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+                program_status: program_status(),
+                event_authority: event_authority(),
+                program: token_vesting::ID,
+            },
+            instruction::Claim { amount, dry_run: false, claim_nonce: None },
+        );
+        self.send(ix, &[&wallet]).await
+    }
+
+    async fn schedule_cancel(&mut self) -> std::result::Result<(), BanksClientError> {
+        let ix = build_instruction(
+            accounts::ScheduleCancel {
+                data_account: self.data_account,
+                token_mint: self.mint,
+                sender: self.authority.pubkey(),
+                program_status: program_status(),
+                event_authority: event_authority(),
+                program: token_vesting::ID,
+            },
+            instruction::ScheduleCancel {},
+        );
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn execute_cancel(&mut self) -> std::result::Result<(), BanksClientError> {
+        let ix = build_instruction(
+            accounts::ExecuteCancel {
+                data_account: self.data_account,
+                escrow_wallet: self.escrow_wallet,
+                recipient: self.authority_tokens,
+                sender: self.authority.pubkey(),
+                token_mint: self.mint,
+                token_program: spl_token::ID,
+                program_status: program_status(),
+                event_authority: event_authority(),
+                program: token_vesting::ID,
+            },
+            instruction::ExecuteCancel {},
+        );
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn withdraw_unclaimed(&mut self) -> std::result::Result<(), BanksClientError> {
+        self.withdraw_unclaimed_split(&[]).await
+    }
+
+    // `withdraw_unclaimed` with the token accounts of a withdrawal split as remaining accounts.
+    async fn withdraw_unclaimed_split(&mut self, destinations: &[Pubkey]) -> std::result::Result<(), BanksClientError> {
+        let mut ix = build_instruction(
+            accounts::WithdrawUnclaimed {
+                data_account: self.data_account,
+                escrow_wallet: self.escrow_wallet,
+                token_mint: self.mint,
+                recipient: self.authority_tokens,
+                sender: self.authority.pubkey(),
+                token_program: spl_token::ID,
+                program_status: program_status(),
+            },
+            instruction::WithdrawUnclaimed {},
+        );
+        for destination in destinations {
+            ix.accounts.push(AccountMeta::new(*destination, false));
+        }
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    // `sunset_claim` for a beneficiary, sent by the test's fee payer rather than anyone involved in the schedule.
+    async fn sunset_claim(&mut self, index: usize) -> std::result::Result<(), BanksClientError> {
+        let wallet = self.beneficiaries[index].pubkey();
+        let beneficiary_account = self.beneficiary_account(index);
+        let position = self.account::<BeneficiaryAccount>(beneficiary_account).await.index;
+        let ix = build_instruction(
+            accounts::SunsetClaim {
+                data_account: self.data_account,
+                escrow_wallet: self.escrow_wallet,
+                beneficiary_account,
+                beneficiary_index: self.beneficiary_index(position),
+                beneficiary_wallet: wallet,
+                wallet_to_deposit_to: get_associated_token_address(&wallet, &self.mint),
+                rent_recipient: self.authority.pubkey(),
+                token_mint: self.mint,
+                sender: self.context.payer.pubkey(),
+                associated_token_program: associated_token::ID,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+                program_status: program_status(),
+                event_authority: event_authority(),
+                program: token_vesting::ID,
+            },
+            instruction::SunsetClaim {},
+        );
+        self.send(ix, &[]).await
+    }
+
+    async fn sunset_close_schedule(&mut self) -> std::result::Result<(), BanksClientError> {
+        let ix = build_instruction(
+            accounts::SunsetCloseSchedule {
+                data_account: self.data_account,
+                escrow_wallet: self.escrow_wallet,
+                recipient: self.authority_tokens,
+                initializer: self.authority.pubkey(),
+                token_mint: self.mint,
+                sender: self.context.payer.pubkey(),
+                token_program: spl_token::ID,
+                program_status: program_status(),
+            },
+            instruction::SunsetCloseSchedule {},
+        );
+        self.send(ix, &[]).await
+    }
+
+    async fn amend_cliff(&mut self, cliff_months: u8) -> std::result::Result<(), BanksClientError> {
+        let ix = build_instruction(
+            accounts::AmendSchedule {
+                data_account: self.data_account,
+                beneficiary_account: None,
+                cohort: None,
+                beneficiary: None,
+                token_mint: self.mint,
+                sender: self.authority.pubkey(),
+                program_status: program_status(),
+            },
+            instruction::AmendSchedule {
+                vesting_months: None,
+                cliff_months: Some(cliff_months),
+                allocated_tokens: None,
+            },
+        );
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    fn beneficiary_account(&self, index: usize) -> Pubkey {
+        let wallet = self.beneficiaries[index].pubkey();
+        pda(&[BENEFICIARY_SEED, self.data_account.as_ref(), wallet.as_ref()])
+    }
+
+    // Halts or resumes the program, as `set_program_halted` would.
+    fn set_halted(&mut self, is_halted: bool) {
+        let (program_state, bump) = Pubkey::find_program_address(&[PROGRAM_STATE_SEED], &token_vesting::ID);
+        self.context.set_account(&program_state, &program_state_account(is_halted, KNOWN_FEATURES, 0, bump).into());
+    }
+
+    // Drops the program state, as on a deployment where `set_program_halted` never ran.
+    fn remove_program_state(&mut self) {
+        self.context.set_account(&pda(&[PROGRAM_STATE_SEED]), &SolanaAccount::default().into());
+    }
+
+    fn set_features(&mut self, features: u64) {
+        let (program_state, bump) = Pubkey::find_program_address(&[PROGRAM_STATE_SEED], &token_vesting::ID);
+        self.context.set_account(&program_state, &program_state_account(false, features, 0, bump).into());
+    }
+
+    // Retires the program from `sunset_at`, as `set_sunset_mode` would after its delay.
+    fn set_sunset(&mut self, sunset_at: i64) {
+        let (program_state, bump) = Pubkey::find_program_address(&[PROGRAM_STATE_SEED], &token_vesting::ID);
+        self.context
+            .set_account(&program_state, &program_state_account(false, KNOWN_FEATURES, sunset_at, bump).into());
+    }
+
+    fn cohort(&self, cohort_id: u32) -> Pubkey {
+        pda(&[COHORT_SEED, self.data_account.as_ref(), &cohort_id.to_le_bytes()])
+    }
+
+    fn beneficiary_index(&self, index: u32) -> Pubkey {
+        pda(&[BENEFICIARY_INDEX_SEED, self.data_account.as_ref(), &index.to_le_bytes()])
+    }
+
+    async fn account<T: AccountDeserialize>(&mut self, address: Pubkey) -> T {
+        let account = self.context.banks_client.get_account(address).await.unwrap().unwrap();
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    async fn claimed(&mut self, index: usize) -> u64 {
+        let address = self.beneficiary_account(index);
+        self.account::<BeneficiaryAccount>(address).await.claimed_tokens
+    }
+
+    // Raw token balance of a token account (0 if it doesn't exist yet)
+    async fn balance(&mut self, address: Pubkey) -> u64 {
+        match self.context.banks_client.get_account(address).await.unwrap() {
+            Some(account) => spl_token::state::Account::unpack(&account.data).unwrap().amount,
+            None => 0,
+        }
+    }
+
+    async fn supply(&mut self) -> u64 {
+        let account = self.context.banks_client.get_account(self.mint).await.unwrap().unwrap();
+        spl_token::state::Mint::unpack(&account.data).unwrap().supply
+    }
+
+    async fn wallet_balance(&mut self, index: usize) -> u64 {
+        let ata = get_associated_token_address(&self.beneficiaries[index].pubkey(), &self.mint);
+        self.balance(ata).await
+    }
+}
+
+// A rent-funded account owned by the token program holding the packed `state`
+fn token_program_account<T: Pack>(state: T) -> SolanaAccount {
+    let mut data = vec![0; T::LEN];
+    T::pack(state, &mut data).unwrap();
+    SolanaAccount {
+        lamports: SOL,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+// The harness enables every feature (`KNOWN_FEATURES`) unless a test is about the feature gates.
+fn program_state_account(is_halted: bool, features: u64, sunset_at: i64, bump: u8) -> SolanaAccount {
+    let mut data = Vec::new();
+    ProgramState { is_halted, bump, features, sunset_at, max_start_in_past: 0, max_start_in_future: 0 }
+        .try_serialize(&mut data)
+        .unwrap();
+    SolanaAccount {
+        lamports: SOL,
+        data,
+        owner: token_vesting::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &token_vesting::ID).0
+}
+
+// The program state PDA every state-changing instruction takes, whether or not it exists yet.
+fn program_status() -> accounts::ProgramStatus {
+    accounts::ProgramStatus { program_state: pda(&[PROGRAM_STATE_SEED]) }
+}
+
+fn event_authority() -> Pubkey {
+    pda(&[b"__event_authority"])
+}
+
+fn build_instruction(accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: token_vesting::ID,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+fn assert_vesting_error(result: std::result::Result<(), BanksClientError>, error: VestingError) {
+    let code = u32::from(error);
+    match result.expect_err("transaction should have failed").unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(actual)) => assert_eq!(actual, code),
+        other => panic!("expected custom error {code}, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn nothing_is_claimable_before_start_or_during_the_cliff() {
+    let mut harness = Harness::new(1).await;
+    harness.initialize(DEPOSIT, true).await.unwrap();
+    harness.add_beneficiaries(&[(0, DEPOSIT)]).await.unwrap();
+    harness.amend_cliff(6).await.unwrap();
+
+    assert_vesting_error(harness.claim(0, 0).await, VestingError::VestingNotStarted);
+
+    // One second before the cliff ends
+    harness.warp_to(START + 6 * SECONDS_PER_MONTH - 1).await;
+    assert_vesting_error(harness.claim(0, 0).await, VestingError::ClaimNotAllowed);
+    assert_eq!(harness.claimed(0).await, 0);
+
+    // At the cliff the first six months vest at once: 6 / 36 = 16%
+    harness.warp_to(START + 6 * SECONDS_PER_MONTH).await;
+    harness.claim(0, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, 160);
+    assert_eq!(harness.wallet_balance(0).await, 160 * UNIT);
+}
+
+#[tokio::test]
+async fn partial_vesting_follows_elapsed_months() {
+    let mut harness = Harness::new(2).await;
+    harness.initialize(DEPOSIT, false).await.unwrap();
+    harness.add_beneficiaries(&[(0, 600), (1, 400)]).await.unwrap();
+
+    // 12 of 36 months: 33%
+    harness.warp_to(START + 12 * SECONDS_PER_MONTH).await;
+    harness.claim(0, 0).await.unwrap();
+    harness.claim(1, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, 198);
+    assert_eq!(harness.claimed(1).await, 132);
+
+    // Part of the vested amount can be claimed now and the rest later
+    harness.warp_to(START + 18 * SECONDS_PER_MONTH).await;
+    harness.claim(0, 50).await.unwrap();
+    assert_eq!(harness.claimed(0).await, 248);
+    // 18 of 36 months: 50% of 600 = 300, of which 248 are claimed
+    assert_vesting_error(harness.claim(0, 53).await, VestingError::ClaimAmountTooHigh);
+    harness.claim(0, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, 300);
+
+    let data_account: DataAccount = harness.account(harness.data_account).await;
+    assert_eq!(data_account.claimed_total, 300 + 132);
+    assert_eq!(data_account.total_allocated, 600 + 400);
+    assert_eq!(data_account.beneficiary_count, 2);
+}
+
+#[tokio::test]
+async fn full_vesting_pays_out_exactly_the_allocation() {
+    let mut harness = Harness::new(1).await;
+    harness.initialize(DEPOSIT, false).await.unwrap();
+    harness.add_beneficiaries(&[(0, DEPOSIT)]).await.unwrap();
+
+    harness.warp_to(START + 7 * SECONDS_PER_MONTH).await;
+    harness.claim(0, 0).await.unwrap();
+    // Long after the end the remainder vests, without rounding left-overs
+    harness.warp_to(START + 100 * SECONDS_PER_MONTH).await;
+    harness.claim(0, 0).await.unwrap();
+
+    assert_eq!(harness.claimed(0).await, DEPOSIT);
+    assert_eq!(harness.wallet_balance(0).await, DEPOSIT * UNIT);
+    let escrow_wallet = harness.escrow_wallet;
+    assert_eq!(harness.balance(escrow_wallet).await, 0);
+}
+
+#[tokio::test]
+async fn claiming_twice_in_a_row_pays_nothing_more() {
+    let mut harness = Harness::new(1).await;
+    harness.initialize(DEPOSIT, false).await.unwrap();
+    harness.add_beneficiaries(&[(0, DEPOSIT)]).await.unwrap();
+
+    harness.warp_to(START + 12 * SECONDS_PER_MONTH).await;
+    harness.claim(0, 0).await.unwrap();
+    let balance = harness.wallet_balance(0).await;
+
+    harness.claim(0, 0).await.unwrap();
+    assert_vesting_error(harness.claim(0, 1).await, VestingError::ClaimAmountTooHigh);
+    assert_eq!(harness.claimed(0).await, 330);
+    assert_eq!(harness.wallet_balance(0).await, balance);
+}
+
+#[tokio::test]
+async fn cancel_freezes_vesting_and_returns_the_unvested_remainder() {
+    let mut harness = Harness::new(1).await;
+    harness.initialize(DEPOSIT, true).await.unwrap();
+    harness.add_beneficiaries(&[(0, DEPOSIT)]).await.unwrap();
+    let authority_tokens = harness.authority_tokens;
+    let before = harness.balance(authority_tokens).await;
+
+    harness.warp_to(START + 12 * SECONDS_PER_MONTH).await;
+    harness.schedule_cancel().await.unwrap();
+    assert_vesting_error(harness.execute_cancel().await, VestingError::CancelGracePeriodActive);
+
+    // Still within the 12th month once the grace period is over
+    harness.warp_to(START + 12 * SECONDS_PER_MONTH + token_vesting::CANCEL_GRACE_PERIOD).await;
+    harness.execute_cancel().await.unwrap();
+    let data_account: DataAccount = harness.account(harness.data_account).await;
+    assert!(data_account.cancelled);
+    assert_eq!(data_account.final_vested_bps, 3_300);
+    assert_eq!(harness.balance(authority_tokens).await - before, 670 * UNIT);
+
+    // The beneficiary keeps what had vested, and nothing more vests afterwards
+    harness.warp_to(START + 40 * SECONDS_PER_MONTH).await;
+    harness.claim(0, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, 330);
+    let escrow_wallet = harness.escrow_wallet;
+    assert_eq!(harness.balance(escrow_wallet).await, 0);
+}
+
+#[tokio::test]
+async fn cancel_can_burn_the_unvested_remainder() {
+    let mut harness = Harness::new(1).await;
+    harness.initialize(DEPOSIT, true).await.unwrap();
+    harness.add_beneficiaries(&[(0, DEPOSIT)]).await.unwrap();
+    assert_vesting_error(
+        harness.set_cancel_disposition(Disposition::SendToTreasury).await,
+        VestingError::TreasurerNotSet,
+    );
+    harness.set_cancel_disposition(Disposition::Burn).await.unwrap();
+    let authority_tokens = harness.authority_tokens;
+    let before = harness.balance(authority_tokens).await;
+    let supply_before = harness.supply().await;
+
+    harness.warp_to(START + 12 * SECONDS_PER_MONTH).await;
+    harness.schedule_cancel().await.unwrap();
+    harness.warp_to(START + 12 * SECONDS_PER_MONTH + token_vesting::CANCEL_GRACE_PERIOD).await;
+    harness.execute_cancel().await.unwrap();
+    // The 67% that hadn't vested is burned instead of returned
+    assert_eq!(supply_before - harness.supply().await, 670 * UNIT);
+    assert_eq!(harness.balance(authority_tokens).await, before);
+
+    harness.claim(0, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, 330);
+    let escrow_wallet = harness.escrow_wallet;
+    assert_eq!(harness.balance(escrow_wallet).await, 0);
+}
+
+#[tokio::test]
+async fn withdraw_unclaimed_only_after_the_end() {
+    let mut harness = Harness::new(1).await;
+    harness.initialize(DEPOSIT, true).await.unwrap();
+    harness.add_beneficiaries(&[(0, 600)]).await.unwrap();
+    let authority_tokens = harness.authority_tokens;
+
+    harness.warp_to(START + 35 * SECONDS_PER_MONTH).await;
+    assert_vesting_error(harness.withdraw_unclaimed().await, VestingError::VestingStillActive);
+
+    harness.warp_to(START + 36 * SECONDS_PER_MONTH).await;
+    harness.claim(0, 0).await.unwrap();
+    let before = harness.balance(authority_tokens).await;
+    harness.withdraw_unclaimed().await.unwrap();
+    assert_eq!(harness.balance(authority_tokens).await - before, 400 * UNIT);
+    assert_vesting_error(harness.withdraw_unclaimed().await, VestingError::NoUnclaimedTokens);
+}
+
+#[tokio::test]
+async fn withdraw_split_pays_and_burns_the_residual_by_share() {
+    let mut harness = Harness::new(1).await;
+    harness.initialize(DEPOSIT, true).await.unwrap();
+    harness.add_beneficiaries(&[(0, 600)]).await.unwrap();
+    let treasury = harness.authority_tokens;
+    // The beneficiary's token account stands in for a community fund
+    let community = get_associated_token_address(&harness.beneficiaries[0].pubkey(), &harness.mint);
+
+    let share = |recipient, bps| WithdrawShare { recipient, bps };
+    assert_vesting_error(
+        harness.set_withdraw_split(vec![share(Some(treasury), 5_000), share(None, 4_000)]).await,
+        VestingError::InvalidWithdrawSplit,
+    );
+    harness
+        .set_withdraw_split(vec![share(Some(treasury), 5_000), share(None, 3_000), share(Some(community), 2_000)])
+        .await
+        .unwrap();
+
+    harness.warp_to(START + 36 * SECONDS_PER_MONTH).await;
+    assert_vesting_error(harness.set_withdraw_split(Vec::new()).await, VestingError::WithdrawSplitLocked);
+    harness.claim(0, 0).await.unwrap();
+    let treasury_before = harness.balance(treasury).await;
+    let supply_before = harness.supply().await;
+    assert_vesting_error(
+        harness.withdraw_unclaimed_split(&[community, treasury]).await,
+        VestingError::InvalidRecipient,
+    );
+
+    // 400 unclaimed tokens: 200 to the treasury, 120 burned and 80 to the community fund
+    harness.withdraw_unclaimed_split(&[treasury, community]).await.unwrap();
+    assert_eq!(harness.balance(treasury).await - treasury_before, 200 * UNIT);
+    assert_eq!(supply_before - harness.supply().await, 120 * UNIT);
+    assert_eq!(harness.wallet_balance(0).await, (600 + 80) * UNIT);
+    let escrow_wallet = harness.escrow_wallet;
+    assert_eq!(harness.balance(escrow_wallet).await, 0);
+}
+
+#[tokio::test]
+async fn irrevocable_schedules_cannot_be_cancelled_or_swept() {
+    let mut harness = Harness::new(1).await;
+    harness.initialize(DEPOSIT, false).await.unwrap();
+    harness.add_beneficiaries(&[(0, DEPOSIT)]).await.unwrap();
+
+    assert_vesting_error(harness.schedule_cancel().await, VestingError::VestingNotCancellable);
+    harness.warp_to(START + 40 * SECONDS_PER_MONTH).await;
+    assert_vesting_error(harness.withdraw_unclaimed().await, VestingError::VestingNotCancellable);
+}
+
+#[tokio::test]
+async fn deposit_overflowing_u64_when_scaled_is_rejected() {
+    let mut harness = Harness::new(0).await;
+    // Scaled by 10^6 this wraps around to 448_384 raw units, which the authority could pay
+    let amount = u64::MAX / UNIT + 1;
+    assert!(harness.initialize(amount, false).await.is_err());
+    let data_account = harness.data_account;
+    assert!(harness.context.banks_client.get_account(data_account).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn initialize_checks_the_funding_account_first() {
+    let mut harness = Harness::new(0).await;
+    // The authority holds 2 * DEPOSIT tokens
+    assert_vesting_error(harness.initialize(2 * DEPOSIT + 1, false).await, VestingError::InsufficientFunding);
+    let data_account = harness.data_account;
+    assert!(harness.context.banks_client.get_account(data_account).await.unwrap().is_none());
+    harness.initialize(2 * DEPOSIT, false).await.unwrap();
+}
+
+#[tokio::test]
+async fn allocations_overflowing_u64_are_rejected() {
+    let mut harness = Harness::new(2).await;
+    harness.initialize(DEPOSIT, false).await.unwrap();
+    harness.add_beneficiaries(&[(0, 1)]).await.unwrap();
+
+    assert_vesting_error(
+        harness.add_beneficiaries(&[(1, u64::MAX)]).await,
+        VestingError::AllocationExceedsEscrow,
+    );
+    // A claim of u64::MAX tokens is refused, not scaled into an overflow
+    harness.warp_to(START + 36 * SECONDS_PER_MONTH).await;
+    assert_vesting_error(harness.claim(0, u64::MAX).await, VestingError::ClaimAmountTooHigh);
+    harness.claim(0, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, 1);
+}
+
+#[tokio::test]
+async fn published_offsets_match_the_serialized_accounts() {
+    let mut harness = Harness::new(1).await;
+    harness.initialize(DEPOSIT, false).await.unwrap();
+    harness.add_beneficiaries(&[(0, DEPOSIT)]).await.unwrap();
+    let pubkey_at = |data: &[u8], offset: usize| Pubkey::try_from(&data[offset..offset + 32]).unwrap();
+
+    let data_account = harness.context.banks_client.get_account(harness.data_account).await.unwrap().unwrap();
+    let data = data_account.data.as_slice();
+    assert_eq!(data[..8], schema::DATA_ACCOUNT_DISCRIMINATOR);
+    assert_eq!(pubkey_at(data, schema::DATA_ACCOUNT_INITIALIZER_OFFSET), harness.authority.pubkey());
+    assert_eq!(pubkey_at(data, schema::DATA_ACCOUNT_ESCROW_WALLET_OFFSET), harness.escrow_wallet);
+    assert_eq!(pubkey_at(data, schema::DATA_ACCOUNT_TOKEN_MINT_OFFSET), harness.mint);
+    assert_eq!(data[schema::DATA_ACCOUNT_DECIMALS_OFFSET], DECIMALS);
+
+    let address = harness.beneficiary_account(0);
+    let beneficiary = harness.context.banks_client.get_account(address).await.unwrap().unwrap();
+    let data = beneficiary.data.as_slice();
+    let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+    assert_eq!(data[..8], schema::BENEFICIARY_ACCOUNT_DISCRIMINATOR);
+    assert_eq!(pubkey_at(data, schema::BENEFICIARY_ACCOUNT_KEY_OFFSET), harness.beneficiaries[0].pubkey());
+    assert_eq!(u64_at(schema::BENEFICIARY_ACCOUNT_ALLOCATED_TOKENS_OFFSET), DEPOSIT);
+    assert_eq!(u64_at(schema::BENEFICIARY_ACCOUNT_CLAIMED_TOKENS_OFFSET), 0);
+}
+
+#[tokio::test]
+async fn beneficiary_index_enumerates_added_beneficiaries() {
+    let mut harness = Harness::new(3).await;
+    harness.initialize(DEPOSIT, false).await.unwrap();
+    harness.add_beneficiaries(&[(0, 100), (1, 200)]).await.unwrap();
+    harness.add_beneficiaries(&[(2, 300)]).await.unwrap();
+
+    let data_account: DataAccount = harness.account(harness.data_account).await;
+    assert_eq!(data_account.beneficiary_index_len, 3);
+    for index in 0..3 {
+        let entry: BeneficiaryIndex = harness.account(harness.beneficiary_index(index)).await;
+        assert_eq!(entry.data_account, harness.data_account);
+        assert_eq!(entry.beneficiary, harness.beneficiaries[index as usize].pubkey());
+        let beneficiary: BeneficiaryAccount = harness.account(harness.beneficiary_account(index as usize)).await;
+        assert_eq!(beneficiary.index, index);
+    }
+}
+
+#[tokio::test]
+async fn feature_gated_instructions_wait_for_their_feature() {
+    let mut harness = Harness::new(1).await;
+    harness.initialize(DEPOSIT, false).await.unwrap();
+
+    harness.set_features(KNOWN_FEATURES & !FEATURE_VESTING_CURVES);
+    assert_vesting_error(
+        harness.set_vesting_curve(CurveType::BackWeighted).await,
+        VestingError::FeatureDisabled,
+    );
+    // Other features stay usable
+    harness.set_cancel_disposition(Disposition::Burn).await.unwrap();
+
+    harness.set_features(KNOWN_FEATURES);
+    harness.set_vesting_curve(CurveType::BackWeighted).await.unwrap();
+}
+
+#[tokio::test]
+async fn halted_program_rejects_claims_until_resumed() {
+    let mut harness = Harness::new(1).await;
+    harness.initialize(DEPOSIT, false).await.unwrap();
+    harness.add_beneficiaries(&[(0, DEPOSIT)]).await.unwrap();
+    harness.warp_to(START + 36 * SECONDS_PER_MONTH).await;
+
+    harness.set_halted(true);
+    assert_vesting_error(harness.claim(0, 0).await, VestingError::ProgramHalted);
+    assert_eq!(harness.claimed(0).await, 0);
+
+    harness.set_halted(false);
+    harness.claim(0, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, DEPOSIT);
+}
+
+#[tokio::test]
+async fn missing_program_state_means_not_halted() {
+    let mut harness = Harness::new(1).await;
+    harness.remove_program_state();
+    harness.initialize(DEPOSIT, false).await.unwrap();
+    harness.add_beneficiaries(&[(0, DEPOSIT)]).await.unwrap();
+    harness.warp_to(START + 36 * SECONDS_PER_MONTH).await;
+
+    harness.claim(0, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, DEPOSIT);
+}
+
+#[tokio::test]
+async fn v1_and_v2_schedules_of_one_mint_sign_with_their_own_seeds() {
+    let mut harness = Harness::new(1).await;
+    harness.initialize(DEPOSIT, false).await.unwrap();
+    harness.add_beneficiaries(&[(0, 600)]).await.unwrap();
+    let (v1_data_account, v1_escrow_wallet) = (harness.data_account, harness.escrow_wallet);
+
+    harness.initialize_v2(7, DEPOSIT).await.unwrap();
+    harness.add_beneficiaries(&[(0, 400)]).await.unwrap();
+    let data_account: DataAccount = harness.account(harness.data_account).await;
+    assert_eq!((data_account.seed_version, data_account.schedule_id), (SEED_VERSION_V2, 7));
+
+    // The v2 escrow pays out with the schedule id in the signer seeds
+    harness.warp_to(START + 36 * SECONDS_PER_MONTH).await;
+    harness.claim(0, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, 400);
+    let escrow_wallet = harness.escrow_wallet;
+    assert_eq!(harness.balance(escrow_wallet).await, 600 * UNIT);
+
+    // The v1 schedule keeps its mint-only address and signer
+    harness.data_account = v1_data_account;
+    harness.escrow_wallet = v1_escrow_wallet;
+    let data_account: DataAccount = harness.account(v1_data_account).await;
+    assert_eq!(data_account.seed_version, SEED_VERSION_V1);
+    harness.claim(0, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, 600);
+    assert_eq!(harness.wallet_balance(0).await, DEPOSIT * UNIT);
+}
+
+#[tokio::test]
+async fn beneficiaries_beyond_the_cap_are_rejected() {
+    let mut harness = Harness::new(3).await;
+    harness.initialize(DEPOSIT, false).await.unwrap();
+    harness.set_max_beneficiaries(2).await.unwrap();
+    harness.add_beneficiaries(&[(0, 100), (1, 100)]).await.unwrap();
+
+    assert_vesting_error(harness.add_beneficiaries(&[(2, 100)]).await, VestingError::BeneficiaryLimitReached);
+    let data_account: DataAccount = harness.account(harness.data_account).await;
+    assert_eq!(data_account.beneficiary_count, 2);
+
+    // Lifting the cap lets the next chunk in
+    harness.set_max_beneficiaries(0).await.unwrap();
+    harness.add_beneficiaries(&[(2, 100)]).await.unwrap();
+    let data_account: DataAccount = harness.account(harness.data_account).await;
+    assert_eq!(data_account.beneficiary_count, 3);
+}
+
+#[tokio::test]
+async fn anyone_finalizes_a_schedule_after_the_grace_period() {
+    let mut harness = Harness::new(1).await;
+    harness.initialize(DEPOSIT, false).await.unwrap();
+    harness.add_beneficiaries(&[(0, DEPOSIT)]).await.unwrap();
+
+    let end = START + 36 * SECONDS_PER_MONTH;
+    harness.warp_to(end + token_vesting::FINALIZE_GRACE_PERIOD - 1).await;
+    assert_vesting_error(harness.finalize_expired().await, VestingError::ScheduleNotExpired);
+
+    harness.warp_to(end + token_vesting::FINALIZE_GRACE_PERIOD).await;
+    harness.finalize_expired().await.unwrap();
+    let data_account: DataAccount = harness.account(harness.data_account).await;
+    assert!(data_account.completed);
+    assert_eq!(data_account.percent_available, 100);
+    assert_vesting_error(harness.finalize_expired().await, VestingError::ScheduleAlreadyCompleted);
+
+    harness.claim(0, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, DEPOSIT);
+}
+
+#[tokio::test]
+async fn initialize_with_beneficiaries_is_all_or_nothing() {
+    let mut harness = Harness::new(2).await;
+    // Over-allocating leaves neither the schedule nor a beneficiary behind
+    assert_vesting_error(
+        harness.initialize_with_beneficiaries(DEPOSIT, &[(0, 600), (1, 401)]).await,
+        VestingError::AllocationExceedsEscrow,
+    );
+    let data_account = harness.data_account;
+    assert!(harness.context.banks_client.get_account(data_account).await.unwrap().is_none());
+
+    harness.initialize_with_beneficiaries(DEPOSIT, &[(0, 600), (1, 400)]).await.unwrap();
+    let data_account: DataAccount = harness.account(harness.data_account).await;
+    assert_eq!(data_account.total_allocated, DEPOSIT);
+    assert_eq!(data_account.beneficiary_count, 2);
+    assert_eq!(data_account.batch_cursor, 0);
+
+    harness.warp_to(START + 36 * SECONDS_PER_MONTH).await;
+    harness.claim(1, 0).await.unwrap();
+    assert_eq!(harness.claimed(1).await, 400);
+}
+
+#[tokio::test]
+async fn claims_are_capped_at_the_funded_share_until_installments_arrive() {
+    let mut harness = Harness::new(2).await;
+    harness.initialize_with_installments(DEPOSIT, 300).await.unwrap();
+    harness.add_beneficiaries(&[(0, 600), (1, 400)]).await.unwrap();
+    let escrow_wallet = harness.escrow_wallet;
+    assert_eq!(harness.balance(escrow_wallet).await, 300 * UNIT);
+
+    // Fully vested, but only 30% of the commitment is funded
+    harness.warp_to(START + 36 * SECONDS_PER_MONTH).await;
+    harness.claim(0, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, 180);
+    assert_vesting_error(harness.claim(1, 121).await, VestingError::ClaimAmountTooHigh);
+
+    // Installments can't fund more than was committed
+    assert_vesting_error(harness.fund_installment(701).await, VestingError::InvalidInstallment);
+    harness.fund_installment(700).await.unwrap();
+    let data_account: DataAccount = harness.account(harness.data_account).await;
+    assert_eq!(data_account.funded_amount, DEPOSIT);
+
+    harness.claim(0, 0).await.unwrap();
+    harness.claim(1, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, 600);
+    assert_eq!(harness.claimed(1).await, 400);
+    assert_eq!(harness.balance(escrow_wallet).await, 0);
+}
+
+#[tokio::test]
+async fn removals_wait_out_the_dispute_window_and_can_be_vetoed() {
+    let mut harness = Harness::new(2).await;
+    harness.initialize(DEPOSIT, false).await.unwrap();
+    harness.add_beneficiaries(&[(0, 600), (1, 400)]).await.unwrap();
+    let arbiter = Keypair::new();
+    harness.set_removal_arbiter(arbiter.pubkey()).await.unwrap();
+
+    // Removal only works on scheduled beneficiaries, and only after the dispute window
+    assert_vesting_error(harness.execute_removal(0, true).await, VestingError::RemovalNotScheduled);
+    harness.schedule_removal(0).await.unwrap();
+    assert_vesting_error(harness.schedule_removal(0).await, VestingError::RemovalAlreadyScheduled);
+    assert_vesting_error(harness.execute_removal(0, true).await, VestingError::DisputeWindowActive);
+
+    // The arbiter's veto drops the scheduled removal
+    harness.veto_removal(0, &arbiter).await.unwrap();
+    harness.warp_to(GENESIS + 8 * SECONDS_PER_DAY).await;
+    assert_vesting_error(harness.execute_removal(0, true).await, VestingError::RemovalNotScheduled);
+
+    // The beneficiary keeps claiming during the window
+    harness.warp_to(START + 12 * SECONDS_PER_MONTH).await;
+    harness.schedule_removal(1).await.unwrap();
+    harness.claim(1, 0).await.unwrap();
+    assert_eq!(harness.claimed(1).await, 132);
+    harness.warp_to(START + 12 * SECONDS_PER_MONTH + 7 * SECONDS_PER_DAY).await;
+    harness.execute_removal(1, true).await.unwrap();
+
+    let beneficiary_account = harness.beneficiary_account(1);
+    assert!(harness.context.banks_client.get_account(beneficiary_account).await.unwrap().is_none());
+    let data_account: DataAccount = harness.account(harness.data_account).await;
+    assert_eq!(data_account.beneficiary_count, 1);
+    assert_eq!(data_account.total_allocated, 600);
+}
+
+#[tokio::test]
+async fn nearest_rounding_pays_partial_shares_to_the_closest_token() {
+    let mut harness = Harness::new(2).await;
+    harness.initialize(DEPOSIT, false).await.unwrap();
+    harness.set_rounding_policy(RoundingPolicy::Nearest).await.unwrap();
+    harness.add_beneficiaries(&[(0, 5), (1, 995)]).await.unwrap();
+
+    // 12 of 36 months: 33% of 5 is 1.65 and of 995 is 328.35
+    harness.warp_to(START + 12 * SECONDS_PER_MONTH).await;
+    harness.claim(0, 0).await.unwrap();
+    harness.claim(1, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, 2);
+    assert_eq!(harness.claimed(1).await, 328);
+    assert_vesting_error(harness.set_rounding_policy(RoundingPolicy::Floor).await, VestingError::RoundingLocked);
+
+    // The final claims still add up to exactly the deposit
+    harness.warp_to(START + 36 * SECONDS_PER_MONTH).await;
+    harness.claim(0, 0).await.unwrap();
+    harness.claim(1, 0).await.unwrap();
+    let escrow_wallet = harness.escrow_wallet;
+    assert_eq!(harness.balance(escrow_wallet).await, 0);
+}
+
+#[tokio::test]
+async fn back_weighted_curve_vests_more_each_year() {
+    let mut harness = Harness::new(1).await;
+    harness.initialize(DEPOSIT, false).await.unwrap();
+    assert_vesting_error(harness.set_vesting_curve(CurveType::Custom(Vec::new())).await, VestingError::InvalidCurve);
+    harness.set_vesting_curve(CurveType::BackWeighted).await.unwrap();
+    harness.add_beneficiaries(&[(0, DEPOSIT)]).await.unwrap();
+
+    // Over 36 months the three years weigh 1, 2 and 3: 16.66% after the first year, half after the second
+    harness.warp_to(START + 12 * SECONDS_PER_MONTH).await;
+    harness.claim(0, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, 166);
+    assert_vesting_error(harness.set_vesting_curve(CurveType::Linear).await, VestingError::CurveLocked);
+
+    harness.warp_to(START + 24 * SECONDS_PER_MONTH).await;
+    harness.claim(0, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, 500);
+
+    harness.warp_to(START + 36 * SECONDS_PER_MONTH).await;
+    harness.claim(0, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, DEPOSIT);
+}
+
+#[tokio::test]
+async fn cohort_overrides_apply_to_members_at_claim_time() {
+    let mut harness = Harness::new(2).await;
+    harness.initialize(DEPOSIT, false).await.unwrap();
+    harness.add_beneficiaries(&[(0, 600), (1, 400)]).await.unwrap();
+    harness.create_cohort(7, Some(12), None).await.unwrap();
+    harness.assign_to_cohort(1, Some(7)).await.unwrap();
+
+    // The member is still in its cohort's 12-month cliff, and has to claim with the cohort account
+    harness.warp_to(START + 6 * SECONDS_PER_MONTH).await;
+    harness.claim(0, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, 96);
+    assert_vesting_error(harness.claim(1, 0).await, VestingError::CohortMismatch);
+    assert_vesting_error(harness.claim_in_cohort(1, 0, Some(7)).await, VestingError::ClaimNotAllowed);
+
+    // Extending the cohort reaches the member without touching its account: an 18-month cliff over 48 months
+    harness.update_cohort(7, Some(18), Some(48)).await.unwrap();
+    harness.warp_to(START + 12 * SECONDS_PER_MONTH).await;
+    assert_vesting_error(harness.claim_in_cohort(1, 0, Some(7)).await, VestingError::ClaimNotAllowed);
+
+    // 18 of 48 months: 37%, while the schedule is at 50%
+    harness.warp_to(START + 18 * SECONDS_PER_MONTH).await;
+    harness.claim_in_cohort(1, 0, Some(7)).await.unwrap();
+    assert_eq!(harness.claimed(1).await, 148);
+    harness.claim(0, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, 300);
+
+    // Withdrawals wait for the longest cohort
+    let data_account: DataAccount = harness.account(harness.data_account).await;
+    assert_eq!(data_account.max_cohort_vesting_months, 48);
+}
+
+#[tokio::test]
+async fn sunset_pays_out_every_beneficiary_and_closes_the_schedule() {
+    let mut harness = Harness::new(2).await;
+    harness.initialize(DEPOSIT, false).await.unwrap();
+    harness.add_beneficiaries(&[(0, 600), (1, 300)]).await.unwrap();
+    harness.warp_to(START + 12 * SECONDS_PER_MONTH).await;
+    harness.claim(0, 0).await.unwrap();
+    assert_eq!(harness.claimed(0).await, 198);
+
+    harness.set_sunset(START + 18 * SECONDS_PER_MONTH);
+    assert_vesting_error(harness.sunset_claim(0).await, VestingError::SunsetNotActive);
+
+    // Once the sunset is in effect anyone can push out the rest of each allocation, vested or not
+    harness.warp_to(START + 18 * SECONDS_PER_MONTH).await;
+    assert_vesting_error(harness.sunset_close_schedule().await, VestingError::BeneficiariesRemaining);
+    harness.sunset_claim(0).await.unwrap();
+    harness.sunset_claim(1).await.unwrap();
+    assert_eq!(harness.wallet_balance(0).await, 600 * UNIT);
+    assert_eq!(harness.wallet_balance(1).await, 300 * UNIT);
+    let beneficiary_account = harness.beneficiary_account(0);
+    assert!(harness.context.banks_client.get_account(beneficiary_account).await.unwrap().is_none());
+
+    // The 100 unallocated tokens go back to the initializer, and the schedule's accounts are closed
+    harness.sunset_close_schedule().await.unwrap();
+    let authority_tokens = harness.authority_tokens;
+    assert_eq!(harness.balance(authority_tokens).await, (DEPOSIT + 100) * UNIT);
+    let data_account = harness.data_account;
+    assert!(harness.context.banks_client.get_account(data_account).await.unwrap().is_none());
+}
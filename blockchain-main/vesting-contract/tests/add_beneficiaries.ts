@@ -11,6 +11,7 @@ import {
   createAssociatedTokenAccount,
 } from "@solana/spl-token";
 import { assert } from "chai";
+import { ensureConfig, fixedBytes, getBeneficiaryIndexPda } from "./helpers";
 
 describe("token_vesting full suite", () => {
   const provider = anchor.AnchorProvider.env();
@@ -23,8 +24,7 @@ describe("token_vesting full suite", () => {
   let senderAta: anchor.web3.PublicKey;
   let escrowWallet: anchor.web3.PublicKey;
   let dataAccount: anchor.web3.PublicKey;
-  let dataBump: number;
-  let escrowBump: number;
+  let config: anchor.web3.PublicKey;
   let beneficiaryKeypair: anchor.web3.Keypair;
   let beneficiaryPda: anchor.web3.PublicKey;
 
@@ -34,18 +34,19 @@ describe("token_vesting full suite", () => {
   const pastTimestamp = Math.floor(Date.now() / 1000) - 36 * SECONDS_IN_MONTH;
 
   function getPDAs(tokenMint: anchor.web3.PublicKey) {
-    const [dataAccount, dataBump] = anchor.web3.PublicKey.findProgramAddressSync(
+    const [dataAccount] = anchor.web3.PublicKey.findProgramAddressSync(
       [Buffer.from("data_account"), tokenMint.toBuffer()],
       program.programId
     );
-    const [escrowWallet, escrowBump] = anchor.web3.PublicKey.findProgramAddressSync(
+    const [escrowWallet] = anchor.web3.PublicKey.findProgramAddressSync(
       [Buffer.from("escrow_wallet"), tokenMint.toBuffer()],
       program.programId
     );
-    return { dataAccount, dataBump, escrowWallet, escrowBump };
+    return { dataAccount, escrowWallet };
   }
 
   it("Initializes vesting contract", async () => {
+    config = await ensureConfig(program, provider);
     tokenMint = await createMint(
       provider.connection,
       payer.payer,
@@ -72,12 +73,10 @@ describe("token_vesting full suite", () => {
 
     const pda = getPDAs(tokenMint);
     dataAccount = pda.dataAccount;
-    dataBump = pda.dataBump;
     escrowWallet = pda.escrowWallet;
-    escrowBump = pda.escrowBump;
 
     await program.methods
-      .initialize(dataBump, new anchor.BN(vestingAmount), tokenDecimals, new anchor.BN(pastTimestamp))
+      .initialize(new anchor.BN(vestingAmount), tokenDecimals, new anchor.BN(pastTimestamp), true, false, true, 0, null, 0, 0, { both: {} }, fixedBytes("Add beneficiaries", 32), fixedBytes("", 128))
       .accounts({
         dataAccount,
         escrowWallet,
@@ -107,8 +106,9 @@ describe("token_vesting full suite", () => {
         {
           key: beneficiaryKeypair.publicKey,
           allocatedTokens: new anchor.BN(100),
+          recoveryAuthority: null,
         },
-      ])
+      ], 0)
       .accounts({
         dataAccount,
         sender: payer.publicKey,
@@ -121,6 +121,11 @@ describe("token_vesting full suite", () => {
           isSigner: false,
           isWritable: true,
         },
+        {
+          pubkey: getBeneficiaryIndexPda(program, dataAccount, 0),
+          isSigner: false,
+          isWritable: true,
+        },
       ])
       .rpc();
 
@@ -128,6 +133,100 @@ describe("token_vesting full suite", () => {
     assert.ok(acc.key.equals(beneficiaryKeypair.publicKey));
     assert.ok(acc.allocatedTokens.eq(new anchor.BN(100)));
     assert.ok(acc.claimedTokens.eq(new anchor.BN(0)));
+    assert.equal(acc.index, 0);
+
+    const entry = await program.account.beneficiaryIndex.fetch(getBeneficiaryIndexPda(program, dataAccount, 0));
+    assert.ok(entry.dataAccount.equals(dataAccount));
+    assert.ok(entry.beneficiary.equals(beneficiaryKeypair.publicKey));
+  });
+
+  it("Rejects a beneficiary account that isn't the beneficiary PDA", async () => {
+    const other = anchor.web3.Keypair.generate();
+    let threw = false;
+    try {
+      await program.methods
+        .addBeneficiaries([
+          {
+            key: other.publicKey,
+            allocatedTokens: new anchor.BN(1),
+            recoveryAuthority: null,
+          },
+        ], 1)
+        .accounts({
+          dataAccount,
+          sender: payer.publicKey,
+          tokenMint,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([
+          {
+            pubkey: anchor.web3.Keypair.generate().publicKey,
+            isSigner: false,
+            isWritable: true,
+          },
+          {
+            pubkey: getBeneficiaryIndexPda(program, dataAccount, 1),
+            isSigner: false,
+            isWritable: true,
+          },
+        ])
+        .rpc();
+    } catch (e) {
+      threw = true;
+      assert.ok(e.message.includes("InvalidBeneficiaryPDA"));
+    }
+    assert.isTrue(threw);
+  });
+
+  it("Finalizes the beneficiary batch", async () => {
+    let data = await program.account.dataAccount.fetch(dataAccount);
+    assert.equal(data.batchCursor, 1);
+
+    await program.methods
+      .finalizeBeneficiaryBatch(1)
+      .accounts({
+        dataAccount,
+        tokenMint,
+        sender: payer.publicKey,
+      })
+      .rpc();
+
+    data = await program.account.dataAccount.fetch(dataAccount);
+    assert.equal(data.batchCursor, 0);
+  });
+
+  it("Adds a single beneficiary whose PDA is resolved by the client", async () => {
+    const other = anchor.web3.Keypair.generate();
+
+    // Neither the data account nor the beneficiary PDA is passed: both are derived from the IDL seeds
+    await program.methods
+      .addBeneficiary({
+        key: other.publicKey,
+        allocatedTokens: new anchor.BN(50),
+        recoveryAuthority: null,
+      })
+      .accounts({
+        sender: payer.publicKey,
+        tokenMint,
+      })
+      .rpc();
+
+    const [otherPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("beneficiary"), dataAccount.toBuffer(), other.publicKey.toBuffer()],
+      program.programId
+    );
+    const acc = await program.account.beneficiaryAccount.fetch(otherPda);
+    assert.ok(acc.key.equals(other.publicKey));
+    assert.ok(acc.allocatedTokens.eq(new anchor.BN(50)));
+
+    const data = await program.account.dataAccount.fetch(dataAccount);
+    assert.ok(data.totalAllocated.eq(new anchor.BN(150)));
+    assert.equal(data.batchCursor, 0);
+
+    // The beneficiary index entry is resolved from `beneficiary_index_len` too
+    assert.equal(data.beneficiaryIndexLen, 2);
+    const entry = await program.account.beneficiaryIndex.fetch(getBeneficiaryIndexPda(program, dataAccount, 1));
+    assert.ok(entry.beneficiary.equals(other.publicKey));
   });
 
   it("Allows beneficiary to claim available tokens", async () => {
@@ -136,14 +235,27 @@ describe("token_vesting full suite", () => {
     await new Promise((res) => setTimeout(res, 2000));
 
     const tx = await program.methods
-      .claim(dataBump, 255) // dummy bump, can skip PDA check if test-only
+      .claim(new anchor.BN(0), false, null)
       .accounts({
         dataAccount,
         beneficiaryAccount: beneficiaryPda,
         escrowWallet,
+        beneficiaryWallet: beneficiaryKeypair.publicKey,
         sender: beneficiaryKeypair.publicKey,
         tokenMint,
         walletToDepositTo: beneficiaryAta,
+        payoutTokenAccount: null,
+        config,
+        feeRecipient: payer.publicKey,
+        feeTokenAccount: senderAta,
+        claimApprover: null,
+        memoProgram: null,
+        positionTokenAccount: null,
+        claimRecord: null,
+        secondaryTokenAccount: null,
+        blacklist: null,
+        gateProgram: null,
+        gateConfig: null,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
@@ -159,7 +271,7 @@ describe("token_vesting full suite", () => {
     const recipientAta = await getAssociatedTokenAddress(tokenMint, payer.publicKey);
 
     await program.methods
-      .withdrawUnclaimed(dataBump, escrowBump)
+      .withdrawUnclaimed()
       .accounts({
         dataAccount,
         escrowWallet,
@@ -7,6 +7,7 @@ import {
   mintTo,
 } from "@solana/spl-token";
 import { assert } from "chai";
+import { ensureConfig, fixedBytes, getBeneficiaryIndexPda } from "./helpers";
 import { BN } from "bn.js";
 import {
   Keypair,
@@ -24,14 +25,14 @@ describe("advanced-token-vesting", () => {
   let senderTokenAccount: PublicKey;
   let dataAccount: PublicKey;
   let escrowWallet: PublicKey;
-  let dataBump: number;
-  let escrowBump: number;
+  let config: PublicKey;
 
   const tokenDecimals = 6;
   const amount = new BN(1); // 1 token (multiplied inside program)
   const startTimestamp = Math.floor(Date.now() / 1000) + 60; // 1 minute from now
 
   before(async () => {
+    config = await ensureConfig(program, provider);
     mint = await createMint(
       provider.connection,
       sender.payer,
@@ -58,18 +59,18 @@ describe("advanced-token-vesting", () => {
       amount.toNumber() * 10 ** tokenDecimals
     );
 
-    [dataAccount, dataBump] = PublicKey.findProgramAddressSync(
+    [dataAccount] = PublicKey.findProgramAddressSync(
       [Buffer.from("data_account"), mint.toBuffer()],
       program.programId
     );
 
-    [escrowWallet, escrowBump] = PublicKey.findProgramAddressSync(
+    [escrowWallet] = PublicKey.findProgramAddressSync(
       [Buffer.from("escrow_wallet"), mint.toBuffer()],
       program.programId
     );
 
     await program.methods
-      .initialize(dataBump, amount, tokenDecimals, new BN(startTimestamp))
+      .initialize(amount, tokenDecimals, new BN(startTimestamp), false, false, false, 0, null, 0, 0, { both: {} }, fixedBytes("Advanced", 32), fixedBytes("", 128))
       .accounts({
         dataAccount,
         escrowWallet,
@@ -87,15 +88,16 @@ describe("advanced-token-vesting", () => {
     const newBeneficiary = {
       key: beneficiary.publicKey,
       allocatedTokens: new BN(1),
+      recoveryAuthority: null,
     };
 
-    const [beneficiaryAccount, beneficiaryBump] = PublicKey.findProgramAddressSync(
+    const [beneficiaryAccount] = PublicKey.findProgramAddressSync(
       [Buffer.from("beneficiary"), dataAccount.toBuffer(), beneficiary.publicKey.toBuffer()],
       program.programId
     );
 
     await program.methods
-      .addBeneficiaries([newBeneficiary])
+      .addBeneficiaries([newBeneficiary], 0)
       .accounts({
         dataAccount,
         tokenMint: mint,
@@ -104,6 +106,7 @@ describe("advanced-token-vesting", () => {
       })
       .remainingAccounts([
         { pubkey: beneficiaryAccount, isSigner: false, isWritable: true },
+        { pubkey: getBeneficiaryIndexPda(program, dataAccount, 0), isSigner: false, isWritable: true },
       ])
       .rpc();
 
@@ -117,14 +120,27 @@ describe("advanced-token-vesting", () => {
     let threw = false;
     try {
       await program.methods
-        .claim(dataBump, beneficiaryBump)
+        .claim(new BN(0), false, null)
         .accounts({
           dataAccount,
           beneficiaryAccount,
           escrowWallet,
+          beneficiaryWallet: beneficiary.publicKey,
           sender: beneficiary.publicKey,
           tokenMint: mint,
           walletToDepositTo: beneficiaryATA,
+          payoutTokenAccount: null,
+          config,
+          feeRecipient: sender.publicKey,
+          feeTokenAccount: senderTokenAccount,
+          claimApprover: null,
+          memoProgram: null,
+          positionTokenAccount: null,
+          claimRecord: null,
+          secondaryTokenAccount: null,
+          blacklist: null,
+          gateProgram: null,
+          gateConfig: null,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
@@ -140,7 +156,7 @@ describe("advanced-token-vesting", () => {
 
   it("Allows releasing percent multiple times", async () => {
     await program.methods
-      .release(dataBump, 10)
+      .release(10)
       .accounts({
         dataAccount,
         tokenMint: mint,
@@ -150,7 +166,7 @@ describe("advanced-token-vesting", () => {
       .rpc();
 
     await program.methods
-      .release(dataBump, 20)
+      .release(20)
       .accounts({
         dataAccount,
         tokenMint: mint,
@@ -162,4 +178,96 @@ describe("advanced-token-vesting", () => {
     const acc = await program.account.dataAccount.fetch(dataAccount);
     assert.equal(acc.percentAvailable, 30);
   });
+
+  it("Lets an operator release until the role is revoked", async () => {
+    const operator = Keypair.generate();
+    const roleAccounts = {
+      dataAccount,
+      tokenMint: mint,
+      sender: sender.publicKey,
+    };
+    const releaseAccounts = {
+      dataAccount,
+      tokenMint: mint,
+      sender: operator.publicKey,
+      systemProgram: SystemProgram.programId,
+    };
+
+    await program.methods
+      .grantRole({ operator: {} }, operator.publicKey)
+      .accounts(roleAccounts)
+      .rpc();
+    await program.methods
+      .release(5)
+      .accounts(releaseAccounts)
+      .signers([operator])
+      .rpc();
+    const acc = await program.account.dataAccount.fetch(dataAccount);
+    assert.equal(acc.percentAvailable, 35);
+    assert.ok(acc.operator.equals(operator.publicKey));
+
+    await program.methods
+      .revokeRole({ operator: {} })
+      .accounts(roleAccounts)
+      .rpc();
+    let threw = false;
+    try {
+      await program.methods
+        .release(5)
+        .accounts(releaseAccounts)
+        .signers([operator])
+        .rpc();
+    } catch (e) {
+      threw = true;
+      assert.ok(e.message.includes("InvalidSender"));
+    }
+    assert.isTrue(threw);
+  });
+
+  it("Rejects withdrawing unclaimed tokens from an irrevocable schedule", async () => {
+    let threw = false;
+    try {
+      await program.methods
+        .withdrawUnclaimed()
+        .accounts({
+          dataAccount,
+          escrowWallet,
+          tokenMint: mint,
+          recipient: senderTokenAccount,
+          sender: sender.publicKey,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+    } catch (e) {
+      threw = true;
+      assert.ok(e.message.includes("Vesting schedule is irrevocable"));
+    }
+    assert.isTrue(threw);
+  });
+
+  it("Migrates the escrow to a new token account", async () => {
+    const newEscrowWallet = Keypair.generate();
+    const balanceBefore = await provider.connection.getTokenAccountBalance(escrowWallet);
+
+    await program.methods
+      .migrateEscrow()
+      .accounts({
+        dataAccount,
+        escrowWallet,
+        newEscrowWallet: newEscrowWallet.publicKey,
+        tokenMint: mint,
+        sender: sender.publicKey,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      })
+      .signers([newEscrowWallet])
+      .rpc();
+
+    const acc = await program.account.dataAccount.fetch(dataAccount);
+    assert.ok(acc.escrowWallet.equals(newEscrowWallet.publicKey));
+    const balanceAfter = await provider.connection.getTokenAccountBalance(newEscrowWallet.publicKey);
+    assert.equal(balanceAfter.value.amount, balanceBefore.value.amount);
+    // The old escrow is closed
+    assert.isNull(await provider.connection.getAccountInfo(escrowWallet));
+  });
 });
@@ -0,0 +1,76 @@
+// tests/helpers.ts
+
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TokenVesting } from "../target/types/token_vesting";
+
+const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new anchor.web3.PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
+
+export function getConfigPda(program: Program<TokenVesting>) {
+  const [config] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+  return config;
+}
+
+// Entry `index` of a schedule's beneficiary index; `add_beneficiaries` takes one after each beneficiary PDA.
+export function getBeneficiaryIndexPda(
+  program: Program<TokenVesting>,
+  dataAccount: anchor.web3.PublicKey,
+  index: number
+) {
+  const indexBytes = Buffer.alloc(4);
+  indexBytes.writeUInt32LE(index);
+  const [entry] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("beneficiary_index"), dataAccount.toBuffer(), indexBytes],
+    program.programId
+  );
+  return entry;
+}
+
+// The config PDA can only be created once per deployment, so every suite calls this
+// and only the first one actually initializes it (fee-free, fees go to the provider wallet).
+export async function ensureConfig(
+  program: Program<TokenVesting>,
+  provider: anchor.AnchorProvider
+) {
+  const config = getConfigPda(program);
+  if (await program.account.config.fetchNullable(config)) {
+    return config;
+  }
+
+  const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+  );
+  // Every state-changing instruction checks the program state, so it is created (not halted) first
+  await program.methods
+    .setProgramHalted(false)
+    .accounts({
+      program: program.programId,
+      programData,
+      sender: provider.wallet.publicKey,
+    })
+    .rpc();
+  await program.methods
+    .initializeConfig(provider.wallet.publicKey, 0)
+    .accounts({
+      config,
+      program: program.programId,
+      programData,
+      sender: provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    })
+    .rpc();
+  return config;
+}
+
+// Zero-padded UTF-8 bytes for fixed-size string fields like the schedule `name` and `uri`.
+export function fixedBytes(text: string, length: number): number[] {
+  const bytes = Buffer.alloc(length);
+  Buffer.from(text, "utf8").copy(bytes, 0, 0, length);
+  return Array.from(bytes);
+}
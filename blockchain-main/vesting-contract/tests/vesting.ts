@@ -11,6 +11,7 @@ import {
   createAssociatedTokenAccount,
 } from "@solana/spl-token";
 import { assert } from "chai";
+import { ensureConfig, fixedBytes, getBeneficiaryIndexPda } from "./helpers";
 
 describe("token_vesting full suite", () => {
   const provider = anchor.AnchorProvider.env();
@@ -23,11 +24,9 @@ describe("token_vesting full suite", () => {
   let senderAta: anchor.web3.PublicKey;
   let escrowWallet: anchor.web3.PublicKey;
   let dataAccount: anchor.web3.PublicKey;
-  let dataBump: number;
-  let escrowBump: number;
+  let config: anchor.web3.PublicKey;
   let beneficiaryKeypair: anchor.web3.Keypair;
   let beneficiaryPda: anchor.web3.PublicKey;
-  let beneficiaryBump: number;
 
   const vestingAmount = 1000;
   const tokenDecimals = 6;
@@ -35,18 +34,19 @@ describe("token_vesting full suite", () => {
   const pastTimestamp = Math.floor(Date.now() / 1000) - 36 * SECONDS_IN_MONTH;
 
   function getPDAs(tokenMint: anchor.web3.PublicKey) {
-    const [dataAccount, dataBump] = anchor.web3.PublicKey.findProgramAddressSync(
+    const [dataAccount] = anchor.web3.PublicKey.findProgramAddressSync(
       [Buffer.from("data_account"), tokenMint.toBuffer()],
       program.programId
     );
-    const [escrowWallet, escrowBump] = anchor.web3.PublicKey.findProgramAddressSync(
+    const [escrowWallet] = anchor.web3.PublicKey.findProgramAddressSync(
       [Buffer.from("escrow_wallet"), tokenMint.toBuffer()],
       program.programId
     );
-    return { dataAccount, dataBump, escrowWallet, escrowBump };
+    return { dataAccount, escrowWallet };
   }
 
   it("Initializes vesting contract", async () => {
+    config = await ensureConfig(program, provider);
     tokenMint = await createMint(
       provider.connection,
       payer.payer,
@@ -73,12 +73,10 @@ describe("token_vesting full suite", () => {
 
     const pda = getPDAs(tokenMint);
     dataAccount = pda.dataAccount;
-    dataBump = pda.dataBump;
     escrowWallet = pda.escrowWallet;
-    escrowBump = pda.escrowBump;
 
     await program.methods
-      .initialize(dataBump, new anchor.BN(vestingAmount), tokenDecimals, new anchor.BN(pastTimestamp))
+      .initialize(new anchor.BN(vestingAmount), tokenDecimals, new anchor.BN(pastTimestamp), true, false, true, 0, null, 0, 0, { both: {} }, fixedBytes("Full suite", 32), fixedBytes("", 128))
       .accounts({
         dataAccount,
         escrowWallet,
@@ -91,10 +89,36 @@ describe("token_vesting full suite", () => {
       .rpc();
   });
 
+  it("Lists the schedule in the creator registry", async () => {
+    const [creatorRegistry] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("creator_registry"), payer.publicKey.toBuffer()],
+      program.programId
+    );
+    const registry = await program.account.creatorRegistry.fetch(creatorRegistry);
+    assert.ok(registry.creator.equals(payer.publicKey));
+    const entry = registry.entries.find((e) => e.dataAccount.equals(dataAccount));
+    assert.ok(entry);
+    assert.ok(entry.mint.equals(tokenMint));
+  });
+
+  it("Updates the schedule metadata", async () => {
+    await program.methods
+      .updateMetadata(fixedBytes("Team 2024", 32), fixedBytes("https://example.com/team-2024.json", 128))
+      .accounts({
+        dataAccount,
+        tokenMint,
+        sender: payer.publicKey,
+      })
+      .rpc();
+
+    const data = await program.account.dataAccount.fetch(dataAccount);
+    assert.equal(Buffer.from(data.name).toString("utf8").replace(/\0+$/, ""), "Team 2024");
+  });
+
   it("Initializes data account and adds a beneficiary", async () => {
     beneficiaryKeypair = anchor.web3.Keypair.generate();
 
-    [beneficiaryPda, beneficiaryBump] = anchor.web3.PublicKey.findProgramAddressSync(
+    [beneficiaryPda] = anchor.web3.PublicKey.findProgramAddressSync(
       [
         Buffer.from("beneficiary"),
         dataAccount.toBuffer(),
@@ -108,8 +132,9 @@ describe("token_vesting full suite", () => {
         {
           key: beneficiaryKeypair.publicKey,
           allocatedTokens: new anchor.BN(100),
+          recoveryAuthority: null,
         },
-      ])
+      ], 0)
       .accounts({
         dataAccount,
         sender: payer.publicKey,
@@ -122,6 +147,11 @@ describe("token_vesting full suite", () => {
           isSigner: false,
           isWritable: true,
         },
+        {
+          pubkey: getBeneficiaryIndexPda(program, dataAccount, 0),
+          isSigner: false,
+          isWritable: true,
+        },
       ])
       .rpc();
 
@@ -133,7 +163,7 @@ describe("token_vesting full suite", () => {
 
   it("Releases 100% of tokens manually", async () => {
     await program.methods
-      .release(dataBump, 100)
+      .release(100)
       .accounts({
         dataAccount,
         tokenMint,
@@ -146,21 +176,88 @@ describe("token_vesting full suite", () => {
     assert.equal(data.percentAvailable, 100);
   });
 
-  it("Allows beneficiary to claim available tokens", async () => {
+  it("Reports claimable amounts via get_claimable", async () => {
+    const amounts = await program.methods
+      .getClaimable()
+      .accounts({
+        dataAccount,
+        beneficiaryAccount: beneficiaryPda,
+        tokenMint,
+      })
+      .view();
+
+    assert.ok(amounts.vestedAmount.eq(new anchor.BN(100)));
+    assert.ok(amounts.claimableNow.eq(new anchor.BN(100)));
+    assert.ok(amounts.nextUnlockTimestamp.eq(new anchor.BN(0)));
+  });
+
+  it("Previews a claim with dry_run without paying out", async () => {
     await provider.connection.requestAirdrop(beneficiaryKeypair.publicKey, 1 * anchor.web3.LAMPORTS_PER_SOL);
     await new Promise(resolve => setTimeout(resolve, 1000));
 
+    const beneficiaryAta = await getAssociatedTokenAddress(tokenMint, beneficiaryKeypair.publicKey);
+    const escrowBefore = await getAccount(provider.connection, escrowWallet);
+
+    await program.methods
+      .claim(new anchor.BN(0), true, null)
+      .accounts({
+        dataAccount,
+        beneficiaryAccount: beneficiaryPda,
+        escrowWallet,
+        beneficiaryWallet: beneficiaryKeypair.publicKey,
+        sender: beneficiaryKeypair.publicKey,
+        tokenMint,
+        walletToDepositTo: beneficiaryAta,
+        payoutTokenAccount: null,
+        config,
+        feeRecipient: payer.publicKey,
+        feeTokenAccount: senderAta,
+        claimApprover: null,
+        memoProgram: null,
+        positionTokenAccount: null,
+        claimRecord: null,
+        secondaryTokenAccount: null,
+        blacklist: null,
+        gateProgram: null,
+        gateConfig: null,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([beneficiaryKeypair])
+      .rpc();
+
+    const escrowAfter = await getAccount(provider.connection, escrowWallet);
+    assert.equal(escrowAfter.amount, escrowBefore.amount);
+    const beneficiary = await program.account.beneficiaryAccount.fetch(beneficiaryPda);
+    assert.ok(beneficiary.claimedTokens.eq(new anchor.BN(0)));
+  });
+
+  it("Allows beneficiary to claim available tokens", async () => {
     const beneficiaryAta = await getAssociatedTokenAddress(tokenMint, beneficiaryKeypair.publicKey);
 
     await program.methods
-      .claim(dataBump, beneficiaryBump)
+      .claim(new anchor.BN(0), false, new anchor.BN(1))
       .accounts({
         dataAccount,
         beneficiaryAccount: beneficiaryPda,
         escrowWallet,
+        beneficiaryWallet: beneficiaryKeypair.publicKey,
         sender: beneficiaryKeypair.publicKey,
         tokenMint,
         walletToDepositTo: beneficiaryAta,
+        payoutTokenAccount: null,
+        config,
+        feeRecipient: payer.publicKey,
+        feeTokenAccount: senderAta,
+        claimApprover: null,
+        memoProgram: null,
+        positionTokenAccount: null,
+        claimRecord: null,
+        secondaryTokenAccount: null,
+        blacklist: null,
+        gateProgram: null,
+        gateConfig: null,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
@@ -172,11 +269,53 @@ describe("token_vesting full suite", () => {
     assert.isAbove(Number(balance.amount), 0);
   });
 
+  it("Rejects a retried claim with the same nonce", async () => {
+    const beneficiaryAta = await getAssociatedTokenAddress(tokenMint, beneficiaryKeypair.publicKey);
+
+    let threw = false;
+    try {
+      await program.methods
+        .claim(new anchor.BN(0), false, new anchor.BN(1))
+        .accounts({
+          dataAccount,
+          beneficiaryAccount: beneficiaryPda,
+          escrowWallet,
+          beneficiaryWallet: beneficiaryKeypair.publicKey,
+          sender: beneficiaryKeypair.publicKey,
+          tokenMint,
+          walletToDepositTo: beneficiaryAta,
+          payoutTokenAccount: null,
+          config,
+          feeRecipient: payer.publicKey,
+          feeTokenAccount: senderAta,
+          claimApprover: null,
+          memoProgram: null,
+          positionTokenAccount: null,
+          claimRecord: null,
+          secondaryTokenAccount: null,
+          blacklist: null,
+          gateProgram: null,
+          gateConfig: null,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([beneficiaryKeypair])
+        .rpc();
+    } catch (e) {
+      threw = true;
+      assert.ok(e.message.includes("DuplicateClaim"));
+    }
+    assert.isTrue(threw);
+    const beneficiary = await program.account.beneficiaryAccount.fetch(beneficiaryPda);
+    assert.ok(beneficiary.lastClaimNonce.eq(new anchor.BN(1)));
+  });
+
   it("Withdraws unclaimed tokens after full vesting", async () => {
     const recipientAta = await getAssociatedTokenAddress(tokenMint, payer.publicKey);
 
     await program.methods
-      .withdrawUnclaimed(dataBump, escrowBump)
+      .withdrawUnclaimed()
       .accounts({
         dataAccount,
         escrowWallet,